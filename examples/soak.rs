@@ -0,0 +1,211 @@
+//! Long-run soak test of an SCD30 attached to a Linux I2C bus, e.g. to qualify a new board or its
+//! bus wiring.
+//!
+//! ```text
+//! cargo run --example soak --features linux -- [--bus /dev/i2c-1] [--hours 4]
+//! ```
+//!
+//! The sensor is measured continuously with a 2 s interval. Every measurement is read with the
+//! data ready poll, every 30th cycle the settings, the firmware version and the health report are
+//! read back and the configuration is written again. A soft reset is issued every 500 cycles.
+//! Failures are counted per error code instead of aborting the run. Statistics are printed every
+//! 100 cycles and at the end.
+//!
+//! The previous configuration of the sensor is restored when the run completes.
+
+#![forbid(unsafe_code)]
+
+use std::{
+    collections::BTreeMap,
+    process::ExitCode,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
+};
+
+use scd30_interface::{
+    blocking::Scd30,
+    data::{AmbientPressureCompensation, MeasurementInterval},
+    error::Scd30Error,
+    linux::{I2CError, I2cdev},
+};
+
+const DEFAULT_BUS: &str = "/dev/i2c-1";
+const DEFAULT_HOURS: f64 = 4.0;
+const INTERVAL_S: u16 = 2;
+const DATA_READY_TIMEOUT_MS: u32 = 5_000;
+const SETTINGS_EVERY: u64 = 30;
+const RESET_EVERY: u64 = 500;
+const REPORT_EVERY: u64 = 100;
+
+type Sensor = Scd30<I2cdev>;
+type SoakError = Box<dyn std::error::Error>;
+
+/// Blocking delay based on [thread::sleep].
+struct StdDelay;
+
+impl embedded_hal::delay::DelayNs for StdDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        thread::sleep(Duration::from_nanos(ns.into()));
+    }
+}
+
+/// Milliseconds since the start of the run, used as clock of the interface.
+fn now_ms() -> u64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+#[derive(Default)]
+struct Statistics {
+    cycles: u64,
+    measurements: u64,
+    operations: u64,
+    failures: BTreeMap<&'static str, u64>,
+    co2_min: Option<f32>,
+    co2_max: Option<f32>,
+}
+
+impl Statistics {
+    fn record<T>(&mut self, result: Result<T, Scd30Error<I2CError>>) -> Option<T> {
+        self.operations += 1;
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                *self.failures.entry(error.code()).or_default() += 1;
+                eprintln!("[{:>10} ms] {error}", now_ms());
+                None
+            }
+        }
+    }
+
+    fn print(&self, sensor: &Sensor, started: Instant) {
+        let failures: u64 = self.failures.values().sum();
+        let timings = sensor.timings();
+        let samples = sensor.sample_accounting();
+        let metrics = sensor.metrics();
+        println!(
+            "--- after {:.1} min",
+            started.elapsed().as_secs_f64() / 60.0
+        );
+        println!(
+            "cycles: {}, measurements: {}, operations: {}",
+            self.cycles, self.measurements, self.operations
+        );
+        println!(
+            "failures: {} ({:.4} %) {:?}",
+            failures,
+            failures as f64 * 100.0 / self.operations.max(1) as f64,
+            self.failures
+        );
+        println!(
+            "response latency: mean {:?} ms, max {} ms; measurement read: mean {:?} ms, max {} ms",
+            timings.response.mean_ms(),
+            timings.response.max_ms,
+            timings.measurement_read.mean_ms(),
+            timings.measurement_read.max_ms
+        );
+        println!(
+            "samples: {} ready, {} read, {} missed; CO2 range: {:?}..{:?} ppm",
+            samples.data_ready, samples.read, samples.missed, self.co2_min, self.co2_max
+        );
+        println!("errors: {:?}", sensor.error_counters());
+        println!(
+            "bus: {} transactions, {} bytes written, {} bytes read",
+            metrics.measurement.transactions
+                + metrics.configuration.transactions
+                + metrics.control.transactions,
+            metrics.measurement.bytes_written
+                + metrics.configuration.bytes_written
+                + metrics.control.bytes_written,
+            metrics.measurement.bytes_read
+                + metrics.configuration.bytes_read
+                + metrics.control.bytes_read
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    let mut bus = DEFAULT_BUS.to_string();
+    let mut hours = DEFAULT_HOURS;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--bus", Some(value)) => bus = value,
+            ("--hours", Some(value)) => match value.parse() {
+                Ok(value) => hours = value,
+                Err(_) => {
+                    eprintln!("invalid number of hours: {value}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            _ => {
+                eprintln!("usage: soak [--bus <path>] [--hours <hours>]");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match soak(&bus, Duration::from_secs_f64(hours * 3600.0)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn soak(bus: &str, duration: Duration) -> Result<(), SoakError> {
+    let mut sensor = Scd30::open(bus)
+        .map_err(|error| format!("opening {bus} failed: {error}"))?
+        .with_clock(now_ms);
+    let firmware = sensor.init()?;
+    let snapshot = sensor.snapshot()?;
+    println!("soaking sensor with firmware {firmware} on {bus} for {duration:?}");
+    println!("{}", sensor.read_all_settings()?);
+
+    let interval = MeasurementInterval::try_from(INTERVAL_S)?;
+    sensor.set_measurement_interval(interval)?;
+    sensor.trigger_continuous_measurements(Some(AmbientPressureCompensation::DefaultPressure))?;
+
+    let started = Instant::now();
+    let mut stats = Statistics::default();
+    while started.elapsed() < duration {
+        stats.cycles += 1;
+        let ready = stats.record(sensor.wait_for_data_ready(&mut StdDelay, DATA_READY_TIMEOUT_MS));
+        if ready.is_some() {
+            if let Some(measurement) = stats.record(sensor.read_measurement()) {
+                stats.measurements += 1;
+                let co2 = measurement.co2_concentration;
+                stats.co2_min = Some(stats.co2_min.map_or(co2, |min| min.min(co2)));
+                stats.co2_max = Some(stats.co2_max.map_or(co2, |max| max.max(co2)));
+            }
+        }
+
+        if stats.cycles % SETTINGS_EVERY == 0 {
+            stats.record(sensor.read_all_settings());
+            stats.record(sensor.read_firmware_version());
+            stats.record(sensor.get_forced_recalibration());
+            stats.record(sensor.health_report());
+            stats.record(sensor.set_measurement_interval(interval));
+        }
+
+        if stats.cycles % RESET_EVERY == 0 {
+            stats.record(sensor.soft_reset());
+            thread::sleep(Duration::from_secs(2));
+            stats.record(sensor.set_measurement_interval(interval));
+            stats.record(sensor.trigger_continuous_measurements(Some(
+                AmbientPressureCompensation::DefaultPressure,
+            )));
+        }
+
+        if stats.cycles % REPORT_EVERY == 0 {
+            stats.print(&sensor, started);
+        }
+    }
+
+    stats.print(&sensor, started);
+    sensor.stop_continuous_measurements()?;
+    sensor.restore(&snapshot, &mut StdDelay)?;
+    Ok(())
+}
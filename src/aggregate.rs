@@ -0,0 +1,270 @@
+//! Windowed statistics of streamed measurements.
+//!
+//! Instead of reporting every sample over a constrained uplink, an [Aggregator] condenses the
+//! measurements of fixed time windows, e.g. an hour or a day, into [Rollup]s containing the
+//! minimum, maximum and mean of each quantity.
+
+use heapless::Deque;
+
+use crate::data::Measurement;
+
+/// Minimum, maximum and mean of a quantity within a window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    /// Smallest value within the window.
+    pub min: f32,
+    /// Largest value within the window.
+    pub max: f32,
+    /// Arithmetic mean of the values within the window.
+    pub mean: f32,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Stats {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}/{}/{}", self.min, self.mean, self.max)
+    }
+}
+
+/// Statistics of all measurements within one window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rollup {
+    /// Start of the window in milliseconds.
+    pub start_ms: u64,
+    /// Number of measurements within the window.
+    pub samples: u32,
+    /// Statistics of the CO2 concentration in ppm.
+    pub co2_concentration: Stats,
+    /// Statistics of the temperature in °C.
+    pub temperature: Stats,
+    /// Statistics of the relative humidity in %.
+    pub humidity: Stats,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Rollup {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{}ms ({} samples): {}ppm, {}°C, {}%",
+            self.start_ms,
+            self.samples,
+            self.co2_concentration,
+            self.temperature,
+            self.humidity
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Accumulator {
+    min: f32,
+    max: f32,
+    // Summed up in f64 so long windows do not lose precision.
+    sum: f64,
+}
+
+impl Accumulator {
+    fn new(value: f32) -> Self {
+        Self {
+            min: value,
+            max: value,
+            sum: value as f64,
+        }
+    }
+
+    fn add(&mut self, value: f32) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value as f64;
+    }
+
+    fn stats(&self, samples: u32) -> Stats {
+        Stats {
+            min: self.min,
+            max: self.max,
+            mean: (self.sum / samples as f64) as f32,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Window {
+    start_ms: u64,
+    samples: u32,
+    co2_concentration: Accumulator,
+    temperature: Accumulator,
+    humidity: Accumulator,
+}
+
+impl Window {
+    fn new(start_ms: u64, measurement: &Measurement) -> Self {
+        Self {
+            start_ms,
+            samples: 1,
+            co2_concentration: Accumulator::new(measurement.co2_concentration),
+            temperature: Accumulator::new(measurement.temperature),
+            humidity: Accumulator::new(measurement.humidity),
+        }
+    }
+
+    fn add(&mut self, measurement: &Measurement) {
+        self.samples = self.samples.saturating_add(1);
+        self.co2_concentration.add(measurement.co2_concentration);
+        self.temperature.add(measurement.temperature);
+        self.humidity.add(measurement.humidity);
+    }
+
+    fn rollup(&self) -> Rollup {
+        Rollup {
+            start_ms: self.start_ms,
+            samples: self.samples,
+            co2_concentration: self.co2_concentration.stats(self.samples),
+            temperature: self.temperature.stats(self.samples),
+            humidity: self.humidity.stats(self.samples),
+        }
+    }
+}
+
+/// Aggregates measurements into [Rollup]s of fixed windows, keeping up to `N` completed rollups.
+///
+/// Windows are aligned to multiples of the window length, so a window of one hour always starts
+/// at a full hour of the used time base. If more than `N` rollups are completed without being
+/// taken out, the oldest one is dropped.
+pub struct Aggregator<const N: usize> {
+    window_ms: u64,
+    current: Option<Window>,
+    completed: Deque<Rollup, N>,
+}
+
+impl<const N: usize> Aggregator<N> {
+    /// Creates an aggregator with windows of `window_ms` milliseconds. A length of 0 is treated
+    /// as 1 ms.
+    pub const fn new(window_ms: u64) -> Self {
+        Self {
+            window_ms: if window_ms == 0 { 1 } else { window_ms },
+            current: None,
+            completed: Deque::new(),
+        }
+    }
+
+    /// Adds a measurement taken at `timestamp_ms`. Returns a copy of the rollup of the previous
+    /// window if the measurement starts a new one.
+    ///
+    /// Measurements older than the current window are ignored.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: &Measurement) -> Option<Rollup> {
+        let start_ms = timestamp_ms - timestamp_ms % self.window_ms;
+        match &mut self.current {
+            Some(window) if window.start_ms == start_ms => {
+                window.add(measurement);
+                None
+            }
+            Some(window) if window.start_ms > start_ms => None,
+            _ => {
+                let completed = self.current.replace(Window::new(start_ms, measurement));
+                completed.map(|window| self.complete(window.rollup()))
+            }
+        }
+    }
+
+    /// Completes the current window early, e.g. before shutting down, and returns its rollup.
+    pub fn flush(&mut self) -> Option<Rollup> {
+        self.current
+            .take()
+            .map(|window| self.complete(window.rollup()))
+    }
+
+    /// Returns the rollup of the current, not yet completed window.
+    pub fn current(&self) -> Option<Rollup> {
+        self.current.as_ref().map(Window::rollup)
+    }
+
+    /// Takes out the oldest completed rollup.
+    pub fn pop(&mut self) -> Option<Rollup> {
+        self.completed.pop_front()
+    }
+
+    /// Returns an iterator over the completed rollups, starting with the oldest.
+    pub fn rollups(&self) -> impl Iterator<Item = &Rollup> {
+        self.completed.iter()
+    }
+
+    fn complete(&mut self, rollup: Rollup) -> Rollup {
+        if self.completed.is_full() {
+            self.completed.pop_front();
+        }
+        // Space was made above, so the rollup always fits.
+        let _ = self.completed.push_back(rollup);
+        rollup
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    fn measurement(co2_concentration: f32, temperature: f32, humidity: f32) -> Measurement {
+        Measurement {
+            co2_concentration,
+            temperature,
+            humidity,
+        }
+    }
+
+    #[test]
+    fn rollup_contains_window_statistics() {
+        let mut aggregator = Aggregator::<4>::new(HOUR_MS);
+        assert_eq!(aggregator.push(1000, &measurement(400.0, 20.0, 40.0)), None);
+        assert_eq!(aggregator.push(2000, &measurement(600.0, 22.0, 50.0)), None);
+        assert_eq!(aggregator.push(3000, &measurement(500.0, 24.0, 60.0)), None);
+
+        let rollup = aggregator
+            .push(HOUR_MS + 5, &measurement(700.0, 20.0, 40.0))
+            .unwrap();
+        assert_eq!(rollup.start_ms, 0);
+        assert_eq!(rollup.samples, 3);
+        assert_eq!(
+            rollup.co2_concentration,
+            Stats {
+                min: 400.0,
+                max: 600.0,
+                mean: 500.0
+            }
+        );
+        assert_eq!(rollup.temperature.mean, 22.0);
+        assert_eq!(rollup.humidity.max, 60.0);
+        assert_eq!(aggregator.pop(), Some(rollup));
+        assert_eq!(aggregator.pop(), None);
+    }
+
+    #[test]
+    fn oldest_rollup_is_dropped_when_full() {
+        let mut aggregator = Aggregator::<2>::new(HOUR_MS);
+        for hour in 0..4 {
+            aggregator.push(hour * HOUR_MS, &measurement(400.0, 20.0, 40.0));
+        }
+        let starts: Vec<u64> = aggregator.rollups().map(|rollup| rollup.start_ms).collect();
+        assert_eq!(starts, [HOUR_MS, 2 * HOUR_MS]);
+    }
+
+    #[test]
+    fn flush_completes_current_window() {
+        let mut aggregator = Aggregator::<2>::new(HOUR_MS);
+        aggregator.push(HOUR_MS + 10, &measurement(400.0, 20.0, 40.0));
+        assert_eq!(aggregator.current().unwrap().samples, 1);
+        let rollup = aggregator.flush().unwrap();
+        assert_eq!(rollup.start_ms, HOUR_MS);
+        assert_eq!(aggregator.current(), None);
+        assert_eq!(aggregator.rollups().count(), 1);
+    }
+
+    #[test]
+    fn late_measurements_are_ignored() {
+        let mut aggregator = Aggregator::<2>::new(HOUR_MS);
+        aggregator.push(HOUR_MS, &measurement(400.0, 20.0, 40.0));
+        assert_eq!(aggregator.push(10, &measurement(800.0, 20.0, 40.0)), None);
+        assert_eq!(aggregator.current().unwrap().co2_concentration.max, 400.0);
+    }
+}
@@ -0,0 +1,232 @@
+//! Detection of spikes on the CO2 channel.
+//!
+//! Single implausible samples, e.g. caused by someone breathing onto the sensor or a glitch,
+//! trigger alarms and distort the calibration logic. A [SpikeDetector] flags samples which jump
+//! faster than physically plausible for a room or deviate too far from the recent level, before
+//! they reach these consumers:
+//!
+//! ```ignore
+//! let mut detector = SpikeDetector::new(SpikeCriteria::default());
+//! loop {
+//!     let measurement = sensor.read_measurement()?;
+//!     match detector.check(clock(), &measurement) {
+//!         Some(anomaly) => log::warn!("ignoring spike: {:?}", anomaly),
+//!         None => alarm.update(measurement.co2_concentration),
+//!     }
+//! }
+//! ```
+//!
+//! A genuine step change, e.g. when a room fills, is flagged at first, but accepted as new level
+//! once it persists for [confirm_samples](SpikeCriteria::confirm_samples) samples.
+
+use crate::{data::Measurement, util::abs};
+
+/// Weight of a new sample in the running mean and variance.
+const SMOOTHING: f32 = 0.1;
+
+/// Criteria for samples to be flagged as spikes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpikeCriteria {
+    /// Largest plausible rate of change of the CO2 concentration in ppm per second.
+    pub max_rate_ppm_per_s: f32,
+    /// Largest plausible deviation from the running mean, in standard deviations.
+    pub z_threshold: f32,
+    /// Lower bound of the standard deviation in ppm, so the noise of a stable signal is not
+    /// flagged. Should be at least the accuracy of the sensor.
+    pub min_deviation_ppm: f32,
+    /// Number of consecutive flagged samples after which the concentration is accepted as new
+    /// level. 0 and 1 accept every sample after flagging it.
+    pub confirm_samples: u8,
+}
+
+impl Default for SpikeCriteria {
+    /// 50 ppm/s, 4 standard deviations of at least 30 ppm, and a new level accepted after 3
+    /// samples.
+    fn default() -> Self {
+        Self {
+            max_rate_ppm_per_s: 50.0,
+            z_threshold: 4.0,
+            min_deviation_ppm: 30.0,
+            confirm_samples: 3,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SpikeCriteria {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "SpikeCriteria {{ max_rate_ppm_per_s: {}, z_threshold: {}, min_deviation_ppm: {}, confirm_samples: {} }}",
+            self.max_rate_ppm_per_s,
+            self.z_threshold,
+            self.min_deviation_ppm,
+            self.confirm_samples
+        )
+    }
+}
+
+/// Reason a sample was flagged by a [SpikeDetector].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anomaly {
+    /// The concentration changed faster than
+    /// [max_rate_ppm_per_s](SpikeCriteria::max_rate_ppm_per_s) since the last accepted sample.
+    RateOfChange {
+        /// Rate of change in ppm per second.
+        rate_ppm_per_s: f32,
+    },
+    /// The concentration deviates more than [z_threshold](SpikeCriteria::z_threshold) standard
+    /// deviations from the running mean.
+    Outlier {
+        /// Deviation from the running mean in ppm.
+        deviation_ppm: f32,
+    },
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Anomaly {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Anomaly::RateOfChange { rate_ppm_per_s } => {
+                defmt::write!(f, "RateOfChange({} ppm/s)", rate_ppm_per_s)
+            }
+            Anomaly::Outlier { deviation_ppm } => {
+                defmt::write!(f, "Outlier({} ppm)", deviation_ppm)
+            }
+        }
+    }
+}
+
+/// Flags implausible jumps of the CO2 concentration. See the [module documentation](self).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpikeDetector {
+    criteria: SpikeCriteria,
+    // Last accepted sample as timestamp and concentration.
+    level: Option<(u64, f32)>,
+    mean: f32,
+    variance: f32,
+    flagged: u8,
+}
+
+impl SpikeDetector {
+    /// Creates a detector flagging samples according to `criteria`.
+    pub const fn new(criteria: SpikeCriteria) -> Self {
+        Self {
+            criteria,
+            level: None,
+            mean: 0.0,
+            variance: 0.0,
+            flagged: 0,
+        }
+    }
+
+    /// Checks a measurement taken at `timestamp_ms`, returning the anomaly if it is flagged.
+    /// Flagged samples do not update the level and statistics the following samples are compared
+    /// to, unless they persist.
+    pub fn check(&mut self, timestamp_ms: u64, measurement: &Measurement) -> Option<Anomaly> {
+        let value = measurement.co2_concentration;
+        if !value.is_finite() {
+            return Some(Anomaly::Outlier {
+                deviation_ppm: value,
+            });
+        }
+        let Some((level_ms, level)) = self.level else {
+            self.restart(timestamp_ms, value);
+            return None;
+        };
+
+        let elapsed_s = timestamp_ms.saturating_sub(level_ms).max(1) as f32 / 1000.0;
+        let rate_ppm_per_s = (value - level) / elapsed_s;
+        let deviation_ppm = value - self.mean;
+        let min_variance = self.criteria.min_deviation_ppm * self.criteria.min_deviation_ppm;
+        let threshold = self.criteria.z_threshold * self.criteria.z_threshold;
+        let anomaly = if abs(rate_ppm_per_s) > self.criteria.max_rate_ppm_per_s {
+            Some(Anomaly::RateOfChange { rate_ppm_per_s })
+        } else if deviation_ppm * deviation_ppm > threshold * self.variance.max(min_variance) {
+            Some(Anomaly::Outlier { deviation_ppm })
+        } else {
+            None
+        };
+
+        match anomaly {
+            Some(_) if self.flagged.saturating_add(1) < self.criteria.confirm_samples => {
+                self.flagged += 1;
+                anomaly
+            }
+            Some(_) => {
+                self.restart(timestamp_ms, value);
+                anomaly
+            }
+            None => {
+                self.flagged = 0;
+                self.level = Some((timestamp_ms, value));
+                self.mean += SMOOTHING * deviation_ppm;
+                self.variance =
+                    (1.0 - SMOOTHING) * (self.variance + SMOOTHING * deviation_ppm * deviation_ppm);
+                None
+            }
+        }
+    }
+
+    /// Forgets the history, e.g. after the sensor was restarted.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.criteria);
+    }
+
+    fn restart(&mut self, timestamp_ms: u64, value: f32) {
+        self.level = Some((timestamp_ms, value));
+        self.mean = value;
+        self.variance = 0.0;
+        self.flagged = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn co2(ppm: f32) -> Measurement {
+        Measurement::new(ppm, 21.0, 45.0)
+    }
+
+    #[test]
+    fn single_spikes_are_flagged() {
+        let mut detector = SpikeDetector::new(SpikeCriteria::default());
+        assert_eq!(detector.check(0, &co2(600.0)), None);
+        assert_eq!(detector.check(2000, &co2(610.0)), None);
+        assert_eq!(
+            detector.check(4000, &co2(1610.0)),
+            Some(Anomaly::RateOfChange {
+                rate_ppm_per_s: 500.0
+            })
+        );
+        assert_eq!(detector.check(6000, &co2(615.0)), None);
+    }
+
+    #[test]
+    fn slow_drifts_far_from_the_mean_are_outliers() {
+        let mut detector = SpikeDetector::new(SpikeCriteria::default());
+        detector.check(0, &co2(600.0));
+        assert!(matches!(
+            detector.check(60_000, &co2(800.0)),
+            Some(Anomaly::Outlier { .. })
+        ));
+        assert!(matches!(
+            detector.check(0, &co2(f32::NAN)),
+            Some(Anomaly::Outlier { .. })
+        ));
+    }
+
+    #[test]
+    fn persistent_steps_are_accepted() {
+        let mut detector = SpikeDetector::new(SpikeCriteria::default());
+        detector.check(0, &co2(600.0));
+        assert!(detector.check(2000, &co2(1200.0)).is_some());
+        assert!(detector.check(4000, &co2(1210.0)).is_some());
+        assert!(detector.check(6000, &co2(1205.0)).is_some());
+        assert_eq!(detector.check(8000, &co2(1215.0)), None);
+
+        detector.reset();
+        assert_eq!(detector.check(10_000, &co2(400.0)), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Command line tool for bring-up and provisioning of SCD30 sensors attached to a Linux I2C bus.
+//!
+//! ```text
+//! scd30-cli [--bus /dev/i2c-1] <command>
+//! ```
+//!
+//! Run `scd30-cli help` for the list of commands.
+
+#![forbid(unsafe_code)]
+
+use std::{
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant},
+};
+
+use scd30_interface::{
+    blocking::Scd30,
+    data::{
+        AltitudeCompensation, AmbientPressure, AmbientPressureCompensation,
+        AutomaticSelfCalibration, ForcedRecalibrationValue, MeasurementInterval, TemperatureOffset,
+    },
+    linux::I2cdev,
+};
+
+const DEFAULT_BUS: &str = "/dev/i2c-1";
+// Longest measurement interval supported by the sensor plus some margin.
+const DATA_READY_TIMEOUT_MS: u32 = 1_810_000;
+
+const USAGE: &str = "\
+Usage: scd30-cli [--bus <path>] <command>
+
+Options:
+    --bus <path>                  I2C bus device, defaults to /dev/i2c-1
+
+Commands:
+    read                          Read a single measurement
+    stream [<count>]              Print measurements as CSV, endlessly or <count> times
+    config                        Print the sensor's configuration
+    start [<pressure>]            Start continuous measurements, optionally compensating the
+                                  ambient pressure in mBar
+    stop                          Stop continuous measurements
+    set interval <seconds>        Set the measurement interval
+    set asc <on|off>              Enable or disable the automatic self-calibration
+    set temperature-offset <°C>   Set the temperature offset
+    set altitude <meters>         Set the altitude compensation
+    frc <ppm>                     Run a forced recalibration to the given reference concentration
+    reset                         Soft reset the sensor
+    help                          Print this help";
+
+type Sensor = Scd30<I2cdev>;
+type CliError = Box<dyn std::error::Error>;
+
+/// Blocking delay based on [thread::sleep].
+struct StdDelay;
+
+impl embedded_hal::delay::DelayNs for StdDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        thread::sleep(Duration::from_nanos(ns.into()));
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let bus = match args.iter().position(|arg| arg == "--bus") {
+        Some(index) if index + 1 < args.len() => {
+            let bus = args.remove(index + 1);
+            args.remove(index);
+            bus
+        }
+        Some(_) => {
+            eprintln!("--bus requires a path\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+        None => DEFAULT_BUS.into(),
+    };
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    if matches!(args.as_slice(), [] | ["help" | "--help" | "-h"]) {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    match Scd30::open(&bus)
+        .map_err(|error| format!("opening {bus} failed: {error}").into())
+        .and_then(|mut sensor| run(&mut sensor, &args))
+    {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(sensor: &mut Sensor, args: &[&str]) -> Result<(), CliError> {
+    match args {
+        ["read"] => {
+            let (co2, temperature, humidity) = next_measurement(sensor)?;
+            println!("CO2: {co2} ppm\nTemperature: {temperature} °C\nHumidity: {humidity} %");
+        }
+        ["stream"] => stream(sensor, None)?,
+        ["stream", count] => stream(sensor, Some(count.parse()?))?,
+        ["config"] => {
+            let version = sensor.read_firmware_version()?;
+            let config = sensor.read_config()?;
+            println!("firmware_version: {}.{}", version.major, version.minor);
+            println!("measurement_interval: {:?}", config.measurement_interval);
+            println!(
+                "automatic_self_calibration: {:?}",
+                config.automatic_self_calibration
+            );
+            println!("temperature_offset: {:?}", config.temperature_offset);
+            println!("altitude_compensation: {:?}", config.altitude_compensation);
+            println!(
+                "forced_recalibration_value: {:?}",
+                sensor.get_forced_recalibration()?
+            );
+        }
+        ["start"] => {
+            sensor.trigger_continuous_measurements(Some(
+                AmbientPressureCompensation::DefaultPressure,
+            ))?;
+        }
+        ["start", pressure] => {
+            let pressure = AmbientPressure::try_from(pressure.parse::<u16>()?)?;
+            sensor.trigger_continuous_measurements(Some(
+                AmbientPressureCompensation::CompensationPressure(pressure),
+            ))?;
+        }
+        ["stop"] => sensor.stop_continuous_measurements()?,
+        ["set", "interval", seconds] => {
+            sensor.set_measurement_interval(MeasurementInterval::try_from(
+                seconds.parse::<u16>()?,
+            )?)?;
+        }
+        ["set", "asc", "on"] => {
+            sensor.set_automatic_self_calibration(AutomaticSelfCalibration::Active)?
+        }
+        ["set", "asc", "off"] => {
+            sensor.set_automatic_self_calibration(AutomaticSelfCalibration::Inactive)?
+        }
+        ["set", "temperature-offset", offset] => {
+            sensor.set_temperature_offset(TemperatureOffset::try_from(offset.parse::<f32>()?)?)?;
+        }
+        ["set", "altitude", altitude] => {
+            sensor
+                .set_altitude_compensation(AltitudeCompensation::from(altitude.parse::<u16>()?))?;
+        }
+        ["frc", ppm] => {
+            sensor.set_forced_recalibration(ForcedRecalibrationValue::try_from(
+                ppm.parse::<u16>()?,
+            )?)?;
+        }
+        ["reset"] => sensor.soft_reset()?,
+        _ => return Err(format!("invalid command\n\n{USAGE}").into()),
+    }
+    Ok(())
+}
+
+fn next_measurement(sensor: &mut Sensor) -> Result<(String, String, String), CliError> {
+    sensor.wait_for_data_ready(&mut StdDelay, DATA_READY_TIMEOUT_MS)?;
+    let measurement = sensor.read_measurement()?;
+    Ok((
+        measurement.co2_concentration_fixed().to_string(),
+        measurement.temperature_fixed().to_string(),
+        measurement.humidity_fixed().to_string(),
+    ))
+}
+
+fn stream(sensor: &mut Sensor, count: Option<u64>) -> Result<(), CliError> {
+    let started = Instant::now();
+    println!("time_ms,co2_ppm,temperature_c,humidity_pct");
+    let mut streamed = 0;
+    while count.map_or(true, |count| streamed < count) {
+        let (co2, temperature, humidity) = next_measurement(sensor)?;
+        println!(
+            "{},{co2},{temperature},{humidity}",
+            started.elapsed().as_millis()
+        );
+        streamed += 1;
+    }
+    Ok(())
+}
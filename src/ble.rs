@@ -0,0 +1,88 @@
+//! Encoder of the BLE advertisements of Sensirion's gadgets.
+//!
+//! Sensirion's gadgets broadcast their readings in the manufacturer specific data of BLE
+//! advertisements, which the MyAmbience app displays without pairing. [encode_advertisement]
+//! builds this data for a measurement, so a device using the SCD30 shows up in the app like a
+//! gadget. The data is laid out as follows, multi-byte values are little endian:
+//!
+//! | Bytes  | Content                                          |
+//! |--------|--------------------------------------------------|
+//! | 0..2   | Company identifier of Sensirion, 0x06D5          |
+//! | 2      | Advertisement type, 0 for samples                |
+//! | 3      | Sample type, [SAMPLE_TYPE_T_RH_CO2]              |
+//! | 4..6   | Device identifier                                |
+//! | 6..8   | Temperature, `(T + 45 °C) / 175 °C * 65535`      |
+//! | 8..10  | Relative humidity, `RH / 100 % * 65535`          |
+//! | 10..12 | CO2 concentration in ppm                         |
+//!
+//! The data is passed to the BLE stack as manufacturer specific data, which adds the length and
+//! AD type bytes.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{data::Measurement, format::FixedPoint};
+
+/// Bluetooth SIG company identifier of Sensirion AG.
+pub const SENSIRION_COMPANY_ID: u16 = 0x06D5;
+
+/// Sample type of advertisements carrying temperature, relative humidity and CO2.
+pub const SAMPLE_TYPE_T_RH_CO2: u8 = 8;
+
+/// Size of the manufacturer specific data created by [encode_advertisement] in bytes.
+pub const ADVERTISEMENT_LEN: usize = 12;
+
+const ADVERTISEMENT_TYPE_SAMPLES: u8 = 0;
+
+/// Encodes `measurement` as manufacturer specific data of a Sensirion gadget advertisement, see
+/// the [module documentation](self). `device_id` tells several devices apart in the app,
+/// Sensirion's gadgets use the last two bytes of their MAC address. Values outside of the
+/// encodable ranges are saturated.
+pub fn encode_advertisement(
+    measurement: &Measurement,
+    device_id: [u8; 2],
+) -> [u8; ADVERTISEMENT_LEN] {
+    let temperature = scale((measurement.temperature + 45.0) / 175.0);
+    let humidity = scale(measurement.humidity / 100.0);
+    let co2 = saturate_u16(FixedPoint::from_f32(measurement.co2_concentration, 0).mantissa());
+
+    let mut advertisement = [0; ADVERTISEMENT_LEN];
+    LittleEndian::write_u16(&mut advertisement[0..2], SENSIRION_COMPANY_ID);
+    advertisement[2] = ADVERTISEMENT_TYPE_SAMPLES;
+    advertisement[3] = SAMPLE_TYPE_T_RH_CO2;
+    advertisement[4..6].copy_from_slice(&device_id);
+    LittleEndian::write_u16(&mut advertisement[6..8], temperature);
+    LittleEndian::write_u16(&mut advertisement[8..10], humidity);
+    LittleEndian::write_u16(&mut advertisement[10..12], co2);
+    advertisement
+}
+
+/// Scales a fraction of the encodable range to the full range of `u16`.
+fn scale(fraction: f32) -> u16 {
+    saturate_u16(FixedPoint::from_f32(fraction * u16::MAX as f32, 0).mantissa())
+}
+
+fn saturate_u16(value: i32) -> u16 {
+    value.clamp(0, u16::MAX as i32) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measurement_is_encoded_as_gadget_advertisement() {
+        let advertisement =
+            encode_advertisement(&Measurement::new(600.0, 25.0, 50.0), [0xAB, 0xCD]);
+        assert_eq!(
+            advertisement,
+            [0xD5, 0x06, 0x00, 0x08, 0xAB, 0xCD, 0x66, 0x66, 0x00, 0x80, 0x58, 0x02]
+        );
+    }
+
+    #[test]
+    fn values_out_of_range_saturate() {
+        let advertisement =
+            encode_advertisement(&Measurement::new(-10.0, 150.0, f32::NAN), [0x00, 0x00]);
+        assert_eq!(advertisement[6..12], [0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00]);
+    }
+}
@@ -0,0 +1,467 @@
+//! Detection of a drifting CO2 reading.
+//!
+//! Most indoor spaces are regularly ventilated with fresh air, so the lowest CO2 concentration
+//! seen over a day should be close to the outdoor level of roughly [FRESH_AIR_PPM]. The
+//! [DriftDetector] tracks this "fresh-air baseline" as the minimum over several windows and
+//! reports when it moves away from the expected level, indicating the sensor needs to be
+//! recalibrated.
+//!
+//! The [CalibrationAdvisor] combines the drift with the time since the last forced recalibration
+//! and the state of the automatic self-calibration into a [CalibrationAdvice], e.g. to plan the
+//! maintenance of a fleet of sensors.
+//!
+//! The heat dissipated by the sensor and the surrounding electronics raises the temperature
+//! inside an enclosure. The [TemperatureOffsetAdvisor] compares the measured temperature with a
+//! reference thermometer and, once the difference has settled, recommends the
+//! [TemperatureOffset] compensating it:
+//!
+//! ```ignore
+//! let mut advisor = TemperatureOffsetAdvisor::<16>::new(10 * 60_000, 0.1);
+//! loop {
+//!     let measurement = sensor.read_measurement()?;
+//!     advisor.push(clock(), &measurement, reference.temperature());
+//!     if let Some(offset) = advisor.recommend(sensor.get_temperature_offset()?) {
+//!         sensor.set_temperature_offset(offset)?;
+//!         break;
+//!     }
+//! }
+//! ```
+
+use heapless::Deque;
+
+use crate::{
+    data::{AutomaticSelfCalibration, Measurement, TemperatureOffset},
+    event::{EventHook, Scd30Event},
+    util::abs,
+};
+
+/// Expected CO2 concentration of fresh outdoor air in ppm.
+pub const FRESH_AIR_PPM: f32 = 400.0;
+
+/// Tracks the fresh-air baseline as the minimum CO2 concentration over the last `N` windows.
+pub struct DriftDetector<const N: usize> {
+    window_ms: u64,
+    threshold_ppm: f32,
+    current: Option<(u64, f32)>,
+    minima: Deque<f32, N>,
+    on_event: Option<EventHook>,
+}
+
+impl<const N: usize> DriftDetector<N> {
+    /// Creates a detector with windows of `window_ms` milliseconds, e.g. one day, which reports a
+    /// drift once the baseline differs from [FRESH_AIR_PPM] by more than `threshold_ppm`. A window
+    /// length of 0 is treated as 1 ms.
+    pub const fn new(window_ms: u64, threshold_ppm: f32) -> Self {
+        Self {
+            window_ms: if window_ms == 0 { 1 } else { window_ms },
+            threshold_ppm,
+            current: None,
+            minima: Deque::new(),
+            on_event: None,
+        }
+    }
+
+    /// Calls `hook` with [DriftDetected](Scd30Event::DriftDetected) whenever the baseline starts
+    /// to exceed the drift threshold.
+    pub const fn on_event(mut self, hook: EventHook) -> Self {
+        self.on_event = Some(hook);
+        self
+    }
+
+    /// Adds a measurement taken at `timestamp_ms`. Measurements older than the current window
+    /// and invalid CO2 concentrations are ignored.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: &Measurement) {
+        let co2 = measurement.co2_concentration;
+        if co2.is_nan() {
+            return;
+        }
+        let was_drifted = self.on_event.is_some() && self.is_drifted();
+        let start_ms = timestamp_ms - timestamp_ms % self.window_ms;
+        match &mut self.current {
+            Some((current_start, minimum)) if *current_start == start_ms => {
+                *minimum = minimum.min(co2);
+            }
+            Some((current_start, _)) if *current_start > start_ms => {}
+            _ => {
+                if let Some((_, minimum)) = self.current.replace((start_ms, co2)) {
+                    if self.minima.is_full() {
+                        self.minima.pop_front();
+                    }
+                    // Space was made above, so the minimum always fits.
+                    let _ = self.minima.push_back(minimum);
+                }
+            }
+        }
+        if let (Some(hook), false, Some(drift_ppm)) = (self.on_event, was_drifted, self.drift()) {
+            if self.is_drifted() {
+                hook(Scd30Event::DriftDetected { drift_ppm });
+            }
+        }
+    }
+
+    /// Returns the fresh-air baseline in ppm, i.e. the lowest CO2 concentration of the completed
+    /// windows. `None` until the first window is completed.
+    pub fn baseline(&self) -> Option<f32> {
+        self.minima.iter().copied().reduce(f32::min)
+    }
+
+    /// Returns the difference between the baseline and [FRESH_AIR_PPM] in ppm.
+    pub fn drift(&self) -> Option<f32> {
+        self.baseline().map(|baseline| baseline - FRESH_AIR_PPM)
+    }
+
+    /// Returns whether the baseline drifted away from [FRESH_AIR_PPM] by more than the configured
+    /// threshold.
+    pub fn is_drifted(&self) -> bool {
+        self.drift()
+            .is_some_and(|drift| abs(drift) > self.threshold_ppm)
+    }
+
+    /// Returns the configured drift threshold in ppm.
+    pub fn threshold_ppm(&self) -> f32 {
+        self.threshold_ppm
+    }
+
+    /// Discards all tracked data, e.g. after the sensor was recalibrated.
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.minima.clear();
+    }
+}
+
+/// Whether a sensor should be recalibrated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CalibrationAdvice {
+    /// No action required.
+    Ok,
+    /// A recalibration should be scheduled.
+    Recommended,
+    /// The readings are unreliable until the sensor is recalibrated.
+    Required,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CalibrationAdvice {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            CalibrationAdvice::Ok => defmt::write!(f, "Ok"),
+            CalibrationAdvice::Recommended => defmt::write!(f, "Recommended"),
+            CalibrationAdvice::Required => defmt::write!(f, "Required"),
+        }
+    }
+}
+
+/// Derives a [CalibrationAdvice] from the baseline drift, the time since the last forced
+/// recalibration (FRC) and the state of the automatic self-calibration (ASC).
+///
+/// - A drift beyond twice the detector's threshold requires a recalibration.
+/// - A drift beyond the threshold requires a recalibration if ASC is inactive, otherwise it is
+///   recommended as ASC apparently fails to compensate it.
+/// - Without ASC, a recalibration is recommended after `recommend_after_ms` and required after
+///   `require_after_ms` since the last FRC. Sensors never recalibrated are treated as overdue
+///   for a recommendation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalibrationAdvisor {
+    /// Time since the last FRC after which a recalibration is recommended, in milliseconds.
+    pub recommend_after_ms: u64,
+    /// Time since the last FRC after which a recalibration is required, in milliseconds.
+    pub require_after_ms: u64,
+}
+
+impl CalibrationAdvisor {
+    /// Returns the advice for a sensor with the given drift, time since its last FRC in
+    /// milliseconds, if any, and ASC state. An [Unknown](AutomaticSelfCalibration::Unknown) ASC
+    /// state is treated as inactive.
+    pub fn advise<const N: usize>(
+        &self,
+        drift: &DriftDetector<N>,
+        since_last_frc_ms: Option<u64>,
+        asc: AutomaticSelfCalibration,
+    ) -> CalibrationAdvice {
+        let drift_advice = match drift.drift() {
+            Some(drift_ppm) if abs(drift_ppm) > 2.0 * drift.threshold_ppm() => {
+                CalibrationAdvice::Required
+            }
+            Some(drift_ppm) if abs(drift_ppm) > drift.threshold_ppm() => match asc {
+                AutomaticSelfCalibration::Active => CalibrationAdvice::Recommended,
+                AutomaticSelfCalibration::Inactive | AutomaticSelfCalibration::Unknown(_) => {
+                    CalibrationAdvice::Required
+                }
+            },
+            _ => CalibrationAdvice::Ok,
+        };
+        let age_advice = match (asc, since_last_frc_ms) {
+            (AutomaticSelfCalibration::Active, _) => CalibrationAdvice::Ok,
+            (_, None) => CalibrationAdvice::Recommended,
+            (_, Some(age)) if age >= self.require_after_ms => CalibrationAdvice::Required,
+            (_, Some(age)) if age >= self.recommend_after_ms => CalibrationAdvice::Recommended,
+            (_, Some(_)) => CalibrationAdvice::Ok,
+        };
+        drift_advice.max(age_advice)
+    }
+}
+
+/// Observes the difference between the measured temperature and a reference temperature to
+/// derive the [TemperatureOffset] compensating the self-heating of the sensor's enclosure.
+///
+/// Samples taken within `settle_ms` after the first one are ignored, as the enclosure is still
+/// warming up. The difference is considered steady once the last `N` samples spread by no more
+/// than the tolerance.
+pub struct TemperatureOffsetAdvisor<const N: usize> {
+    settle_ms: u64,
+    tolerance: f32,
+    start_ms: Option<u64>,
+    deltas: Deque<f32, N>,
+}
+
+impl<const N: usize> TemperatureOffsetAdvisor<N> {
+    /// Creates an advisor ignoring the first `settle_ms` milliseconds of samples and accepting a
+    /// spread of `tolerance` °C of the steady-state difference.
+    pub const fn new(settle_ms: u64, tolerance: f32) -> Self {
+        Self {
+            settle_ms,
+            tolerance,
+            start_ms: None,
+            deltas: Deque::new(),
+        }
+    }
+
+    /// Adds a measurement taken at `timestamp_ms` together with the `reference` temperature in °C
+    /// at the same time. Samples with an invalid temperature are ignored.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: &Measurement, reference: f32) {
+        let delta = measurement.temperature - reference;
+        if delta.is_nan() {
+            return;
+        }
+        let start_ms = *self.start_ms.get_or_insert(timestamp_ms);
+        if timestamp_ms.saturating_sub(start_ms) < self.settle_ms {
+            return;
+        }
+        if self.deltas.is_full() {
+            self.deltas.pop_front();
+        }
+        // Space was made above, so the difference always fits.
+        let _ = self.deltas.push_back(delta);
+    }
+
+    /// Returns whether the last `N` differences spread by no more than the tolerance.
+    pub fn is_steady(&self) -> bool {
+        let min = self.deltas.iter().copied().reduce(f32::min);
+        let max = self.deltas.iter().copied().reduce(f32::max);
+        match (self.deltas.is_full(), min, max) {
+            (true, Some(min), Some(max)) => max - min <= self.tolerance,
+            _ => false,
+        }
+    }
+
+    /// Returns the mean steady-state difference between the measured and the reference
+    /// temperature in °C. `None` until the difference is steady.
+    pub fn delta(&self) -> Option<f32> {
+        self.is_steady()
+            .then(|| self.deltas.iter().sum::<f32>() / self.deltas.len() as f32)
+    }
+
+    /// Returns the temperature offset compensating the steady-state difference, given the
+    /// offset `current` in place while the samples were taken. `None` until the difference is
+    /// steady or if it is already within the tolerance.
+    ///
+    /// The sensor only compensates self-heating, so a reading below the reference lowers the
+    /// offset to at most 0 °C.
+    pub fn recommend(&self, current: TemperatureOffset) -> Option<TemperatureOffset> {
+        let delta = self.delta().filter(|delta| abs(*delta) > self.tolerance)?;
+        TemperatureOffset::try_from((current.celsius() + delta).max(0.0)).ok()
+    }
+
+    /// Discards all samples, e.g. after a new offset was applied.
+    pub fn reset(&mut self) {
+        self.start_ms = None;
+        self.deltas.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MS: u64 = 86_400_000;
+
+    fn co2(co2_concentration: f32) -> Measurement {
+        Measurement {
+            co2_concentration,
+            temperature: 20.0,
+            humidity: 50.0,
+        }
+    }
+
+    #[test]
+    fn baseline_requires_completed_window() {
+        let mut detector = DriftDetector::<7>::new(DAY_MS, 50.0);
+        detector.push(0, &co2(410.0));
+        assert_eq!(detector.baseline(), None);
+        assert!(!detector.is_drifted());
+        detector.push(DAY_MS, &co2(900.0));
+        assert_eq!(detector.baseline(), Some(410.0));
+        assert_eq!(detector.drift(), Some(10.0));
+        assert!(!detector.is_drifted());
+    }
+
+    #[test]
+    fn drift_is_detected_in_both_directions() {
+        let mut detector = DriftDetector::<7>::new(DAY_MS, 50.0);
+        detector.push(0, &co2(520.0));
+        detector.push(1000, &co2(480.0));
+        detector.push(DAY_MS, &co2(900.0));
+        assert_eq!(detector.drift(), Some(80.0));
+        assert!(detector.is_drifted());
+
+        detector.reset();
+        detector.push(0, &co2(300.0));
+        detector.push(DAY_MS, &co2(900.0));
+        assert!(detector.is_drifted());
+    }
+
+    std::thread_local! {
+        static DRIFT_EVENTS: core::cell::Cell<u32> = const { core::cell::Cell::new(0) };
+    }
+
+    fn count_drift(event: Scd30Event) {
+        assert_eq!(event, Scd30Event::DriftDetected { drift_ppm: 80.0 });
+        DRIFT_EVENTS.with(|events| events.set(events.get() + 1));
+    }
+
+    #[test]
+    fn drift_event_is_reported_once() {
+        let mut detector = DriftDetector::<7>::new(DAY_MS, 50.0).on_event(count_drift);
+        detector.push(0, &co2(480.0));
+        detector.push(DAY_MS, &co2(900.0));
+        detector.push(2 * DAY_MS, &co2(900.0));
+        assert!(detector.is_drifted());
+        assert_eq!(DRIFT_EVENTS.with(|events| events.get()), 1);
+    }
+
+    #[test]
+    fn baseline_rolls_over_last_windows() {
+        let mut detector = DriftDetector::<2>::new(DAY_MS, 50.0);
+        for (day, value) in [300.0, 500.0, 520.0, 900.0].into_iter().enumerate() {
+            detector.push(day as u64 * DAY_MS, &co2(value));
+        }
+        assert_eq!(detector.baseline(), Some(500.0));
+    }
+
+    fn temperature(temperature: f32) -> Measurement {
+        Measurement {
+            co2_concentration: 400.0,
+            temperature,
+            humidity: 50.0,
+        }
+    }
+
+    #[test]
+    fn offset_is_recommended_once_difference_settled() {
+        let mut advisor = TemperatureOffsetAdvisor::<3>::new(60_000, 0.3);
+        let current = TemperatureOffset::try_from(1.0).unwrap();
+        advisor.push(0, &temperature(21.0), 20.0);
+        advisor.push(30_000, &temperature(22.0), 20.0);
+        for (minute, value) in [22.4, 22.5, 22.6].into_iter().enumerate() {
+            assert_eq!(advisor.recommend(current), None);
+            advisor.push(60_000 * (minute as u64 + 1), &temperature(value), 20.0);
+        }
+        assert!(advisor.is_steady());
+        assert!(abs(advisor.delta().unwrap() - 2.5) < 0.01);
+        let offset = advisor.recommend(current).unwrap();
+        assert!(abs(offset.celsius() - 3.5) < 0.01);
+
+        advisor.reset();
+        assert_eq!(advisor.delta(), None);
+    }
+
+    #[test]
+    fn unsteady_or_compensated_difference_is_not_acted_on() {
+        let current = TemperatureOffset::try_from(1.0).unwrap();
+        let mut advisor = TemperatureOffsetAdvisor::<3>::new(0, 0.3);
+        for (second, value) in [21.0, 22.0, 23.0].into_iter().enumerate() {
+            advisor.push(1000 * second as u64, &temperature(value), 20.0);
+        }
+        assert!(!advisor.is_steady());
+        assert_eq!(advisor.recommend(current), None);
+
+        advisor.reset();
+        for (second, value) in [20.1, 20.0, 19.9].into_iter().enumerate() {
+            advisor.push(1000 * second as u64, &temperature(value), 20.0);
+        }
+        assert!(advisor.is_steady());
+        assert_eq!(advisor.recommend(current), None);
+
+        advisor.reset();
+        for second in 0..3 {
+            advisor.push(1000 * second, &temperature(18.0), 20.0);
+        }
+        assert_eq!(
+            advisor.recommend(current),
+            Some(TemperatureOffset::try_from(0.0).unwrap())
+        );
+    }
+
+    const ADVISOR: CalibrationAdvisor = CalibrationAdvisor {
+        recommend_after_ms: 180 * DAY_MS,
+        require_after_ms: 365 * DAY_MS,
+    };
+
+    fn detector_with_baseline(baseline: f32) -> DriftDetector<7> {
+        let mut detector = DriftDetector::new(DAY_MS, 50.0);
+        detector.push(0, &co2(baseline));
+        detector.push(DAY_MS, &co2(900.0));
+        detector
+    }
+
+    #[test]
+    fn advice_follows_drift() {
+        let asc = AutomaticSelfCalibration::Active;
+        assert_eq!(
+            ADVISOR.advise(&detector_with_baseline(420.0), None, asc),
+            CalibrationAdvice::Ok
+        );
+        assert_eq!(
+            ADVISOR.advise(&detector_with_baseline(470.0), None, asc),
+            CalibrationAdvice::Recommended
+        );
+        assert_eq!(
+            ADVISOR.advise(&detector_with_baseline(520.0), None, asc),
+            CalibrationAdvice::Required
+        );
+        assert_eq!(
+            ADVISOR.advise(
+                &detector_with_baseline(470.0),
+                Some(0),
+                AutomaticSelfCalibration::Inactive
+            ),
+            CalibrationAdvice::Required
+        );
+    }
+
+    #[test]
+    fn advice_follows_time_since_frc_without_asc() {
+        let detector = DriftDetector::<7>::new(DAY_MS, 50.0);
+        let asc = AutomaticSelfCalibration::Inactive;
+        assert_eq!(
+            ADVISOR.advise(&detector, Some(DAY_MS), asc),
+            CalibrationAdvice::Ok
+        );
+        assert_eq!(
+            ADVISOR.advise(&detector, Some(200 * DAY_MS), asc),
+            CalibrationAdvice::Recommended
+        );
+        assert_eq!(
+            ADVISOR.advise(&detector, Some(400 * DAY_MS), asc),
+            CalibrationAdvice::Required
+        );
+        assert_eq!(
+            ADVISOR.advise(&detector, None, asc),
+            CalibrationAdvice::Recommended
+        );
+        assert_eq!(
+            ADVISOR.advise(&detector, None, AutomaticSelfCalibration::Active),
+            CalibrationAdvice::Ok
+        );
+    }
+}
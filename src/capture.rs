@@ -0,0 +1,206 @@
+//! Dry-run bus recording the frames the driver would send.
+//!
+//! [CapturingBus] implements the I2C traits without touching any hardware. Written frames are
+//! recorded instead of being sent, reads are recorded and answered with zeroed words carrying
+//! valid CRCs, as an idle sensor would. This shows what the driver sends for a sequence of calls
+//! and helps generating documentation and test fixtures:
+//!
+//! ```ignore
+//! use scd30_interface::{blocking::Scd30, capture::CapturingBus};
+//!
+//! let mut sensor = Scd30::new(CapturingBus::<8>::new());
+//! sensor.trigger_continuous_measurements(None)?;
+//! for frame in sensor.shutdown().frames() {
+//!     println!("{frame}");
+//! }
+//! ```
+
+use core::{convert::Infallible, fmt};
+
+use embedded_hal::i2c::{ErrorType, Operation, SevenBitAddress};
+
+use crate::codec::compute_crc8;
+
+/// Longest frame written by the driver, a command followed by an argument word and its CRC.
+/// Longer writes are truncated to this length.
+pub const MAX_WRITE_LEN: usize = 5;
+
+/// I2C frame recorded by the [CapturingBus].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Frame {
+    /// Bytes written to the device at `address`.
+    Write {
+        /// 7-bit address of the device.
+        address: u8,
+        /// Written bytes.
+        bytes: heapless::Vec<u8, MAX_WRITE_LEN>,
+    },
+    /// Read of `len` bytes from the device at `address`.
+    Read {
+        /// 7-bit address of the device.
+        address: u8,
+        /// Number of bytes read.
+        len: usize,
+    },
+}
+
+impl fmt::Display for Frame {
+    /// Formats the frame like `W 0x61: 00 10 00 00 81` or `R 0x61: 3 bytes`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frame::Write { address, bytes } => {
+                write!(f, "W {:#04x}:", address)?;
+                bytes.iter().try_for_each(|byte| write!(f, " {:02X}", byte))
+            }
+            Frame::Read { address, len } => write!(f, "R {:#04x}: {} bytes", address, len),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Frame {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Frame::Write { address, bytes } => {
+                defmt::write!(f, "W {=u8:#04x}: {=[u8]:02X}", address, bytes)
+            }
+            Frame::Read { address, len } => {
+                defmt::write!(f, "R {=u8:#04x}: {} bytes", address, len)
+            }
+        }
+    }
+}
+
+/// I2C bus recording up to `N` frames instead of sending them, see the
+/// [module documentation](self).
+#[derive(Debug, Default)]
+pub struct CapturingBus<const N: usize> {
+    frames: heapless::Vec<Frame, N>,
+    dropped: u32,
+}
+
+impl<const N: usize> CapturingBus<N> {
+    /// Creates a bus without recorded frames.
+    pub const fn new() -> Self {
+        Self {
+            frames: heapless::Vec::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Returns the recorded frames in the order they were issued.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Returns the number of frames which were not recorded, as the buffer was full.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Discards all recorded frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.dropped = 0;
+    }
+
+    fn record(&mut self, frame: Frame) {
+        if self.frames.push(frame).is_err() {
+            self.dropped = self.dropped.saturating_add(1);
+        }
+    }
+
+    fn capture_write(&mut self, address: SevenBitAddress, write: &[u8]) {
+        let bytes = &write[..write.len().min(MAX_WRITE_LEN)];
+        self.record(Frame::Write {
+            address,
+            // Can not fail, as the slice fits into the buffer.
+            bytes: heapless::Vec::from_slice(bytes).unwrap_or_default(),
+        });
+    }
+
+    fn capture_read(&mut self, address: SevenBitAddress, read: &mut [u8]) {
+        self.record(Frame::Read {
+            address,
+            len: read.len(),
+        });
+        // Zeroed words with valid CRCs, trailing bytes of incomplete words are zeroed.
+        read.fill(0);
+        let crc = compute_crc8(&[0, 0]);
+        read.chunks_exact_mut(3).for_each(|word| word[2] = crc);
+    }
+
+    fn capture(&mut self, address: SevenBitAddress, operations: &mut [Operation<'_>]) {
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => self.capture_read(address, read),
+                Operation::Write(write) => self.capture_write(address, write),
+            }
+        }
+    }
+}
+
+impl<const N: usize> ErrorType for CapturingBus<N> {
+    type Error = Infallible;
+}
+
+impl<const N: usize> embedded_hal::i2c::I2c for CapturingBus<N> {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.capture(address, operations);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<const N: usize> embedded_hal_async::i2c::I2c for CapturingBus<N> {
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.capture(address, operations);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "blocking")]
+mod tests {
+    use super::*;
+    use crate::{blocking::Scd30, data::DataStatus};
+    use std::string::ToString;
+
+    #[test]
+    fn frames_are_recorded_instead_of_sent() {
+        let mut sensor = Scd30::new(CapturingBus::<3>::new());
+
+        sensor.trigger_continuous_measurements(None).unwrap();
+        assert_eq!(sensor.is_data_ready().unwrap(), DataStatus::NotReady);
+
+        let bus = sensor.shutdown();
+        let frames: std::vec::Vec<_> = bus.frames().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            frames,
+            ["W 0x61: 00 10 00 00 81", "W 0x61: 02 02", "R 0x61: 3 bytes"]
+        );
+        assert_eq!(bus.dropped(), 0);
+    }
+
+    #[test]
+    fn frames_beyond_capacity_are_dropped() {
+        let mut sensor = Scd30::new(CapturingBus::<2>::new());
+
+        sensor.is_data_ready().unwrap();
+        sensor.is_data_ready().unwrap();
+
+        let mut bus = sensor.shutdown();
+        assert_eq!(bus.frames().len(), 2);
+        assert_eq!(bus.dropped(), 2);
+        bus.clear();
+        assert!(bus.frames().is_empty());
+    }
+}
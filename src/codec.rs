@@ -0,0 +1,246 @@
+//! CRC and frame validation of the SCD30's I2C protocol.
+//!
+//! Every 16-bit word the sensor sends or receives as argument is followed by a CRC-8. These
+//! functions are used by the driver itself and can be reused by custom transports or log
+//! decoders:
+//!
+//! ```
+//! use scd30_interface::codec::{compute_crc8, crc8_matches, first_invalid_word, validate_frame};
+//!
+//! assert_eq!(compute_crc8(&[0x03, 0x42]), 0xF3);
+//! assert!(crc8_matches(&[0x03, 0x42], 0xF3));
+//!
+//! let frame = [0x00, 0x02, 0xE3, 0x01, 0xF4, 0x00];
+//! assert!(validate_frame(&frame[..3]).is_ok());
+//! assert_eq!(first_invalid_word(&frame), Some(1));
+//! ```
+//!
+//! Responses are parsed by constructing a [Frame], which checks the CRCs once and gives access to
+//! the words. See [sensirion](crate::sensirion) to encode and decode whole frames.
+
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::indexing_slicing,
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used
+    )
+)]
+
+use crate::error::DataError;
+
+/// Size of a word on the wire: two data bytes followed by their CRC.
+pub const WORD_LEN: usize = 3;
+
+const INITIAL: u8 = 0xFF;
+const XOR: u8 = 0x31;
+
+/// Computes a CRC-8 according to NRSC-5
+/// width=8 poly=0x31 init=0xff refin=false refout=false xorout=0x00 check=0xf7 residue=0x00 name="CRC-8/NRSC-5"
+pub const fn compute_crc8(data: &[u8]) -> u8 {
+    let mut crc = INITIAL;
+    let mut data = data;
+    while let [byte, rest @ ..] = data {
+        crc ^= *byte;
+        let mut bit = 0;
+        while bit < 8 {
+            if (crc & 0x80) != 0 {
+                crc = (crc << 1) ^ XOR;
+            } else {
+                crc <<= 1;
+            }
+            bit += 1;
+        }
+        data = rest;
+    }
+    crc
+}
+
+/// Returns whether `crc` is the CRC-8 of `data`.
+pub const fn crc8_matches(data: &[u8], crc: u8) -> bool {
+    compute_crc8(data) == crc
+}
+
+/// Returns the index of the first word in `frame` whose CRC does not match, if any. A trailing
+/// incomplete word is ignored.
+pub fn first_invalid_word(frame: &[u8]) -> Option<usize> {
+    frame
+        .chunks_exact(WORD_LEN)
+        .position(|chunk| !matches!(*chunk, [msb, lsb, crc] if crc8_matches(&[msb, lsb], crc)))
+}
+
+/// Checks that `frame` consists of whole words with matching CRCs, e.g. a response of the sensor
+/// or the arguments of a command without its command word.
+///
+/// # Errors
+///
+/// - [ReceivedBufferWrongSize](DataError::ReceivedBufferWrongSize) if the length of `frame` is not
+///   a multiple of [WORD_LEN].
+/// - [CrcFailed](DataError::CrcFailed) if the CRC of a word does not match.
+pub fn validate_frame(frame: &[u8]) -> Result<(), DataError> {
+    if frame.len() % WORD_LEN != 0 {
+        return Err(DataError::ReceivedBufferWrongSize);
+    }
+    match first_invalid_word(frame) {
+        Some(_) => Err(DataError::CrcFailed),
+        None => Ok(()),
+    }
+}
+
+/// Frame of `WORDS` words received from the sensor. The CRC of every word is checked when the
+/// frame is constructed, so the words can be accessed afterwards without further checks:
+///
+/// ```
+/// use scd30_interface::codec::Frame;
+///
+/// let frame = Frame::<2>::new(&[0x00, 0x02, 0xE3, 0x01, 0xF4, 0x33]).unwrap();
+/// assert_eq!(frame.words(), [0x0002, 0x01F4]);
+/// assert_eq!(frame.word(1), Some(0x01F4));
+/// assert!(Frame::<2>::new(&[0x00, 0x02, 0xE3, 0x01, 0xF4, 0x00]).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frame<const WORDS: usize> {
+    words: [[u8; 2]; WORDS],
+}
+
+impl<const WORDS: usize> Frame<WORDS> {
+    /// Length of the frame on the wire, including the CRCs.
+    pub const LEN: usize = WORDS * WORD_LEN;
+
+    /// Checks the length of `data` and the CRC of every word and returns the frame.
+    ///
+    /// # Errors
+    ///
+    /// - [ReceivedBufferWrongSize](DataError::ReceivedBufferWrongSize) if `data` does not hold
+    ///   exactly `WORDS` words with their CRCs.
+    /// - [CrcFailed](DataError::CrcFailed) if the CRC of a word does not match.
+    pub fn new(data: &[u8]) -> Result<Self, DataError> {
+        if data.len() != Self::LEN {
+            return Err(DataError::ReceivedBufferWrongSize);
+        }
+        let mut words = [[0; 2]; WORDS];
+        for (word, chunk) in words.iter_mut().zip(data.chunks_exact(WORD_LEN)) {
+            match *chunk {
+                [msb, lsb, crc] if crc8_matches(&[msb, lsb], crc) => *word = [msb, lsb],
+                _ => return Err(DataError::CrcFailed),
+            }
+        }
+        Ok(Self { words })
+    }
+
+    /// Returns the words of the frame.
+    pub fn words(&self) -> [u16; WORDS] {
+        self.words.map(u16::from_be_bytes)
+    }
+
+    /// Returns the bytes of the words of the frame, without their CRCs.
+    pub const fn word_bytes(&self) -> [[u8; 2]; WORDS] {
+        self.words
+    }
+
+    /// Returns the word at `index`, `None` if the frame is shorter.
+    pub fn word(&self, index: usize) -> Option<u16> {
+        self.words.get(index).copied().map(u16::from_be_bytes)
+    }
+
+    /// Returns the big-endian float spanning the words at `index` and `index + 1`, e.g. a value of
+    /// a measurement. Returns `None` if the frame is shorter.
+    pub fn float(&self, index: usize) -> Option<f32> {
+        match self.words.get(index..)? {
+            [[b0, b1], [b2, b3], ..] => Some(f32::from_be_bytes([*b0, *b1, *b2, *b3])),
+            _ => None,
+        }
+    }
+}
+
+impl<const WORDS: usize> TryFrom<&[u8]> for Frame<WORDS> {
+    type Error = DataError;
+
+    /// Converts received data to a [Frame], see [Frame::new].
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::new(data)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const WORDS: usize> defmt::Format for Frame<WORDS> {
+    fn format(&self, f: defmt::Formatter) {
+        let words = self.words();
+        defmt::write!(f, "Frame({=[?]})", words.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_data_crc_computes_properly() {
+        let result = compute_crc8(&[0xBE, 0xEF]);
+        assert_eq!(result, 0x92);
+    }
+
+    #[test]
+    fn sample_data_2_crc_computes_properly() {
+        let result = compute_crc8(&[0x43, 0xDB]);
+        assert_eq!(result, 0xCB);
+    }
+
+    #[test]
+    fn sample_firmware_version_crc_computes_properly() {
+        let result = compute_crc8(&[0x03, 0x42]);
+        assert_eq!(result, 0xF3);
+    }
+
+    #[test]
+    fn zero_data_crc_computes_properly() {
+        let result = compute_crc8(&[0x00]);
+        assert_eq!(result, 0xAC);
+    }
+
+    #[test]
+    fn frames_are_validated_word_by_word() {
+        let measurement = [
+            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42, 0x43,
+            0xBF, 0x3A, 0x1B, 0x74,
+        ];
+        assert_eq!(validate_frame(&measurement), Ok(()));
+        assert_eq!(first_invalid_word(&measurement), None);
+        assert_eq!(
+            validate_frame(&measurement[..4]),
+            Err(DataError::ReceivedBufferWrongSize)
+        );
+
+        let mut corrupted = measurement;
+        corrupted[10] ^= 0x01;
+        assert_eq!(validate_frame(&corrupted), Err(DataError::CrcFailed));
+        assert_eq!(first_invalid_word(&corrupted), Some(3));
+    }
+
+    #[test]
+    fn frames_check_length_and_crc_of_every_word() {
+        let measurement = [
+            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42, 0x43,
+            0xBF, 0x3A, 0x1B, 0x74,
+        ];
+        let frame = Frame::<6>::new(&measurement).unwrap();
+        assert_eq!(frame.word(0), Some(0x43DB));
+        assert_eq!(frame.word(6), None);
+        assert_eq!(frame.float(0), Some(439.09515));
+        assert_eq!(frame.float(4), Some(48.806744));
+        assert_eq!(frame.float(5), None);
+
+        assert_eq!(
+            Frame::<6>::new(&measurement[..15]),
+            Err(DataError::ReceivedBufferWrongSize)
+        );
+        assert_eq!(
+            Frame::<1>::new(&measurement[..4]),
+            Err(DataError::ReceivedBufferWrongSize)
+        );
+        let mut corrupted = measurement;
+        corrupted[17] ^= 0x01;
+        assert_eq!(Frame::<6>::new(&corrupted), Err(DataError::CrcFailed));
+    }
+}
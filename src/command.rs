@@ -1,8 +1,10 @@
 //! SCD30 I2C Commands.
 
+use crate::{error::DataError, sensirion};
+
 /// I2C Commands for the SCD30 according to its [interface
 /// description](https://sensirion.com/media/documents/D7CEEF4A/6165372F/Sensirion_CO2_Sensors_SCD30_Interface_Description.pdf)
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Command {
     /// Enable continuous measurements with an ambient pressure compensation. The ambient pressure
     /// compensation is sent as an argument after the command. Setting it to 0 uses the default
@@ -42,9 +44,105 @@ pub enum Command {
 }
 
 impl Command {
+    /// All commands supported by the SCD30.
+    pub const ALL: [Command; 11] = [
+        Command::TriggerContinuousMeasurement,
+        Command::StopContinuousMeasurement,
+        Command::SetMeasurementInterval,
+        Command::GetDataReady,
+        Command::ReadMeasurement,
+        Command::ActivateAutomaticSelfCalibration,
+        Command::ForcedRecalibrationValue,
+        Command::SetTemperatureOffset,
+        Command::SetAltitudeCompensation,
+        Command::ReadFirmwareVersion,
+        Command::SoftReset,
+    ];
+
+    /// Returns the raw 16-bit opcode of the command.
+    pub const fn opcode(&self) -> u16 {
+        *self as u16
+    }
+
     /// Returns a big endian byte representation of the command.
-    pub fn to_be_bytes(&self) -> [u8; 2] {
-        (*self as u16).to_be_bytes()
+    pub const fn to_be_bytes(&self) -> [u8; 2] {
+        sensirion::encode_command(self.opcode())
+    }
+
+    /// Returns the number of bytes the sensor responds with, if the command is sent without an
+    /// argument. Returns `None` for commands without a response.
+    pub const fn response_len(&self) -> Option<usize> {
+        match self {
+            Command::StopContinuousMeasurement | Command::SoftReset => None,
+            Command::ReadMeasurement => Some(18),
+            Command::TriggerContinuousMeasurement
+            | Command::SetMeasurementInterval
+            | Command::GetDataReady
+            | Command::ActivateAutomaticSelfCalibration
+            | Command::ForcedRecalibrationValue
+            | Command::SetTemperatureOffset
+            | Command::SetAltitudeCompensation
+            | Command::ReadFirmwareVersion => Some(3),
+        }
+    }
+
+    /// Returns the complete frame for sending the command followed by a 16-bit `argument` and its
+    /// CRC. Can be evaluated at compile time for fixed arguments.
+    pub const fn encode_with_argument(&self, argument: [u8; 2]) -> [u8; 5] {
+        sensirion::encode_with_argument(self.opcode(), argument)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Command {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u16:#06X}", self.opcode())
+    }
+}
+
+impl From<Command> for u16 {
+    fn from(command: Command) -> Self {
+        command.opcode()
+    }
+}
+
+impl TryFrom<u16> for Command {
+    type Error = DataError;
+
+    /// Converts a raw opcode to a [Command].
+    ///
+    /// # Errors
+    ///
+    /// - [UnknownCommand](crate::error::DataError::UnknownCommand) if `opcode` does not belong to
+    ///   any command of the SCD30.
+    fn try_from(opcode: u16) -> Result<Self, Self::Error> {
+        Command::ALL
+            .into_iter()
+            .find(|command| command.opcode() == opcode)
+            .ok_or(DataError::UnknownCommand { opcode })
+    }
+}
+
+pub(crate) mod sealed {
+    pub trait Sealed {}
+}
+
+/// Data read out from the sensor as the response to a [Command]. Ties each response type to the
+/// command reading it and a receive buffer of the correct size, making it impossible to read a
+/// response into a wrongly sized buffer.
+///
+/// This trait is sealed and implemented for all responses of the SCD30.
+pub trait Response: sealed::Sealed + for<'a> TryFrom<&'a [u8], Error = DataError> {
+    /// Command reading out the response.
+    const COMMAND: Command;
+    /// Receive buffer fitting the response, including its CRCs. The response is parsed from the
+    /// buffer without checking its length at runtime.
+    type Buffer: AsRef<[u8]> + AsMut<[u8]> + Default + TryInto<Self, Error = DataError>;
+
+    /// Represents a received `value` outside the documented range, if the response has a
+    /// variant for it. Used by [ValueDecoding::Lenient](crate::options::ValueDecoding::Lenient).
+    fn unknown(_value: u16) -> Option<Self> {
+        None
     }
 }
 
@@ -73,4 +171,88 @@ mod tests {
             assert_eq!(command.to_be_bytes(), result);
         }
     }
+
+    #[test]
+    fn encode_with_argument_spec_examples_work() {
+        use Command::*;
+        let data = [
+            (
+                TriggerContinuousMeasurement,
+                [0x00, 0x00],
+                [0x00, 0x10, 0x00, 0x00, 0x81],
+            ),
+            (
+                SetMeasurementInterval,
+                [0x00, 0x02],
+                [0x46, 0x00, 0x00, 0x02, 0xE3],
+            ),
+            (
+                ForcedRecalibrationValue,
+                [0x01, 0xC2],
+                [0x52, 0x04, 0x01, 0xC2, 0x50],
+            ),
+        ];
+
+        for (command, argument, result) in data {
+            assert_eq!(command.encode_with_argument(argument), result);
+        }
+    }
+
+    #[test]
+    fn encode_with_argument_is_const() {
+        const FRAME: [u8; 5] = Command::SetAltitudeCompensation.encode_with_argument([0x03, 0xE8]);
+        assert_eq!(FRAME, [0x51, 0x02, 0x03, 0xE8, 0xD4]);
+    }
+
+    #[test]
+    fn opcode_round_trips() {
+        for command in Command::ALL {
+            assert_eq!(Command::try_from(command.opcode()).unwrap(), command);
+            assert_eq!(u16::from(command), command.opcode());
+        }
+    }
+
+    #[test]
+    fn spec_opcodes_convert() {
+        assert_eq!(Command::try_from(0x0300).unwrap(), Command::ReadMeasurement);
+        assert_eq!(Command::try_from(0xD304).unwrap(), Command::SoftReset);
+    }
+
+    #[test]
+    fn unknown_opcode_errors() {
+        assert_eq!(
+            Command::try_from(0x1234).unwrap_err(),
+            DataError::UnknownCommand { opcode: 0x1234 }
+        );
+    }
+
+    fn buffer_len<R: Response>() -> usize {
+        R::Buffer::default().as_ref().len()
+    }
+
+    fn assert_response_len<R: Response>() {
+        assert_eq!(R::COMMAND.response_len(), Some(buffer_len::<R>()));
+    }
+
+    #[test]
+    fn response_buffers_match_response_len() {
+        use crate::data::*;
+        assert_response_len::<AmbientPressureCompensation>();
+        assert_response_len::<MeasurementInterval>();
+        assert_response_len::<DataStatus>();
+        assert_response_len::<Measurement>();
+        assert_response_len::<AutomaticSelfCalibration>();
+        assert_response_len::<ForcedRecalibrationValue>();
+        assert_response_len::<TemperatureOffset>();
+        assert_response_len::<AltitudeCompensation>();
+        assert_response_len::<FirmwareVersion>();
+    }
+
+    #[test]
+    fn commands_without_response_have_no_len() {
+        use Command::*;
+        for command in [StopContinuousMeasurement, SoftReset] {
+            assert_eq!(command.response_len(), None);
+        }
+    }
 }
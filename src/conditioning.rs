@@ -0,0 +1,341 @@
+//! Tracking of the conditioning period of a new sensor.
+//!
+//! A fresh sensor needs several days of operation until its readings are stable. The
+//! [ConditioningTracker] accumulates the powered time of the sensor and compares the lowest CO2
+//! concentration of consecutive windows, e.g. days, to tell when the sensor is conditioned.
+//! Products can show "sensor still stabilizing" until
+//! [is_conditioned](ConditioningTracker::is_conditioned) returns `true`.
+//!
+//! The progress spans power cycles, so it is kept in a [ConditioningState], which is passed to
+//! the persistence hook after every completed window and restored on start-up:
+//!
+//! ```ignore
+//! let state = load(&mut flash).and_then(|bytes| ConditioningState::from_bytes(&bytes));
+//! let mut tracker = ConditioningTracker::new(ConditioningCriteria::default())
+//!     .with_state(state.unwrap_or_default())
+//!     .on_persist(|state| store(state.to_bytes()));
+//! loop {
+//!     tracker.push(clock(), &sensor.read_measurement()?);
+//!     display.set_stabilizing(!tracker.is_conditioned());
+//! }
+//! ```
+
+use crate::{codec::compute_crc8, data::Measurement, util::abs};
+
+/// Size of a serialized [ConditioningState] in bytes.
+pub const STATE_LEN: usize = 15;
+
+const VERSION: u8 = 1;
+const DAY_MS: u64 = 86_400_000;
+
+/// Hook called with the progress to persist.
+pub type PersistHook = fn(&ConditioningState);
+
+/// Criteria a sensor has to meet to be considered conditioned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConditioningCriteria {
+    /// Length of a baseline window in milliseconds.
+    pub window_ms: u64,
+    /// Powered time required in milliseconds.
+    pub powered_ms: u64,
+    /// Largest change of the baseline between consecutive windows in ppm, for which the baseline
+    /// is considered stable.
+    pub baseline_tolerance_ppm: f32,
+    /// Number of consecutive windows with a stable baseline required.
+    pub stable_windows: u8,
+}
+
+impl Default for ConditioningCriteria {
+    /// Seven days of operation, of which the last three days show a baseline stable within
+    /// 20 ppm.
+    fn default() -> Self {
+        Self {
+            window_ms: DAY_MS,
+            powered_ms: 7 * DAY_MS,
+            baseline_tolerance_ppm: 20.0,
+            stable_windows: 3,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ConditioningCriteria {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "ConditioningCriteria {{ window_ms: {}, powered_ms: {}, baseline_tolerance_ppm: {}, stable_windows: {} }}",
+            self.window_ms,
+            self.powered_ms,
+            self.baseline_tolerance_ppm,
+            self.stable_windows
+        )
+    }
+}
+
+/// Conditioning progress of a sensor, persisted across power cycles.
+///
+/// Serialized as [STATE_LEN] bytes, multi-byte values are big endian:
+///
+/// | Bytes | Content                                               |
+/// |-------|-------------------------------------------------------|
+/// | 0     | Version                                               |
+/// | 1..9  | Powered time in milliseconds                          |
+/// | 9..13 | Baseline of the last completed window in ppm, or NaN  |
+/// | 13    | Consecutive windows with a stable baseline            |
+/// | 14    | CRC of bytes 0..14                                    |
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConditioningState {
+    /// Accumulated powered time in milliseconds.
+    pub powered_ms: u64,
+    /// Lowest CO2 concentration of the last completed window in ppm.
+    pub baseline_ppm: Option<f32>,
+    /// Number of consecutive windows with a stable baseline.
+    pub stable_windows: u8,
+}
+
+impl ConditioningState {
+    /// Serializes the state for persistence.
+    pub fn to_bytes(&self) -> [u8; STATE_LEN] {
+        let mut bytes = [0; STATE_LEN];
+        bytes[0] = VERSION;
+        bytes[1..9].copy_from_slice(&self.powered_ms.to_be_bytes());
+        bytes[9..13].copy_from_slice(&self.baseline_ppm.unwrap_or(f32::NAN).to_be_bytes());
+        bytes[13] = self.stable_windows;
+        bytes[14] = compute_crc8(&bytes[..14]);
+        bytes
+    }
+
+    /// Deserializes a persisted state. Returns `None` for corrupted data or an unknown version,
+    /// in which case conditioning has to start over.
+    pub fn from_bytes(bytes: &[u8; STATE_LEN]) -> Option<Self> {
+        if bytes[0] != VERSION || compute_crc8(&bytes[..14]) != bytes[14] {
+            return None;
+        }
+        let mut powered_ms = [0; 8];
+        powered_ms.copy_from_slice(&bytes[1..9]);
+        let baseline_ppm = f32::from_be_bytes([bytes[9], bytes[10], bytes[11], bytes[12]]);
+        Some(Self {
+            powered_ms: u64::from_be_bytes(powered_ms),
+            baseline_ppm: (!baseline_ppm.is_nan()).then_some(baseline_ppm),
+            stable_windows: bytes[13],
+        })
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ConditioningState {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "ConditioningState {{ powered_ms: {}, baseline_ppm: {}, stable_windows: {} }}",
+            self.powered_ms,
+            self.baseline_ppm,
+            self.stable_windows
+        )
+    }
+}
+
+/// Tracks the conditioning of a new sensor, see the [module documentation](self).
+pub struct ConditioningTracker {
+    criteria: ConditioningCriteria,
+    state: ConditioningState,
+    last_ms: Option<u64>,
+    current: Option<(u64, f32)>,
+    on_persist: Option<PersistHook>,
+}
+
+impl ConditioningTracker {
+    /// Creates a tracker for a fresh sensor, which has to meet `criteria`. A window length of 0
+    /// is treated as 1 ms.
+    pub const fn new(criteria: ConditioningCriteria) -> Self {
+        let mut criteria = criteria;
+        if criteria.window_ms == 0 {
+            criteria.window_ms = 1;
+        }
+        Self {
+            criteria,
+            state: ConditioningState {
+                powered_ms: 0,
+                baseline_ppm: None,
+                stable_windows: 0,
+            },
+            last_ms: None,
+            current: None,
+            on_persist: None,
+        }
+    }
+
+    /// Continues tracking from a persisted `state`.
+    pub const fn with_state(mut self, state: ConditioningState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Calls `hook` with the progress whenever a window is completed.
+    pub const fn on_persist(mut self, hook: PersistHook) -> Self {
+        self.on_persist = Some(hook);
+        self
+    }
+
+    /// Adds a measurement taken at `timestamp_ms`. The time since the previous measurement counts
+    /// as powered time, so measurements should be pushed regularly while the sensor is powered.
+    /// Invalid CO2 concentrations only count towards the powered time.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: &Measurement) {
+        if let Some(last_ms) = self.last_ms.replace(timestamp_ms) {
+            self.state.powered_ms = self
+                .state
+                .powered_ms
+                .saturating_add(timestamp_ms.saturating_sub(last_ms));
+        }
+        let co2 = measurement.co2_concentration;
+        if co2.is_nan() {
+            return;
+        }
+        let start_ms = timestamp_ms - timestamp_ms % self.criteria.window_ms;
+        match &mut self.current {
+            Some((current_start, minimum)) if *current_start == start_ms => {
+                *minimum = minimum.min(co2);
+            }
+            Some((current_start, _)) if *current_start > start_ms => {}
+            _ => {
+                if let Some((_, minimum)) = self.current.replace((start_ms, co2)) {
+                    self.complete_window(minimum);
+                }
+            }
+        }
+    }
+
+    fn complete_window(&mut self, minimum: f32) {
+        let stable = self.state.baseline_ppm.is_some_and(|baseline| {
+            abs(minimum - baseline) <= self.criteria.baseline_tolerance_ppm
+        });
+        self.state.stable_windows = if stable {
+            self.state.stable_windows.saturating_add(1)
+        } else {
+            0
+        };
+        self.state.baseline_ppm = Some(minimum);
+        if let Some(hook) = self.on_persist {
+            hook(&self.state);
+        }
+    }
+
+    /// Returns the current progress, e.g. to persist it before powering down.
+    pub fn state(&self) -> ConditioningState {
+        self.state
+    }
+
+    /// Returns the powered time still required in milliseconds.
+    pub fn remaining_ms(&self) -> u64 {
+        self.criteria
+            .powered_ms
+            .saturating_sub(self.state.powered_ms)
+    }
+
+    /// Returns whether the sensor was powered long enough and its baseline is stable.
+    pub fn is_conditioned(&self) -> bool {
+        self.remaining_ms() == 0 && self.state.stable_windows >= self.criteria.stable_windows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    fn co2(co2_concentration: f32) -> Measurement {
+        Measurement {
+            co2_concentration,
+            temperature: 20.0,
+            humidity: 50.0,
+        }
+    }
+
+    fn criteria() -> ConditioningCriteria {
+        ConditioningCriteria {
+            window_ms: DAY_MS,
+            powered_ms: 2 * DAY_MS,
+            baseline_tolerance_ppm: 20.0,
+            stable_windows: 1,
+        }
+    }
+
+    #[test]
+    fn sensor_is_conditioned_after_powered_time_with_stable_baseline() {
+        let mut tracker = ConditioningTracker::new(criteria());
+        for (hour, value) in [(0, 480.0), (24, 430.0), (48, 420.0)] {
+            tracker.push(hour * HOUR_MS, &co2(value));
+            assert!(!tracker.is_conditioned());
+        }
+        assert_eq!(tracker.remaining_ms(), 0);
+        assert_eq!(tracker.state().stable_windows, 0);
+
+        tracker.push(72 * HOUR_MS, &co2(900.0));
+        assert_eq!(
+            tracker.state(),
+            ConditioningState {
+                powered_ms: 3 * DAY_MS,
+                baseline_ppm: Some(420.0),
+                stable_windows: 1,
+            }
+        );
+        assert!(tracker.is_conditioned());
+    }
+
+    #[test]
+    fn unstable_baseline_restarts_stability() {
+        let mut tracker = ConditioningTracker::new(criteria()).with_state(ConditioningState {
+            powered_ms: 2 * DAY_MS,
+            baseline_ppm: Some(420.0),
+            stable_windows: 3,
+        });
+        tracker.push(0, &co2(480.0));
+        tracker.push(DAY_MS, &co2(420.0));
+        assert_eq!(tracker.state().stable_windows, 0);
+        assert!(!tracker.is_conditioned());
+    }
+
+    std::thread_local! {
+        static PERSISTED: core::cell::Cell<Option<ConditioningState>> =
+            const { core::cell::Cell::new(None) };
+    }
+
+    fn persist(state: &ConditioningState) {
+        PERSISTED.with(|persisted| persisted.set(Some(*state)));
+    }
+
+    #[test]
+    fn progress_is_persisted_across_power_cycles() {
+        let mut tracker = ConditioningTracker::new(criteria()).on_persist(persist);
+        tracker.push(0, &co2(430.0));
+        tracker.push(12 * HOUR_MS, &co2(410.0));
+        assert_eq!(PERSISTED.with(|persisted| persisted.get()), None);
+        tracker.push(DAY_MS, &co2(500.0));
+
+        let state = PERSISTED.with(|persisted| persisted.get()).unwrap();
+        assert_eq!(state.baseline_ppm, Some(410.0));
+        let bytes = state.to_bytes();
+        assert_eq!(ConditioningState::from_bytes(&bytes), Some(state));
+
+        // Time between power cycles is not counted.
+        let mut tracker = ConditioningTracker::new(criteria())
+            .with_state(ConditioningState::from_bytes(&bytes).unwrap());
+        tracker.push(10 * DAY_MS, &co2(420.0));
+        assert_eq!(tracker.remaining_ms(), DAY_MS);
+    }
+
+    #[test]
+    fn corrupted_state_is_rejected() {
+        let mut bytes = ConditioningState::default().to_bytes();
+        assert_eq!(
+            ConditioningState::from_bytes(&bytes),
+            Some(ConditioningState::default())
+        );
+        bytes[4] ^= 0x01;
+        assert_eq!(ConditioningState::from_bytes(&bytes), None);
+        let mut bytes = ConditioningState::default().to_bytes();
+        bytes[0] = VERSION + 1;
+        assert_eq!(ConditioningState::from_bytes(&bytes), None);
+    }
+}
@@ -0,0 +1,265 @@
+//! Configuration files for provisioning tools, in TOML or JSON.
+//!
+//! A [ConfigFile] holds the settings of a [Scd30Config] in plain units. Loading it with
+//! [load_toml] or [load_json] validates every value into the runtime checked types, so invalid
+//! files are rejected before anything is written to a sensor:
+//!
+//! ```
+//! use scd30_interface::config_file::load_toml;
+//!
+//! let config = load_toml(
+//!     r#"
+//!     measurement_interval_s = 10
+//!     automatic_self_calibration = false
+//!     temperature_offset_c = 1.5
+//!     altitude_m = 520
+//!     "#,
+//! )
+//! .unwrap();
+//! assert_eq!(config.measurement_interval.unwrap().seconds(), 10);
+//! ```
+//!
+//! Settings missing in the file are left untouched, like `None` settings of a [Scd30Config].
+
+extern crate std;
+
+use std::string::String;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{
+        AltitudeCompensation, AmbientPressure, AmbientPressureCompensation,
+        AutomaticSelfCalibration, MeasurementInterval, Scd30Config, TemperatureOffset,
+    },
+    error::DataError,
+};
+
+/// Loads and validates a configuration from TOML.
+///
+/// # Errors
+///
+/// - [Toml](ConfigFileError::Toml) if `toml` is malformed or contains unknown settings.
+/// - [Invalid](ConfigFileError::Invalid) if a setting is out of range.
+pub fn load_toml(toml: &str) -> Result<Scd30Config, ConfigFileError> {
+    Ok(toml::from_str::<ConfigFile>(toml)?.try_into()?)
+}
+
+/// Loads and validates a configuration from JSON.
+///
+/// # Errors
+///
+/// - [Json](ConfigFileError::Json) if `json` is malformed or contains unknown settings.
+/// - [Invalid](ConfigFileError::Invalid) if a setting is out of range.
+pub fn load_json(json: &str) -> Result<Scd30Config, ConfigFileError> {
+    Ok(serde_json::from_str::<ConfigFile>(json)?.try_into()?)
+}
+
+/// Stores `config` as TOML.
+///
+/// # Errors
+///
+/// - [TomlSerialization](ConfigFileError::TomlSerialization) if serialization fails.
+/// - [Invalid](ConfigFileError::Invalid) if a setting can not be stored.
+pub fn store_toml(config: &Scd30Config) -> Result<String, ConfigFileError> {
+    Ok(toml::to_string(&ConfigFile::try_from(*config)?)?)
+}
+
+/// Stores `config` as pretty-printed JSON.
+///
+/// # Errors
+///
+/// - [Json](ConfigFileError::Json) if serialization fails.
+/// - [Invalid](ConfigFileError::Invalid) if a setting can not be stored.
+pub fn store_json(config: &Scd30Config) -> Result<String, ConfigFileError> {
+    Ok(serde_json::to_string_pretty(&ConfigFile::try_from(
+        *config,
+    )?)?)
+}
+
+/// Error loading or storing a configuration file.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigFileError {
+    /// The file is not valid TOML or does not match the expected structure.
+    #[error("Invalid TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// The configuration could not be serialized to TOML.
+    #[error("Serializing TOML failed: {0}")]
+    TomlSerialization(#[from] toml::ser::Error),
+    /// The file is not valid JSON or does not match the expected structure.
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A setting is out of the range accepted by the sensor.
+    #[error("Invalid setting: {0}")]
+    Invalid(#[from] DataError),
+}
+
+/// Settings of a [Scd30Config] in plain units, as stored in configuration files. Settings set to
+/// `None` are left untouched.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Starts continuous measurements after applying the other settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_continuous_measurements: Option<bool>,
+    /// Ambient pressure in mBar compensated when starting continuous measurements, the default
+    /// pressure of 1013.25 mBar is used if `None` or 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambient_pressure_mbar: Option<u16>,
+    /// Measurement interval in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub measurement_interval_s: Option<u16>,
+    /// State of the automatic self-calibration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub automatic_self_calibration: Option<bool>,
+    /// Temperature offset in °C.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_offset_c: Option<f32>,
+    /// Altitude above sea level in m.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub altitude_m: Option<u16>,
+}
+
+impl TryFrom<ConfigFile> for Scd30Config {
+    type Error = DataError;
+
+    /// Validates the settings of `file` into a [Scd30Config].
+    ///
+    /// # Errors
+    ///
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if a setting is out of
+    ///   range.
+    fn try_from(file: ConfigFile) -> Result<Self, Self::Error> {
+        let continuous_measurement = match (
+            file.start_continuous_measurements,
+            file.ambient_pressure_mbar,
+        ) {
+            (Some(true), None | Some(0)) => Some(AmbientPressureCompensation::DefaultPressure),
+            (Some(true), Some(pressure)) => {
+                Some(AmbientPressureCompensation::CompensationPressure(
+                    AmbientPressure::try_from(pressure)?,
+                ))
+            }
+            _ => None,
+        };
+        Ok(Scd30Config {
+            continuous_measurement,
+            measurement_interval: file
+                .measurement_interval_s
+                .map(MeasurementInterval::try_from)
+                .transpose()?,
+            automatic_self_calibration: file.automatic_self_calibration.map(|active| {
+                if active {
+                    AutomaticSelfCalibration::Active
+                } else {
+                    AutomaticSelfCalibration::Inactive
+                }
+            }),
+            temperature_offset: file
+                .temperature_offset_c
+                .map(TemperatureOffset::try_from)
+                .transpose()?,
+            altitude_compensation: file.altitude_m.map(AltitudeCompensation::from),
+        })
+    }
+}
+
+impl TryFrom<Scd30Config> for ConfigFile {
+    type Error = DataError;
+
+    /// Converts the settings of `config` to plain units.
+    ///
+    /// # Errors
+    ///
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if the automatic
+    ///   self-calibration is [Unknown](AutomaticSelfCalibration::Unknown).
+    fn try_from(config: Scd30Config) -> Result<Self, Self::Error> {
+        let raw = |bytes: [u8; 2]| u16::from_be_bytes(bytes);
+        Ok(Self {
+            start_continuous_measurements: config.continuous_measurement.map(|_| true),
+            ambient_pressure_mbar: match config.continuous_measurement {
+                Some(AmbientPressureCompensation::CompensationPressure(pressure)) => {
+                    Some(raw(pressure.to_be_bytes()))
+                }
+                _ => None,
+            },
+            measurement_interval_s: config
+                .measurement_interval
+                .map(|interval| interval.seconds()),
+            automatic_self_calibration: config
+                .automatic_self_calibration
+                .map(|setting| setting.is_active())
+                .transpose()?,
+            temperature_offset_c: config.temperature_offset.map(|offset| offset.celsius()),
+            altitude_m: config
+                .altitude_compensation
+                .map(|altitude| raw(altitude.to_be_bytes())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Scd30Config {
+        Scd30Config {
+            continuous_measurement: Some(AmbientPressureCompensation::CompensationPressure(
+                AmbientPressure::try_from(950).unwrap(),
+            )),
+            measurement_interval: Some(MeasurementInterval::try_from(10).unwrap()),
+            automatic_self_calibration: Some(AutomaticSelfCalibration::Inactive),
+            temperature_offset: Some(TemperatureOffset::try_from(1.5).unwrap()),
+            altitude_compensation: Some(AltitudeCompensation::from(520)),
+        }
+    }
+
+    #[test]
+    fn config_round_trips_through_toml_and_json() {
+        let toml = store_toml(&config()).unwrap();
+        assert_eq!(load_toml(&toml).unwrap(), config());
+        let json = store_json(&config()).unwrap();
+        assert_eq!(load_json(&json).unwrap(), config());
+    }
+
+    #[test]
+    fn missing_settings_are_left_untouched() {
+        let config = load_json(r#"{ "start_continuous_measurements": true }"#).unwrap();
+        assert_eq!(
+            config,
+            Scd30Config {
+                continuous_measurement: Some(AmbientPressureCompensation::DefaultPressure),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        assert!(matches!(
+            load_toml("measurement_interval_s = 1"),
+            Err(ConfigFileError::Invalid(DataError::ValueOutOfRange { .. }))
+        ));
+        assert!(matches!(
+            load_toml("interval = 10"),
+            Err(ConfigFileError::Toml(_))
+        ));
+        assert!(matches!(load_json("{"), Err(ConfigFileError::Json(_))));
+    }
+
+    #[test]
+    fn unknown_self_calibration_is_not_stored() {
+        let config = Scd30Config {
+            automatic_self_calibration: Some(AutomaticSelfCalibration::Unknown(2)),
+            ..config()
+        };
+        assert!(matches!(
+            store_toml(&config),
+            Err(ConfigFileError::Invalid(DataError::ValueOutOfRange { .. }))
+        ));
+        assert!(matches!(
+            store_json(&config),
+            Err(ConfigFileError::Invalid(DataError::ValueOutOfRange { .. }))
+        ));
+    }
+}
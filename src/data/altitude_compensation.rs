@@ -1,9 +1,11 @@
-use byteorder::{BigEndian, ByteOrder};
-
-use crate::{error::DataError, util::check_deserialization};
+use crate::{
+    command::{sealed::Sealed, Command, Response},
+    error::DataError,
+    util::{deserialize_word, sized_frame},
+};
 
 /// Altitude compensation value ranging from 0 m to 65535 m above sea level.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AltitudeCompensation(u16);
 
 #[cfg(feature = "defmt")]
@@ -26,6 +28,19 @@ impl From<u16> for AltitudeCompensation {
     }
 }
 
+impl TryFrom<[u8; 3]> for AltitudeCompensation {
+    type Error = DataError;
+
+    /// Converts a received frame to an [AltitudeCompensation] value.
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        Ok(Self(deserialize_word(&data)?))
+    }
+}
+
 impl TryFrom<&[u8]> for AltitudeCompensation {
     type Error = DataError;
 
@@ -37,8 +52,32 @@ impl TryFrom<&[u8]> for AltitudeCompensation {
     ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 3)?;
-        Ok(Self(BigEndian::read_u16(&data[..2])))
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for AltitudeCompensation {}
+
+impl Response for AltitudeCompensation {
+    const COMMAND: Command = Command::SetAltitudeCompensation;
+    type Buffer = [u8; 3];
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for AltitudeCompensation {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.int_in_range(0..=u16::MAX)?))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for AltitudeCompensation {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        (0..=u16::MAX).prop_map(Self).boxed()
     }
 }
 
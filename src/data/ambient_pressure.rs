@@ -1,9 +1,13 @@
-use crate::error::DataError;
+use crate::{
+    command::{sealed::Sealed, Command, Response},
+    error::DataError,
+    util::{deserialize_word, sized_frame},
+};
 
 /// A runtime checked representation of the ambient pressure compensation value used as an argument
 /// for the ambient pressure compensation during continuous measurements. Accepted value range:
 /// [700...1400] mBar.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AmbientPressure(u16);
 
 const MIN_AMBIENT_PRESSURE: u16 = 700;
@@ -51,8 +55,44 @@ impl TryFrom<u16> for AmbientPressure {
     }
 }
 
+impl TryFrom<[u8; 3]> for AmbientPressure {
+    type Error = DataError;
+
+    /// Converts a received frame to an [AmbientPressure] value.
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if the received value is lower than 700 or higher than
+    ///   1400 mBar.
+    /// - [UseDefaultPressure](crate::error::DataError::UseDefaultPressure) if the received value is 0, i.e. the default
+    ///   pressure is compensated.
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        Self::try_from(deserialize_word(&data)?)
+    }
+}
+
+impl TryFrom<&[u8]> for AmbientPressure {
+    type Error = DataError;
+
+    /// Converts buffered data to an [AmbientPressure] value.
+    ///
+    /// # Errors
+    ///
+    /// - [ReceivedBufferWrongSize](crate::error::DataError::ReceivedBufferWrongSize) if the `data` buffer is not big enough for the data
+    ///   that should have been received.
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if the received value is lower than 700 or higher than
+    ///   1400 mBar.
+    /// - [UseDefaultPressure](crate::error::DataError::UseDefaultPressure) if the received value is 0, i.e. the default
+    ///   pressure is compensated.
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
 /// Arguments for setting the ambient pressure compensation value.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AmbientPressureCompensation {
     /// Configures ambient pressure compensation to the default value of 1013.25 mBar
     DefaultPressure,
@@ -72,6 +112,51 @@ impl AmbientPressureCompensation {
     }
 }
 
+impl TryFrom<[u8; 3]> for AmbientPressureCompensation {
+    type Error = DataError;
+
+    /// Converts a received frame to an [AmbientPressureCompensation] value. A value of 0 is the
+    /// [DefaultPressure](AmbientPressureCompensation::DefaultPressure).
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if the received value is neither 0 nor between 700
+    ///   and 1400 mBar.
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        match AmbientPressure::try_from(data) {
+            Ok(pressure) => Ok(Self::CompensationPressure(pressure)),
+            Err(DataError::UseDefaultPressure) => Ok(Self::DefaultPressure),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for AmbientPressureCompensation {
+    type Error = DataError;
+
+    /// Converts buffered data to an [AmbientPressureCompensation] value. A value of 0 is the
+    /// [DefaultPressure](AmbientPressureCompensation::DefaultPressure).
+    ///
+    /// # Errors
+    ///
+    /// - [ReceivedBufferWrongSize](crate::error::DataError::ReceivedBufferWrongSize) if the `data` buffer is not big enough for the data
+    ///   that should have been received.
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if the received value is neither 0 nor between 700
+    ///   and 1400 mBar.
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for AmbientPressureCompensation {}
+
+impl Response for AmbientPressureCompensation {
+    const COMMAND: Command = Command::TriggerContinuousMeasurement;
+    type Buffer = [u8; 3];
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for AmbientPressureCompensation {
     fn format(&self, f: defmt::Formatter) {
@@ -84,6 +169,54 @@ impl defmt::Format for AmbientPressureCompensation {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for AmbientPressure {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.int_in_range(
+            MIN_AMBIENT_PRESSURE..=MAX_AMBIENT_PRESSURE,
+        )?))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for AmbientPressure {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        (MIN_AMBIENT_PRESSURE..=MAX_AMBIENT_PRESSURE)
+            .prop_map(Self)
+            .boxed()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for AmbientPressureCompensation {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.arbitrary()? {
+            Some(pressure) => Self::CompensationPressure(pressure),
+            None => Self::DefaultPressure,
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for AmbientPressureCompensation {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        proptest::option::of(proptest::arbitrary::any::<AmbientPressure>())
+            .prop_map(|pressure| match pressure {
+                Some(pressure) => Self::CompensationPressure(pressure),
+                None => Self::DefaultPressure,
+            })
+            .boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +227,32 @@ mod tests {
         assert_eq!(pressure.to_be_bytes(), [0x02, 0xBC]);
     }
 
+    #[test]
+    fn deserialize_sample_works() {
+        let data = [0x03, 0x84, 0x12];
+        assert_eq!(
+            AmbientPressure::try_from(&data[..]).unwrap(),
+            AmbientPressure(900)
+        );
+        assert_eq!(
+            AmbientPressureCompensation::try_from(&data[..]).unwrap(),
+            AmbientPressureCompensation::CompensationPressure(AmbientPressure(900))
+        );
+    }
+
+    #[test]
+    fn deserialize_default_pressure_works() {
+        let data = [0x00, 0x00, 0x81];
+        assert_eq!(
+            AmbientPressure::try_from(&data[..]).unwrap_err(),
+            DataError::UseDefaultPressure
+        );
+        assert_eq!(
+            AmbientPressureCompensation::try_from(&data[..]).unwrap(),
+            AmbientPressureCompensation::DefaultPressure
+        );
+    }
+
     #[test]
     fn create_allowed_value_from_u16_works() {
         let values = [700, 1000, 1400];
@@ -1,16 +1,23 @@
+use crate::codec::Frame;
+use crate::command::{sealed::Sealed, Command, Response};
 use crate::error::DataError;
-use crate::util::check_deserialization;
+use crate::util::sized_frame;
 
 const ASC_VALUE: &str = "Automatic self-calibration";
 const ASC_EXPECTED: &str = "0 or 1";
 
 /// Arguments for configuring the automatic self calibration.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum AutomaticSelfCalibration {
     /// Active automatic self calibration
-    Active = 1,
+    Active,
     /// Inactive automatic self calibration
-    Inactive = 0,
+    Inactive,
+    /// State outside the documented values, only returned with
+    /// [ValueDecoding::Lenient](crate::options::ValueDecoding::Lenient). Can not be written to
+    /// the sensor.
+    Unknown(u16),
 }
 
 #[cfg(feature = "defmt")]
@@ -19,32 +26,56 @@ impl defmt::Format for AutomaticSelfCalibration {
         match self {
             AutomaticSelfCalibration::Active => defmt::write!(f, "Active"),
             AutomaticSelfCalibration::Inactive => defmt::write!(f, "Inactive"),
+            AutomaticSelfCalibration::Unknown(value) => defmt::write!(f, "Unknown({})", value),
         }
     }
 }
 
 impl AutomaticSelfCalibration {
     /// Returns a big endian byte representation of the automatic self calibration value.
-    pub fn to_be_bytes(&self) -> [u8; 2] {
-        (*self as u16).to_be_bytes()
+    /// [Unknown](Self::Unknown) states are represented by their received value.
+    pub const fn to_be_bytes(&self) -> [u8; 2] {
+        match self {
+            Self::Active => 1u16,
+            Self::Inactive => 0,
+            Self::Unknown(value) => *value,
+        }
+        .to_be_bytes()
+    }
+
+    /// Returns whether the automatic self calibration is active.
+    ///
+    /// # Errors
+    ///
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if the state is
+    ///   [Unknown](Self::Unknown).
+    pub(crate) const fn is_active(&self) -> Result<bool, DataError> {
+        match self {
+            Self::Active => Ok(true),
+            Self::Inactive => Ok(false),
+            Self::Unknown(_) => Err(DataError::ValueOutOfRange {
+                parameter: ASC_VALUE,
+                min: 0,
+                max: 1,
+                unit: "",
+            }),
+        }
     }
 }
 
-impl TryFrom<&[u8]> for AutomaticSelfCalibration {
+impl TryFrom<[u8; 3]> for AutomaticSelfCalibration {
     type Error = DataError;
 
-    /// Converts buffered data to an [AutomaticSelfCalibration] value. If `Active` if a `1` is
+    /// Converts a received frame to an [AutomaticSelfCalibration] value. If `Active` if a `1` is
     /// received, `Inactive` if a `0` is received.
     ///
     /// # Errors
     ///
-    /// - [ReceivedBufferWrongSize](crate::error::DataError::ReceivedBufferWrongSize) if the `data` buffer is not big enough for the data
-    ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     /// - [UnexpectedValueReceived](crate::error::DataError::UnexpectedValueReceived) if the received value is not `0` or `1`.
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 3)?;
-        match data[1] {
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        let [[_, value]] = Frame::<1>::new(&data)?.word_bytes();
+        match value {
             1 => Ok(Self::Active),
             0 => Ok(Self::Inactive),
             val => Err(DataError::UnexpectedValueReceived {
@@ -56,6 +87,58 @@ impl TryFrom<&[u8]> for AutomaticSelfCalibration {
     }
 }
 
+impl TryFrom<&[u8]> for AutomaticSelfCalibration {
+    type Error = DataError;
+
+    /// Converts buffered data to an [AutomaticSelfCalibration] value. If `Active` if a `1` is
+    /// received, `Inactive` if a `0` is received.
+    ///
+    /// # Errors
+    ///
+    /// - [ReceivedBufferWrongSize](crate::error::DataError::ReceivedBufferWrongSize) if the `data` buffer is not big enough for the data
+    ///   that should have been received.
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    /// - [UnexpectedValueReceived](crate::error::DataError::UnexpectedValueReceived) if the received value is not `0` or `1`.
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for AutomaticSelfCalibration {}
+
+impl Response for AutomaticSelfCalibration {
+    const COMMAND: Command = Command::ActivateAutomaticSelfCalibration;
+    type Buffer = [u8; 3];
+
+    fn unknown(value: u16) -> Option<Self> {
+        Some(Self::Unknown(value))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for AutomaticSelfCalibration {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            Self::Active
+        } else {
+            Self::Inactive
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for AutomaticSelfCalibration {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        proptest::bool::ANY
+            .prop_map(|value| if value { Self::Active } else { Self::Inactive })
+            .boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,4 +185,21 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn unknown_state_is_neither_active_nor_inactive() {
+        assert_eq!(AutomaticSelfCalibration::Active.is_active(), Ok(true));
+        assert_eq!(AutomaticSelfCalibration::Inactive.is_active(), Ok(false));
+        assert_eq!(
+            AutomaticSelfCalibration::Unknown(2)
+                .is_active()
+                .unwrap_err(),
+            DataError::ValueOutOfRange {
+                parameter: ASC_VALUE,
+                min: 0,
+                max: 1,
+                unit: ""
+            }
+        );
+    }
 }
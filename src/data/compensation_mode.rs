@@ -0,0 +1,120 @@
+use crate::data::{AltitudeCompensation, AmbientPressure, AmbientPressureCompensation};
+
+/// Compensation of the CO2 reading for the air density at the sensor's location.
+///
+/// The sensor either compensates for the altitude or for the ambient pressure, whichever was set
+/// last. Setting the compensation through this type makes the active mode explicit, see
+/// `Scd30::set_compensation()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompensationMode {
+    /// Compensates for the altitude above sea level. Any ambient pressure compensation is
+    /// deactivated.
+    Altitude(AltitudeCompensation),
+    /// Compensates for the ambient pressure, e.g. read from a barometer. Overrides the altitude
+    /// compensation.
+    AmbientPressure(AmbientPressure),
+}
+
+impl CompensationMode {
+    /// Returns the compensation in effect for the given settings: an ambient pressure other than
+    /// the default one takes precedence over the altitude. Returns `None` if neither is set.
+    pub fn from_settings(
+        altitude: Option<AltitudeCompensation>,
+        pressure_compensation: Option<AmbientPressureCompensation>,
+    ) -> Option<Self> {
+        match (pressure_compensation, altitude) {
+            (Some(AmbientPressureCompensation::CompensationPressure(pressure)), _) => {
+                Some(CompensationMode::AmbientPressure(pressure))
+            }
+            (_, Some(altitude)) => Some(CompensationMode::Altitude(altitude)),
+            _ => None,
+        }
+    }
+}
+
+impl CompensationMode {
+    /// Selects the best compensation from the inputs available to the application:
+    ///
+    /// 1. A barometer reading `pressure_mbar` within the range accepted by the sensor selects the
+    ///    ambient pressure compensation, as it also follows the weather.
+    /// 2. Otherwise an `altitude_m` above sea level, e.g. from GPS, selects the altitude
+    ///    compensation. Altitudes below sea level are compensated as sea level.
+    /// 3. Otherwise `None` is returned, i.e. the sensor's default compensation for sea level and
+    ///    1013.25 mBar should be used.
+    ///
+    /// Readings which are `NaN` are ignored.
+    pub fn select(pressure_mbar: Option<f32>, altitude_m: Option<f32>) -> Option<Self> {
+        let pressure = pressure_mbar
+            .filter(|pressure| !pressure.is_nan())
+            .and_then(|pressure| AmbientPressure::try_from(round(pressure)).ok());
+        if let Some(pressure) = pressure {
+            return Some(CompensationMode::AmbientPressure(pressure));
+        }
+        altitude_m
+            .filter(|altitude| !altitude.is_nan())
+            .map(|altitude| CompensationMode::Altitude(AltitudeCompensation::from(round(altitude))))
+    }
+}
+
+// Rounds to the nearest integer, saturating at the bounds of `u16`.
+fn round(value: f32) -> u16 {
+    (value + 0.5) as u16
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CompensationMode {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            CompensationMode::Altitude(altitude) => defmt::write!(f, "Altitude({})", altitude),
+            CompensationMode::AmbientPressure(pressure) => {
+                defmt::write!(f, "AmbientPressure({})", pressure)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ambient_pressure_takes_precedence() {
+        let altitude = AltitudeCompensation::from(500);
+        let pressure = AmbientPressure::try_from(950).unwrap();
+        assert_eq!(
+            CompensationMode::from_settings(
+                Some(altitude),
+                Some(AmbientPressureCompensation::CompensationPressure(pressure))
+            ),
+            Some(CompensationMode::AmbientPressure(pressure))
+        );
+        assert_eq!(
+            CompensationMode::from_settings(
+                Some(altitude),
+                Some(AmbientPressureCompensation::DefaultPressure)
+            ),
+            Some(CompensationMode::Altitude(altitude))
+        );
+        assert_eq!(CompensationMode::from_settings(None, None), None);
+    }
+
+    #[test]
+    fn barometer_is_preferred_over_altitude() {
+        assert_eq!(
+            CompensationMode::select(Some(949.6), Some(520.0)),
+            Some(CompensationMode::AmbientPressure(
+                AmbientPressure::try_from(950).unwrap()
+            ))
+        );
+        assert_eq!(
+            CompensationMode::select(Some(f32::NAN), Some(520.4)),
+            Some(CompensationMode::Altitude(AltitudeCompensation::from(520)))
+        );
+        assert_eq!(
+            CompensationMode::select(Some(300.0), Some(-12.0)),
+            Some(CompensationMode::Altitude(AltitudeCompensation::from(0)))
+        );
+        assert_eq!(CompensationMode::select(None, Some(f32::NAN)), None);
+        assert_eq!(CompensationMode::select(None, None), None);
+    }
+}
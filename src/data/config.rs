@@ -0,0 +1,81 @@
+use crate::data::{
+    AltitudeCompensation, AmbientPressureCompensation, AutomaticSelfCalibration,
+    MeasurementInterval, TemperatureOffset,
+};
+
+/// Set of sensor settings, which can be re-applied after a reset of the sensor. Settings set to
+/// `None` are left untouched.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Scd30Config {
+    /// Starts continuous measurements with the given ambient pressure compensation.
+    pub continuous_measurement: Option<AmbientPressureCompensation>,
+    /// Measurement interval for continuous measurements.
+    pub measurement_interval: Option<MeasurementInterval>,
+    /// State of the automatic self-calibration.
+    pub automatic_self_calibration: Option<AutomaticSelfCalibration>,
+    /// Temperature offset compensating for self-heating.
+    pub temperature_offset: Option<TemperatureOffset>,
+    /// Altitude compensation.
+    pub altitude_compensation: Option<AltitudeCompensation>,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Scd30Config {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Scd30Config {{ continuous_measurement: {}, measurement_interval: {}, automatic_self_calibration: {}, temperature_offset: {}, altitude_compensation: {} }}",
+            self.continuous_measurement,
+            self.measurement_interval,
+            self.automatic_self_calibration,
+            self.temperature_offset,
+            self.altitude_compensation
+        )
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Scd30Config {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            continuous_measurement: u.arbitrary()?,
+            measurement_interval: u.arbitrary()?,
+            automatic_self_calibration: u.arbitrary()?,
+            temperature_offset: u.arbitrary()?,
+            altitude_compensation: u.arbitrary()?,
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Scd30Config {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::{arbitrary::any, strategy::Strategy};
+        any::<(
+            Option<AmbientPressureCompensation>,
+            Option<MeasurementInterval>,
+            Option<AutomaticSelfCalibration>,
+            Option<TemperatureOffset>,
+            Option<AltitudeCompensation>,
+        )>()
+        .prop_map(
+            |(
+                continuous_measurement,
+                measurement_interval,
+                automatic_self_calibration,
+                temperature_offset,
+                altitude_compensation,
+            )| Self {
+                continuous_measurement,
+                measurement_interval,
+                automatic_self_calibration,
+                temperature_offset,
+                altitude_compensation,
+            },
+        )
+        .boxed()
+    }
+}
@@ -1,15 +1,24 @@
-use crate::{error::DataError, util::check_deserialization};
+use crate::{
+    codec::Frame,
+    command::{sealed::Sealed, Command, Response},
+    error::DataError,
+    util::sized_frame,
+};
 
 const DATA_STATUS_VALUE: &str = "Data ready status";
 const DATA_STATUS_EXPECTED: &str = "0 or 1";
 
 /// Information whether a measurement is ready or not for readout.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum DataStatus {
     /// Data is available.
     Ready,
     /// No data is available.
     NotReady,
+    /// Status outside the documented values, only returned with
+    /// [ValueDecoding::Lenient](crate::options::ValueDecoding::Lenient). Treated as not ready.
+    Unknown(u16),
 }
 
 #[cfg(feature = "defmt")]
@@ -18,24 +27,45 @@ impl defmt::Format for DataStatus {
         match self {
             DataStatus::Ready => defmt::write!(f, "Ready"),
             DataStatus::NotReady => defmt::write!(f, "Not Ready"),
+            DataStatus::Unknown(value) => defmt::write!(f, "Unknown({})", value),
         }
     }
 }
 
-impl TryFrom<&[u8]> for DataStatus {
+impl DataStatus {
+    /// Returns whether a measurement is ready for readout.
+    ///
+    /// # Errors
+    ///
+    /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if the status is
+    ///   [Unknown](Self::Unknown).
+    #[cfg(feature = "serde")]
+    pub(crate) const fn is_ready(&self) -> Result<bool, DataError> {
+        match self {
+            Self::Ready => Ok(true),
+            Self::NotReady => Ok(false),
+            Self::Unknown(_) => Err(DataError::ValueOutOfRange {
+                parameter: DATA_STATUS_VALUE,
+                min: 0,
+                max: 1,
+                unit: "",
+            }),
+        }
+    }
+}
+
+impl TryFrom<[u8; 3]> for DataStatus {
     type Error = DataError;
 
-    /// Converts buffered data to an [DataStatus] value.
+    /// Converts a received frame to an [DataStatus] value.
     ///
     /// # Errors
     ///
-    /// - [ReceivedBufferWrongSize](crate::error::DataError::ReceivedBufferWrongSize) if the `data` buffer is not big enough for the data
-    ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     /// - [UnexpectedValueReceived](crate::error::DataError::UnexpectedValueReceived) if the received value is not `0` or `1`.
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 3)?;
-        match data[1] {
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        let [[_, value]] = Frame::<1>::new(&data)?.word_bytes();
+        match value {
             0 => Ok(Self::NotReady),
             1 => Ok(Self::Ready),
             val => Err(DataError::UnexpectedValueReceived {
@@ -47,6 +77,57 @@ impl TryFrom<&[u8]> for DataStatus {
     }
 }
 
+impl TryFrom<&[u8]> for DataStatus {
+    type Error = DataError;
+
+    /// Converts buffered data to an [DataStatus] value.
+    ///
+    /// # Errors
+    ///
+    /// - [ReceivedBufferWrongSize](crate::error::DataError::ReceivedBufferWrongSize) if the `data` buffer is not big enough for the data
+    ///   that should have been received.
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    /// - [UnexpectedValueReceived](crate::error::DataError::UnexpectedValueReceived) if the received value is not `0` or `1`.
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for DataStatus {}
+
+impl Response for DataStatus {
+    const COMMAND: Command = Command::GetDataReady;
+    type Buffer = [u8; 3];
+
+    fn unknown(value: u16) -> Option<Self> {
+        Some(Self::Unknown(value))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DataStatus {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            Self::Ready
+        } else {
+            Self::NotReady
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for DataStatus {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        proptest::bool::ANY
+            .prop_map(|value| if value { Self::Ready } else { Self::NotReady })
+            .boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
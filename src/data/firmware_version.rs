@@ -1,7 +1,15 @@
-use crate::{error::DataError, util::check_deserialization};
+use core::str::FromStr;
 
-/// The firmware version of the sensor.
-#[derive(Clone, Copy, Debug)]
+use crate::{
+    codec::Frame,
+    command::{sealed::Sealed, Command, Response},
+    error::DataError,
+    util::sized_frame,
+};
+
+/// The firmware version of the sensor. Versions are ordered by their major and then their minor
+/// version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FirmwareVersion {
     /// Major version.
     pub major: u8,
@@ -9,6 +17,49 @@ pub struct FirmwareVersion {
     pub minor: u8,
 }
 
+impl FirmwareVersion {
+    /// Creates a firmware version, e.g. as fixture for tests.
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl From<(u8, u8)> for FirmwareVersion {
+    /// Creates a firmware version from a `(major, minor)` tuple.
+    fn from((major, minor): (u8, u8)) -> Self {
+        Self::new(major, minor)
+    }
+}
+
+impl From<FirmwareVersion> for (u8, u8) {
+    fn from(version: FirmwareVersion) -> Self {
+        (version.major, version.minor)
+    }
+}
+
+impl FromStr for FirmwareVersion {
+    type Err = DataError;
+
+    /// Parses a firmware version formatted as `<major>.<minor>`, e.g. `3.66`, as rendered by its
+    /// [Display](core::fmt::Display) implementation. A leading `v` is accepted.
+    ///
+    /// # Errors
+    ///
+    /// - [InvalidFirmwareVersion](crate::error::DataError::InvalidFirmwareVersion) if `text` is
+    ///   not formatted as `<major>.<minor>` or a part does not fit into a `u8`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.strip_prefix('v').unwrap_or(text);
+        let (major, minor) = text
+            .split_once('.')
+            .ok_or(DataError::InvalidFirmwareVersion)?;
+        let parse = |part: &str| {
+            part.parse::<u8>()
+                .map_err(|_| DataError::InvalidFirmwareVersion)
+        };
+        Ok(Self::new(parse(major)?, parse(minor)?))
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for FirmwareVersion {
     fn format(&self, f: defmt::Formatter) {
@@ -16,6 +67,20 @@ impl defmt::Format for FirmwareVersion {
     }
 }
 
+impl TryFrom<[u8; 3]> for FirmwareVersion {
+    type Error = DataError;
+
+    /// Converts a received frame to a [FirmwareVersion] value.
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        let [[major, minor]] = Frame::<1>::new(&data)?.word_bytes();
+        Ok(Self { major, minor })
+    }
+}
+
 impl TryFrom<&[u8]> for FirmwareVersion {
     type Error = DataError;
 
@@ -27,11 +92,34 @@ impl TryFrom<&[u8]> for FirmwareVersion {
     ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 3)?;
-        Ok(Self {
-            major: data[0],
-            minor: data[1],
-        })
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for FirmwareVersion {}
+
+impl Response for FirmwareVersion {
+    const COMMAND: Command = Command::ReadFirmwareVersion;
+    type Buffer = [u8; 3];
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for FirmwareVersion {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for FirmwareVersion {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::{arbitrary::any, strategy::Strategy};
+        any::<(u8, u8)>()
+            .prop_map(|(major, minor)| Self::new(major, minor))
+            .boxed()
     }
 }
 
@@ -39,11 +127,34 @@ impl TryFrom<&[u8]> for FirmwareVersion {
 mod tests {
     use super::*;
 
+    #[test]
+    fn versions_round_trip_through_text_and_tuples() {
+        let version = FirmwareVersion::new(3, 66);
+        assert_eq!(format!("{}", version), "3.66");
+        assert_eq!("3.66".parse(), Ok(version));
+        assert_eq!("v3.66".parse(), Ok(version));
+        assert_eq!(FirmwareVersion::from((3, 66)), version);
+        assert_eq!(<(u8, u8)>::from(version), (3, 66));
+        assert!(FirmwareVersion::new(3, 9) < version);
+        assert!(FirmwareVersion::new(4, 0) > version);
+    }
+
+    #[test]
+    fn malformed_versions_are_rejected() {
+        for text in ["3", "3.", ".66", "3.256", "3.66.1", "a.b"] {
+            assert_eq!(
+                text.parse::<FirmwareVersion>(),
+                Err(DataError::InvalidFirmwareVersion)
+            );
+        }
+    }
+
     #[test]
     fn deserialize_specification_sample_works() {
         let data = [0x03, 0x42, 0xF3];
         let version = FirmwareVersion::try_from(&data[..]).unwrap();
         assert_eq!(version.major, 3);
         assert_eq!(version.minor, 66);
+        assert_eq!(version, FirmwareVersion::new(3, 66));
     }
 }
@@ -1,7 +1,6 @@
-use byteorder::{BigEndian, ByteOrder};
-
+use crate::command::{sealed::Sealed, Command, Response};
 use crate::error::DataError;
-use crate::util::check_deserialization;
+use crate::util::{deserialize_word, sized_frame};
 
 const MIN_FRC: u16 = 400;
 const MAX_FRC: u16 = 2000;
@@ -10,7 +9,7 @@ const PARTICLE_UNIT: &str = "ppm";
 
 /// A runtime checked representation of the forced recalibration value. Accepted value range:
 /// [400...2000] ppm.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ForcedRecalibrationValue(u16);
 
 #[cfg(feature = "defmt")]
@@ -49,6 +48,19 @@ impl TryFrom<u16> for ForcedRecalibrationValue {
     }
 }
 
+impl TryFrom<[u8; 3]> for ForcedRecalibrationValue {
+    type Error = DataError;
+
+    /// Converts a received frame to a [ForcedRecalibrationValue] value.
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        Ok(Self(deserialize_word(&data)?))
+    }
+}
+
 impl TryFrom<&[u8]> for ForcedRecalibrationValue {
     type Error = DataError;
 
@@ -60,8 +72,32 @@ impl TryFrom<&[u8]> for ForcedRecalibrationValue {
     ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 3)?;
-        Ok(Self(BigEndian::read_u16(&data[..2])))
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for ForcedRecalibrationValue {}
+
+impl Response for ForcedRecalibrationValue {
+    const COMMAND: Command = Command::ForcedRecalibrationValue;
+    type Buffer = [u8; 3];
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ForcedRecalibrationValue {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.int_in_range(MIN_FRC..=MAX_FRC)?))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for ForcedRecalibrationValue {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        (MIN_FRC..=MAX_FRC).prop_map(Self).boxed()
     }
 }
 
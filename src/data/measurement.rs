@@ -1,9 +1,15 @@
-use byteorder::{BigEndian, ByteOrder};
-
-use crate::{error::DataError, util::check_deserialization};
+use crate::{
+    codec::Frame,
+    command::{sealed::Sealed, Command, Response},
+    data::{Co2Concentration, Humidity, MeasurementWarnings, Temperature},
+    error::DataError,
+    format::FixedPoint,
+    sensirion::crc8,
+    util::{abs, sized_frame},
+};
 
 /// A measurement read from the SCD30.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Measurement {
     /// The CO2 concentration in ppm, ranging from 0 to 10.000 ppm.
     pub co2_concentration: f32,
@@ -13,19 +19,227 @@ pub struct Measurement {
     pub humidity: f32,
 }
 
+const PPM_PER_PERCENT: f32 = 10_000.0;
+const CO2_RANGE: core::ops::RangeInclusive<f32> = 0.0..=10_000.0;
+const TEMPERATURE_RANGE: core::ops::RangeInclusive<f32> = -40.0..=125.0;
+const PPM_PER_UNIT: f32 = 1_000_000.0;
+
+impl Measurement {
+    /// Creates a measurement from the CO2 concentration in ppm, the temperature in °C and the
+    /// relative humidity in %, e.g. as fixture for tests.
+    pub const fn new(co2_concentration: f32, temperature: f32, humidity: f32) -> Self {
+        Self {
+            co2_concentration,
+            temperature,
+            humidity,
+        }
+    }
+
+    /// Encodes the measurement as the raw frame sent by the sensor, including the CRCs. Useful to
+    /// feed a mocked I2C bus.
+    pub fn to_frame(&self) -> [u8; 18] {
+        let [c0, c1, c2, c3] = self.co2_concentration.to_be_bytes();
+        let [t0, t1, t2, t3] = self.temperature.to_be_bytes();
+        let [h0, h1, h2, h3] = self.humidity.to_be_bytes();
+        [
+            c0,
+            c1,
+            crc8([c0, c1]),
+            c2,
+            c3,
+            crc8([c2, c3]),
+            t0,
+            t1,
+            crc8([t0, t1]),
+            t2,
+            t3,
+            crc8([t2, t3]),
+            h0,
+            h1,
+            crc8([h0, h1]),
+            h2,
+            h3,
+            crc8([h2, h3]),
+        ]
+    }
+
+    /// Returns the CO2 concentration as a unit-bearing value.
+    pub const fn co2(&self) -> Co2Concentration {
+        Co2Concentration(self.co2_concentration)
+    }
+
+    /// Returns the temperature as a unit-bearing value.
+    pub const fn temperature(&self) -> Temperature {
+        Temperature(self.temperature)
+    }
+
+    /// Returns the relative humidity as a unit-bearing value.
+    pub const fn humidity(&self) -> Humidity {
+        Humidity(self.humidity)
+    }
+
+    /// Returns the CO2 concentration in volume percent (vol-%), e.g. `0.04` for 400 ppm.
+    pub fn co2_percent(&self) -> f32 {
+        self.co2_concentration / PPM_PER_PERCENT
+    }
+
+    /// Returns the CO2 concentration as a volume fraction between 0 and 1, e.g. `0.0004` for
+    /// 400 ppm.
+    pub fn co2_fraction(&self) -> f32 {
+        self.co2_concentration / PPM_PER_UNIT
+    }
+
+    /// Returns the CO2 concentration in units of 0.1 ppm, rounded to the nearest integer. Negative
+    /// values are saturated to 0.
+    pub fn co2_ppm_x10(&self) -> u32 {
+        FixedPoint::from_f32(self.co2_concentration, 1)
+            .mantissa()
+            .max(0) as u32
+    }
+
+    /// Returns the temperature in units of 0.01 °C, rounded to the nearest integer and saturated
+    /// to the range of `i16`.
+    pub fn temperature_centi_c(&self) -> i16 {
+        FixedPoint::from_f32(self.temperature, 2)
+            .mantissa()
+            .clamp(i16::MIN as i32, i16::MAX as i32) as i16
+    }
+
+    /// Returns the relative humidity in units of 0.01 %, rounded to the nearest integer and
+    /// saturated to the range of `u16`.
+    pub fn humidity_centi_pct(&self) -> u16 {
+        saturate_u16(FixedPoint::from_f32(self.humidity, 2).mantissa())
+    }
+
+    /// Returns the measurement with the CO2 concentration rounded to the nearest multiple of
+    /// `co2_step` ppm and the temperature and relative humidity rounded to `decimals` decimal
+    /// places, see [Co2Concentration::quantize] and [Temperature::round]. Avoids flickering
+    /// displays and needless transmissions due to float jitter.
+    pub fn quantized(&self, co2_step: u16, decimals: u8) -> Self {
+        Self {
+            co2_concentration: self.co2().quantize(co2_step).ppm(),
+            temperature: self.temperature().round(decimals).celsius(),
+            humidity: self.humidity().round(decimals).percent(),
+        }
+    }
+
+    /// Size of the compact representation created by [to_compact](Self::to_compact) in bytes.
+    pub const COMPACT_SIZE: usize = 6;
+
+    /// Packs the measurement into 6 bytes, e.g. for radio payloads. All values are stored big
+    /// endian and rounded to the nearest representable value:
+    ///
+    /// | Bytes | Quantity          | Type  | Unit      |
+    /// |-------|-------------------|-------|-----------|
+    /// | 0..2  | CO2 concentration | `u16` | 1 ppm     |
+    /// | 2..4  | Temperature       | `i16` | 0.01 °C   |
+    /// | 4..6  | Relative humidity | `u16` | 0.01 %    |
+    ///
+    /// Values outside the range of the respective type are saturated.
+    pub fn to_compact(&self) -> [u8; Self::COMPACT_SIZE] {
+        let co2 = saturate_u16(FixedPoint::from_f32(self.co2_concentration, 0).mantissa());
+        let temperature = self.temperature_centi_c();
+        let humidity = self.humidity_centi_pct();
+        let [c0, c1] = co2.to_be_bytes();
+        let [t0, t1] = temperature.to_be_bytes();
+        let [h0, h1] = humidity.to_be_bytes();
+        [c0, c1, t0, t1, h0, h1]
+    }
+
+    /// Unpacks a measurement created by [to_compact](Self::to_compact).
+    pub fn from_compact(compact: &[u8; Self::COMPACT_SIZE]) -> Self {
+        let [c0, c1, t0, t1, h0, h1] = *compact;
+        Self {
+            co2_concentration: u16::from_be_bytes([c0, c1]) as f32,
+            temperature: i16::from_be_bytes([t0, t1]) as f32 / 100.0,
+            humidity: u16::from_be_bytes([h0, h1]) as f32 / 100.0,
+        }
+    }
+
+    /// Checks the plausibility of the measurement, returning the set of warnings found. Values
+    /// are compared to the specified ranges of the sensor.
+    pub fn validate(&self) -> MeasurementWarnings {
+        let mut warnings = MeasurementWarnings::NONE;
+        let values = [self.co2_concentration, self.temperature, self.humidity];
+        if values.iter().any(|value| !value.is_finite()) {
+            warnings |= MeasurementWarnings::NAN_FIELD;
+        }
+        if self.co2_concentration.is_finite() && !CO2_RANGE.contains(&self.co2_concentration) {
+            warnings |= MeasurementWarnings::OUT_OF_RANGE_CO2;
+        }
+        if self.temperature.is_finite() && !TEMPERATURE_RANGE.contains(&self.temperature) {
+            warnings |= MeasurementWarnings::OUT_OF_RANGE_TEMPERATURE;
+        }
+        if self.humidity.is_finite() && self.humidity > 100.0 {
+            warnings |= MeasurementWarnings::RH_ABOVE_100;
+        }
+        if self.humidity.is_finite() && self.humidity < 0.0 {
+            warnings |= MeasurementWarnings::RH_BELOW_0;
+        }
+        warnings
+    }
+
+    /// Like [validate](Self::validate), additionally flagging a
+    /// [STALE_FRAME](MeasurementWarnings::STALE_FRAME) if the measurement is bit-identical to
+    /// the `previous` one.
+    pub fn validate_after(&self, previous: &Measurement) -> MeasurementWarnings {
+        let mut warnings = self.validate();
+        if self.to_frame() == previous.to_frame() {
+            warnings |= MeasurementWarnings::STALE_FRAME;
+        }
+        warnings
+    }
+
+    /// Returns whether CO2 concentration, temperature and humidity of both measurements each
+    /// differ by at most `epsilon`, in their respective units. Measurements containing `NaN` are
+    /// never approximately equal.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        within(self.co2_concentration, other.co2_concentration, epsilon)
+            && within(self.temperature, other.temperature, epsilon)
+            && within(self.humidity, other.humidity, epsilon)
+    }
+}
+
+fn saturate_u16(value: i32) -> u16 {
+    value.clamp(0, u16::MAX as i32) as u16
+}
+
+fn within(a: f32, b: f32, epsilon: f32) -> bool {
+    abs(a - b) <= epsilon
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for Measurement {
     fn format(&self, f: defmt::Formatter) {
         defmt::write!(
             f,
-            "{}ppm, {}°C, {}%",
-            self.co2_concentration,
-            self.temperature,
-            self.humidity
+            "{} ppm, {} °C, {} %",
+            self.co2_concentration_fixed(),
+            self.temperature_fixed(),
+            self.humidity_fixed()
         )
     }
 }
 
+impl TryFrom<[u8; 18]> for Measurement {
+    type Error = DataError;
+
+    /// Converts a received frame to a [Measurement] value.
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    fn try_from(data: [u8; 18]) -> Result<Self, Self::Error> {
+        let [[c0, c1], [c2, c3], [t0, t1], [t2, t3], [h0, h1], [h2, h3]] =
+            Frame::<6>::new(&data)?.word_bytes();
+        Ok(Self {
+            co2_concentration: f32::from_be_bytes([c0, c1, c2, c3]),
+            temperature: f32::from_be_bytes([t0, t1, t2, t3]),
+            humidity: f32::from_be_bytes([h0, h1, h2, h3]),
+        })
+    }
+}
+
 impl TryFrom<&[u8]> for Measurement {
     type Error = DataError;
 
@@ -37,18 +251,38 @@ impl TryFrom<&[u8]> for Measurement {
     ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 18)?;
-        Ok(Self {
-            co2_concentration: f32::from_bits(BigEndian::read_u32(&[
-                data[0], data[1], data[3], data[4],
-            ])),
-            temperature: f32::from_bits(BigEndian::read_u32(&[
-                data[6], data[7], data[9], data[10],
-            ])),
-            humidity: f32::from_bits(BigEndian::read_u32(&[
-                data[12], data[13], data[15], data[16],
-            ])),
-        })
+        Self::try_from(sized_frame::<18>(data)?)
+    }
+}
+
+impl Sealed for Measurement {}
+
+impl Response for Measurement {
+    const COMMAND: Command = Command::ReadMeasurement;
+    type Buffer = [u8; 18];
+}
+
+/// Generates measurements with arbitrary bit patterns, including `NaN` and infinite values.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Measurement {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+/// Generates measurements within the specified ranges of the sensor.
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Measurement {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        (0.0f32..=10_000.0, -40.0f32..=125.0, 0.0f32..=100.0)
+            .prop_map(|(co2_concentration, temperature, humidity)| {
+                Self::new(co2_concentration, temperature, humidity)
+            })
+            .boxed()
     }
 }
 
@@ -56,6 +290,14 @@ impl TryFrom<&[u8]> for Measurement {
 mod tests {
     use super::*;
 
+    #[test]
+    fn quantized_rounds_all_quantities() {
+        let measurement = Measurement::new(439.09515, 27.23828, 48.806744).quantized(5, 1);
+        assert_eq!(measurement.co2_concentration, 440.0);
+        assert_eq!(measurement.temperature, 27.2);
+        assert_eq!(measurement.humidity, 48.8);
+    }
+
     #[test]
     fn sample_measurement_deserializes_properly() {
         let data: [u8; 18] = [
@@ -67,4 +309,166 @@ mod tests {
         assert_eq!(result.temperature, 27.23828);
         assert_eq!(result.humidity, 48.806744);
     }
+
+    #[test]
+    fn frame_with_wrong_crc_fails_to_deserialize() {
+        let mut data = Measurement::new(439.09515, 27.23828, 48.806744).to_frame();
+        data[17] ^= 0xFF;
+        assert_eq!(
+            Measurement::try_from(data).unwrap_err(),
+            DataError::CrcFailed
+        );
+        assert_eq!(
+            Measurement::try_from(&data[..17]).unwrap_err(),
+            DataError::ReceivedBufferWrongSize
+        );
+    }
+
+    #[test]
+    fn to_frame_spec_sample() {
+        let data: [u8; 18] = [
+            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42, 0x43,
+            0xBF, 0x3A, 0x1B, 0x74,
+        ];
+        let measurement = Measurement::new(439.09515, 27.23828, 48.806744);
+        assert_eq!(measurement.to_frame(), data);
+    }
+
+    #[test]
+    fn co2_conversions_work() {
+        let measurement = Measurement {
+            co2_concentration: 400.0,
+            temperature: 20.0,
+            humidity: 50.0,
+        };
+        assert_eq!(measurement.co2_percent(), 0.04);
+        assert_eq!(measurement.co2_fraction(), 0.0004);
+    }
+
+    #[test]
+    fn typed_accessors_carry_units() {
+        let measurement = Measurement::new(400.0, 20.0, 50.0);
+        assert_eq!(measurement.co2(), Co2Concentration(400.0));
+        assert_eq!(measurement.temperature(), Temperature(20.0));
+        assert_eq!(measurement.humidity(), Humidity(50.0));
+        assert_eq!(measurement.humidity().fraction(), 0.5);
+    }
+
+    #[test]
+    fn validate_flags_implausible_values() {
+        assert!(Measurement::new(420.0, 21.0, 45.0).validate().is_empty());
+        assert_eq!(
+            Measurement::new(12_000.0, -50.0, 101.0).validate(),
+            MeasurementWarnings::OUT_OF_RANGE_CO2
+                | MeasurementWarnings::OUT_OF_RANGE_TEMPERATURE
+                | MeasurementWarnings::RH_ABOVE_100
+        );
+        assert_eq!(
+            Measurement::new(f32::NAN, 21.0, -1.0).validate(),
+            MeasurementWarnings::NAN_FIELD | MeasurementWarnings::RH_BELOW_0
+        );
+    }
+
+    #[test]
+    fn validate_after_flags_repeated_frames() {
+        let measurement = Measurement::new(420.0, 21.0, 45.0);
+        assert_eq!(
+            measurement.validate_after(&measurement),
+            MeasurementWarnings::STALE_FRAME
+        );
+        assert!(measurement
+            .validate_after(&Measurement::new(421.0, 21.0, 45.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn approx_eq_compares_each_quantity() {
+        let measurement = Measurement {
+            co2_concentration: 400.0,
+            temperature: 20.0,
+            humidity: 50.0,
+        };
+        let other = Measurement {
+            co2_concentration: 400.4,
+            temperature: 19.7,
+            humidity: 50.5,
+        };
+        assert!(measurement.approx_eq(&other, 0.5));
+        assert!(other.approx_eq(&measurement, 0.5));
+        assert!(!measurement.approx_eq(&other, 0.45));
+    }
+
+    #[test]
+    fn compact_representation_spec_sample() {
+        let measurement = Measurement {
+            co2_concentration: 439.09515,
+            temperature: 27.23828,
+            humidity: 48.806744,
+        };
+        let compact = measurement.to_compact();
+        assert_eq!(compact, [0x01, 0xB7, 0x0A, 0xA4, 0x13, 0x11]);
+        assert!(Measurement::from_compact(&compact).approx_eq(&measurement, 0.5));
+    }
+
+    #[test]
+    fn integer_accessors_spec_sample() {
+        let measurement = Measurement::new(439.09515, 27.23828, 48.806744);
+        assert_eq!(measurement.co2_ppm_x10(), 4391);
+        assert_eq!(measurement.temperature_centi_c(), 2724);
+        assert_eq!(measurement.humidity_centi_pct(), 4881);
+    }
+
+    #[test]
+    fn integer_accessors_saturate() {
+        let measurement = Measurement::new(-5.0, 400.0, 1000.0);
+        assert_eq!(measurement.co2_ppm_x10(), 0);
+        assert_eq!(measurement.temperature_centi_c(), i16::MAX);
+        assert_eq!(measurement.humidity_centi_pct(), 65535);
+    }
+
+    #[test]
+    fn compact_representation_saturates() {
+        let measurement = Measurement {
+            co2_concentration: 100_000.0,
+            temperature: -400.0,
+            humidity: -1.0,
+        };
+        let compact = Measurement::from_compact(&measurement.to_compact());
+        assert_eq!(compact.co2_concentration, 65535.0);
+        assert_eq!(compact.temperature, -327.68);
+        assert_eq!(compact.humidity, 0.0);
+    }
+
+    #[test]
+    fn approx_eq_rejects_nan() {
+        let measurement = Measurement {
+            co2_concentration: f32::NAN,
+            temperature: 20.0,
+            humidity: 50.0,
+        };
+        assert!(!measurement.approx_eq(&measurement, f32::INFINITY));
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn frame_round_trips(measurement: Measurement) {
+            let decoded = Measurement::try_from(measurement.to_frame()).unwrap();
+            proptest::prop_assert!(decoded.approx_eq(&measurement, 0.0));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_frame_round_trips() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes = [0x5A; 12];
+        let measurement = Measurement::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let decoded = Measurement::try_from(measurement.to_frame()).unwrap();
+        assert_eq!(
+            decoded.co2_concentration.to_bits(),
+            measurement.co2_concentration.to_bits()
+        );
+    }
 }
@@ -1,10 +1,12 @@
-use byteorder::{BigEndian, ByteOrder};
-
-use crate::{error::DataError, util::check_deserialization};
+use crate::{
+    command::{sealed::Sealed, Command, Response},
+    error::DataError,
+    util::{deserialize_word, sized_frame},
+};
 
 /// A runtime checked representation of the measurement interval configurable for the
 /// continuous measurements. Accepted value range: [2...1800] s.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct MeasurementInterval(u16);
 
 const MIN_MEASUREMENT_INTERVAL: u16 = 2;
@@ -13,6 +15,11 @@ const MEASUREMENT_INTERVAL_VAL: &str = "Measurement interval";
 const INTERVAL_UNIT: &str = "s";
 
 impl MeasurementInterval {
+    /// Returns the measurement interval in seconds.
+    pub const fn seconds(&self) -> u16 {
+        self.0
+    }
+
     /// Returns a big endian byte representation of the measurement interval.
     pub const fn to_be_bytes(&self) -> [u8; 2] {
         self.0.to_be_bytes()
@@ -49,6 +56,19 @@ impl TryFrom<u16> for MeasurementInterval {
     }
 }
 
+impl TryFrom<[u8; 3]> for MeasurementInterval {
+    type Error = DataError;
+
+    /// Converts a received frame to a [MeasurementInterval].
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        Ok(Self(deserialize_word(&data)?))
+    }
+}
+
 impl TryFrom<&[u8]> for MeasurementInterval {
     type Error = DataError;
 
@@ -60,8 +80,36 @@ impl TryFrom<&[u8]> for MeasurementInterval {
     ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 3)?;
-        Ok(Self(BigEndian::read_u16(&data[..2])))
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for MeasurementInterval {}
+
+impl Response for MeasurementInterval {
+    const COMMAND: Command = Command::SetMeasurementInterval;
+    type Buffer = [u8; 3];
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for MeasurementInterval {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.int_in_range(
+            MIN_MEASUREMENT_INTERVAL..=MAX_MEASUREMENT_INTERVAL,
+        )?))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for MeasurementInterval {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        (MIN_MEASUREMENT_INTERVAL..=MAX_MEASUREMENT_INTERVAL)
+            .prop_map(Self)
+            .boxed()
     }
 }
 
@@ -76,6 +124,11 @@ mod tests {
         assert_eq!(interval, MeasurementInterval(2));
     }
 
+    #[test]
+    fn seconds_returns_interval() {
+        assert_eq!(MeasurementInterval(901).seconds(), 901);
+    }
+
     #[test]
     fn serialize_sample_works() {
         let interval = MeasurementInterval(2);
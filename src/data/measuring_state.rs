@@ -0,0 +1,23 @@
+/// Whether the sensor runs continuous measurements, as tracked by the interface.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeasuringState {
+    /// Neither started nor stopped through the interface. The sensor keeps measuring across
+    /// power cycles, so it may still be measuring from a previous run.
+    #[default]
+    Unknown,
+    /// Continuous measurements were started.
+    Measuring,
+    /// Continuous measurements were stopped.
+    Stopped,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MeasuringState {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            MeasuringState::Unknown => defmt::write!(f, "Unknown"),
+            MeasuringState::Measuring => defmt::write!(f, "Measuring"),
+            MeasuringState::Stopped => defmt::write!(f, "Stopped"),
+        }
+    }
+}
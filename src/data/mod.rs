@@ -1,20 +1,47 @@
 //! Data send to or received from the SCD30 sensor.
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::indexing_slicing,
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used
+    )
+)]
 mod altitude_compensation;
 mod ambient_pressure;
 mod automatic_self_calibration;
+mod compensation_mode;
+mod config;
 mod data_status;
 mod firmware_version;
 mod forced_recalibration_value;
 mod measurement;
 mod measurement_interval;
+mod measuring_state;
+mod preset;
+mod quality;
+mod quantities;
+mod settings;
+mod snapshot;
 mod temperature_offset;
+mod warnings;
 
 pub use altitude_compensation::AltitudeCompensation;
 pub use ambient_pressure::{AmbientPressure, AmbientPressureCompensation};
 pub use automatic_self_calibration::AutomaticSelfCalibration;
+pub use compensation_mode::CompensationMode;
+pub use config::Scd30Config;
 pub use data_status::DataStatus;
 pub use firmware_version::FirmwareVersion;
 pub use forced_recalibration_value::ForcedRecalibrationValue;
 pub use measurement::Measurement;
 pub use measurement_interval::MeasurementInterval;
+pub use measuring_state::MeasuringState;
+pub use preset::Preset;
+pub use quality::{QualifiedMeasurement, SampleQuality};
+pub use quantities::{Co2Concentration, Humidity, Temperature};
+pub use settings::SettingsSnapshot;
+pub use snapshot::SensorSnapshot;
 pub use temperature_offset::TemperatureOffset;
+pub use warnings::MeasurementWarnings;
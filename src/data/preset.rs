@@ -0,0 +1,86 @@
+use crate::data::{
+    AmbientPressureCompensation, AutomaticSelfCalibration, MeasurementInterval, Scd30Config,
+};
+
+/// Named configurations for common use cases, applied with `Scd30::apply_preset()`.
+///
+/// Every preset sets the measurement interval and the automatic self-calibration and starts
+/// continuous measurements with the default ambient pressure. The temperature offset and the
+/// altitude depend on the installation and are left untouched, set them afterwards or use
+/// [config](Self::config) as a starting point for a [Scd30Config].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// Measures every 60 s with the automatic self-calibration active. Lowers the average supply
+    /// current and the self-heating of the sensor, at the cost of slower reactions to changes and
+    /// a higher noise per sample. Suited for battery powered room monitors.
+    LowPower,
+    /// Measures every 10 s with the automatic self-calibration active. A compromise between
+    /// responsiveness and power consumption for mains powered devices, keeping the sensor
+    /// calibrated as long as it sees fresh air regularly.
+    Balanced,
+    /// Measures every 2 s, the interval the accuracy of the sensor is specified for, with the
+    /// automatic self-calibration inactive. Meant for sensors which are not exposed to fresh air
+    /// daily and are instead calibrated with a forced recalibration against a reference.
+    HighAccuracy,
+}
+
+impl Preset {
+    /// All presets, e.g. to list them in a user interface.
+    pub const ALL: [Preset; 3] = [Preset::LowPower, Preset::Balanced, Preset::HighAccuracy];
+
+    /// Returns the settings of the preset.
+    pub fn config(&self) -> Scd30Config {
+        let (interval, automatic_self_calibration) = match self {
+            Preset::LowPower => (60, AutomaticSelfCalibration::Active),
+            Preset::Balanced => (10, AutomaticSelfCalibration::Active),
+            Preset::HighAccuracy => (2, AutomaticSelfCalibration::Inactive),
+        };
+        Scd30Config {
+            continuous_measurement: Some(AmbientPressureCompensation::DefaultPressure),
+            measurement_interval: MeasurementInterval::try_from(interval).ok(),
+            automatic_self_calibration: Some(automatic_self_calibration),
+            temperature_offset: None,
+            altitude_compensation: None,
+        }
+    }
+}
+
+impl From<Preset> for Scd30Config {
+    fn from(preset: Preset) -> Self {
+        preset.config()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Preset {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Preset::LowPower => defmt::write!(f, "LowPower"),
+            Preset::Balanced => defmt::write!(f, "Balanced"),
+            Preset::HighAccuracy => defmt::write!(f, "HighAccuracy"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_set_interval_and_asc() {
+        let intervals = Preset::ALL.map(|preset| preset.config().measurement_interval.unwrap());
+        assert_eq!(intervals.map(|interval| interval.seconds()), [60, 10, 2]);
+
+        let config = Scd30Config::from(Preset::HighAccuracy);
+        assert_eq!(
+            config.automatic_self_calibration,
+            Some(AutomaticSelfCalibration::Inactive)
+        );
+        assert_eq!(
+            config.continuous_measurement,
+            Some(AmbientPressureCompensation::DefaultPressure)
+        );
+        assert_eq!(config.temperature_offset, None);
+        assert_eq!(config.altitude_compensation, None);
+    }
+}
@@ -0,0 +1,119 @@
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::data::{Measurement, MeasurementWarnings};
+
+/// Set of flags describing how a sample was obtained, attached by
+/// `Scd30::read_qualified_measurement()`. Downstream pipelines can use them to weigh or discard
+/// samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SampleQuality(u8);
+
+impl SampleQuality {
+    /// No flags, the sample was read without anomalies.
+    pub const NONE: Self = Self(0);
+    /// The first read failed the CRC check and the sample was read again.
+    pub const RETRIED_CRC: Self = Self(1 << 0);
+    /// The sample was read during the warm-up of the configured
+    /// [WarmUpPolicy](crate::options::WarmUpPolicy).
+    pub const WARM_UP: Self = Self(1 << 1);
+    /// The sample was read later than the measurement interval allows, so earlier samples were
+    /// missed.
+    pub const OVERDUE: Self = Self(1 << 2);
+    /// At least one value is implausible, see the
+    /// [warnings](QualifiedMeasurement::warnings) for details.
+    pub const OUT_OF_SPEC: Self = Self(1 << 3);
+
+    /// Returns the raw bits of the set.
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns whether no flag is set.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns whether all flags of `other` are set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Adds the flags of `other` to the set.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Removes the flags of `other` from the set.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for SampleQuality {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for SampleQuality {
+    fn bitor_assign(&mut self, other: Self) {
+        self.insert(other);
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SampleQuality {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "SampleQuality({=u8:#06b})", self.0)
+    }
+}
+
+/// A [Measurement] together with the circumstances it was read under.
+#[derive(Clone, Copy, Debug)]
+pub struct QualifiedMeasurement {
+    /// The measurement as read from the sensor.
+    pub measurement: Measurement,
+    /// Flags describing how the sample was obtained.
+    pub quality: SampleQuality,
+    /// Plausibility warnings of the measurement, see `Measurement::validate()`.
+    pub warnings: MeasurementWarnings,
+}
+
+impl QualifiedMeasurement {
+    /// Returns whether the sample was read without any flag or warning.
+    pub const fn is_clean(&self) -> bool {
+        self.quality.is_empty() && self.warnings.is_empty()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for QualifiedMeasurement {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "QualifiedMeasurement {{ measurement: {}, quality: {}, warnings: {} }}",
+            self.measurement,
+            self.quality,
+            self.warnings
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_operations_work() {
+        let mut quality = SampleQuality::NONE;
+        assert!(quality.is_empty());
+        quality |= SampleQuality::RETRIED_CRC | SampleQuality::OVERDUE;
+        assert!(quality.contains(SampleQuality::OVERDUE));
+        assert!(!quality.contains(SampleQuality::WARM_UP));
+        quality.remove(SampleQuality::RETRIED_CRC);
+        assert_eq!(quality, SampleQuality::OVERDUE);
+        assert_eq!(quality.bits(), 0b100);
+    }
+}
@@ -0,0 +1,197 @@
+use core::fmt;
+
+use crate::format::FixedPoint;
+
+/// Writes `value` followed by `unit`, honoring the precision of the formatter, e.g. `{:.1}`.
+fn write_with_unit(f: &mut fmt::Formatter<'_>, value: f32, unit: &str) -> fmt::Result {
+    match f.precision() {
+        Some(precision) => write!(f, "{:.*} {}", precision, value, unit),
+        None => write!(f, "{} {}", value, unit),
+    }
+}
+
+/// Rounds `value` to `decimals` decimal places, half away from zero. Values which are not finite
+/// are returned unchanged.
+fn round_decimals(value: f32, decimals: u8) -> f32 {
+    if value.is_finite() {
+        FixedPoint::from_f32(value, decimals).to_f32()
+    } else {
+        value
+    }
+}
+
+/// CO2 concentration in ppm.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Co2Concentration(pub f32);
+
+impl Co2Concentration {
+    /// Returns the concentration in ppm.
+    pub const fn ppm(self) -> f32 {
+        self.0
+    }
+
+    /// Returns the concentration in volume percent (vol-%), e.g. `0.04` for 400 ppm.
+    pub fn percent(self) -> f32 {
+        self.0 / 10_000.0
+    }
+
+    /// Returns the concentration as a volume fraction between 0 and 1, e.g. `0.0004` for
+    /// 400 ppm.
+    pub fn fraction(self) -> f32 {
+        self.0 / 1_000_000.0
+    }
+
+    /// Rounds the concentration to the nearest multiple of `step` ppm, e.g. 5 or 10 ppm, to avoid
+    /// flickering displays. A `step` of 0 returns the concentration unchanged, as do values
+    /// which are not finite.
+    pub fn quantize(self, step: u16) -> Self {
+        if step == 0 || !self.0.is_finite() {
+            return self;
+        }
+        let step = step as f32;
+        Self(FixedPoint::from_f32(self.0 / step, 0).to_f32() * step)
+    }
+}
+
+impl From<Co2Concentration> for f32 {
+    fn from(concentration: Co2Concentration) -> Self {
+        concentration.0
+    }
+}
+
+impl fmt::Display for Co2Concentration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_with_unit(f, self.0, "ppm")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Co2Concentration {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{} ppm", self.0)
+    }
+}
+
+/// Temperature in °C.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Temperature(pub f32);
+
+impl Temperature {
+    /// Returns the temperature in °C.
+    pub const fn celsius(self) -> f32 {
+        self.0
+    }
+
+    /// Returns the temperature in °F.
+    pub fn fahrenheit(self) -> f32 {
+        self.0 * 9.0 / 5.0 + 32.0
+    }
+
+    /// Returns the temperature in K.
+    pub fn kelvin(self) -> f32 {
+        self.0 + 273.15
+    }
+
+    /// Rounds the temperature to `decimals` decimal places, half away from zero. At most 9
+    /// decimal places are supported.
+    pub fn round(self, decimals: u8) -> Self {
+        Self(round_decimals(self.0, decimals))
+    }
+}
+
+impl From<Temperature> for f32 {
+    fn from(temperature: Temperature) -> Self {
+        temperature.0
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_with_unit(f, self.0, "°C")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Temperature {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{} °C", self.0)
+    }
+}
+
+/// Relative humidity in %.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Humidity(pub f32);
+
+impl Humidity {
+    /// Returns the relative humidity in %.
+    pub const fn percent(self) -> f32 {
+        self.0
+    }
+
+    /// Returns the relative humidity as a fraction between 0 and 1.
+    pub fn fraction(self) -> f32 {
+        self.0 / 100.0
+    }
+
+    /// Rounds the relative humidity to `decimals` decimal places, half away from zero. At most 9
+    /// decimal places are supported.
+    pub fn round(self, decimals: u8) -> Self {
+        Self(round_decimals(self.0, decimals))
+    }
+}
+
+impl From<Humidity> for f32 {
+    fn from(humidity: Humidity) -> Self {
+        humidity.0
+    }
+}
+
+impl fmt::Display for Humidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_with_unit(f, self.0, "%")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Humidity {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{} %", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_work() {
+        assert_eq!(Co2Concentration(400.0).percent(), 0.04);
+        assert_eq!(Co2Concentration(400.0).fraction(), 0.0004);
+        assert_eq!(Temperature(100.0).fahrenheit(), 212.0);
+        assert_eq!(Temperature(0.0).kelvin(), 273.15);
+        assert_eq!(Humidity(45.0).fraction(), 0.45);
+        assert_eq!(f32::from(Humidity(45.0)), 45.0);
+    }
+
+    #[test]
+    fn quantization_rounds_to_steps() {
+        assert_eq!(Co2Concentration(437.4).quantize(5), Co2Concentration(435.0));
+        assert_eq!(Co2Concentration(437.5).quantize(5), Co2Concentration(440.0));
+        assert_eq!(
+            Co2Concentration(444.9).quantize(10),
+            Co2Concentration(440.0)
+        );
+        assert_eq!(Co2Concentration(444.9).quantize(0), Co2Concentration(444.9));
+        assert_eq!(Temperature(21.456).round(1), Temperature(21.5));
+        assert_eq!(Temperature(-3.25).round(1), Temperature(-3.3));
+        assert_eq!(Humidity(48.806744).round(0), Humidity(49.0));
+        assert!(Humidity(f32::NAN).round(1).percent().is_nan());
+    }
+
+    #[test]
+    fn display_includes_unit() {
+        assert_eq!(format!("{}", Co2Concentration(439.5)), "439.5 ppm");
+        assert_eq!(format!("{:.1}", Temperature(21.456)), "21.5 °C");
+        assert_eq!(format!("{:.0}", Humidity(48.7)), "49 %");
+    }
+}
@@ -0,0 +1,172 @@
+use crate::data::{
+    AltitudeCompensation, AutomaticSelfCalibration, DataStatus, FirmwareVersion,
+    ForcedRecalibrationValue, MeasurementInterval, TemperatureOffset,
+};
+
+/// Every setting and the data ready status as read back from a sensor with
+/// `Scd30::read_all_settings()`, e.g. for diagnostics dumps or to verify a provisioned sensor.
+///
+/// With the `serde` feature, the snapshot is (de-)serialized in plain units, e.g. as
+/// `{"measurement_interval_s": 2, "automatic_self_calibration": false, ...}`. Snapshots holding
+/// an `Unknown` self-calibration state or data ready status can not be serialized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(try_from = "SettingsRecord")
+)]
+pub struct SettingsSnapshot {
+    /// Measurement interval for continuous measurements.
+    pub measurement_interval: MeasurementInterval,
+    /// State of the automatic self-calibration.
+    pub automatic_self_calibration: AutomaticSelfCalibration,
+    /// Last reference value of a forced recalibration.
+    pub forced_recalibration: ForcedRecalibrationValue,
+    /// Temperature offset compensating for self-heating.
+    pub temperature_offset: TemperatureOffset,
+    /// Altitude compensation.
+    pub altitude_compensation: AltitudeCompensation,
+    /// Firmware version of the sensor.
+    pub firmware_version: FirmwareVersion,
+    /// Whether a measurement was ready for readout.
+    pub data_status: DataStatus,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SettingsSnapshot {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "SettingsSnapshot {{ measurement_interval: {}, automatic_self_calibration: {}, forced_recalibration: {}, temperature_offset: {}, altitude_compensation: {}, firmware_version: {}, data_status: {} }}",
+            self.measurement_interval,
+            self.automatic_self_calibration,
+            self.forced_recalibration,
+            self.temperature_offset,
+            self.altitude_compensation,
+            self.firmware_version,
+            self.data_status
+        )
+    }
+}
+
+/// Plain units representation of a [SettingsSnapshot] for serde.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SettingsRecord {
+    measurement_interval_s: u16,
+    automatic_self_calibration: bool,
+    forced_recalibration_ppm: u16,
+    temperature_offset_c: f32,
+    altitude_m: u16,
+    firmware_major: u8,
+    firmware_minor: u8,
+    data_ready: bool,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SettingsSnapshot {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SettingsRecord::try_from(*self)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SettingsSnapshot> for SettingsRecord {
+    type Error = crate::error::DataError;
+
+    fn try_from(snapshot: SettingsSnapshot) -> Result<Self, Self::Error> {
+        let raw = |bytes: [u8; 2]| u16::from_be_bytes(bytes);
+        Ok(Self {
+            measurement_interval_s: snapshot.measurement_interval.seconds(),
+            automatic_self_calibration: snapshot.automatic_self_calibration.is_active()?,
+            forced_recalibration_ppm: raw(snapshot.forced_recalibration.to_be_bytes()),
+            temperature_offset_c: snapshot.temperature_offset.celsius(),
+            altitude_m: raw(snapshot.altitude_compensation.to_be_bytes()),
+            firmware_major: snapshot.firmware_version.major,
+            firmware_minor: snapshot.firmware_version.minor,
+            data_ready: snapshot.data_status.is_ready()?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SettingsRecord> for SettingsSnapshot {
+    type Error = crate::error::DataError;
+
+    fn try_from(record: SettingsRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            measurement_interval: MeasurementInterval::try_from(record.measurement_interval_s)?,
+            automatic_self_calibration: if record.automatic_self_calibration {
+                AutomaticSelfCalibration::Active
+            } else {
+                AutomaticSelfCalibration::Inactive
+            },
+            forced_recalibration: ForcedRecalibrationValue::try_from(
+                record.forced_recalibration_ppm,
+            )?,
+            temperature_offset: TemperatureOffset::try_from(record.temperature_offset_c)?,
+            altitude_compensation: AltitudeCompensation::from(record.altitude_m),
+            firmware_version: FirmwareVersion::new(record.firmware_major, record.firmware_minor),
+            data_status: if record.data_ready {
+                DataStatus::Ready
+            } else {
+                DataStatus::NotReady
+            },
+        })
+    }
+}
+
+#[cfg(all(test, feature = "config-file"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_is_serialized_in_plain_units() {
+        let snapshot = SettingsSnapshot {
+            measurement_interval: MeasurementInterval::try_from(2).unwrap(),
+            automatic_self_calibration: AutomaticSelfCalibration::Inactive,
+            forced_recalibration: ForcedRecalibrationValue::try_from(450).unwrap(),
+            temperature_offset: TemperatureOffset::try_from(5.0_f32).unwrap(),
+            altitude_compensation: AltitudeCompensation::from(1000),
+            firmware_version: FirmwareVersion::new(3, 66),
+            data_status: DataStatus::Ready,
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            json,
+            r#"{"measurement_interval_s":2,"automatic_self_calibration":false,"forced_recalibration_ppm":450,"temperature_offset_c":5.0,"altitude_m":1000,"firmware_major":3,"firmware_minor":66,"data_ready":true}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<SettingsSnapshot>(&json).unwrap(),
+            snapshot
+        );
+        assert!(serde_json::from_str::<SettingsSnapshot>(&json.replace(
+            r#""measurement_interval_s":2"#,
+            r#""measurement_interval_s":1"#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn snapshot_with_unknown_values_is_not_serialized() {
+        let snapshot = SettingsSnapshot {
+            measurement_interval: MeasurementInterval::try_from(2).unwrap(),
+            automatic_self_calibration: AutomaticSelfCalibration::Unknown(2),
+            forced_recalibration: ForcedRecalibrationValue::try_from(450).unwrap(),
+            temperature_offset: TemperatureOffset::try_from(5.0_f32).unwrap(),
+            altitude_compensation: AltitudeCompensation::from(1000),
+            firmware_version: FirmwareVersion::new(3, 66),
+            data_status: DataStatus::Ready,
+        };
+        assert!(serde_json::to_string(&snapshot).is_err());
+        let snapshot = SettingsSnapshot {
+            automatic_self_calibration: AutomaticSelfCalibration::Active,
+            data_status: DataStatus::Unknown(2),
+            ..snapshot
+        };
+        assert!(serde_json::to_string(&snapshot).is_err());
+    }
+}
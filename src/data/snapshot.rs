@@ -0,0 +1,29 @@
+use crate::data::{FirmwareVersion, ForcedRecalibrationValue, Scd30Config};
+
+/// Every readable setting of a sensor, taken with `Scd30::snapshot()`. Restoring the snapshot
+/// with `Scd30::restore()` migrates the configuration, e.g. across firmware updates or to a
+/// replacement sensor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SensorSnapshot {
+    /// Firmware version of the sensor the snapshot was taken from.
+    pub firmware_version: FirmwareVersion,
+    /// Settings of the sensor. The ambient pressure compensation is read back unless continuous
+    /// measurements were stopped through the interface.
+    pub config: Scd30Config,
+    /// Last reference value of a forced recalibration. It is not restored, as writing it
+    /// recalibrates the sensor to the current environment.
+    pub forced_recalibration: ForcedRecalibrationValue,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SensorSnapshot {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "SensorSnapshot {{ firmware_version: {}, config: {}, forced_recalibration: {} }}",
+            self.firmware_version,
+            self.config,
+            self.forced_recalibration
+        )
+    }
+}
@@ -1,6 +1,8 @@
-use byteorder::{BigEndian, ByteOrder};
-
-use crate::{error::DataError, util::check_deserialization};
+use crate::{
+    command::{sealed::Sealed, Command, Response},
+    error::DataError,
+    util::{deserialize_word, sized_frame},
+};
 
 const MIN_TEMPERATURE_OFFSET: f32 = 0.0;
 const MAX_TEMPERATURE_OFFSET: f32 = 0.1 * u16::MAX as f32;
@@ -9,7 +11,7 @@ const TEMPERATURE_UNIT: &str = "°C";
 
 /// A runtime checked representation of the forced recalibration value. Accepted value range:
 /// [0.0...6553.5] °C.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct TemperatureOffset(u16);
 
 impl TemperatureOffset {
@@ -17,6 +19,11 @@ impl TemperatureOffset {
     pub const fn to_be_bytes(&self) -> [u8; 2] {
         self.0.to_be_bytes()
     }
+
+    /// Returns the temperature offset in °C.
+    pub fn celsius(&self) -> f32 {
+        self.0 as f32 / 100.0
+    }
 }
 
 #[cfg(feature = "defmt")]
@@ -74,6 +81,19 @@ impl TryFrom<f64> for TemperatureOffset {
     }
 }
 
+impl TryFrom<[u8; 3]> for TemperatureOffset {
+    type Error = DataError;
+
+    /// Converts a received frame to a [TemperatureOffset] value.
+    ///
+    /// # Errors
+    ///
+    /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
+    fn try_from(data: [u8; 3]) -> Result<Self, Self::Error> {
+        Ok(Self(deserialize_word(&data)?))
+    }
+}
+
 impl TryFrom<&[u8]> for TemperatureOffset {
     type Error = DataError;
 
@@ -85,8 +105,32 @@ impl TryFrom<&[u8]> for TemperatureOffset {
     ///   that should have been received.
     /// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of the received data does not match.
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        check_deserialization(data, 3)?;
-        Ok(Self(BigEndian::read_u16(&data[..2])))
+        Self::try_from(sized_frame::<3>(data)?)
+    }
+}
+
+impl Sealed for TemperatureOffset {}
+
+impl Response for TemperatureOffset {
+    const COMMAND: Command = Command::SetTemperatureOffset;
+    type Buffer = [u8; 3];
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for TemperatureOffset {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.int_in_range(0..=u16::MAX)?))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for TemperatureOffset {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        (0..=u16::MAX).prop_map(Self).boxed()
     }
 }
 
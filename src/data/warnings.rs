@@ -0,0 +1,88 @@
+use core::ops::{BitOr, BitOrAssign};
+
+/// Set of plausibility warnings for a [Measurement](crate::data::Measurement), returned by
+/// `Measurement::validate()`. Applications can decide per warning whether to keep, clamp or
+/// discard the sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MeasurementWarnings(u8);
+
+impl MeasurementWarnings {
+    /// No warnings.
+    pub const NONE: Self = Self(0);
+    /// The CO2 concentration is outside the specified range of 0 to 10.000 ppm.
+    pub const OUT_OF_RANGE_CO2: Self = Self(1 << 0);
+    /// The temperature is outside the specified range of -40 to 125 °C.
+    pub const OUT_OF_RANGE_TEMPERATURE: Self = Self(1 << 1);
+    /// The relative humidity is above 100 %.
+    pub const RH_ABOVE_100: Self = Self(1 << 2);
+    /// The relative humidity is below 0 %.
+    pub const RH_BELOW_0: Self = Self(1 << 3);
+    /// At least one value is `NaN` or infinite. Such values are not checked against their range.
+    pub const NAN_FIELD: Self = Self(1 << 4);
+    /// The sample is bit-identical to the previous one, hinting at a sensor repeating its last
+    /// frame instead of delivering a new sample.
+    pub const STALE_FRAME: Self = Self(1 << 5);
+
+    /// Returns the raw bits of the set.
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns whether no warning is set.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns whether all warnings of `other` are set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Adds the warnings of `other` to the set.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Removes the warnings of `other` from the set.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for MeasurementWarnings {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for MeasurementWarnings {
+    fn bitor_assign(&mut self, other: Self) {
+        self.insert(other);
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MeasurementWarnings {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "MeasurementWarnings({=u8:#08b})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_operations_work() {
+        let mut warnings = MeasurementWarnings::NONE;
+        assert!(warnings.is_empty());
+        warnings |= MeasurementWarnings::RH_ABOVE_100 | MeasurementWarnings::STALE_FRAME;
+        assert!(warnings.contains(MeasurementWarnings::RH_ABOVE_100));
+        assert!(!warnings.contains(MeasurementWarnings::NAN_FIELD));
+        warnings.remove(MeasurementWarnings::RH_ABOVE_100);
+        assert_eq!(warnings, MeasurementWarnings::STALE_FRAME);
+        assert_eq!(warnings.bits(), 0b10_0000);
+    }
+}
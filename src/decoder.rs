@@ -0,0 +1,416 @@
+//! Host-side decoder reconstructing the conversation with the sensor from captured I2C traffic.
+//!
+//! Captures of a logic analyzer are turned into [Transfer]s, e.g. with [parse_saleae_csv] for the
+//! CSV export of the I2C analyzer of Saleae Logic 2. [decode] then pairs the transfers into typed
+//! commands and responses, checking the CRC of every word:
+//!
+//! ```ignore
+//! use scd30_interface::decoder::{decode, parse_saleae_csv};
+//!
+//! let transfers = parse_saleae_csv(&std::fs::read_to_string("capture.csv")?)?;
+//! for message in decode(&transfers) {
+//!     println!("{message}");
+//! }
+//! ```
+//!
+//! ```text
+//! -> GetDataReady
+//! <- GetDataReady: Ready
+//! -> SetAltitudeCompensation 0x03E8 (CRC mismatch: 0xD5, expected 0xD4)
+//! ```
+
+extern crate std;
+
+use std::{fmt, format, string::String, vec::Vec};
+
+use crate::{
+    command::Command,
+    data::{
+        AltitudeCompensation, AutomaticSelfCalibration, DataStatus, FirmwareVersion,
+        ForcedRecalibrationValue, Measurement, MeasurementInterval, TemperatureOffset,
+    },
+    interface::{ADDRESS, GENERAL_CALL_ADDRESS, GENERAL_CALL_RESET},
+    sensirion::{crc8, WORD_LEN},
+};
+
+/// Direction of a [Transfer], as seen from the controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// The controller wrote to the device.
+    Write,
+    /// The controller read from the device.
+    Read,
+}
+
+/// I2C transfer between a start and a stop or repeated start condition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transfer {
+    /// 7-bit address of the device.
+    pub address: u8,
+    /// Direction of the transfer.
+    pub direction: Direction,
+    /// Transferred bytes, excluding the address byte.
+    pub bytes: Vec<u8>,
+}
+
+/// 16-bit word followed by its CRC, as transmitted on the bus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Word {
+    /// Value of the word.
+    pub value: u16,
+    /// CRC transmitted with the word.
+    pub crc: u8,
+}
+
+impl Word {
+    /// Returns the CRC the word should have been transmitted with.
+    pub fn expected_crc(&self) -> u8 {
+        crc8(self.value.to_be_bytes())
+    }
+
+    /// Returns whether the transmitted CRC matches the word.
+    pub fn crc_matches(&self) -> bool {
+        self.crc == self.expected_crc()
+    }
+}
+
+/// Message of the conversation with the sensor, reconstructed from one [Transfer].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Command sent to the sensor, with its argument if one was sent.
+    Command {
+        /// Command sent.
+        command: Command,
+        /// Argument sent after the command.
+        argument: Option<Word>,
+    },
+    /// Response read from the sensor.
+    Response {
+        /// Last command sent to the sensor, `None` if no command preceded the response.
+        command: Option<Command>,
+        /// Bytes read, including the CRCs.
+        bytes: Vec<u8>,
+    },
+    /// I2C general call reset, resetting all devices on the bus.
+    GeneralCallReset,
+    /// Transfer which does not belong to the protocol of the SCD30, e.g. an unknown command or
+    /// traffic to other devices.
+    Unknown(Transfer),
+}
+
+impl Message {
+    /// Returns the words of a response or the argument of a command. Trailing bytes not forming a
+    /// complete word are skipped.
+    pub fn words(&self) -> Vec<Word> {
+        match self {
+            Message::Command { argument, .. } => argument.iter().copied().collect(),
+            Message::Response { bytes, .. } => words(bytes),
+            Message::GeneralCallReset | Message::Unknown(_) => Vec::new(),
+        }
+    }
+
+    /// Returns whether the CRCs of all words of the message match.
+    pub fn crcs_match(&self) -> bool {
+        self.words().iter().all(Word::crc_matches)
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Command { command, argument } => {
+                write!(f, "-> {:?}", command)?;
+                if let Some(argument) = argument {
+                    write!(f, " {:#06X}", argument.value)?;
+                }
+                write_crc_verdicts(f, &self.words())
+            }
+            Message::Response { command, bytes } => {
+                match command {
+                    Some(command) => write!(f, "<- {:?}: ", command)?,
+                    None => write!(f, "<- ")?,
+                }
+                match command {
+                    Some(command) if self.crcs_match() => {
+                        f.write_str(&describe(*command, bytes))?
+                    }
+                    _ => write!(f, "{:02X?}", bytes)?,
+                }
+                write_crc_verdicts(f, &self.words())
+            }
+            Message::GeneralCallReset => write!(f, "-> GeneralCallReset"),
+            Message::Unknown(transfer) => write!(
+                f,
+                "?? {:?} {:#04x}: {:02X?}",
+                transfer.direction, transfer.address, transfer.bytes
+            ),
+        }
+    }
+}
+
+/// Reconstructs the conversation with the sensor from the captured `transfers`.
+pub fn decode(transfers: &[Transfer]) -> Vec<Message> {
+    let mut last_command = None;
+    transfers
+        .iter()
+        .map(|transfer| {
+            let message = decode_transfer(transfer, last_command);
+            if let Message::Command { command, .. } = message {
+                last_command = Some(command);
+            }
+            message
+        })
+        .collect()
+}
+
+fn decode_transfer(transfer: &Transfer, last_command: Option<Command>) -> Message {
+    match (
+        transfer.address,
+        transfer.direction,
+        transfer.bytes.as_slice(),
+    ) {
+        (GENERAL_CALL_ADDRESS, Direction::Write, [GENERAL_CALL_RESET]) => Message::GeneralCallReset,
+        (ADDRESS, Direction::Write, [msb, lsb, argument @ ..])
+            if argument.is_empty() || argument.len() == WORD_LEN =>
+        {
+            match Command::try_from(u16::from_be_bytes([*msb, *lsb])) {
+                Ok(command) => Message::Command {
+                    command,
+                    argument: words(argument).first().copied(),
+                },
+                Err(_) => Message::Unknown(transfer.clone()),
+            }
+        }
+        (ADDRESS, Direction::Read, bytes) => Message::Response {
+            command: last_command,
+            bytes: bytes.to_vec(),
+        },
+        _ => Message::Unknown(transfer.clone()),
+    }
+}
+
+fn words(bytes: &[u8]) -> Vec<Word> {
+    bytes
+        .chunks_exact(WORD_LEN)
+        .map(|chunk| Word {
+            value: u16::from_be_bytes([chunk[0], chunk[1]]),
+            crc: chunk[2],
+        })
+        .collect()
+}
+
+fn describe(command: Command, bytes: &[u8]) -> String {
+    let described = match command {
+        Command::GetDataReady => DataStatus::try_from(bytes).map(|value| format!("{:?}", value)),
+        Command::ReadMeasurement => Measurement::try_from(bytes).map(|value| format!("{}", value)),
+        Command::SetMeasurementInterval => {
+            MeasurementInterval::try_from(bytes).map(|value| format!("{:?}", value))
+        }
+        Command::ActivateAutomaticSelfCalibration => {
+            AutomaticSelfCalibration::try_from(bytes).map(|value| format!("{:?}", value))
+        }
+        Command::ForcedRecalibrationValue => {
+            ForcedRecalibrationValue::try_from(bytes).map(|value| format!("{:?}", value))
+        }
+        Command::SetTemperatureOffset => {
+            TemperatureOffset::try_from(bytes).map(|value| format!("{:?}", value))
+        }
+        Command::SetAltitudeCompensation => {
+            AltitudeCompensation::try_from(bytes).map(|value| format!("{:?}", value))
+        }
+        Command::ReadFirmwareVersion => FirmwareVersion::try_from(bytes)
+            .map(|version| format!("{}.{}", version.major, version.minor)),
+        _ => return format!("{:02X?}", bytes),
+    };
+    described.unwrap_or_else(|error| format!("{:02X?} ({})", bytes, error))
+}
+
+fn write_crc_verdicts(f: &mut fmt::Formatter<'_>, words: &[Word]) -> fmt::Result {
+    words
+        .iter()
+        .filter(|word| !word.crc_matches())
+        .try_for_each(|word| {
+            write!(
+                f,
+                " (CRC mismatch: {:#04X}, expected {:#04X})",
+                word.crc,
+                word.expected_crc()
+            )
+        })
+}
+
+/// Error parsing a capture, see [parse_saleae_csv].
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum CaptureError {
+    /// A column required for decoding is missing in the header.
+    #[error("Missing column {0} in header.")]
+    MissingColumn(&'static str),
+    /// A value of the capture could not be parsed.
+    #[error("Invalid value {value:?} in line {line}.")]
+    InvalidValue {
+        /// Line number, starting at 1
+        line: usize,
+        /// Value which could not be parsed
+        value: String,
+    },
+}
+
+/// Parses the CSV export of the I2C analyzer of Saleae Logic 2 into [Transfer]s. The analyzer has
+/// to report 7-bit addresses, the default setting.
+///
+/// # Errors
+///
+/// - [MissingColumn](CaptureError::MissingColumn) if the header lacks the `type`, `address`, `read`
+///   or `data` column.
+/// - [InvalidValue](CaptureError::InvalidValue) if an address or data byte is not a number.
+pub fn parse_saleae_csv(csv: &str) -> Result<Vec<Transfer>, CaptureError> {
+    let mut lines = csv.lines().enumerate();
+    let header: Vec<&str> = lines
+        .next()
+        .map(|(_, header)| fields(header))
+        .unwrap_or_default();
+    let column = |name: &'static str| {
+        header
+            .iter()
+            .position(|field| *field == name)
+            .ok_or(CaptureError::MissingColumn(name))
+    };
+    let (kind, address, read, data) = (
+        column("type")?,
+        column("address")?,
+        column("read")?,
+        column("data")?,
+    );
+
+    let mut transfers = Vec::new();
+    let mut current: Option<Transfer> = None;
+    for (index, line) in lines {
+        let fields = fields(line);
+        let field = |column: usize| fields.get(column).copied().unwrap_or_default();
+        let byte = |column: usize| {
+            parse_byte(field(column)).ok_or_else(|| CaptureError::InvalidValue {
+                line: index + 1,
+                value: field(column).into(),
+            })
+        };
+        match field(kind) {
+            "start" | "stop" => transfers.extend(current.take()),
+            "address" => {
+                transfers.extend(current.take());
+                current = Some(Transfer {
+                    address: byte(address)?,
+                    direction: match field(read) {
+                        "true" => Direction::Read,
+                        _ => Direction::Write,
+                    },
+                    bytes: Vec::new(),
+                });
+            }
+            "data" => {
+                let byte = byte(data)?;
+                if let Some(transfer) = current.as_mut() {
+                    transfer.bytes.push(byte);
+                }
+            }
+            _ => {}
+        }
+    }
+    transfers.extend(current);
+    Ok(transfers)
+}
+
+fn fields(line: &str) -> Vec<&str> {
+    line.split(',')
+        .map(|field| field.trim().trim_matches('"'))
+        .collect()
+}
+
+fn parse_byte(value: &str) -> Option<u8> {
+    match value.strip_prefix("0x").or(value.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{string::ToString, vec};
+
+    const CAPTURE: &str = "\
+name,type,start_time,duration,\"ack\",\"address\",\"read\",\"data\"
+\"I2C\",\"start\",0.0001,1e-07,,,,
+\"I2C\",\"address\",0.0001,9e-05,true,0x61,false,
+\"I2C\",\"data\",0.0002,9e-05,true,,,0x02
+\"I2C\",\"data\",0.0003,9e-05,true,,,0x02
+\"I2C\",\"stop\",0.0004,1e-07,,,,
+\"I2C\",\"start\",0.0050,1e-07,,,,
+\"I2C\",\"address\",0.0050,9e-05,true,0x61,true,
+\"I2C\",\"data\",0.0051,9e-05,true,,,0x00
+\"I2C\",\"data\",0.0052,9e-05,true,,,0x01
+\"I2C\",\"data\",0.0053,9e-05,false,,,0xB0
+\"I2C\",\"stop\",0.0054,1e-07,,,,
+";
+
+    #[test]
+    fn saleae_export_is_parsed() {
+        assert_eq!(
+            parse_saleae_csv(CAPTURE).unwrap(),
+            [
+                Transfer {
+                    address: 0x61,
+                    direction: Direction::Write,
+                    bytes: vec![0x02, 0x02],
+                },
+                Transfer {
+                    address: 0x61,
+                    direction: Direction::Read,
+                    bytes: vec![0x00, 0x01, 0xB0],
+                },
+            ]
+        );
+        assert_eq!(
+            parse_saleae_csv("type,address,read\n"),
+            Err(CaptureError::MissingColumn("data"))
+        );
+        assert_eq!(
+            parse_saleae_csv("type,address,read,data\naddress,0xZZ,false,\n"),
+            Err(CaptureError::InvalidValue {
+                line: 2,
+                value: "0xZZ".into()
+            })
+        );
+    }
+
+    #[test]
+    fn conversation_is_decoded_with_crc_verdicts() {
+        let transfer = |address, direction, bytes: &[u8]| Transfer {
+            address,
+            direction,
+            bytes: bytes.to_vec(),
+        };
+        let transfers = [
+            transfer(0x61, Direction::Write, &[0x02, 0x02]),
+            transfer(0x61, Direction::Read, &[0x00, 0x01, 0xB0]),
+            transfer(0x61, Direction::Write, &[0x51, 0x02, 0x03, 0xE8, 0xD5]),
+            transfer(0x61, Direction::Write, &[0xD1, 0x00]),
+            transfer(0x61, Direction::Read, &[0x03, 0x42, 0xF3]),
+            transfer(0x00, Direction::Write, &[0x06]),
+            transfer(0x61, Direction::Write, &[0xAB, 0xCD]),
+        ];
+
+        let messages: Vec<_> = decode(&transfers).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "-> GetDataReady",
+                "<- GetDataReady: Ready",
+                "-> SetAltitudeCompensation 0x03E8 (CRC mismatch: 0xD5, expected 0xD4)",
+                "-> ReadFirmwareVersion",
+                "<- ReadFirmwareVersion: 3.66",
+                "-> GeneralCallReset",
+                "?? Write 0x61: [AB, CD]",
+            ]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Object-safe facade of the blocking interface.
+//!
+//! [blocking::Scd30] is generic over its I2C and delay types, so sensors on different buses have
+//! different types. [AnyScd30] hides these behind a trait object with a fixed error type, so
+//! applications can hold sensors of any bus, e.g. `Box<dyn AnyScd30>` in a plugin registry on
+//! Linux:
+//!
+//! ```ignore
+//! let mut sensors: Vec<Box<dyn AnyScd30>> = vec![
+//!     Box::new(Scd30::new(I2cdev::new("/dev/i2c-1")?)),
+//!     Box::new(Scd30::new(mux.channel(2))),
+//! ];
+//! for sensor in &mut sensors {
+//!     if sensor.is_data_ready()? == DataStatus::Ready {
+//!         println!("{}", sensor.read_measurement()?);
+//!     }
+//! }
+//! ```
+//!
+//! I2C errors are reported by their [ErrorKind], see [Scd30Error::into_kind].
+
+use embedded_hal::i2c::ErrorKind;
+
+use crate::{
+    blocking::Scd30,
+    data::{
+        AltitudeCompensation, AmbientPressureCompensation, AutomaticSelfCalibration, DataStatus,
+        FirmwareVersion, ForcedRecalibrationValue, Measurement, MeasurementInterval, Scd30Config,
+        TemperatureOffset,
+    },
+    error::Scd30Error,
+};
+
+/// Error returned through [AnyScd30].
+pub type AnyScd30Error = Scd30Error<ErrorKind>;
+
+/// Object-safe subset of the blocking interface, implemented by every [Scd30]. See the
+/// [module documentation](self).
+pub trait AnyScd30 {
+    /// See [Scd30::trigger_continuous_measurements].
+    fn trigger_continuous_measurements(
+        &mut self,
+        pressure_compensation: Option<AmbientPressureCompensation>,
+    ) -> Result<(), AnyScd30Error>;
+
+    /// See [Scd30::stop_continuous_measurements].
+    fn stop_continuous_measurements(&mut self) -> Result<(), AnyScd30Error>;
+
+    /// See [Scd30::set_measurement_interval].
+    fn set_measurement_interval(
+        &mut self,
+        interval: MeasurementInterval,
+    ) -> Result<(), AnyScd30Error>;
+
+    /// See [Scd30::get_measurement_interval].
+    fn get_measurement_interval(&mut self) -> Result<MeasurementInterval, AnyScd30Error>;
+
+    /// See [Scd30::is_data_ready].
+    fn is_data_ready(&mut self) -> Result<DataStatus, AnyScd30Error>;
+
+    /// See [Scd30::read_measurement].
+    fn read_measurement(&mut self) -> Result<Measurement, AnyScd30Error>;
+
+    /// See [Scd30::set_automatic_self_calibration].
+    fn set_automatic_self_calibration(
+        &mut self,
+        setting: AutomaticSelfCalibration,
+    ) -> Result<(), AnyScd30Error>;
+
+    /// See [Scd30::get_automatic_self_calibration].
+    fn get_automatic_self_calibration(&mut self)
+        -> Result<AutomaticSelfCalibration, AnyScd30Error>;
+
+    /// See [Scd30::set_forced_recalibration].
+    fn set_forced_recalibration(
+        &mut self,
+        frc: ForcedRecalibrationValue,
+    ) -> Result<(), AnyScd30Error>;
+
+    /// See [Scd30::get_forced_recalibration].
+    fn get_forced_recalibration(&mut self) -> Result<ForcedRecalibrationValue, AnyScd30Error>;
+
+    /// See [Scd30::set_temperature_offset].
+    fn set_temperature_offset(&mut self, offset: TemperatureOffset) -> Result<(), AnyScd30Error>;
+
+    /// See [Scd30::get_temperature_offset].
+    fn get_temperature_offset(&mut self) -> Result<TemperatureOffset, AnyScd30Error>;
+
+    /// See [Scd30::set_altitude_compensation].
+    fn set_altitude_compensation(
+        &mut self,
+        altitude: AltitudeCompensation,
+    ) -> Result<(), AnyScd30Error>;
+
+    /// See [Scd30::get_altitude_compensation].
+    fn get_altitude_compensation(&mut self) -> Result<AltitudeCompensation, AnyScd30Error>;
+
+    /// See [Scd30::read_config].
+    fn read_config(&mut self) -> Result<Scd30Config, AnyScd30Error>;
+
+    /// See [Scd30::read_firmware_version].
+    fn read_firmware_version(&mut self) -> Result<FirmwareVersion, AnyScd30Error>;
+
+    /// See [Scd30::soft_reset].
+    fn soft_reset(&mut self) -> Result<(), AnyScd30Error>;
+}
+
+impl<I2C, D, I2cErr> AnyScd30 for Scd30<I2C, D>
+where
+    I2C: embedded_hal::i2c::I2c<Error = I2cErr>,
+    D: embedded_hal::delay::DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    fn trigger_continuous_measurements(
+        &mut self,
+        pressure_compensation: Option<AmbientPressureCompensation>,
+    ) -> Result<(), AnyScd30Error> {
+        Scd30::trigger_continuous_measurements(self, pressure_compensation)
+            .map_err(Scd30Error::into_kind)
+    }
+
+    fn stop_continuous_measurements(&mut self) -> Result<(), AnyScd30Error> {
+        Scd30::stop_continuous_measurements(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn set_measurement_interval(
+        &mut self,
+        interval: MeasurementInterval,
+    ) -> Result<(), AnyScd30Error> {
+        Scd30::set_measurement_interval(self, interval).map_err(Scd30Error::into_kind)
+    }
+
+    fn get_measurement_interval(&mut self) -> Result<MeasurementInterval, AnyScd30Error> {
+        Scd30::get_measurement_interval(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn is_data_ready(&mut self) -> Result<DataStatus, AnyScd30Error> {
+        Scd30::is_data_ready(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn read_measurement(&mut self) -> Result<Measurement, AnyScd30Error> {
+        Scd30::read_measurement(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn set_automatic_self_calibration(
+        &mut self,
+        setting: AutomaticSelfCalibration,
+    ) -> Result<(), AnyScd30Error> {
+        Scd30::set_automatic_self_calibration(self, setting).map_err(Scd30Error::into_kind)
+    }
+
+    fn get_automatic_self_calibration(
+        &mut self,
+    ) -> Result<AutomaticSelfCalibration, AnyScd30Error> {
+        Scd30::get_automatic_self_calibration(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn set_forced_recalibration(
+        &mut self,
+        frc: ForcedRecalibrationValue,
+    ) -> Result<(), AnyScd30Error> {
+        Scd30::set_forced_recalibration(self, frc).map_err(Scd30Error::into_kind)
+    }
+
+    fn get_forced_recalibration(&mut self) -> Result<ForcedRecalibrationValue, AnyScd30Error> {
+        Scd30::get_forced_recalibration(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn set_temperature_offset(&mut self, offset: TemperatureOffset) -> Result<(), AnyScd30Error> {
+        Scd30::set_temperature_offset(self, offset).map_err(Scd30Error::into_kind)
+    }
+
+    fn get_temperature_offset(&mut self) -> Result<TemperatureOffset, AnyScd30Error> {
+        Scd30::get_temperature_offset(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn set_altitude_compensation(
+        &mut self,
+        altitude: AltitudeCompensation,
+    ) -> Result<(), AnyScd30Error> {
+        Scd30::set_altitude_compensation(self, altitude).map_err(Scd30Error::into_kind)
+    }
+
+    fn get_altitude_compensation(&mut self) -> Result<AltitudeCompensation, AnyScd30Error> {
+        Scd30::get_altitude_compensation(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn read_config(&mut self) -> Result<Scd30Config, AnyScd30Error> {
+        Scd30::read_config(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn read_firmware_version(&mut self) -> Result<FirmwareVersion, AnyScd30Error> {
+        Scd30::read_firmware_version(self).map_err(Scd30Error::into_kind)
+    }
+
+    fn soft_reset(&mut self) -> Result<(), AnyScd30Error> {
+        Scd30::soft_reset(self).map_err(Scd30Error::into_kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command::Command, error::Phase};
+    use embedded_hal::i2c::NoAcknowledgeSource;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    #[test]
+    fn sensors_on_different_buses_share_a_trait_object() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+        ];
+        let mut owned = Scd30::new(I2cMock::new(&expected_transactions));
+        let mut borrowed_i2c = I2cMock::new(&[I2cTransaction::write(0x61, vec![0xD3, 0x04])
+            .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))]);
+        let mut borrowed = Scd30::new(&mut borrowed_i2c);
+
+        let sensors: [&mut dyn AnyScd30; 2] = [&mut owned, &mut borrowed];
+        let [owned_sensor, borrowed_sensor] = sensors;
+        assert_eq!(owned_sensor.is_data_ready(), Ok(DataStatus::Ready));
+        assert_eq!(
+            borrowed_sensor.soft_reset(),
+            Err(Scd30Error::CommandFailed {
+                command: Command::SoftReset,
+                phase: Phase::Write,
+                error: ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+            })
+        );
+
+        owned.shutdown().done();
+        borrowed.shutdown();
+        borrowed_i2c.done();
+    }
+}
@@ -0,0 +1,303 @@
+//! Helpers for running the SCD30 with [embassy](https://embassy.dev).
+//!
+//! Timing is provided by [embassy_time::Timer], so no [DelayNs](embedded_hal_async::delay::DelayNs)
+//! implementation has to be wired up by the user.
+//!
+//! Additionally [measurement_task] and [measurement_watch_task] run the complete poll and read loop
+//! and publish the measurements into an [embassy_sync] channel or watch. As embassy tasks can not
+//! be generic, call them from a task of your application:
+//!
+//! ```ignore
+//! static MEASUREMENTS: Channel<CriticalSectionRawMutex, Measurement, 4> = Channel::new();
+//!
+//! #[embassy_executor::task]
+//! async fn sensor_task(sensor: Scd30<I2c<'static, Async>>) {
+//!     let error = measurement_task(sensor, MEASUREMENTS.sender()).await;
+//!     defmt::error!("SCD30 failed: {}", error);
+//! }
+//! ```
+
+use embassy_sync::{blocking_mutex::raw::RawMutex, channel, watch};
+use embassy_time::{Delay, Duration};
+
+use crate::{asynch::Scd30, data::Measurement, error::Scd30Error};
+
+/// Interval in which the data ready status is polled while waiting for a measurement.
+pub const DATA_READY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+impl<I2C, D, I2cErr> Scd30<I2C, D>
+where
+    I2C: embedded_hal_async::i2c::I2c<Error = I2cErr>,
+    D: embedded_hal_async::delay::DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    /// Waits until a measurement is available for readout. The data ready status is polled every
+    /// [DATA_READY_POLL_INTERVAL].
+    ///
+    /// # Errors
+    ///
+    /// - [Timeout](crate::error::Scd30Error::Timeout) if no measurement became ready within
+    ///   `timeout`, e.g. after a brown-out of the sensor.
+    pub async fn wait_for_data_ready(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        let timeout_ms = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+        self.wait_for_sample(&mut Delay, timeout_ms).await
+    }
+
+    /// Waits for the next available measurement and reads it out. Samples discarded by the
+    /// configured [WarmUpPolicy](crate::options::WarmUpPolicy) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// - [Timeout](crate::error::Scd30Error::Timeout) if no measurement became ready within
+    ///   twice the measurement interval.
+    pub async fn next_measurement(&mut self) -> Result<Measurement, Scd30Error<I2cErr>> {
+        loop {
+            let timeout_ms = self.sample_timeout_ms();
+            self.wait_for_sample(&mut Delay, timeout_ms).await?;
+            match self.read_measurement().await {
+                Err(Scd30Error::WarmingUp) => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Owns the sensor and continuously publishes every new [Measurement] into the channel behind
+/// `sender`. If the channel is full, the task waits until space is available.
+///
+/// The loop only ends if an error occurs, which is then returned.
+pub async fn measurement_task<I2C, D, I2cErr, M, const N: usize>(
+    mut sensor: Scd30<I2C, D>,
+    sender: channel::Sender<'_, M, Measurement, N>,
+) -> Scd30Error<I2cErr>
+where
+    I2C: embedded_hal_async::i2c::I2c<Error = I2cErr>,
+    D: embedded_hal_async::delay::DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+    M: RawMutex,
+{
+    loop {
+        match sensor.next_measurement().await {
+            Ok(measurement) => sender.send(measurement).await,
+            Err(error) => return error,
+        }
+    }
+}
+
+/// Owns the sensor and continuously publishes every new [Measurement] into the watch behind
+/// `sender`. Receivers only see the latest measurement.
+///
+/// The loop only ends if an error occurs, which is then returned.
+pub async fn measurement_watch_task<I2C, D, I2cErr, M, const N: usize>(
+    mut sensor: Scd30<I2C, D>,
+    sender: watch::Sender<'_, M, Measurement, N>,
+) -> Scd30Error<I2cErr>
+where
+    I2C: embedded_hal_async::i2c::I2c<Error = I2cErr>,
+    D: embedded_hal_async::delay::DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+    M: RawMutex,
+{
+    loop {
+        match sensor.next_measurement().await {
+            Ok(measurement) => sender.send(measurement),
+            Err(error) => return error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command::Command, error::Phase};
+    use embassy_sync::{blocking_mutex::raw::NoopRawMutex, channel::Channel, watch::Watch};
+    use embedded_hal::i2c;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    #[tokio::test]
+    async fn wait_for_data_ready_polls_until_ready() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x81]),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+        ];
+
+        let i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = Scd30::new(i2c);
+
+        sensor
+            .wait_for_data_ready(Duration::from_secs(1))
+            .await
+            .unwrap();
+        sensor.shutdown().done();
+    }
+
+    #[tokio::test]
+    async fn wait_for_data_ready_times_out() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x81]),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x81]),
+        ];
+
+        let i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = Scd30::new(i2c);
+
+        assert_eq!(
+            sensor
+                .wait_for_data_ready(DATA_READY_POLL_INTERVAL)
+                .await
+                .unwrap_err(),
+            Scd30Error::Timeout
+        );
+        assert_eq!(sensor.error_counters().timeout, 1);
+        sensor.shutdown().done();
+    }
+
+    struct WedgedBus;
+
+    impl i2c::ErrorType for WedgedBus {
+        type Error = i2c::ErrorKind;
+    }
+
+    impl embedded_hal_async::i2c::I2c for WedgedBus {
+        async fn transaction(
+            &mut self,
+            _address: u8,
+            _operations: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            core::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn wedged_bus_times_out() {
+        let mut sensor = Scd30::new(WedgedBus).with_timeout(10);
+
+        assert_eq!(
+            sensor.is_data_ready().await.unwrap_err(),
+            Scd30Error::Timeout
+        );
+        assert_eq!(
+            sensor.stop_continuous_measurements().await.unwrap_err(),
+            Scd30Error::Timeout
+        );
+        assert_eq!(sensor.error_counters().timeout, 2);
+    }
+
+    #[tokio::test]
+    async fn next_measurement_spec_example() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+            I2cTransaction::write(0x61, vec![0x03, 0x00]),
+            I2cTransaction::read(
+                0x61,
+                vec![
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ],
+            ),
+        ];
+
+        let i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = Scd30::new(i2c);
+
+        let measurement = sensor.next_measurement().await.unwrap();
+        assert_eq!(measurement.co2_concentration, 439.09515);
+        assert_eq!(measurement.temperature, 27.23828);
+        assert_eq!(measurement.humidity, 48.806744);
+        sensor.shutdown().done();
+    }
+
+    #[tokio::test]
+    async fn next_measurement_errors_on_i2c_error() {
+        let expected_transactions =
+            [I2cTransaction::write(0x61, vec![0x02, 0x02]).with_error(i2c::ErrorKind::Other)];
+
+        let i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = Scd30::new(i2c);
+
+        assert_eq!(
+            sensor.next_measurement().await.unwrap_err(),
+            Scd30Error::CommandFailed {
+                command: Command::GetDataReady,
+                phase: Phase::Write,
+                error: i2c::ErrorKind::Other,
+            }
+        );
+        sensor.shutdown().done();
+    }
+
+    #[tokio::test]
+    async fn measurement_task_publishes_into_channel() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+            I2cTransaction::write(0x61, vec![0x03, 0x00]),
+            I2cTransaction::read(
+                0x61,
+                vec![
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ],
+            ),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]).with_error(i2c::ErrorKind::Other),
+        ];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+        let channel = Channel::<NoopRawMutex, Measurement, 2>::new();
+
+        let sensor = Scd30::new(i2c.clone());
+
+        let error = measurement_task(sensor, channel.sender()).await;
+        assert_eq!(
+            error.failed_command(),
+            Some((Command::GetDataReady, Phase::Write))
+        );
+        let measurement = channel.try_receive().unwrap();
+        assert_eq!(measurement.co2_concentration, 439.09515);
+        assert!(channel.try_receive().is_err());
+        i2c.done();
+    }
+
+    #[tokio::test]
+    async fn measurement_watch_task_publishes_into_watch() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+            I2cTransaction::write(0x61, vec![0x03, 0x00]),
+            I2cTransaction::read(
+                0x61,
+                vec![
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ],
+            ),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]).with_error(i2c::ErrorKind::Other),
+        ];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+        let watch = Watch::<NoopRawMutex, Measurement, 1>::new();
+
+        let sensor = Scd30::new(i2c.clone());
+
+        let error = measurement_watch_task(sensor, watch.sender()).await;
+        assert_eq!(
+            error.failed_command(),
+            Some((Command::GetDataReady, Phase::Write))
+        );
+        let measurement = watch.try_get().unwrap();
+        assert_eq!(measurement.temperature, 27.23828);
+        i2c.done();
+    }
+}
@@ -0,0 +1,249 @@
+//! Pluggable output formats for measurements.
+//!
+//! Every output format implements [MeasurementEncoder], so applications can select the format at
+//! runtime, e.g. from a configuration, or add their own without changes to the driver:
+//!
+//! ```
+//! use scd30_interface::{
+//!     data::Measurement,
+//!     encoder::{CayenneLpp, Compact, Csv, Json, MeasurementEncoder},
+//! };
+//!
+//! fn encoder(format: &str) -> &'static dyn MeasurementEncoder {
+//!     match format {
+//!         "csv" => &Csv,
+//!         "json" => &Json,
+//!         "lpp" => &CayenneLpp { channel: 1 },
+//!         _ => &Compact,
+//!     }
+//! }
+//!
+//! let mut buf = [0; 64];
+//! let len = encoder("json")
+//!     .encode(&Measurement::new(811.6, 23.44, 45.06), &mut buf)
+//!     .unwrap();
+//! assert_eq!(
+//!     &buf[..len],
+//!     br#"{"co2_ppm":812,"temperature_c":23.4,"humidity_pct":45.1}"#
+//! );
+//! ```
+//!
+//! The text formats use the fixed-point numbers of the [format](crate::format) module, so no float
+//! formatting code is pulled in.
+
+use core::fmt::{self, Write};
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{
+    ble::encode_advertisement,
+    data::Measurement,
+    format::{BufWriter, FixedPoint},
+};
+
+/// Encodes measurements into a byte buffer in a specific output format.
+pub trait MeasurementEncoder {
+    /// Encodes `measurement` into `buf` and returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// - [fmt::Error] if `buf` is too small to hold the encoded measurement.
+    fn encode(&self, measurement: &Measurement, buf: &mut [u8]) -> Result<usize, fmt::Error>;
+
+    /// Encodes `measurement` into a buffer growing to fit the encoded measurement, for targets
+    /// with a heap.
+    ///
+    /// # Errors
+    ///
+    /// - [fmt::Error] if the encoder fails or needs more than [MAX_ENCODED_LEN] bytes.
+    #[cfg(feature = "alloc")]
+    fn encode_to_vec(&self, measurement: &Measurement) -> Result<Vec<u8>, fmt::Error> {
+        let mut buf = vec![0; 16];
+        loop {
+            if let Ok(len) = self.encode(measurement, &mut buf) {
+                buf.truncate(len);
+                return Ok(buf);
+            }
+            if buf.len() >= MAX_ENCODED_LEN {
+                return Err(fmt::Error);
+            }
+            buf.resize(buf.len() * 2, 0);
+        }
+    }
+}
+
+/// Size up to which [MeasurementEncoder::encode_to_vec] grows its buffer.
+#[cfg(feature = "alloc")]
+pub const MAX_ENCODED_LEN: usize = 1024;
+
+/// The 6 byte representation of [Measurement::to_compact].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Compact;
+
+impl MeasurementEncoder for Compact {
+    fn encode(&self, measurement: &Measurement, buf: &mut [u8]) -> Result<usize, fmt::Error> {
+        copy_into(&measurement.to_compact(), buf)
+    }
+}
+
+/// A CSV record like `812,23.4,45.1`, terminated by a newline. The columns are described by
+/// [Csv::HEADER].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Csv;
+
+impl Csv {
+    /// Header line of the records, terminated by a newline.
+    pub const HEADER: &'static str = "co2_ppm,temperature_c,humidity_pct\n";
+}
+
+impl MeasurementEncoder for Csv {
+    fn encode(&self, measurement: &Measurement, buf: &mut [u8]) -> Result<usize, fmt::Error> {
+        let mut writer = BufWriter::new(buf);
+        writeln!(
+            writer,
+            "{},{},{}",
+            measurement.co2_concentration_fixed(),
+            measurement.temperature_fixed(),
+            measurement.humidity_fixed()
+        )?;
+        Ok(writer.as_str().len())
+    }
+}
+
+/// A JSON object like `{"co2_ppm":812,"temperature_c":23.4,"humidity_pct":45.1}`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Json;
+
+impl MeasurementEncoder for Json {
+    fn encode(&self, measurement: &Measurement, buf: &mut [u8]) -> Result<usize, fmt::Error> {
+        let mut writer = BufWriter::new(buf);
+        write!(
+            writer,
+            r#"{{"co2_ppm":{},"temperature_c":{},"humidity_pct":{}}}"#,
+            measurement.co2_concentration_fixed(),
+            measurement.temperature_fixed(),
+            measurement.humidity_fixed()
+        )?;
+        Ok(writer.as_str().len())
+    }
+}
+
+/// A [Cayenne LPP](https://docs.mydevices.com/docs/lorawan/cayenne-lpp) payload for LoRaWAN, with
+/// the temperature (type 103), the relative humidity (type 104) and the CO2 concentration as
+/// concentration in ppm (type 125) on the same channel. Takes 11 bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CayenneLpp {
+    /// Channel of the data points.
+    pub channel: u8,
+}
+
+const LPP_TEMPERATURE: u8 = 103;
+const LPP_HUMIDITY: u8 = 104;
+const LPP_CONCENTRATION: u8 = 125;
+
+impl MeasurementEncoder for CayenneLpp {
+    fn encode(&self, measurement: &Measurement, buf: &mut [u8]) -> Result<usize, fmt::Error> {
+        let temperature = FixedPoint::from_f32(measurement.temperature, 1)
+            .mantissa()
+            .clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        // Humidity is encoded in steps of 0.5 %.
+        let humidity = FixedPoint::from_f32(measurement.humidity * 2.0, 0)
+            .mantissa()
+            .clamp(0, u8::MAX as i32) as u8;
+        let co2 = FixedPoint::from_f32(measurement.co2_concentration, 0)
+            .mantissa()
+            .clamp(0, u16::MAX as i32) as u16;
+
+        let mut payload = [0; 11];
+        payload[0] = self.channel;
+        payload[1] = LPP_TEMPERATURE;
+        BigEndian::write_i16(&mut payload[2..4], temperature);
+        payload[4] = self.channel;
+        payload[5] = LPP_HUMIDITY;
+        payload[6] = humidity;
+        payload[7] = self.channel;
+        payload[8] = LPP_CONCENTRATION;
+        BigEndian::write_u16(&mut payload[9..11], co2);
+        copy_into(&payload, buf)
+    }
+}
+
+/// The BLE advertisement of Sensirion's gadgets, see [ble](crate::ble).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GadgetAdvertisement {
+    /// Identifier of the device, see [encode_advertisement].
+    pub device_id: [u8; 2],
+}
+
+impl MeasurementEncoder for GadgetAdvertisement {
+    fn encode(&self, measurement: &Measurement, buf: &mut [u8]) -> Result<usize, fmt::Error> {
+        copy_into(&encode_advertisement(measurement, self.device_id), buf)
+    }
+}
+
+fn copy_into(data: &[u8], buf: &mut [u8]) -> Result<usize, fmt::Error> {
+    buf.get_mut(..data.len())
+        .ok_or(fmt::Error)?
+        .copy_from_slice(data);
+    Ok(data.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEASUREMENT: Measurement = Measurement::new(811.6, 23.44, 45.06);
+
+    fn encode(encoder: &dyn MeasurementEncoder) -> Vec<u8> {
+        let mut buf = [0; 64];
+        let len = encoder.encode(&MEASUREMENT, &mut buf).unwrap();
+        buf[..len].to_vec()
+    }
+
+    #[test]
+    fn text_formats_render_fixed_point_values() {
+        assert_eq!(encode(&Csv), b"812,23.4,45.1\n");
+        assert_eq!(
+            encode(&Json),
+            br#"{"co2_ppm":812,"temperature_c":23.4,"humidity_pct":45.1}"#
+        );
+    }
+
+    #[test]
+    fn binary_formats_are_encoded() {
+        assert_eq!(encode(&Compact), MEASUREMENT.to_compact());
+        assert_eq!(
+            encode(&CayenneLpp { channel: 1 }),
+            [0x01, 0x67, 0x00, 0xEA, 0x01, 0x68, 0x5A, 0x01, 0x7D, 0x03, 0x2C]
+        );
+        assert_eq!(
+            encode(&GadgetAdvertisement { device_id: [0, 1] }),
+            encode_advertisement(&MEASUREMENT, [0, 1])
+        );
+    }
+
+    #[test]
+    fn small_buffers_are_rejected() {
+        let mut buf = [0; 5];
+        assert_eq!(Compact.encode(&MEASUREMENT, &mut buf), Err(fmt::Error));
+        assert_eq!(Json.encode(&MEASUREMENT, &mut buf), Err(fmt::Error));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn buffer_grows_to_fit_encoded_measurement() {
+        struct Failing;
+        impl MeasurementEncoder for Failing {
+            fn encode(&self, _: &Measurement, _: &mut [u8]) -> Result<usize, fmt::Error> {
+                Err(fmt::Error)
+            }
+        }
+
+        assert_eq!(Json.encode_to_vec(&MEASUREMENT), Ok(encode(&Json)));
+        assert_eq!(Compact.encode_to_vec(&MEASUREMENT), Ok(encode(&Compact)));
+        assert_eq!(Failing.encode_to_vec(&MEASUREMENT), Err(fmt::Error));
+    }
+}
@@ -1,8 +1,12 @@
 //! Errors emitted by this library.
 
+use core::fmt;
+
 use embedded_hal::i2c;
 use thiserror::Error;
 
+use crate::command::Command;
+
 /// Error variants emitted by this library.
 #[derive(Debug, Error, PartialEq)]
 pub enum Scd30Error<I2cErr: i2c::Error> {
@@ -12,10 +16,46 @@ pub enum Scd30Error<I2cErr: i2c::Error> {
     /// Emitted when an error with the underlying I2C bus has occurred.
     #[error(transparent)]
     I2cError(#[from] I2cErr),
+    /// Emitted when an error with the underlying I2C bus has occurred while sending a command or
+    /// reading its response. Bus errors outside of commands, e.g. of a general call reset, are
+    /// emitted as [I2cError](Scd30Error::I2cError).
+    #[error("{phase} {command:?} failed: {error}")]
+    CommandFailed {
+        /// Command whose transfer failed
+        command: Command,
+        /// Whether writing the command or reading its response failed
+        phase: Phase,
+        /// Error of the I2C bus
+        error: I2cErr,
+    },
     /// Emitted when the argument intended to be sent to the sensor is bigger than 16-bits. Should
     /// only occur if modifications to this library where made that send such data.
     #[error("Only 16-bits of data can be send")]
     SentDataToBig,
+    /// Emitted when a measurement was read during the warm-up period and the configured
+    /// [WarmUpPolicy](crate::options::WarmUpPolicy) discards such samples.
+    #[error("Measurement discarded during sensor warm-up")]
+    WarmingUp,
+    /// Emitted by the data-ready watchdog when no new sample became ready within the configured
+    /// multiple of the measurement interval. This commonly happens after a brown-out of the
+    /// sensor; restarting continuous measurements or resetting the sensor usually recovers it.
+    #[error("No measurement became ready for {elapsed_ms} ms")]
+    MeasurementOverdue {
+        /// Time since the last sample became ready, in milliseconds
+        elapsed_ms: u64,
+    },
+    /// Emitted when an operation did not complete within the configured timeout, e.g. due to a
+    /// hung bus or the sensor stretching the clock for too long.
+    #[error("Operation timed out")]
+    Timeout,
+    /// Emitted when reading a measurement or performing a forced recalibration after continuous
+    /// measurements were stopped, as the sensor would only return stale data.
+    #[error("Continuous measurements are stopped")]
+    NotMeasuring,
+    /// Emitted in strict mode when a command would violate the sequencing rules of the
+    /// interface description, see [SequenceGuard](crate::sequencing::SequenceGuard).
+    #[error(transparent)]
+    Sequence(#[from] SequenceViolation),
 }
 
 #[cfg(feature = "defmt")]
@@ -25,6 +65,361 @@ impl<I2cErr: i2c::Error> defmt::Format for Scd30Error<I2cErr> {
     }
 }
 
+impl<I2cErr: i2c::Error> Scd30Error<I2cErr> {
+    /// Returns the error of the underlying bus, if this is an [I2cError](Scd30Error::I2cError) or
+    /// a [CommandFailed](Scd30Error::CommandFailed).
+    pub fn i2c_error(&self) -> Option<&I2cErr> {
+        match self {
+            Scd30Error::I2cError(error) | Scd30Error::CommandFailed { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Returns the command and its phase in which the bus failed, if this is a
+    /// [CommandFailed](Scd30Error::CommandFailed).
+    pub fn failed_command(&self) -> Option<(Command, Phase)> {
+        match self {
+            Scd30Error::CommandFailed { command, phase, .. } => Some((*command, *phase)),
+            _ => None,
+        }
+    }
+
+    /// Returns the classification of the underlying bus error, if this is an
+    /// [I2cError](Scd30Error::I2cError) or a [CommandFailed](Scd30Error::CommandFailed).
+    pub fn bus_fault(&self) -> Option<BusFault> {
+        self.i2c_error().map(|error| BusFault::from(error.kind()))
+    }
+
+    /// Returns a short static code identifying the error, e.g. `"E_CRC"`, for logs and panic
+    /// messages on targets too small for the [Display](core::fmt::Display) formatting.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Scd30Error::DataError(error) => error.code(),
+            Scd30Error::I2cError(error) | Scd30Error::CommandFailed { error, .. } => {
+                match error.kind() {
+                    i2c::ErrorKind::NoAcknowledge(_) => "E_NACK",
+                    i2c::ErrorKind::ArbitrationLoss => "E_ARBITRATION",
+                    i2c::ErrorKind::Bus => "E_BUS",
+                    i2c::ErrorKind::Overrun => "E_OVERRUN",
+                    _ => "E_I2C",
+                }
+            }
+            Scd30Error::SentDataToBig => "E_ARG_SIZE",
+            Scd30Error::WarmingUp => "E_WARMUP",
+            Scd30Error::MeasurementOverdue { .. } => "E_OVERDUE",
+            Scd30Error::Timeout => "E_TIMEOUT",
+            Scd30Error::NotMeasuring => "E_STOPPED",
+            Scd30Error::Sequence(_) => "E_SEQUENCE",
+        }
+    }
+
+    /// Replaces the I2C error by its [ErrorKind](i2c::ErrorKind), e.g. to handle errors of
+    /// different I2C implementations alike.
+    pub fn into_kind(self) -> Scd30Error<i2c::ErrorKind> {
+        match self {
+            Scd30Error::DataError(error) => Scd30Error::DataError(error),
+            Scd30Error::I2cError(error) => Scd30Error::I2cError(error.kind()),
+            Scd30Error::CommandFailed {
+                command,
+                phase,
+                error,
+            } => Scd30Error::CommandFailed {
+                command,
+                phase,
+                error: error.kind(),
+            },
+            Scd30Error::SentDataToBig => Scd30Error::SentDataToBig,
+            Scd30Error::WarmingUp => Scd30Error::WarmingUp,
+            Scd30Error::MeasurementOverdue { elapsed_ms } => {
+                Scd30Error::MeasurementOverdue { elapsed_ms }
+            }
+            Scd30Error::Timeout => Scd30Error::Timeout,
+            Scd30Error::NotMeasuring => Scd30Error::NotMeasuring,
+            Scd30Error::Sequence(violation) => Scd30Error::Sequence(violation),
+        }
+    }
+
+    /// Returns the suggested action to recover from the error, if the error is transient.
+    /// Configuration errors like out of range values return `None`.
+    pub fn recovery_action(&self) -> Option<RecoveryAction> {
+        match self {
+            Scd30Error::I2cError(_) | Scd30Error::CommandFailed { .. } => {
+                self.bus_fault().map(|fault| fault.recovery_action())
+            }
+            Scd30Error::DataError(
+                DataError::CrcFailed
+                | DataError::ReceivedBufferWrongSize
+                | DataError::InconsistentReads,
+            ) => Some(RecoveryAction::Retry),
+            Scd30Error::MeasurementOverdue { .. } => Some(RecoveryAction::GeneralCallReset),
+            Scd30Error::Timeout => Some(RecoveryAction::BusClear),
+            _ => None,
+        }
+    }
+}
+
+/// Phase of a command in which the bus failed, see [CommandFailed](Scd30Error::CommandFailed).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Writing the command, including its argument.
+    Write,
+    /// Reading the response of the command.
+    Read,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Write => write!(f, "Writing"),
+            Phase::Read => write!(f, "Reading the response of"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Phase {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Phase::Write => defmt::write!(f, "Write"),
+            Phase::Read => defmt::write!(f, "Read"),
+        }
+    }
+}
+
+/// Errors emitted by the supervised interface, see
+/// [SupervisedScd30](crate::supervisor::blocking::SupervisedScd30).
+#[derive(Debug, Error, PartialEq)]
+pub enum SupervisorError<I2cErr: i2c::Error> {
+    /// Emitted for errors which can not be resolved by resetting the sensor, e.g. values out of
+    /// range or samples discarded during the warm-up. The error is passed on unchanged.
+    #[error(transparent)]
+    Sensor(Scd30Error<I2cErr>),
+    /// Emitted when the sensor still fails after all recovery steps were taken. Contains the last
+    /// error encountered. The sensor likely needs to be power cycled or the bus to be cleared.
+    #[error("Sensor unresponsive after all recovery steps: {0}")]
+    Unresponsive(Scd30Error<I2cErr>),
+}
+
+impl<I2cErr: i2c::Error> SupervisorError<I2cErr> {
+    /// Returns a short static code identifying the error, see [Scd30Error::code].
+    pub fn code(&self) -> &'static str {
+        match self {
+            SupervisorError::Sensor(error) => error.code(),
+            SupervisorError::Unresponsive(_) => "E_UNRESPONSIVE",
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<I2cErr: i2c::Error> defmt::Format for SupervisorError<I2cErr> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            SupervisorError::Sensor(error) => defmt::write!(f, "{}", error),
+            SupervisorError::Unresponsive(error) => defmt::write!(f, "Unresponsive: {}", error),
+        }
+    }
+}
+
+/// Errors emitted when saving or loading a configuration record, see [crate::storage].
+#[cfg(feature = "storage")]
+#[derive(Debug, Error, PartialEq)]
+pub enum StorageError<FlashErr: embedded_storage::nor_flash::NorFlashError> {
+    /// Emitted when the flash reported an error, e.g. for an offset not aligned to its erase size.
+    #[error("Flash access failed: {0:?}")]
+    Flash(FlashErr),
+    /// Emitted when the record's length is not a multiple of the flash's read or write size.
+    #[error("Flash read or write size not supported")]
+    UnsupportedFlash,
+    /// Emitted when the stored record fails its CRC check or contains invalid values.
+    #[error("Stored configuration record is corrupted")]
+    Corrupted,
+    /// Emitted when the stored record was written in a format not known to this version of the
+    /// library.
+    #[error("Stored configuration record has unsupported version {0}")]
+    UnsupportedVersion(u8),
+    /// Emitted when a setting of the record can not be saved, e.g. an
+    /// [Unknown](crate::data::AutomaticSelfCalibration::Unknown) self-calibration state.
+    #[error("Invalid setting: {0}")]
+    Invalid(DataError),
+}
+
+#[cfg(feature = "storage")]
+impl<FlashErr: embedded_storage::nor_flash::NorFlashError> StorageError<FlashErr> {
+    /// Returns a short static code identifying the error, see [Scd30Error::code].
+    pub fn code(&self) -> &'static str {
+        match self {
+            StorageError::Flash(_) => "E_FLASH",
+            StorageError::UnsupportedFlash => "E_FLASH_SIZE",
+            StorageError::Corrupted => "E_RECORD_CORRUPT",
+            StorageError::UnsupportedVersion(_) => "E_RECORD_VERSION",
+            StorageError::Invalid(_) => "E_RECORD_INVALID",
+        }
+    }
+}
+
+#[cfg(all(feature = "storage", feature = "defmt"))]
+impl<FlashErr: embedded_storage::nor_flash::NorFlashError> defmt::Format
+    for StorageError<FlashErr>
+{
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            StorageError::Flash(error) => {
+                defmt::write!(f, "Flash access failed: {}", defmt::Debug2Format(error))
+            }
+            StorageError::UnsupportedFlash => {
+                defmt::write!(f, "Flash read or write size not supported")
+            }
+            StorageError::Corrupted => defmt::write!(f, "Stored configuration record is corrupted"),
+            StorageError::UnsupportedVersion(version) => defmt::write!(
+                f,
+                "Stored configuration record has unsupported version {}",
+                version
+            ),
+            StorageError::Invalid(error) => defmt::write!(f, "Invalid setting: {}", error),
+        }
+    }
+}
+
+/// Classification of I2C bus errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusFault {
+    /// The sensor did not acknowledge its address. It is either absent, unpowered or still
+    /// booting.
+    AddressNack,
+    /// The sensor did not acknowledge a data byte, it is likely in an inconsistent state.
+    DataNack,
+    /// Another controller on the bus won the arbitration.
+    ArbitrationLoss,
+    /// A misplaced start or stop condition was detected, e.g. due to a stuck data line.
+    Bus,
+    /// The controller could not keep up with the received data.
+    Overrun,
+    /// Any other error reported by the I2C implementation.
+    Other,
+}
+
+impl From<i2c::ErrorKind> for BusFault {
+    fn from(kind: i2c::ErrorKind) -> Self {
+        match kind {
+            i2c::ErrorKind::NoAcknowledge(i2c::NoAcknowledgeSource::Data) => BusFault::DataNack,
+            i2c::ErrorKind::NoAcknowledge(_) => BusFault::AddressNack,
+            i2c::ErrorKind::ArbitrationLoss => BusFault::ArbitrationLoss,
+            i2c::ErrorKind::Bus => BusFault::Bus,
+            i2c::ErrorKind::Overrun => BusFault::Overrun,
+            _ => BusFault::Other,
+        }
+    }
+}
+
+impl BusFault {
+    /// Returns the action suggested to recover from this fault.
+    pub const fn recovery_action(&self) -> RecoveryAction {
+        match self {
+            BusFault::AddressNack | BusFault::ArbitrationLoss | BusFault::Overrun => {
+                RecoveryAction::Retry
+            }
+            BusFault::DataNack | BusFault::Other => RecoveryAction::GeneralCallReset,
+            BusFault::Bus => RecoveryAction::BusClear,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for BusFault {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            BusFault::AddressNack => defmt::write!(f, "AddressNack"),
+            BusFault::DataNack => defmt::write!(f, "DataNack"),
+            BusFault::ArbitrationLoss => defmt::write!(f, "ArbitrationLoss"),
+            BusFault::Bus => defmt::write!(f, "Bus"),
+            BusFault::Overrun => defmt::write!(f, "Overrun"),
+            BusFault::Other => defmt::write!(f, "Other"),
+        }
+    }
+}
+
+/// Actions suggested to recover from a transient error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Retry the failed operation, optionally after a short delay.
+    Retry,
+    /// Reset the sensor with `general_call_reset()` and retry afterwards.
+    GeneralCallReset,
+    /// Release the bus by clocking SCL until SDA is released, then retry. This requires direct
+    /// control over the pins, see [clear_bus](crate::recovery::clear_bus).
+    BusClear,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RecoveryAction {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            RecoveryAction::Retry => defmt::write!(f, "Retry"),
+            RecoveryAction::GeneralCallReset => defmt::write!(f, "GeneralCallReset"),
+            RecoveryAction::BusClear => defmt::write!(f, "BusClear"),
+        }
+    }
+}
+
+/// Sequencing rules of the interface description violated by a command, see
+/// [SequenceGuard](crate::sequencing::SequenceGuard).
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum SequenceViolation {
+    /// The sensor is still booting after a reset and would not acknowledge the command.
+    #[error("Sensor is booting for another {remaining_ms} ms after the reset")]
+    SensorBooting {
+        /// Time until the sensor accepts commands, in milliseconds
+        remaining_ms: u64,
+    },
+    /// The previous configuration command was sent too recently, the sensor may still process
+    /// it and ignore this one.
+    #[error("Configuration command sent {remaining_ms} ms too early after the previous one")]
+    CommandTooEarly {
+        /// Time until the next configuration command may be sent, in milliseconds
+        remaining_ms: u64,
+    },
+    /// A forced recalibration was requested without continuous measurements running.
+    #[error("Forced recalibration requires running continuous measurements")]
+    RecalibrationNotMeasuring,
+    /// A forced recalibration was requested before the sensor measured for the calibration soak.
+    #[error("Forced recalibration requires another {remaining_ms} ms of continuous measurements")]
+    RecalibrationBeforeSoak {
+        /// Time until the soak is completed, in milliseconds
+        remaining_ms: u64,
+    },
+    /// The measurement interval was changed during the calibration soak.
+    #[error(
+        "Measurement interval changed {remaining_ms} ms before the calibration soak completes"
+    )]
+    IntervalChangeDuringSoak {
+        /// Time until the soak is completed, in milliseconds
+        remaining_ms: u64,
+    },
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SequenceViolation {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            SequenceViolation::SensorBooting { remaining_ms } => {
+                defmt::write!(f, "SensorBooting({}ms)", remaining_ms)
+            }
+            SequenceViolation::CommandTooEarly { remaining_ms } => {
+                defmt::write!(f, "CommandTooEarly({}ms)", remaining_ms)
+            }
+            SequenceViolation::RecalibrationNotMeasuring => {
+                defmt::write!(f, "RecalibrationNotMeasuring")
+            }
+            SequenceViolation::RecalibrationBeforeSoak { remaining_ms } => {
+                defmt::write!(f, "RecalibrationBeforeSoak({}ms)", remaining_ms)
+            }
+            SequenceViolation::IntervalChangeDuringSoak { remaining_ms } => {
+                defmt::write!(f, "IntervalChangeDuringSoak({}ms)", remaining_ms)
+            }
+        }
+    }
+}
+
 /// Error variants handling data errors.
 #[derive(Debug, Error, PartialEq)]
 pub enum DataError {
@@ -62,6 +457,43 @@ pub enum DataError {
         /// Actual value received
         actual: u16,
     },
+    /// Emitted when the sensor reports a failed forced recalibration, e.g. because it was not
+    /// measuring long enough before.
+    #[error("Forced recalibration failed.")]
+    RecalibrationFailed,
+    /// Emitted when a raw value does not correspond to any command of the SCD30.
+    #[error("Unknown command {opcode:#06X}.")]
+    UnknownCommand {
+        /// Raw value of the command
+        opcode: u16,
+    },
+    /// Emitted by the [double read verification](crate::options::ReadVerification::DoubleRead)
+    /// when two reads of the same response differ, e.g. due to a glitch on the bus passing the
+    /// CRC check.
+    #[error("Repeated reads returned different data.")]
+    InconsistentReads,
+    /// Emitted when a text can not be parsed as [FirmwareVersion](crate::data::FirmwareVersion),
+    /// which is formatted as `<major>.<minor>`, e.g. `3.66`.
+    #[error("Firmware version must be formatted as <major>.<minor>.")]
+    InvalidFirmwareVersion,
+}
+
+impl DataError {
+    /// Returns a short static code identifying the error, e.g. `"E_RANGE"`, for logs and panic
+    /// messages on targets too small for the [Display](core::fmt::Display) formatting.
+    pub const fn code(&self) -> &'static str {
+        match self {
+            DataError::ValueOutOfRange { .. } => "E_RANGE",
+            DataError::UseDefaultPressure => "E_PRESSURE",
+            DataError::CrcFailed => "E_CRC",
+            DataError::ReceivedBufferWrongSize => "E_SIZE",
+            DataError::UnexpectedValueReceived { .. } => "E_VALUE",
+            DataError::RecalibrationFailed => "E_FRC",
+            DataError::UnknownCommand { .. } => "E_COMMAND",
+            DataError::InconsistentReads => "E_MISMATCH",
+            DataError::InvalidFirmwareVersion => "E_VERSION",
+        }
+    }
 }
 
 #[cfg(feature = "defmt")]
@@ -70,3 +502,77 @@ impl defmt::Format for DataError {
         defmt::write!(f, "{}", self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+    #[test]
+    fn nack_is_classified_by_source() {
+        let error = Scd30Error::I2cError(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        assert_eq!(error.bus_fault(), Some(BusFault::AddressNack));
+        assert_eq!(error.recovery_action(), Some(RecoveryAction::Retry));
+
+        let error = Scd30Error::I2cError(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+        assert_eq!(error.bus_fault(), Some(BusFault::DataNack));
+        assert_eq!(
+            error.recovery_action(),
+            Some(RecoveryAction::GeneralCallReset)
+        );
+    }
+
+    #[test]
+    fn failed_command_keeps_bus_error() {
+        let error = Scd30Error::CommandFailed {
+            command: Command::GetDataReady,
+            phase: Phase::Read,
+            error: ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data),
+        };
+        assert_eq!(error.bus_fault(), Some(BusFault::DataNack));
+        assert_eq!(error.code(), "E_NACK");
+        assert_eq!(
+            error.failed_command(),
+            Some((Command::GetDataReady, Phase::Read))
+        );
+        assert_eq!(Scd30Error::I2cError(ErrorKind::Bus).failed_command(), None);
+    }
+
+    #[test]
+    fn bus_error_suggests_bus_clear() {
+        let error = Scd30Error::I2cError(ErrorKind::Bus);
+        assert_eq!(error.bus_fault(), Some(BusFault::Bus));
+        assert_eq!(error.recovery_action(), Some(RecoveryAction::BusClear));
+    }
+
+    #[test]
+    fn errors_have_static_codes() {
+        let error: Scd30Error<ErrorKind> = DataError::CrcFailed.into();
+        assert_eq!(error.code(), "E_CRC");
+        let error: Scd30Error<ErrorKind> = DataError::ValueOutOfRange {
+            parameter: "Altitude",
+            min: 0,
+            max: 1,
+            unit: "m",
+        }
+        .into();
+        assert_eq!(error.code(), "E_RANGE");
+        let error = Scd30Error::I2cError(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+        assert_eq!(error.code(), "E_NACK");
+        assert_eq!(Scd30Error::<ErrorKind>::Timeout.code(), "E_TIMEOUT");
+        assert_eq!(
+            SupervisorError::Unresponsive(Scd30Error::<ErrorKind>::Timeout).code(),
+            "E_UNRESPONSIVE"
+        );
+    }
+
+    #[test]
+    fn data_errors_have_no_bus_fault() {
+        let error: Scd30Error<ErrorKind> = DataError::CrcFailed.into();
+        assert_eq!(error.bus_fault(), None);
+        assert_eq!(error.recovery_action(), Some(RecoveryAction::Retry));
+
+        let error: Scd30Error<ErrorKind> = DataError::UseDefaultPressure.into();
+        assert_eq!(error.recovery_action(), None);
+    }
+}
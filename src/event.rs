@@ -0,0 +1,62 @@
+//! Maintenance events reported through a hook, e.g. to log them centrally.
+//!
+//! Both the interface and the [DriftDetector](crate::calibration::DriftDetector) accept an
+//! [EventHook] through their `on_event()` builder methods:
+//!
+//! ```ignore
+//! fn log_event(event: Scd30Event) {
+//!     defmt::info!("sensor maintenance: {}", event);
+//! }
+//!
+//! let mut sensor = Scd30::new(i2c).on_event(log_event);
+//! ```
+
+use crate::{data::AutomaticSelfCalibration, health::ResetCause};
+
+/// Maintenance actions performed by the interface and conditions detected by this library.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scd30Event {
+    /// The sensor was reset.
+    Reset(ResetCause),
+    /// A forced recalibration to the given reference concentration was applied.
+    ForcedRecalibration {
+        /// Reference CO2 concentration in ppm.
+        reference_ppm: u16,
+    },
+    /// The automatic self-calibration was activated or deactivated.
+    AutomaticSelfCalibration(AutomaticSelfCalibration),
+    /// The fresh-air baseline drifted beyond the threshold of the
+    /// [DriftDetector](crate::calibration::DriftDetector).
+    DriftDetected {
+        /// Difference between the baseline and the expected fresh-air level in ppm.
+        drift_ppm: f32,
+    },
+    /// Samples were overwritten by the sensor before being read, leaving a hole in the time
+    /// series.
+    SampleGap {
+        /// Number of missed samples.
+        missed: u32,
+    },
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Scd30Event {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Scd30Event::Reset(cause) => defmt::write!(f, "Reset({})", cause),
+            Scd30Event::ForcedRecalibration { reference_ppm } => {
+                defmt::write!(f, "ForcedRecalibration({}ppm)", reference_ppm)
+            }
+            Scd30Event::AutomaticSelfCalibration(setting) => {
+                defmt::write!(f, "AutomaticSelfCalibration({})", setting)
+            }
+            Scd30Event::DriftDetected { drift_ppm } => {
+                defmt::write!(f, "DriftDetected({}ppm)", drift_ppm)
+            }
+            Scd30Event::SampleGap { missed } => defmt::write!(f, "SampleGap({})", missed),
+        }
+    }
+}
+
+/// Function called for every [Scd30Event].
+pub type EventHook = fn(Scd30Event);
@@ -0,0 +1,225 @@
+//! Time-weighted average (TWA) exposure to CO2.
+//!
+//! Occupational exposure limits for CO2 are given as the average concentration over a work
+//! shift, usually 8 hours, e.g. 5000 ppm. A [TwaAccumulator] integrates the CO2 concentration of
+//! timestamped measurements over a rolling window and reports the [Exposure] within it, so
+//! workplace monitors can report compliance values directly.
+//!
+//! Each measurement is assumed to hold until the next one. The window is split into `N` buckets,
+//! which is the resolution the window rolls with, e.g. 8 buckets of an hour for an 8 hour window.
+
+use heapless::Deque;
+
+use crate::data::Measurement;
+
+/// Length of an occupational work shift of 8 hours in milliseconds.
+pub const OCCUPATIONAL_WINDOW_MS: u64 = 8 * 60 * 60 * 1000;
+
+/// Common 8 hour TWA exposure limit for CO2 in ppm, e.g. of OSHA and the EU.
+pub const CO2_TWA_LIMIT_PPM: f32 = 5000.0;
+
+/// Exposure within the window of a [TwaAccumulator].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exposure {
+    /// Time-weighted average of the CO2 concentration in ppm. Times without measurements count as
+    /// 0 ppm, as the average always refers to the full window.
+    pub twa_ppm: f32,
+    /// Time of the window covered by measurements in milliseconds. Compliance values require the
+    /// full window to be covered.
+    pub covered_ms: u64,
+    /// Length of the window in milliseconds.
+    pub window_ms: u64,
+}
+
+impl Exposure {
+    /// Returns whether the measurements cover the full window.
+    pub fn is_complete(&self) -> bool {
+        self.covered_ms >= self.window_ms
+    }
+
+    /// Returns whether the TWA exceeds `limit_ppm`, e.g. [CO2_TWA_LIMIT_PPM].
+    pub fn exceeds(&self, limit_ppm: f32) -> bool {
+        self.twa_ppm > limit_ppm
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Exposure {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{} ppm TWA ({}/{} ms covered)",
+            self.twa_ppm,
+            self.covered_ms,
+            self.window_ms
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Bucket {
+    start_ms: u64,
+    // Integral of the concentration in ppm·ms, in f64 so a full shift does not lose precision.
+    dose: f64,
+    covered_ms: u64,
+}
+
+/// Accumulates the CO2 exposure over a rolling window split into `N` buckets. See the
+/// [module documentation](self).
+pub struct TwaAccumulator<const N: usize> {
+    window_ms: u64,
+    bucket_ms: u64,
+    max_gap_ms: u64,
+    last: Option<(u64, f32)>,
+    buckets: Deque<Bucket, N>,
+}
+
+impl<const N: usize> TwaAccumulator<N> {
+    /// Creates an accumulator with a window of `window_ms` milliseconds, e.g.
+    /// [OCCUPATIONAL_WINDOW_MS]. A length of 0 is treated as 1 ms.
+    pub const fn new(window_ms: u64) -> Self {
+        let window_ms = if window_ms == 0 { 1 } else { window_ms };
+        let bucket_ms = if N == 0 {
+            window_ms
+        } else {
+            window_ms / N as u64
+        };
+        Self {
+            window_ms,
+            bucket_ms: if bucket_ms == 0 { 1 } else { bucket_ms },
+            max_gap_ms: u64::MAX,
+            last: None,
+            buckets: Deque::new(),
+        }
+    }
+
+    /// Sets the longest time in milliseconds a measurement is assumed to hold. Longer gaps, e.g.
+    /// while the sensor was switched off, are not counted as covered. By default, measurements
+    /// hold until the next one.
+    pub const fn with_max_gap(mut self, max_gap_ms: u64) -> Self {
+        self.max_gap_ms = max_gap_ms;
+        self
+    }
+
+    /// Adds a measurement taken at `timestamp_ms`. Measurements older than the previous one are
+    /// ignored.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: &Measurement) {
+        if let Some((last_ms, concentration)) = self.last {
+            if timestamp_ms < last_ms {
+                return;
+            }
+            if timestamp_ms - last_ms <= self.max_gap_ms {
+                let from_ms = last_ms.max(timestamp_ms.saturating_sub(self.window_ms));
+                self.integrate(from_ms, timestamp_ms, concentration);
+            }
+        }
+        self.last = Some((timestamp_ms, measurement.co2_concentration));
+    }
+
+    /// Returns the exposure within the window ending with the latest measurement.
+    pub fn exposure(&self) -> Exposure {
+        let end_ms = self.last.map_or(0, |(last_ms, _)| last_ms);
+        let start_ms = end_ms.saturating_sub(self.window_ms);
+        let (dose, covered_ms) = self
+            .buckets
+            .iter()
+            .filter(|bucket| bucket.start_ms + self.bucket_ms > start_ms)
+            .fold((0.0, 0), |(dose, covered_ms), bucket| {
+                (dose + bucket.dose, covered_ms + bucket.covered_ms)
+            });
+        Exposure {
+            twa_ppm: (dose / self.window_ms as f64) as f32,
+            covered_ms: covered_ms.min(self.window_ms),
+            window_ms: self.window_ms,
+        }
+    }
+
+    /// Clears all measurements, e.g. at the start of a new shift.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.buckets.clear();
+    }
+
+    fn integrate(&mut self, mut from_ms: u64, to_ms: u64, concentration: f32) {
+        while from_ms < to_ms {
+            let start_ms = from_ms - from_ms % self.bucket_ms;
+            let until_ms = to_ms.min(start_ms + self.bucket_ms);
+            self.add(start_ms, concentration, until_ms - from_ms);
+            from_ms = until_ms;
+        }
+    }
+
+    fn add(&mut self, start_ms: u64, concentration: f32, duration_ms: u64) {
+        let dose = concentration as f64 * duration_ms as f64;
+        match self.buckets.back_mut() {
+            Some(bucket) if bucket.start_ms == start_ms => {
+                bucket.dose += dose;
+                bucket.covered_ms += duration_ms;
+            }
+            _ => {
+                if self.buckets.is_full() {
+                    self.buckets.pop_front();
+                }
+                // Space was made above, so the bucket always fits.
+                let _ = self.buckets.push_back(Bucket {
+                    start_ms,
+                    dose,
+                    covered_ms: duration_ms,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 60 * 60 * 1000;
+
+    fn co2(ppm: f32) -> Measurement {
+        Measurement::new(ppm, 21.0, 45.0)
+    }
+
+    #[test]
+    fn twa_weights_concentrations_by_duration() {
+        let mut twa = TwaAccumulator::<8>::new(OCCUPATIONAL_WINDOW_MS);
+        twa.push(0, &co2(1000.0));
+        twa.push(6 * HOUR_MS, &co2(9000.0));
+        assert!(!twa.exposure().is_complete());
+        twa.push(8 * HOUR_MS, &co2(400.0));
+
+        let exposure = twa.exposure();
+        assert_eq!(exposure.twa_ppm, 3000.0);
+        assert!(exposure.is_complete());
+        assert!(!exposure.exceeds(CO2_TWA_LIMIT_PPM));
+    }
+
+    #[test]
+    fn window_rolls_with_bucket_resolution() {
+        let mut twa = TwaAccumulator::<4>::new(4 * HOUR_MS);
+        twa.push(0, &co2(8000.0));
+        twa.push(2 * HOUR_MS, &co2(4000.0));
+        twa.push(6 * HOUR_MS, &co2(4000.0));
+
+        let exposure = twa.exposure();
+        assert_eq!(exposure.twa_ppm, 4000.0);
+        assert_eq!(exposure.covered_ms, 4 * HOUR_MS);
+
+        twa.reset();
+        assert_eq!(twa.exposure().twa_ppm, 0.0);
+    }
+
+    #[test]
+    fn gaps_are_not_covered() {
+        let mut twa = TwaAccumulator::<8>::new(OCCUPATIONAL_WINDOW_MS).with_max_gap(HOUR_MS);
+        twa.push(0, &co2(6000.0));
+        twa.push(HOUR_MS, &co2(6000.0));
+        twa.push(5 * HOUR_MS, &co2(6000.0));
+        twa.push(4 * HOUR_MS, &co2(10_000.0));
+
+        let exposure = twa.exposure();
+        assert_eq!(exposure.covered_ms, HOUR_MS);
+        assert_eq!(exposure.twa_ppm, 750.0);
+    }
+}
@@ -0,0 +1,319 @@
+//! Fault injection for testing the error handling of applications using this driver.
+//!
+//! [FaultyI2c] wraps the I2C peripheral handed to the driver and injects faults into the I2C
+//! operations according to a [Schedule]. As the schedule is a plain function of the operation's
+//! index, faults occur deterministically:
+//!
+//! ```ignore
+//! use scd30_interface::fault::{Fault, FaultyI2c, FaultyScd30};
+//!
+//! // Every fifth operation is not acknowledged.
+//! let i2c = FaultyI2c::new(i2c, |operation| (operation % 5 == 4).then_some(Fault::Nack));
+//! let mut sensor = FaultyScd30::new(i2c);
+//! ```
+
+use embedded_hal::i2c::{
+    self, ErrorKind, ErrorType, NoAcknowledgeSource, Operation, SevenBitAddress,
+};
+
+/// Faults which can be injected into I2C operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The received data is corrupted, so its CRC check fails. If scheduled for a write, the data
+    /// received by the next read is corrupted.
+    CrcFailure,
+    /// The operation fails as if the bus timed out.
+    Timeout,
+    /// The operation fails as the address was not acknowledged.
+    Nack,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Fault {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Fault::CrcFailure => defmt::write!(f, "CrcFailure"),
+            Fault::Timeout => defmt::write!(f, "Timeout"),
+            Fault::Nack => defmt::write!(f, "Nack"),
+        }
+    }
+}
+
+/// Decides which fault, if any, is injected into the I2C operation with the given zero-based
+/// index.
+pub type Schedule = fn(operation: u32) -> Option<Fault>;
+
+/// Errors emitted by [FaultyI2c].
+#[derive(Debug, PartialEq)]
+pub enum FaultyI2cError<E> {
+    /// Error of the wrapped I2C peripheral.
+    Bus(E),
+    /// Injected fault.
+    Injected(ErrorKind),
+}
+
+impl<E: i2c::Error> i2c::Error for FaultyI2cError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            FaultyI2cError::Bus(error) => error.kind(),
+            FaultyI2cError::Injected(kind) => *kind,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E: defmt::Format> defmt::Format for FaultyI2cError<E> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            FaultyI2cError::Bus(error) => defmt::write!(f, "Bus({})", error),
+            FaultyI2cError::Injected(kind) => defmt::write!(f, "Injected({})", kind),
+        }
+    }
+}
+
+/// I2C peripheral decorator injecting faults according to a [Schedule].
+pub struct FaultyI2c<I2C> {
+    i2c: I2C,
+    schedule: Schedule,
+    operation: u32,
+    corrupt_next_read: bool,
+}
+
+impl<I2C> FaultyI2c<I2C> {
+    /// Wraps `i2c`, injecting faults according to `schedule`.
+    pub fn new(i2c: I2C, schedule: Schedule) -> Self {
+        Self {
+            i2c,
+            schedule,
+            operation: 0,
+            corrupt_next_read: false,
+        }
+    }
+
+    /// Returns the number of I2C operations executed so far.
+    pub fn operations(&self) -> u32 {
+        self.operation
+    }
+
+    /// Consumes the decorator and returns the wrapped I2C peripheral.
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    fn next_fault<E>(&mut self) -> Result<(), FaultyI2cError<E>> {
+        let fault = (self.schedule)(self.operation);
+        self.operation = self.operation.wrapping_add(1);
+        match fault {
+            Some(Fault::CrcFailure) => {
+                self.corrupt_next_read = true;
+                Ok(())
+            }
+            Some(Fault::Timeout) => Err(FaultyI2cError::Injected(ErrorKind::Other)),
+            Some(Fault::Nack) => Err(FaultyI2cError::Injected(ErrorKind::NoAcknowledge(
+                NoAcknowledgeSource::Address,
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn corrupt(&mut self, read: &mut [u8]) {
+        if core::mem::take(&mut self.corrupt_next_read) {
+            if let Some(byte) = read.first_mut() {
+                *byte ^= 0xFF;
+            }
+        }
+    }
+}
+
+impl<I2C: ErrorType> ErrorType for FaultyI2c<I2C> {
+    type Error = FaultyI2cError<I2C::Error>;
+}
+
+impl<I2C: i2c::I2c> i2c::I2c for FaultyI2c<I2C> {
+    fn read(&mut self, address: SevenBitAddress, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c.read(address, read).map_err(FaultyI2cError::Bus)?;
+        self.corrupt(read);
+        Ok(())
+    }
+
+    fn write(&mut self, address: SevenBitAddress, write: &[u8]) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c.write(address, write).map_err(FaultyI2cError::Bus)
+    }
+
+    fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c
+            .write_read(address, write, read)
+            .map_err(FaultyI2cError::Bus)?;
+        self.corrupt(read);
+        Ok(())
+    }
+
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c
+            .transaction(address, operations)
+            .map_err(FaultyI2cError::Bus)?;
+        if let Some(Operation::Read(read)) = operations
+            .iter_mut()
+            .find(|operation| matches!(operation, Operation::Read(_)))
+        {
+            self.corrupt(read);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I2C: embedded_hal_async::i2c::I2c> embedded_hal_async::i2c::I2c for FaultyI2c<I2C> {
+    async fn read(&mut self, address: SevenBitAddress, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c
+            .read(address, read)
+            .await
+            .map_err(FaultyI2cError::Bus)?;
+        self.corrupt(read);
+        Ok(())
+    }
+
+    async fn write(&mut self, address: SevenBitAddress, write: &[u8]) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c
+            .write(address, write)
+            .await
+            .map_err(FaultyI2cError::Bus)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c
+            .write_read(address, write, read)
+            .await
+            .map_err(FaultyI2cError::Bus)?;
+        self.corrupt(read);
+        Ok(())
+    }
+
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.next_fault()?;
+        self.i2c
+            .transaction(address, operations)
+            .await
+            .map_err(FaultyI2cError::Bus)?;
+        if let Some(Operation::Read(read)) = operations
+            .iter_mut()
+            .find(|operation| matches!(operation, Operation::Read(_)))
+        {
+            self.corrupt(read);
+        }
+        Ok(())
+    }
+}
+
+/// Blocking SCD30 interface with fault injection.
+#[cfg(feature = "blocking")]
+pub type FaultyScd30<I2C> = crate::blocking::Scd30<FaultyI2c<I2C>>;
+
+#[cfg(test)]
+#[cfg(feature = "blocking")]
+mod tests {
+    use super::*;
+    use crate::{
+        command::Command,
+        data::{DataStatus, FirmwareVersion},
+        error::{DataError, Phase, Scd30Error},
+    };
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    #[test]
+    fn injects_nack_on_schedule() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+        ];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = FaultyScd30::new(FaultyI2c::new(i2c.clone(), |operation| {
+            (operation == 0).then_some(Fault::Nack)
+        }));
+
+        assert_eq!(
+            sensor.is_data_ready().unwrap_err(),
+            Scd30Error::CommandFailed {
+                command: Command::GetDataReady,
+                phase: Phase::Write,
+                error: FaultyI2cError::Injected(ErrorKind::NoAcknowledge(
+                    NoAcknowledgeSource::Address
+                )),
+            }
+        );
+        assert_eq!(sensor.is_data_ready().unwrap(), DataStatus::Ready);
+        assert_eq!(sensor.shutdown().operations(), 3);
+        i2c.done();
+    }
+
+    #[test]
+    fn injects_timeout_into_read() {
+        let expected_transactions = [I2cTransaction::write(0x61, vec![0xD1, 0x00])];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = FaultyScd30::new(FaultyI2c::new(i2c.clone(), |operation| {
+            (operation == 1).then_some(Fault::Timeout)
+        }));
+
+        assert_eq!(
+            sensor.read_firmware_version().unwrap_err(),
+            Scd30Error::CommandFailed {
+                command: Command::ReadFirmwareVersion,
+                phase: Phase::Read,
+                error: FaultyI2cError::Injected(ErrorKind::Other),
+            }
+        );
+        i2c.done();
+    }
+
+    #[test]
+    fn injects_crc_failure() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0xD1, 0x00]),
+            I2cTransaction::read(0x61, vec![0x03, 0x42, 0xF3]),
+            I2cTransaction::write(0x61, vec![0xD1, 0x00]),
+            I2cTransaction::read(0x61, vec![0x03, 0x42, 0xF3]),
+        ];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = FaultyScd30::new(FaultyI2c::new(i2c.clone(), |operation| {
+            (operation == 0).then_some(Fault::CrcFailure)
+        }));
+
+        assert_eq!(
+            sensor.read_firmware_version().unwrap_err(),
+            Scd30Error::DataError(DataError::CrcFailed)
+        );
+        let _: FirmwareVersion = sensor.read_firmware_version().unwrap();
+        i2c.done();
+    }
+}
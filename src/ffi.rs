@@ -0,0 +1,554 @@
+//! C interface to the blocking driver.
+//!
+//! The functions of this module use the C ABI, so C firmware can drive the sensor through
+//! headers generated with [cbindgen](https://github.com/mozilla/cbindgen) from the `cbindgen.toml`
+//! of this repository. The driver is linked as a static library, built by a crate depending on
+//! this one with the `ffi` feature and `crate-type = ["staticlib"]`.
+//!
+//! The application provides the bus as a pair of callbacks in a [Scd30Bus] and the memory of the
+//! driver as a [Scd30Storage], e.g. a static variable, as no allocator is required:
+//!
+//! ```c
+//! static Scd30Storage storage;
+//!
+//! Scd30Bus bus = { .context = &hi2c1, .write = i2c_write, .read = i2c_read };
+//! Scd30Handle *sensor = scd30_init(&storage, bus);
+//! scd30_start(sensor, 0);
+//! Scd30Measurement measurement;
+//! if (scd30_read_measurement(sensor, &measurement) == SCD30_STATUS_OK) {
+//!     printf("%.0f ppm\n", measurement.co2_concentration);
+//! }
+//! ```
+//!
+//! Every function returns a [Scd30Status], negative values are errors.
+
+use core::{
+    ffi::c_void,
+    mem::{align_of, size_of},
+    ptr,
+};
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation, SevenBitAddress};
+
+use crate::{
+    blocking::Scd30,
+    data::{
+        AltitudeCompensation, AmbientPressure, AmbientPressureCompensation,
+        AutomaticSelfCalibration, DataStatus, ForcedRecalibrationValue, MeasurementInterval,
+        TemperatureOffset,
+    },
+    error::{DataError, Scd30Error},
+};
+
+/// Size of a [Scd30Storage] in bytes.
+pub const STORAGE_LEN: usize = 512;
+
+/// Value returned by a bus callback if the device did not acknowledge, any other non-zero value
+/// is reported as a bus error.
+pub const BUS_NACK: i32 = 1;
+
+/// Writes `len` bytes from `data` to the device at the 7-bit `address`. Returns 0 on success.
+pub type BusWrite =
+    unsafe extern "C" fn(context: *mut c_void, address: u8, data: *const u8, len: usize) -> i32;
+
+/// Reads `len` bytes from the device at the 7-bit `address` into `data`. Returns 0 on success.
+pub type BusRead =
+    unsafe extern "C" fn(context: *mut c_void, address: u8, data: *mut u8, len: usize) -> i32;
+
+/// I2C bus provided by the application, passing `context` to every callback.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Scd30Bus {
+    /// Pointer passed to the callbacks, e.g. to the handle of the I2C peripheral.
+    pub context: *mut c_void,
+    /// Callback writing to the bus.
+    pub write: BusWrite,
+    /// Callback reading from the bus.
+    pub read: BusRead,
+}
+
+impl Scd30Bus {
+    fn check(result: i32) -> Result<(), ErrorKind> {
+        match result {
+            0 => Ok(()),
+            BUS_NACK => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)),
+            _ => Err(ErrorKind::Other),
+        }
+    }
+}
+
+impl ErrorType for Scd30Bus {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal::i2c::I2c for Scd30Bus {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            // SAFETY: The callbacks are provided by the application, which promised in
+            // `scd30_init` to pass valid callbacks accepting the given buffers.
+            let result = unsafe {
+                match operation {
+                    Operation::Write(data) => {
+                        (self.write)(self.context, address, data.as_ptr(), data.len())
+                    }
+                    Operation::Read(data) => {
+                        (self.read)(self.context, address, data.as_mut_ptr(), data.len())
+                    }
+                }
+            };
+            Self::check(result)?;
+        }
+        Ok(())
+    }
+}
+
+/// Memory holding a driver instance, provided by the application.
+#[repr(C, align(8))]
+pub struct Scd30Storage {
+    bytes: [u8; STORAGE_LEN],
+}
+
+/// Opaque handle of a driver instance returned by [scd30_init].
+pub struct Scd30Handle(Scd30<Scd30Bus>);
+
+const _: () = assert!(size_of::<Scd30Handle>() <= STORAGE_LEN);
+const _: () = assert!(align_of::<Scd30Handle>() <= align_of::<Scd30Storage>());
+
+/// Measurement as returned by [scd30_read_measurement].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Scd30Measurement {
+    /// CO2 concentration in ppm.
+    pub co2_concentration: f32,
+    /// Temperature in °C.
+    pub temperature: f32,
+    /// Relative humidity in %.
+    pub humidity: f32,
+}
+
+/// Result of a call, negative values are errors.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scd30Status {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer passed to the call was null.
+    NullPointer = -1,
+    /// The device did not acknowledge.
+    Nack = -2,
+    /// The bus reported an error.
+    Bus = -3,
+    /// The CRC of the received data did not match.
+    Crc = -4,
+    /// A value was out of the range accepted by the sensor.
+    OutOfRange = -5,
+    /// The sensor sent unexpected data.
+    InvalidData = -6,
+    /// Continuous measurements are stopped.
+    NotMeasuring = -7,
+    /// The operation timed out or no measurement became ready in time.
+    Timeout = -8,
+    /// Any other error.
+    Other = -9,
+    /// The command violated the sequencing rules of the strict mode.
+    Sequence = -10,
+}
+
+impl From<Scd30Error<ErrorKind>> for Scd30Status {
+    fn from(error: Scd30Error<ErrorKind>) -> Self {
+        match error {
+            Scd30Error::I2cError(ErrorKind::NoAcknowledge(_))
+            | Scd30Error::CommandFailed {
+                error: ErrorKind::NoAcknowledge(_),
+                ..
+            } => Scd30Status::Nack,
+            Scd30Error::I2cError(_) | Scd30Error::CommandFailed { .. } => Scd30Status::Bus,
+            Scd30Error::DataError(error) => error.into(),
+            Scd30Error::NotMeasuring => Scd30Status::NotMeasuring,
+            Scd30Error::Sequence(_) => Scd30Status::Sequence,
+            Scd30Error::Timeout | Scd30Error::MeasurementOverdue { .. } => Scd30Status::Timeout,
+            _ => Scd30Status::Other,
+        }
+    }
+}
+
+impl From<DataError> for Scd30Status {
+    fn from(error: DataError) -> Self {
+        match error {
+            DataError::CrcFailed => Scd30Status::Crc,
+            DataError::ValueOutOfRange { .. } | DataError::UseDefaultPressure => {
+                Scd30Status::OutOfRange
+            }
+            _ => Scd30Status::InvalidData,
+        }
+    }
+}
+
+fn status<E: Into<Scd30Status>>(result: Result<(), E>) -> Scd30Status {
+    match result {
+        Ok(()) => Scd30Status::Ok,
+        Err(error) => error.into(),
+    }
+}
+
+fn with_sensor(
+    handle: *mut Scd30Handle,
+    call: impl FnOnce(&mut Scd30<Scd30Bus>) -> Result<(), Scd30Status>,
+) -> Scd30Status {
+    // SAFETY: The caller guarantees that a non-null `handle` was returned by `scd30_init` and is
+    // not used concurrently.
+    match unsafe { handle.as_mut() } {
+        Some(handle) => status(call(&mut handle.0)),
+        None => Scd30Status::NullPointer,
+    }
+}
+
+/// Creates a driver for the sensor on `bus` in `storage` and returns its handle, or null if
+/// `storage` is null.
+///
+/// # Safety
+///
+/// `storage` must be valid for writes and outlive the handle. The callbacks of `bus` must be
+/// safe to call with `bus.context` and buffers of the given length.
+#[no_mangle]
+pub unsafe extern "C" fn scd30_init(storage: *mut Scd30Storage, bus: Scd30Bus) -> *mut Scd30Handle {
+    if storage.is_null() {
+        return ptr::null_mut();
+    }
+    let handle = storage.cast::<Scd30Handle>();
+    // SAFETY: The storage is valid for writes and fits the handle, see the assertions above.
+    unsafe { handle.write(Scd30Handle(Scd30::new(bus))) };
+    handle
+}
+
+/// Destroys the driver behind `handle`, after which its storage can be reused.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init] and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn scd30_deinit(handle: *mut Scd30Handle) {
+    if !handle.is_null() {
+        // SAFETY: The handle was initialized by `scd30_init` and is not used afterwards.
+        unsafe { ptr::drop_in_place(handle) };
+    }
+}
+
+/// Starts continuous measurements compensating `pressure_mbar`, 0 for the default pressure.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_start(handle: *mut Scd30Handle, pressure_mbar: u16) -> Scd30Status {
+    with_sensor(handle, |sensor| {
+        let compensation = match pressure_mbar {
+            0 => AmbientPressureCompensation::DefaultPressure,
+            pressure => AmbientPressureCompensation::CompensationPressure(
+                AmbientPressure::try_from(pressure)?,
+            ),
+        };
+        Ok(sensor.trigger_continuous_measurements(Some(compensation))?)
+    })
+}
+
+/// Stops continuous measurements.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_stop(handle: *mut Scd30Handle) -> Scd30Status {
+    with_sensor(handle, |sensor| Ok(sensor.stop_continuous_measurements()?))
+}
+
+/// Stores in `ready` whether a measurement is ready for readout.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init], `ready` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn scd30_data_ready(
+    handle: *mut Scd30Handle,
+    ready: *mut bool,
+) -> Scd30Status {
+    if ready.is_null() {
+        return Scd30Status::NullPointer;
+    }
+    with_sensor(handle, |sensor| {
+        let status = sensor.is_data_ready()?;
+        // SAFETY: `ready` is not null and valid for writes.
+        unsafe { ready.write(status == DataStatus::Ready) };
+        Ok(())
+    })
+}
+
+/// Reads out a measurement into `measurement`.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init], `measurement` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn scd30_read_measurement(
+    handle: *mut Scd30Handle,
+    measurement: *mut Scd30Measurement,
+) -> Scd30Status {
+    if measurement.is_null() {
+        return Scd30Status::NullPointer;
+    }
+    with_sensor(handle, |sensor| {
+        let read = sensor.read_measurement()?;
+        let read = Scd30Measurement {
+            co2_concentration: read.co2_concentration,
+            temperature: read.temperature,
+            humidity: read.humidity,
+        };
+        // SAFETY: `measurement` is not null and valid for writes.
+        unsafe { measurement.write(read) };
+        Ok(())
+    })
+}
+
+/// Sets the measurement interval to `seconds`, ranging from 2 s to 1800 s.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_set_measurement_interval(
+    handle: *mut Scd30Handle,
+    seconds: u16,
+) -> Scd30Status {
+    with_sensor(handle, |sensor| {
+        let interval = MeasurementInterval::try_from(seconds)?;
+        Ok(sensor.set_measurement_interval(interval)?)
+    })
+}
+
+/// Activates or deactivates the automatic self-calibration.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_set_automatic_self_calibration(
+    handle: *mut Scd30Handle,
+    active: bool,
+) -> Scd30Status {
+    with_sensor(handle, |sensor| {
+        let setting = if active {
+            AutomaticSelfCalibration::Active
+        } else {
+            AutomaticSelfCalibration::Inactive
+        };
+        Ok(sensor.set_automatic_self_calibration(setting)?)
+    })
+}
+
+/// Performs a forced recalibration to the reference concentration `ppm`, ranging from 400 ppm to
+/// 2000 ppm.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_set_forced_recalibration(
+    handle: *mut Scd30Handle,
+    ppm: u16,
+) -> Scd30Status {
+    with_sensor(handle, |sensor| {
+        let frc = ForcedRecalibrationValue::try_from(ppm)?;
+        Ok(sensor.set_forced_recalibration(frc)?)
+    })
+}
+
+/// Sets the temperature offset to `celsius`.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_set_temperature_offset(
+    handle: *mut Scd30Handle,
+    celsius: f32,
+) -> Scd30Status {
+    with_sensor(handle, |sensor| {
+        let offset = TemperatureOffset::try_from(celsius)?;
+        Ok(sensor.set_temperature_offset(offset)?)
+    })
+}
+
+/// Sets the altitude compensation to `meters` above sea level.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_set_altitude_compensation(
+    handle: *mut Scd30Handle,
+    meters: u16,
+) -> Scd30Status {
+    with_sensor(handle, |sensor| {
+        Ok(sensor.set_altitude_compensation(AltitudeCompensation::from(meters))?)
+    })
+}
+
+/// Stores the firmware version in `major` and `minor`.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init], `major` and `minor` must be null or valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn scd30_read_firmware_version(
+    handle: *mut Scd30Handle,
+    major: *mut u8,
+    minor: *mut u8,
+) -> Scd30Status {
+    if major.is_null() || minor.is_null() {
+        return Scd30Status::NullPointer;
+    }
+    with_sensor(handle, |sensor| {
+        let version = sensor.read_firmware_version()?;
+        // SAFETY: `major` and `minor` are not null and valid for writes.
+        unsafe {
+            major.write(version.major);
+            minor.write(version.minor);
+        }
+        Ok(())
+    })
+}
+
+/// Executes a soft reset of the sensor.
+///
+/// # Safety
+///
+/// `handle` must be null or returned by [scd30_init].
+#[no_mangle]
+pub unsafe extern "C" fn scd30_soft_reset(handle: *mut Scd30Handle) -> Scd30Status {
+    with_sensor(handle, |sensor| Ok(sensor.soft_reset()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use std::vec::Vec;
+
+    // Simulated bus: records written frames and answers reads with the queued responses.
+    #[derive(Default)]
+    struct Recorder {
+        written: Vec<Vec<u8>>,
+        responses: Vec<Vec<u8>>,
+        nack: bool,
+    }
+
+    unsafe extern "C" fn write(
+        context: *mut c_void,
+        _address: u8,
+        data: *const u8,
+        len: usize,
+    ) -> i32 {
+        let recorder = unsafe { &*(context as *const RefCell<Recorder>) };
+        let mut recorder = recorder.borrow_mut();
+        if recorder.nack {
+            return BUS_NACK;
+        }
+        let data = unsafe { core::slice::from_raw_parts(data, len) };
+        recorder.written.push(data.to_vec());
+        0
+    }
+
+    unsafe extern "C" fn read(
+        context: *mut c_void,
+        _address: u8,
+        data: *mut u8,
+        len: usize,
+    ) -> i32 {
+        let recorder = unsafe { &*(context as *const RefCell<Recorder>) };
+        let response = recorder.borrow_mut().responses.remove(0);
+        let data = unsafe { core::slice::from_raw_parts_mut(data, len) };
+        data.copy_from_slice(&response);
+        0
+    }
+
+    fn bus(recorder: &RefCell<Recorder>) -> Scd30Bus {
+        Scd30Bus {
+            context: recorder as *const RefCell<Recorder> as *mut c_void,
+            write,
+            read,
+        }
+    }
+
+    #[test]
+    fn measurement_is_read_through_callbacks() {
+        let recorder = RefCell::new(Recorder {
+            responses: std::vec![
+                std::vec![0x00, 0x01, 0xB0],
+                std::vec![
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ],
+            ],
+            ..Default::default()
+        });
+        let mut storage = Scd30Storage {
+            bytes: [0; STORAGE_LEN],
+        };
+
+        unsafe {
+            let handle = scd30_init(&mut storage, bus(&recorder));
+            assert_eq!(scd30_start(handle, 0), Scd30Status::Ok);
+            let mut ready = false;
+            assert_eq!(scd30_data_ready(handle, &mut ready), Scd30Status::Ok);
+            assert!(ready);
+            let mut measurement = Scd30Measurement::default();
+            assert_eq!(
+                scd30_read_measurement(handle, &mut measurement),
+                Scd30Status::Ok
+            );
+            assert!((measurement.co2_concentration - 439.09515).abs() < 0.001);
+            scd30_deinit(handle);
+        }
+        assert_eq!(
+            recorder.borrow().written,
+            [
+                std::vec![0x00, 0x10, 0x00, 0x00, 0x81],
+                std::vec![0x02, 0x02],
+                std::vec![0x03, 0x00],
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_are_mapped_to_status_codes() {
+        let recorder = RefCell::new(Recorder {
+            nack: true,
+            ..Default::default()
+        });
+        let mut storage = Scd30Storage {
+            bytes: [0; STORAGE_LEN],
+        };
+
+        unsafe {
+            assert!(scd30_init(ptr::null_mut(), bus(&recorder)).is_null());
+            assert_eq!(scd30_stop(ptr::null_mut()), Scd30Status::NullPointer);
+            let handle = scd30_init(&mut storage, bus(&recorder));
+            assert_eq!(scd30_stop(handle), Scd30Status::Nack);
+            assert_eq!(
+                scd30_set_measurement_interval(handle, 1),
+                Scd30Status::OutOfRange
+            );
+            assert_eq!(
+                scd30_data_ready(handle, ptr::null_mut()),
+                Scd30Status::NullPointer
+            );
+            scd30_deinit(handle);
+        }
+    }
+}
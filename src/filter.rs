@@ -0,0 +1,431 @@
+//! Filters smoothing measurements and a pipeline composing them with validators.
+//!
+//! The signal path of an application, e.g. rejecting implausible samples and spikes before
+//! smoothing the remaining ones, is configured declaratively with a [Pipeline]:
+//!
+//! ```
+//! use scd30_interface::{
+//!     anomaly::SpikeCriteria,
+//!     data::Measurement,
+//!     filter::{Ema, Median, Pipeline},
+//! };
+//!
+//! let mut pipeline = Pipeline::new()
+//!     .reject_implausible()
+//!     .reject_spikes(SpikeCriteria::default())
+//!     .then(Median::<3>::new())
+//!     .then(Ema::new(0.5));
+//!
+//! let processed = pipeline.process(0, &Measurement::new(600.0, 21.0, 45.0));
+//! assert!(processed.is_accepted());
+//! assert_eq!(processed.measurement.co2_concentration, 600.0);
+//!
+//! let processed = pipeline.process(2000, &Measurement::new(5000.0, 21.0, 45.0));
+//! assert!(!processed.is_accepted());
+//! ```
+//!
+//! Filters are applied in the order they are added, to all three quantities independently.
+//! Custom filters implement [Filter].
+
+use heapless::Deque;
+
+use crate::{
+    anomaly::{Anomaly, SpikeCriteria, SpikeDetector},
+    data::{Measurement, MeasurementWarnings},
+};
+
+/// A filter processing a stream of measurements.
+pub trait Filter {
+    /// Feeds the next measurement into the filter and returns the filtered measurement.
+    fn filter(&mut self, measurement: Measurement) -> Measurement;
+
+    /// Forgets the measurements seen so far.
+    fn reset(&mut self);
+}
+
+/// Applies a function to the three quantities of two measurements.
+fn zip_with(a: Measurement, b: Measurement, f: impl Fn(f32, f32) -> f32) -> Measurement {
+    Measurement::new(
+        f(a.co2_concentration, b.co2_concentration),
+        f(a.temperature, b.temperature),
+        f(a.humidity, b.humidity),
+    )
+}
+
+/// Passes measurements through unchanged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Identity;
+
+impl Filter for Identity {
+    fn filter(&mut self, measurement: Measurement) -> Measurement {
+        measurement
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// Applies the filter `A` and then the filter `B`, created by [Pipeline::then].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Chain<A, B>(pub A, pub B);
+
+impl<A: Filter, B: Filter> Filter for Chain<A, B> {
+    fn filter(&mut self, measurement: Measurement) -> Measurement {
+        self.1.filter(self.0.filter(measurement))
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+        self.1.reset();
+    }
+}
+
+/// Exponential moving average, weighting each new measurement with `alpha`.
+#[derive(Clone, Copy, Debug)]
+pub struct Ema {
+    alpha: f32,
+    average: Option<Measurement>,
+}
+
+impl Ema {
+    /// Creates an exponential moving average with the weight `alpha` of a new measurement,
+    /// clamped to 0 to 1. Smaller values smooth stronger, but follow changes slower.
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            average: None,
+        }
+    }
+}
+
+impl Filter for Ema {
+    fn filter(&mut self, measurement: Measurement) -> Measurement {
+        let alpha = self.alpha;
+        let average = match self.average {
+            Some(average) => zip_with(average, measurement, |average, value| {
+                average + alpha * (value - average)
+            }),
+            None => measurement,
+        };
+        self.average = Some(average);
+        average
+    }
+
+    fn reset(&mut self) {
+        self.average = None;
+    }
+}
+
+/// Median of the last `N` measurements, removing single outliers while preserving steps.
+#[derive(Clone, Debug, Default)]
+pub struct Median<const N: usize> {
+    window: Deque<Measurement, N>,
+}
+
+impl<const N: usize> Median<N> {
+    /// Creates a median filter over the last `N` measurements. Until `N` measurements were seen,
+    /// the median of all measurements so far is returned.
+    pub const fn new() -> Self {
+        Self {
+            window: Deque::new(),
+        }
+    }
+
+    fn median(&self, quantity: impl Fn(&Measurement) -> f32) -> f32 {
+        let mut values = [0.0; N];
+        let len = self.window.len();
+        for (value, measurement) in values.iter_mut().zip(self.window.iter()) {
+            *value = quantity(measurement);
+        }
+        let values = &mut values[..len];
+        values.sort_unstable_by(f32::total_cmp);
+        if len % 2 == 1 {
+            values[len / 2]
+        } else {
+            (values[len / 2 - 1] + values[len / 2]) / 2.0
+        }
+    }
+}
+
+impl<const N: usize> Filter for Median<N> {
+    fn filter(&mut self, measurement: Measurement) -> Measurement {
+        if N == 0 {
+            return measurement;
+        }
+        if self.window.is_full() {
+            self.window.pop_front();
+        }
+        // Space was made above, so the measurement always fits.
+        let _ = self.window.push_back(measurement);
+        Measurement::new(
+            self.median(|measurement| measurement.co2_concentration),
+            self.median(|measurement| measurement.temperature),
+            self.median(|measurement| measurement.humidity),
+        )
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+/// One-dimensional Kalman filter for a slowly varying signal, applied to each quantity.
+#[derive(Clone, Copy, Debug)]
+pub struct Kalman {
+    process_noise: f32,
+    measurement_noise: f32,
+    // Estimated value and variance of its error for each quantity.
+    estimates: Option<[(f32, f32); 3]>,
+}
+
+impl Kalman {
+    /// Creates a Kalman filter with the variance `process_noise` by which the true values change
+    /// between two measurements and the variance `measurement_noise` of the sensor readings, both
+    /// in squared units of the quantities. The higher the ratio of measurement to process noise,
+    /// the stronger the smoothing.
+    pub const fn new(process_noise: f32, measurement_noise: f32) -> Self {
+        Self {
+            process_noise,
+            measurement_noise,
+            estimates: None,
+        }
+    }
+
+    fn update(&self, (estimate, error): &mut (f32, f32), value: f32) -> f32 {
+        let predicted_error = *error + self.process_noise;
+        let gain = predicted_error / (predicted_error + self.measurement_noise);
+        *estimate += gain * (value - *estimate);
+        *error = (1.0 - gain) * predicted_error;
+        *estimate
+    }
+}
+
+impl Filter for Kalman {
+    fn filter(&mut self, measurement: Measurement) -> Measurement {
+        let values = [
+            measurement.co2_concentration,
+            measurement.temperature,
+            measurement.humidity,
+        ];
+        let Some(mut estimates) = self.estimates else {
+            self.estimates = Some(values.map(|value| (value, self.measurement_noise)));
+            return measurement;
+        };
+        let [co2, temperature, humidity] = &mut estimates;
+        let filtered = Measurement::new(
+            self.update(co2, values[0]),
+            self.update(temperature, values[1]),
+            self.update(humidity, values[2]),
+        );
+        self.estimates = Some(estimates);
+        filtered
+    }
+
+    fn reset(&mut self) {
+        self.estimates = None;
+    }
+}
+
+/// Result of a measurement processed by a [Pipeline].
+#[derive(Clone, Copy, Debug)]
+pub struct Processed {
+    /// Output of the filters. Rejected measurements are not fed into the filters, so the last
+    /// output is repeated, or the raw measurement if there is none yet.
+    pub measurement: Measurement,
+    /// Measurement as passed to the pipeline.
+    pub raw: Measurement,
+    /// Plausibility warnings of the raw measurement, if checked.
+    pub warnings: MeasurementWarnings,
+    /// Spike detected in the raw measurement, if checked.
+    pub anomaly: Option<Anomaly>,
+    accepted: bool,
+}
+
+impl Processed {
+    /// Returns whether the measurement passed the validators and was fed into the filters.
+    pub const fn is_accepted(&self) -> bool {
+        self.accepted
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Processed {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Processed {{ measurement: {}, raw: {}, warnings: {}, anomaly: {}, accepted: {} }}",
+            self.measurement,
+            self.raw,
+            self.warnings,
+            self.anomaly,
+            self.accepted
+        )
+    }
+}
+
+/// Signal path of validators and filters. See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Pipeline<F> {
+    filter: F,
+    reject_implausible: bool,
+    spikes: Option<SpikeDetector>,
+    last: Option<Measurement>,
+}
+
+impl Pipeline<Identity> {
+    /// Creates a pipeline passing measurements through unchanged.
+    pub const fn new() -> Self {
+        Self {
+            filter: Identity,
+            reject_implausible: false,
+            spikes: None,
+            last: None,
+        }
+    }
+}
+
+impl Default for Pipeline<Identity> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Filter> Pipeline<F> {
+    /// Appends `filter` to the filters of the pipeline.
+    pub fn then<G: Filter>(self, filter: G) -> Pipeline<Chain<F, G>> {
+        Pipeline {
+            filter: Chain(self.filter, filter),
+            reject_implausible: self.reject_implausible,
+            spikes: self.spikes,
+            last: self.last,
+        }
+    }
+
+    /// Rejects measurements with plausibility warnings, see [Measurement::validate].
+    pub fn reject_implausible(mut self) -> Self {
+        self.reject_implausible = true;
+        self
+    }
+
+    /// Rejects spikes of the CO2 concentration according to `criteria`, see
+    /// [SpikeDetector].
+    pub fn reject_spikes(mut self, criteria: SpikeCriteria) -> Self {
+        self.spikes = Some(SpikeDetector::new(criteria));
+        self
+    }
+
+    /// Processes a measurement taken at `timestamp_ms`. The validators are checked first, the
+    /// measurement is then passed through the filters if it is accepted.
+    pub fn process(&mut self, timestamp_ms: u64, measurement: &Measurement) -> Processed {
+        let warnings = if self.reject_implausible {
+            measurement.validate()
+        } else {
+            MeasurementWarnings::NONE
+        };
+        let anomaly = match &mut self.spikes {
+            Some(spikes) if warnings.is_empty() => spikes.check(timestamp_ms, measurement),
+            _ => None,
+        };
+        let accepted = warnings.is_empty() && anomaly.is_none();
+        let output = if accepted {
+            let output = self.filter.filter(*measurement);
+            self.last = Some(output);
+            output
+        } else {
+            self.last.unwrap_or(*measurement)
+        };
+        Processed {
+            measurement: output,
+            raw: *measurement,
+            warnings,
+            anomaly,
+            accepted,
+        }
+    }
+
+    /// Resets the validators and filters, e.g. after the sensor was restarted.
+    pub fn reset(&mut self) {
+        self.filter.reset();
+        if let Some(spikes) = &mut self.spikes {
+            spikes.reset();
+        }
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn co2(ppm: f32) -> Measurement {
+        Measurement::new(ppm, 21.0, 45.0)
+    }
+
+    fn run(filter: &mut impl Filter, values: &[f32]) -> Vec<f32> {
+        values
+            .iter()
+            .map(|value| filter.filter(co2(*value)).co2_concentration)
+            .collect()
+    }
+
+    #[test]
+    fn filters_smooth_measurements() {
+        assert_eq!(
+            run(&mut Ema::new(0.5), &[400.0, 600.0, 600.0]),
+            [400.0, 500.0, 550.0]
+        );
+        assert_eq!(
+            run(&mut Median::<3>::new(), &[400.0, 420.0, 2000.0, 410.0]),
+            [400.0, 410.0, 420.0, 420.0]
+        );
+        let kalman = run(&mut Kalman::new(1.0, 100.0), &[400.0, 500.0, 500.0, 500.0]);
+        assert_eq!(kalman[0], 400.0);
+        assert!(kalman.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(kalman[3] < 500.0);
+    }
+
+    #[test]
+    fn pipeline_chains_filters_in_order() {
+        let mut pipeline = Pipeline::new().then(Ema::new(0.5)).then(Ema::new(0.5));
+        pipeline.process(0, &co2(400.0));
+        assert_eq!(
+            pipeline
+                .process(2000, &co2(800.0))
+                .measurement
+                .co2_concentration,
+            500.0
+        );
+
+        pipeline.reset();
+        assert_eq!(
+            pipeline
+                .process(4000, &co2(800.0))
+                .measurement
+                .co2_concentration,
+            800.0
+        );
+    }
+
+    #[test]
+    fn rejected_measurements_bypass_filters() {
+        let mut pipeline = Pipeline::new()
+            .reject_implausible()
+            .reject_spikes(SpikeCriteria::default())
+            .then(Ema::new(0.5));
+        pipeline.process(0, &co2(600.0));
+
+        let processed = pipeline.process(2000, &Measurement::new(600.0, 21.0, 120.0));
+        assert!(!processed.is_accepted());
+        assert!(processed
+            .warnings
+            .contains(MeasurementWarnings::RH_ABOVE_100));
+        assert_eq!(processed.measurement.humidity, 45.0);
+
+        let processed = pipeline.process(4000, &co2(3000.0));
+        assert!(matches!(
+            processed.anomaly,
+            Some(Anomaly::RateOfChange { .. })
+        ));
+        assert_eq!(processed.measurement.co2_concentration, 600.0);
+        assert!(pipeline.process(6000, &co2(620.0)).is_accepted());
+    }
+}
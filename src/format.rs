@@ -0,0 +1,717 @@
+//! Formatting of measurement values without the floating point machinery of `core::fmt`.
+//!
+//! Formatting an `f32` with `core::fmt` pulls in several KiB of code, which hurts on small targets
+//! like Cortex-M0 parts. The helpers in this module convert the values into decimal fixed-point
+//! numbers first, so only integer formatting is required.
+//!
+//! Measurements and settings implement [Display](fmt::Display) on this basis and can be rendered
+//! into a [heapless::String] with `format_into()`, e.g. for a display:
+//!
+//! ```
+//! use scd30_interface::data::{Measurement, MeasurementInterval};
+//!
+//! let measurement = Measurement::new(811.6, 23.44, 45.06);
+//! let text = measurement.format_into::<32>().unwrap();
+//! assert_eq!(text, "812 ppm, 23.4 °C, 45.1 %");
+//!
+//! let interval = MeasurementInterval::try_from(10).unwrap();
+//! assert_eq!(interval.format_into::<8>().unwrap(), "10 s");
+//! ```
+//!
+//! With the `alloc` feature, the values are rendered into a `String` with `to_string()` instead
+//! and the status report with `report_string()`.
+
+use core::fmt::{self, Write};
+
+use crate::data::{
+    AltitudeCompensation, AmbientPressure, AmbientPressureCompensation, AutomaticSelfCalibration,
+    DataStatus, FirmwareVersion, ForcedRecalibrationValue, Measurement, MeasurementInterval,
+    MeasurementWarnings, Scd30Config, SettingsSnapshot, TemperatureOffset,
+};
+
+const MAX_DECIMALS: u8 = 9;
+
+/// A decimal fixed-point number with the value `mantissa / 10^decimals`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedPoint {
+    mantissa: i32,
+    decimals: u8,
+}
+
+impl FixedPoint {
+    /// Creates a fixed-point number with the value `mantissa / 10^decimals`. At most 9 decimal
+    /// places are supported, `decimals` is clamped accordingly.
+    pub const fn new(mantissa: i32, decimals: u8) -> Self {
+        let decimals = if decimals > MAX_DECIMALS {
+            MAX_DECIMALS
+        } else {
+            decimals
+        };
+        Self { mantissa, decimals }
+    }
+
+    /// Converts `value` to a fixed-point number with `decimals` decimal places, rounding half away
+    /// from zero. Values not representable are saturated, `NaN` is converted to zero.
+    pub fn from_f32(value: f32, decimals: u8) -> Self {
+        let decimals = decimals.min(MAX_DECIMALS);
+        let scaled = value * pow10(decimals) as f32;
+        let rounded = if scaled < 0.0 {
+            scaled - 0.5
+        } else {
+            scaled + 0.5
+        };
+        Self {
+            mantissa: rounded as i32,
+            decimals,
+        }
+    }
+
+    /// Returns the raw mantissa of the number.
+    pub const fn mantissa(&self) -> i32 {
+        self.mantissa
+    }
+
+    /// Returns the number of decimal places.
+    pub const fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// Returns the integer part of the number, truncated towards zero.
+    pub const fn integer(&self) -> i32 {
+        self.mantissa / pow10(self.decimals) as i32
+    }
+
+    /// Returns the digits after the decimal point as an unsigned integer.
+    pub const fn fraction(&self) -> u32 {
+        self.mantissa.unsigned_abs() % pow10(self.decimals)
+    }
+
+    /// Converts the number back to a float, e.g. to transmit a value rounded with
+    /// [from_f32](Self::from_f32).
+    pub fn to_f32(&self) -> f32 {
+        self.mantissa as f32 / pow10(self.decimals) as f32
+    }
+}
+
+impl fmt::Display for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mantissa < 0 {
+            f.write_char('-')?;
+        }
+        let integer = self.mantissa.unsigned_abs() / pow10(self.decimals);
+        if self.decimals == 0 {
+            write!(f, "{}", integer)
+        } else {
+            write!(
+                f,
+                "{}.{:0width$}",
+                integer,
+                self.fraction(),
+                width = self.decimals as usize
+            )
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for FixedPoint {
+    fn format(&self, f: defmt::Formatter) {
+        let integer = self.mantissa.unsigned_abs() / pow10(self.decimals);
+        if self.mantissa < 0 {
+            defmt::write!(f, "-");
+        }
+        defmt::write!(f, "{}", integer);
+        if self.decimals > 0 {
+            let fraction = self.fraction();
+            defmt::write!(f, ".");
+            let mut digit = pow10(self.decimals - 1);
+            while digit > 1 && fraction < digit {
+                defmt::write!(f, "0");
+                digit /= 10;
+            }
+            defmt::write!(f, "{}", fraction);
+        }
+    }
+}
+
+const fn pow10(exponent: u8) -> u32 {
+    let mut result = 1;
+    let mut i = 0;
+    while i < exponent {
+        result *= 10;
+        i += 1;
+    }
+    result
+}
+
+/// A [fmt::Write] implementation writing into a byte buffer.
+pub struct BufWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> BufWriter<'a> {
+    /// Creates a writer filling `buf` from its start.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Returns the text written so far.
+    pub fn as_str(&self) -> &str {
+        // Only complete `str`s are ever copied into the buffer.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+
+    /// Consumes the writer and returns the text written into the buffer.
+    pub fn into_str(self) -> &'a str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for BufWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl Measurement {
+    /// Returns the CO2 concentration in ppm as a fixed-point number without decimal places.
+    pub fn co2_concentration_fixed(&self) -> FixedPoint {
+        FixedPoint::from_f32(self.co2_concentration, 0)
+    }
+
+    /// Returns the temperature in °C as a fixed-point number with one decimal place.
+    pub fn temperature_fixed(&self) -> FixedPoint {
+        FixedPoint::from_f32(self.temperature, 1)
+    }
+
+    /// Returns the relative humidity in % as a fixed-point number with one decimal place.
+    pub fn humidity_fixed(&self) -> FixedPoint {
+        FixedPoint::from_f32(self.humidity, 1)
+    }
+}
+
+/// Renders the measurement as e.g. `"812 ppm, 23.4 °C, 45.1 %"` using only integer formatting.
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ppm, {} °C, {} %",
+            self.co2_concentration_fixed(),
+            self.temperature_fixed(),
+            self.humidity_fixed()
+        )
+    }
+}
+
+/// Renders `measurement` as e.g. `"812 ppm, 23.4 °C, 45.1 %"` into `buf` and returns the written
+/// text.
+///
+/// # Errors
+///
+/// - [fmt::Error] if `buf` is too small to hold the text.
+pub fn write_measurement<'a>(
+    measurement: &Measurement,
+    buf: &'a mut [u8],
+) -> Result<&'a str, fmt::Error> {
+    let mut writer = BufWriter::new(buf);
+    write!(writer, "{}", measurement)?;
+    Ok(writer.into_str())
+}
+
+const WARNING_NAMES: [(MeasurementWarnings, &str); 6] = [
+    (MeasurementWarnings::OUT_OF_RANGE_CO2, "CO2 out of range"),
+    (
+        MeasurementWarnings::OUT_OF_RANGE_TEMPERATURE,
+        "temperature out of range",
+    ),
+    (MeasurementWarnings::RH_ABOVE_100, "humidity above 100 %"),
+    (MeasurementWarnings::RH_BELOW_0, "humidity below 0 %"),
+    (MeasurementWarnings::NAN_FIELD, "invalid value"),
+    (MeasurementWarnings::STALE_FRAME, "stale frame"),
+];
+
+/// Writes a multi-line status block of the sensor for debug consoles and support tickets, e.g.:
+///
+/// ```text
+/// SCD30 status
+///   Firmware:            3.66
+///   Interval:            2 s
+///   Self-calibration:    inactive
+///   Recalibration value: 450 ppm
+///   Temperature offset:  5.00 °C
+///   Altitude:            1000 m
+///   Data:                ready
+///   CO2:                 812 ppm
+///   Temperature:         23.4 °C
+///   Humidity:            45.1 %
+///   Warnings:            none
+/// ```
+///
+/// The warnings are the result of [Measurement::validate].
+///
+/// # Errors
+///
+/// - [fmt::Error] if `writer` fails, e.g. as its buffer is full.
+pub fn write_report(
+    writer: &mut impl Write,
+    settings: &SettingsSnapshot,
+    measurement: &Measurement,
+) -> fmt::Result {
+    writeln!(writer, "SCD30 status")?;
+    writeln!(
+        writer,
+        "  Firmware:            {}",
+        settings.firmware_version
+    )?;
+    writeln!(
+        writer,
+        "  Interval:            {}",
+        settings.measurement_interval
+    )?;
+    writeln!(
+        writer,
+        "  Self-calibration:    {}",
+        settings.automatic_self_calibration
+    )?;
+    writeln!(
+        writer,
+        "  Recalibration value: {}",
+        settings.forced_recalibration
+    )?;
+    writeln!(
+        writer,
+        "  Temperature offset:  {}",
+        settings.temperature_offset
+    )?;
+    writeln!(
+        writer,
+        "  Altitude:            {}",
+        settings.altitude_compensation
+    )?;
+    writeln!(writer, "  Data:                {}", settings.data_status)?;
+    writeln!(
+        writer,
+        "  CO2:                 {} ppm",
+        measurement.co2_concentration_fixed()
+    )?;
+    writeln!(
+        writer,
+        "  Temperature:         {} °C",
+        measurement.temperature_fixed()
+    )?;
+    writeln!(
+        writer,
+        "  Humidity:            {} %",
+        measurement.humidity_fixed()
+    )?;
+    write!(writer, "  Warnings:            ")?;
+    let warnings = measurement.validate();
+    if warnings.is_empty() {
+        write!(writer, "none")?;
+    }
+    let mut separator = "";
+    for (_, name) in WARNING_NAMES
+        .iter()
+        .filter(|(warning, _)| warnings.contains(*warning))
+    {
+        write!(writer, "{}{}", separator, name)?;
+        separator = ", ";
+    }
+    writeln!(writer)
+}
+
+/// Renders the report of [write_report] into a [String](alloc::string::String), for targets with
+/// a heap.
+#[cfg(feature = "alloc")]
+pub fn report_string(
+    settings: &SettingsSnapshot,
+    measurement: &Measurement,
+) -> alloc::string::String {
+    let mut report = alloc::string::String::new();
+    // Writing into a String only fails if a Display implementation fails, which none of the
+    // reported values does.
+    let _ = write_report(&mut report, settings, measurement);
+    report
+}
+
+/// Renders `value` into a [heapless::String] of capacity `N`.
+///
+/// # Errors
+///
+/// - [fmt::Error] if the text does not fit into `N` bytes.
+pub fn format_into<const N: usize>(
+    value: &impl fmt::Display,
+) -> Result<heapless::String<N>, fmt::Error> {
+    let mut text = heapless::String::new();
+    write!(text, "{}", value)?;
+    Ok(text)
+}
+
+macro_rules! impl_format_into {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl $type {
+                /// Renders the value into a [heapless::String] of capacity `N`, as formatted by
+                /// its [Display](fmt::Display) implementation.
+                ///
+                /// # Errors
+                ///
+                /// - [fmt::Error] if the text does not fit into `N` bytes.
+                pub fn format_into<const N: usize>(
+                    &self,
+                ) -> Result<heapless::String<N>, fmt::Error> {
+                    format_into(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_format_into!(
+    Measurement,
+    MeasurementInterval,
+    TemperatureOffset,
+    AltitudeCompensation,
+    AmbientPressure,
+    AmbientPressureCompensation,
+    AutomaticSelfCalibration,
+    ForcedRecalibrationValue,
+    FirmwareVersion,
+    Scd30Config,
+    DataStatus,
+    SettingsSnapshot,
+);
+
+/// Renders the interval as e.g. `"10 s"`.
+impl fmt::Display for MeasurementInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} s", self.seconds())
+    }
+}
+
+/// Renders the offset as e.g. `"1.50 °C"`.
+impl fmt::Display for TemperatureOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let centi_degrees = u16::from_be_bytes(self.to_be_bytes());
+        write!(f, "{} °C", FixedPoint::new(centi_degrees.into(), 2))
+    }
+}
+
+/// Renders the altitude as e.g. `"520 m"`.
+impl fmt::Display for AltitudeCompensation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} m", u16::from_be_bytes(self.to_be_bytes()))
+    }
+}
+
+/// Renders the pressure as e.g. `"950 mBar"`.
+impl fmt::Display for AmbientPressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} mBar", u16::from_be_bytes(self.to_be_bytes()))
+    }
+}
+
+/// Renders the compensation as e.g. `"950 mBar"` or `"default pressure"`.
+impl fmt::Display for AmbientPressureCompensation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmbientPressureCompensation::DefaultPressure => f.write_str("default pressure"),
+            AmbientPressureCompensation::CompensationPressure(pressure) => {
+                write!(f, "{}", pressure)
+            }
+        }
+    }
+}
+
+/// Renders the state as `"active"`, `"inactive"` or e.g. `"unknown (2)"`.
+impl fmt::Display for AutomaticSelfCalibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutomaticSelfCalibration::Active => f.write_str("active"),
+            AutomaticSelfCalibration::Inactive => f.write_str("inactive"),
+            AutomaticSelfCalibration::Unknown(value) => write!(f, "unknown ({value})"),
+        }
+    }
+}
+
+/// Renders the reference value as e.g. `"450 ppm"`.
+impl fmt::Display for ForcedRecalibrationValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ppm", u16::from_be_bytes(self.to_be_bytes()))
+    }
+}
+
+/// Renders the version as e.g. `"3.66"`, which can be parsed again with
+/// [FromStr](core::str::FromStr).
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Renders the status as `"ready"`, `"not ready"` or e.g. `"unknown (2)"`.
+impl fmt::Display for DataStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataStatus::Ready => f.write_str("ready"),
+            DataStatus::NotReady => f.write_str("not ready"),
+            DataStatus::Unknown(value) => write!(f, "unknown ({value})"),
+        }
+    }
+}
+
+/// Renders the settings which are set, e.g.
+/// `"interval: 10 s, ASC: inactive, offset: 1.50 °C, altitude: 520 m, pressure: 950 mBar"`.
+/// A configuration without settings is rendered as `"unchanged"`.
+impl fmt::Display for Scd30Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        let mut setting = |f: &mut fmt::Formatter<'_>, name: &str, value: &dyn fmt::Display| {
+            let result = write!(f, "{}{}: {}", separator, name, value);
+            separator = ", ";
+            result
+        };
+        if let Some(interval) = &self.measurement_interval {
+            setting(f, "interval", interval)?;
+        }
+        if let Some(asc) = &self.automatic_self_calibration {
+            setting(f, "ASC", asc)?;
+        }
+        if let Some(offset) = &self.temperature_offset {
+            setting(f, "offset", offset)?;
+        }
+        if let Some(altitude) = &self.altitude_compensation {
+            setting(f, "altitude", altitude)?;
+        }
+        if let Some(pressure) = &self.continuous_measurement {
+            setting(f, "pressure", pressure)?;
+        }
+        if separator.is_empty() {
+            f.write_str("unchanged")?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders all settings, e.g. `"interval: 2 s, ASC: inactive, FRC: 450 ppm, offset: 5.00 °C,
+/// altitude: 1000 m, firmware: 3.66, data: ready"`.
+impl fmt::Display for SettingsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "interval: {}, ASC: {}, FRC: {}, offset: {}, altitude: {}, firmware: {}, data: {}",
+            self.measurement_interval,
+            self.automatic_self_calibration,
+            self.forced_recalibration,
+            self.temperature_offset,
+            self.altitude_compensation,
+            self.firmware_version,
+            self.data_status
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(value: FixedPoint) -> String {
+        format!("{}", value)
+    }
+
+    #[test]
+    fn from_f32_rounds_half_away_from_zero() {
+        assert_eq!(FixedPoint::from_f32(23.45, 1), FixedPoint::new(235, 1));
+        assert_eq!(FixedPoint::from_f32(-23.45, 1), FixedPoint::new(-235, 1));
+        assert_eq!(FixedPoint::from_f32(811.6, 0), FixedPoint::new(812, 0));
+    }
+
+    #[test]
+    fn from_f32_saturates_and_handles_nan() {
+        assert_eq!(FixedPoint::from_f32(f32::MAX, 0).mantissa(), i32::MAX);
+        assert_eq!(FixedPoint::from_f32(f32::NAN, 2).mantissa(), 0);
+    }
+
+    #[test]
+    fn to_f32_restores_value() {
+        assert_eq!(FixedPoint::new(-2345, 2).to_f32(), -23.45);
+        assert_eq!(FixedPoint::from_f32(21.456, 1).to_f32(), 21.5);
+    }
+
+    #[test]
+    fn decimals_are_clamped() {
+        assert_eq!(FixedPoint::new(1, 12).decimals(), 9);
+        assert_eq!(FixedPoint::from_f32(1.0, 12).decimals(), 9);
+    }
+
+    #[test]
+    fn integer_and_fraction_split_properly() {
+        let value = FixedPoint::new(-2345, 2);
+        assert_eq!(value.integer(), -23);
+        assert_eq!(value.fraction(), 45);
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(render(FixedPoint::new(812, 0)), "812");
+        assert_eq!(render(FixedPoint::new(234, 1)), "23.4");
+        assert_eq!(render(FixedPoint::new(5, 2)), "0.05");
+        assert_eq!(render(FixedPoint::new(-5, 1)), "-0.5");
+        assert_eq!(render(FixedPoint::new(-1234, 2)), "-12.34");
+    }
+
+    #[test]
+    fn write_measurement_spec_sample_works() {
+        let measurement = Measurement {
+            co2_concentration: 439.09515,
+            temperature: 27.23828,
+            humidity: 48.806744,
+        };
+        let mut buf = [0; 32];
+        assert_eq!(
+            write_measurement(&measurement, &mut buf).unwrap(),
+            "439 ppm, 27.2 °C, 48.8 %"
+        );
+    }
+
+    #[test]
+    fn display_measurement_works() {
+        let measurement = Measurement::new(811.6, -3.04, 45.06);
+        assert_eq!(format!("{}", measurement), "812 ppm, -3.0 °C, 45.1 %");
+    }
+
+    #[test]
+    fn report_lists_settings_and_values() {
+        let settings = SettingsSnapshot {
+            measurement_interval: MeasurementInterval::try_from(2).unwrap(),
+            automatic_self_calibration: AutomaticSelfCalibration::Inactive,
+            forced_recalibration: ForcedRecalibrationValue::try_from(450).unwrap(),
+            temperature_offset: TemperatureOffset::try_from(5.0_f32).unwrap(),
+            altitude_compensation: AltitudeCompensation::from(1000),
+            firmware_version: FirmwareVersion::new(3, 66),
+            data_status: DataStatus::Ready,
+        };
+        let mut report = String::new();
+        write_report(
+            &mut report,
+            &settings,
+            &Measurement::new(811.6, 23.44, 45.06),
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            "SCD30 status\n  Firmware:            3.66\n  Interval:            2 s\n  \
+             Self-calibration:    inactive\n  Recalibration value: 450 ppm\n  \
+             Temperature offset:  5.00 °C\n  Altitude:            1000 m\n  \
+             Data:                ready\n  CO2:                 812 ppm\n  \
+             Temperature:         23.4 °C\n  Humidity:            45.1 %\n  \
+             Warnings:            none\n"
+        );
+
+        let mut report = String::new();
+        write_report(
+            &mut report,
+            &settings,
+            &Measurement::new(811.6, 23.44, 105.0),
+        )
+        .unwrap();
+        assert!(report.ends_with("Warnings:            humidity above 100 %\n"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn report_is_rendered_into_string() {
+        let settings = SettingsSnapshot {
+            measurement_interval: MeasurementInterval::try_from(2).unwrap(),
+            automatic_self_calibration: AutomaticSelfCalibration::Inactive,
+            forced_recalibration: ForcedRecalibrationValue::try_from(450).unwrap(),
+            temperature_offset: TemperatureOffset::try_from(5.0_f32).unwrap(),
+            altitude_compensation: AltitudeCompensation::from(1000),
+            firmware_version: FirmwareVersion::new(3, 66),
+            data_status: DataStatus::Ready,
+        };
+        let measurement = Measurement::new(811.6, 23.44, 45.06);
+        let mut report = String::new();
+        write_report(&mut report, &settings, &measurement).unwrap();
+        assert_eq!(report_string(&settings, &measurement), report);
+    }
+
+    #[test]
+    fn display_settings_snapshot_works() {
+        let settings = SettingsSnapshot {
+            measurement_interval: MeasurementInterval::try_from(2).unwrap(),
+            automatic_self_calibration: AutomaticSelfCalibration::Inactive,
+            forced_recalibration: ForcedRecalibrationValue::try_from(450).unwrap(),
+            temperature_offset: TemperatureOffset::try_from(5.0_f32).unwrap(),
+            altitude_compensation: AltitudeCompensation::from(1000),
+            firmware_version: FirmwareVersion::new(3, 66),
+            data_status: DataStatus::Ready,
+        };
+        assert_eq!(
+            format!("{}", settings),
+            "interval: 2 s, ASC: inactive, FRC: 450 ppm, offset: 5.00 °C, altitude: 1000 m, firmware: 3.66, data: ready"
+        );
+    }
+
+    #[test]
+    fn format_into_renders_settings() {
+        let config = Scd30Config {
+            continuous_measurement: Some(AmbientPressureCompensation::CompensationPressure(
+                AmbientPressure::try_from(950).unwrap(),
+            )),
+            measurement_interval: Some(MeasurementInterval::try_from(10).unwrap()),
+            automatic_self_calibration: Some(AutomaticSelfCalibration::Inactive),
+            temperature_offset: Some(TemperatureOffset::try_from(1.5).unwrap()),
+            altitude_compensation: Some(AltitudeCompensation::from(520)),
+        };
+        assert_eq!(
+            config.format_into::<96>().unwrap(),
+            "interval: 10 s, ASC: inactive, offset: 1.50 °C, altitude: 520 m, pressure: 950 mBar"
+        );
+        assert_eq!(
+            Scd30Config::default().format_into::<16>().unwrap(),
+            "unchanged"
+        );
+        assert_eq!(
+            ForcedRecalibrationValue::try_from(450)
+                .unwrap()
+                .format_into::<8>()
+                .unwrap(),
+            "450 ppm"
+        );
+        assert_eq!(
+            FirmwareVersion::new(3, 66).format_into::<8>().unwrap(),
+            "3.66"
+        );
+        assert_eq!(
+            AmbientPressureCompensation::DefaultPressure
+                .format_into::<16>()
+                .unwrap(),
+            "default pressure"
+        );
+    }
+
+    #[test]
+    fn format_into_errors_on_small_capacity() {
+        let measurement = Measurement::new(811.6, -3.04, 45.06);
+        assert_eq!(measurement.format_into::<8>(), Err(fmt::Error));
+    }
+
+    #[test]
+    fn write_measurement_errors_on_small_buffer() {
+        let measurement = Measurement {
+            co2_concentration: 439.09515,
+            temperature: 27.23828,
+            humidity: 48.806744,
+        };
+        let mut buf = [0; 8];
+        assert_eq!(write_measurement(&measurement, &mut buf), Err(fmt::Error));
+    }
+}
@@ -0,0 +1,145 @@
+//! Detection of gaps in a recorded time series.
+//!
+//! A history of measurements may lack samples, e.g. while the sensor was unpowered or the bus was
+//! blocked. Plotting or averaging such a history silently bridges the outages as if the values
+//! were stable. [find_gaps] scans the timestamps of a history for gaps longer than the
+//! measurement interval, so they can be annotated or reported:
+//!
+//! ```
+//! use scd30_interface::{data::MeasurementInterval, gaps::find_gaps};
+//!
+//! let interval = MeasurementInterval::try_from(2).unwrap();
+//! let timestamps = [0, 2000, 4100, 12_000, 14_000];
+//! let gaps: Vec<_> = find_gaps(timestamps, interval).collect();
+//! assert_eq!(gaps.len(), 1);
+//! assert_eq!((gaps[0].start_ms, gaps[0].end_ms), (4100, 12_000));
+//! assert_eq!(gaps[0].missed, 3);
+//! ```
+//!
+//! Streams of measurements are checked sample by sample with a [GapDetector].
+
+use crate::data::MeasurementInterval;
+
+/// A gap between two consecutive samples of a time series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gap {
+    /// Timestamp of the last sample before the gap in milliseconds.
+    pub start_ms: u64,
+    /// Timestamp of the first sample after the gap in milliseconds.
+    pub end_ms: u64,
+    /// Number of samples missing within the gap.
+    pub missed: u32,
+}
+
+impl Gap {
+    /// Returns the duration of the gap in milliseconds.
+    pub const fn duration_ms(&self) -> u64 {
+        self.end_ms - self.start_ms
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Gap {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Gap {{ {}..{} ms, missed: {} }}",
+            self.start_ms,
+            self.end_ms,
+            self.missed
+        )
+    }
+}
+
+/// Returns the number of samples of an interval of `interval_ms` missing between two samples
+/// `elapsed_ms` apart.
+pub(crate) fn missed_samples(elapsed_ms: u64, interval_ms: u64) -> u32 {
+    let interval_ms = interval_ms.max(1);
+    // Samples are counted as missed once the gap exceeds half an interval beyond the next one,
+    // tolerating jitter of the sensor and the polling.
+    ((elapsed_ms + interval_ms / 2) / interval_ms)
+        .saturating_sub(1)
+        .min(u64::from(u32::MAX)) as u32
+}
+
+/// Detects gaps in a stream of timestamps, see the [module documentation](self).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GapDetector {
+    interval_ms: u64,
+    last_ms: Option<u64>,
+}
+
+impl GapDetector {
+    /// Creates a detector for samples taken every `interval`.
+    pub const fn new(interval: MeasurementInterval) -> Self {
+        Self {
+            interval_ms: interval.seconds() as u64 * 1000,
+            last_ms: None,
+        }
+    }
+
+    /// Adds a sample taken at `timestamp_ms` and returns the gap before it, if any. Samples older
+    /// than the previous one are ignored.
+    pub fn push(&mut self, timestamp_ms: u64) -> Option<Gap> {
+        let start_ms = self.last_ms.replace(timestamp_ms);
+        match start_ms {
+            Some(start_ms) if timestamp_ms < start_ms => {
+                self.last_ms = Some(start_ms);
+                None
+            }
+            Some(start_ms) => {
+                let missed = missed_samples(timestamp_ms - start_ms, self.interval_ms);
+                (missed > 0).then_some(Gap {
+                    start_ms,
+                    end_ms: timestamp_ms,
+                    missed,
+                })
+            }
+            None => None,
+        }
+    }
+}
+
+/// Returns an iterator over the gaps between the `timestamps` of a history sampled every
+/// `interval`. The timestamps are expected in ascending order, see [GapDetector::push].
+pub fn find_gaps<I: IntoIterator<Item = u64>>(
+    timestamps: I,
+    interval: MeasurementInterval,
+) -> impl Iterator<Item = Gap> {
+    let mut detector = GapDetector::new(interval);
+    timestamps
+        .into_iter()
+        .filter_map(move |timestamp_ms| detector.push(timestamp_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval() -> MeasurementInterval {
+        MeasurementInterval::try_from(2).unwrap()
+    }
+
+    #[test]
+    fn jitter_is_tolerated() {
+        assert_eq!(find_gaps([0, 2900, 5000, 6000], interval()).count(), 0);
+    }
+
+    #[test]
+    fn gaps_are_reported_with_missed_samples() {
+        let mut detector = GapDetector::new(interval());
+        assert_eq!(detector.push(1000), None);
+        let gap = detector.push(9000).unwrap();
+        assert_eq!(gap.missed, 3);
+        assert_eq!(gap.duration_ms(), 8000);
+        assert_eq!(detector.push(8000), None);
+        assert_eq!(
+            detector.push(12_200),
+            Some(Gap {
+                start_ms: 9000,
+                end_ms: 12_200,
+                missed: 1
+            })
+        );
+    }
+}
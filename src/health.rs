@@ -0,0 +1,372 @@
+//! Diagnostics of the sensor and its interface.
+
+use crate::{
+    command::Command,
+    data::{FirmwareVersion, Scd30Config},
+    gaps::missed_samples,
+};
+
+/// Number of errors encountered by the interface since its creation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ErrorCounters {
+    /// Errors reported by the I2C bus.
+    pub i2c: u32,
+    /// Received data failing the CRC check.
+    pub crc: u32,
+    /// Other invalid data received, e.g. of wrong size or with values out of range.
+    pub data: u32,
+    /// Operations exceeding the configured timeout.
+    pub timeout: u32,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ErrorCounters {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "ErrorCounters {{ i2c: {}, crc: {}, data: {}, timeout: {} }}",
+            self.i2c,
+            self.crc,
+            self.data,
+            self.timeout
+        )
+    }
+}
+
+/// I2C traffic of one category of operations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Traffic {
+    /// Number of I2C transactions, including failed ones and retries.
+    pub transactions: u32,
+    /// Number of bytes written to the bus, excluding address bytes.
+    pub bytes_written: u32,
+    /// Number of bytes read from the bus.
+    pub bytes_read: u32,
+}
+
+impl Traffic {
+    fn add(&mut self, other: Traffic) {
+        self.transactions = self.transactions.saturating_add(other.transactions);
+        self.bytes_written = self.bytes_written.saturating_add(other.bytes_written);
+        self.bytes_read = self.bytes_read.saturating_add(other.bytes_read);
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Traffic {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{} transactions, {}B written, {}B read",
+            self.transactions,
+            self.bytes_written,
+            self.bytes_read
+        )
+    }
+}
+
+/// I2C traffic caused by the interface since its creation, split by category of operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BusMetrics {
+    /// Polling the data ready status and reading measurements.
+    pub measurement: Traffic,
+    /// Reading and writing settings like the measurement interval, calibration and compensations,
+    /// as well as reading the firmware version.
+    pub configuration: Traffic,
+    /// Starting and stopping measurements and resetting the sensor.
+    pub control: Traffic,
+}
+
+impl BusMetrics {
+    /// Returns the traffic of all categories combined.
+    pub fn total(&self) -> Traffic {
+        let mut total = self.measurement;
+        total.add(self.configuration);
+        total.add(self.control);
+        total
+    }
+
+    pub(crate) fn record(&mut self, command: Option<Command>, written: usize, read: usize) {
+        let traffic = match command {
+            Some(Command::GetDataReady | Command::ReadMeasurement) => &mut self.measurement,
+            Some(
+                Command::SetMeasurementInterval
+                | Command::ActivateAutomaticSelfCalibration
+                | Command::ForcedRecalibrationValue
+                | Command::SetTemperatureOffset
+                | Command::SetAltitudeCompensation
+                | Command::ReadFirmwareVersion,
+            ) => &mut self.configuration,
+            Some(
+                Command::TriggerContinuousMeasurement
+                | Command::StopContinuousMeasurement
+                | Command::SoftReset,
+            )
+            | None => &mut self.control,
+        };
+        traffic.add(Traffic {
+            transactions: 1,
+            bytes_written: written as u32,
+            bytes_read: read as u32,
+        });
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for BusMetrics {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "BusMetrics {{ measurement: {}, configuration: {}, control: {} }}",
+            self.measurement,
+            self.configuration,
+            self.control
+        )
+    }
+}
+
+/// Durations of one kind of operation in milliseconds, as measured with the configured
+/// [Clock](crate::options::Clock).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationTiming {
+    /// Number of measured operations.
+    pub count: u32,
+    /// Duration of the last operation.
+    pub last_ms: u64,
+    /// Longest duration observed.
+    pub max_ms: u64,
+    /// Sum of all durations, see [mean_ms](Self::mean_ms).
+    pub total_ms: u64,
+}
+
+impl OperationTiming {
+    /// Returns the mean duration of the operations, `None` if none was measured.
+    pub fn mean_ms(&self) -> Option<u64> {
+        (self.count > 0).then(|| self.total_ms / u64::from(self.count))
+    }
+
+    fn record(&mut self, duration_ms: u64) {
+        self.count = self.count.saturating_add(1);
+        self.last_ms = duration_ms;
+        self.max_ms = self.max_ms.max(duration_ms);
+        self.total_ms = self.total_ms.saturating_add(duration_ms);
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for OperationTiming {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{} operations, last {}ms, max {}ms",
+            self.count,
+            self.last_ms,
+            self.max_ms
+        )
+    }
+}
+
+/// Durations of the operations of the interface, to detect stalls caused by clock stretching and
+/// to tune the bus speed and polling cadence. Only successful operations are measured, and only if
+/// a [Clock](crate::options::Clock) is configured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// From sending a command until its response is read, including the configured read gap,
+    /// clock stretching and retries.
+    pub response: OperationTiming,
+    /// Full readout of a measurement, from sending the command until all 18 bytes are read.
+    pub measurement_read: OperationTiming,
+}
+
+impl Timings {
+    pub(crate) fn record(&mut self, command: Command, duration_ms: u64) {
+        self.response.record(duration_ms);
+        if command == Command::ReadMeasurement {
+            self.measurement_read.record(duration_ms);
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Timings {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Timings {{ response: {}, measurement_read: {} }}",
+            self.response,
+            self.measurement_read
+        )
+    }
+}
+
+/// Accounting of the samples delivered by the sensor, to detect holes in the time series caused by
+/// slow polling or bus contention.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SampleAccounting {
+    /// Number of data ready polls reporting a sample.
+    pub data_ready: u32,
+    /// Number of samples read successfully.
+    pub read: u32,
+    /// Number of samples the sensor produced but which were overwritten before being read.
+    /// Requires a [Clock](crate::options::Clock) to be configured.
+    pub missed: u32,
+}
+
+impl SampleAccounting {
+    /// Records a sample read `elapsed_ms` after the previous one, returning the number of samples
+    /// missed in between for the measurement interval of `interval_s` seconds.
+    pub(crate) fn record_read(&mut self, elapsed_ms: Option<u64>, interval_s: u16) -> u32 {
+        self.read = self.read.saturating_add(1);
+        let interval_ms = u64::from(interval_s.max(1)) * 1000;
+        let missed = elapsed_ms
+            .map(|elapsed_ms| missed_samples(elapsed_ms, interval_ms))
+            .unwrap_or_default();
+        self.missed = self.missed.saturating_add(missed);
+        missed
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SampleAccounting {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "SampleAccounting {{ data_ready: {}, read: {}, missed: {} }}",
+            self.data_ready,
+            self.read,
+            self.missed
+        )
+    }
+}
+
+/// Cause of the last reset of the sensor issued through the interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetCause {
+    /// Reset using the soft reset command.
+    SoftReset,
+    /// Reset using the I2C general call reset.
+    GeneralCallReset,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ResetCause {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            ResetCause::SoftReset => defmt::write!(f, "SoftReset"),
+            ResetCause::GeneralCallReset => defmt::write!(f, "GeneralCallReset"),
+        }
+    }
+}
+
+/// Health report of a sensor, suitable for periodic diagnostics uplinks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HealthReport {
+    /// Firmware version of the sensor.
+    pub firmware_version: FirmwareVersion,
+    /// Configuration read back from the sensor. The ambient pressure compensation is reported as
+    /// `None` if continuous measurements were stopped through the interface.
+    pub config: Scd30Config,
+    /// Errors encountered by the interface so far.
+    pub errors: ErrorCounters,
+    /// I2C traffic caused by the interface so far.
+    pub metrics: BusMetrics,
+    /// Durations of the operations of the interface so far.
+    pub timings: Timings,
+    /// Samples delivered by the sensor so far.
+    pub samples: SampleAccounting,
+    /// Cause of the last reset issued through the interface, `None` if no reset was issued.
+    pub last_reset: Option<ResetCause>,
+    /// Time in milliseconds between the previous sample and the next one becoming ready, as last
+    /// observed. Requires a [Clock](crate::options::Clock) to be configured.
+    pub data_ready_latency_ms: Option<u64>,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for HealthReport {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "HealthReport {{ firmware_version: {}, config: {}, errors: {}, metrics: {}, timings: {}, samples: {}, last_reset: {}, data_ready_latency_ms: {} }}",
+            self.firmware_version,
+            self.config,
+            self.errors,
+            self.metrics,
+            self.timings,
+            self.samples,
+            self.last_reset,
+            self.data_ready_latency_ms
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_are_recorded_per_operation() {
+        let mut timings = Timings::default();
+        assert_eq!(timings.response.mean_ms(), None);
+        timings.record(Command::GetDataReady, 2);
+        timings.record(Command::ReadMeasurement, 6);
+        timings.record(Command::GetDataReady, 1);
+
+        assert_eq!(
+            timings.response,
+            OperationTiming {
+                count: 3,
+                last_ms: 1,
+                max_ms: 6,
+                total_ms: 9
+            }
+        );
+        assert_eq!(timings.response.mean_ms(), Some(3));
+        assert_eq!(timings.measurement_read.count, 1);
+        assert_eq!(timings.measurement_read.max_ms, 6);
+    }
+
+    #[test]
+    fn gaps_between_reads_count_as_missed_samples() {
+        let mut samples = SampleAccounting::default();
+        assert_eq!(samples.record_read(None, 2), 0);
+        assert_eq!(samples.record_read(Some(2900), 2), 0);
+        assert_eq!(samples.record_read(Some(3100), 2), 1);
+        assert_eq!(samples.record_read(Some(8000), 2), 3);
+        assert_eq!(
+            samples,
+            SampleAccounting {
+                data_ready: 0,
+                read: 4,
+                missed: 4
+            }
+        );
+    }
+
+    #[test]
+    fn traffic_is_recorded_per_category() {
+        let mut metrics = BusMetrics::default();
+        metrics.record(Some(Command::GetDataReady), 2, 0);
+        metrics.record(Some(Command::GetDataReady), 0, 3);
+        metrics.record(Some(Command::SetAltitudeCompensation), 5, 0);
+        metrics.record(None, 1, 0);
+
+        assert_eq!(
+            metrics.measurement,
+            Traffic {
+                transactions: 2,
+                bytes_written: 2,
+                bytes_read: 3
+            }
+        );
+        assert_eq!(metrics.configuration.bytes_written, 5);
+        assert_eq!(metrics.control.transactions, 1);
+        assert_eq!(
+            metrics.total(),
+            Traffic {
+                transactions: 4,
+                bytes_written: 8,
+                bytes_read: 3
+            }
+        );
+    }
+}
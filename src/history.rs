@@ -0,0 +1,143 @@
+//! Growable history of measurements for targets with a heap.
+//!
+//! The rollups and filters of this crate keep fixed-size buffers, so they fit targets without an
+//! allocator. On an ESP32 or Linux a [History] stores the timestamped measurements in heap memory
+//! instead, optionally bounded to a number of samples:
+//!
+//! ```
+//! use scd30_interface::{data::Measurement, history::History};
+//!
+//! let mut history = History::with_limit(2);
+//! history.push(0, Measurement::new(400.0, 20.0, 40.0));
+//! history.push(2000, Measurement::new(410.0, 20.5, 41.0));
+//! history.push(4000, Measurement::new(420.0, 21.0, 42.0));
+//! assert_eq!(history.len(), 2);
+//! assert_eq!(history.latest().map(|(timestamp_ms, _)| timestamp_ms), Some(4000));
+//! ```
+
+use alloc::collections::VecDeque;
+
+use crate::{
+    data::{Measurement, MeasurementInterval},
+    gaps::{find_gaps, Gap},
+};
+
+/// Timestamped measurements in the order they were taken. See the
+/// [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    samples: VecDeque<(u64, Measurement)>,
+    limit: Option<usize>,
+}
+
+impl History {
+    /// Creates a history growing without bounds.
+    pub const fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            limit: None,
+        }
+    }
+
+    /// Creates a history keeping the latest `limit` measurements, dropping the oldest one once
+    /// more are pushed.
+    pub const fn with_limit(limit: usize) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            limit: Some(limit),
+        }
+    }
+
+    /// Adds a measurement taken at `timestamp_ms`.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: Measurement) {
+        if let Some(limit) = self.limit {
+            if limit == 0 {
+                return;
+            }
+            while self.samples.len() >= limit {
+                self.samples.pop_front();
+            }
+        }
+        self.samples.push_back((timestamp_ms, measurement));
+    }
+
+    /// Returns the number of measurements in the history.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns whether the history is empty.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the latest measurement with its timestamp.
+    pub fn latest(&self) -> Option<(u64, Measurement)> {
+        self.samples.back().copied()
+    }
+
+    /// Returns an iterator over the measurements with their timestamps, starting with the oldest.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, Measurement)> + '_ {
+        self.samples.iter().copied()
+    }
+
+    /// Returns an iterator over the measurements taken at or after `timestamp_ms`.
+    pub fn since(&self, timestamp_ms: u64) -> impl Iterator<Item = (u64, Measurement)> + '_ {
+        self.iter()
+            .skip_while(move |(sample_ms, _)| *sample_ms < timestamp_ms)
+    }
+
+    /// Returns an iterator over the gaps in the history, for measurements taken every `interval`.
+    /// See [gaps](crate::gaps).
+    pub fn gaps(&self, interval: MeasurementInterval) -> impl Iterator<Item = Gap> + '_ {
+        find_gaps(
+            self.samples.iter().map(|(timestamp_ms, _)| *timestamp_ms),
+            interval,
+        )
+    }
+
+    /// Removes all measurements.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(co2: f32) -> Measurement {
+        Measurement::new(co2, 20.0, 40.0)
+    }
+
+    #[test]
+    fn limited_history_drops_oldest_measurements() {
+        let mut history = History::with_limit(3);
+        for (index, co2) in [400.0, 410.0, 420.0, 430.0].into_iter().enumerate() {
+            history.push(index as u64 * 2000, measurement(co2));
+        }
+        let co2: Vec<_> = history.iter().map(|(_, m)| m.co2_concentration).collect();
+        assert_eq!(co2, [410.0, 420.0, 430.0]);
+        assert_eq!(history.since(4000).count(), 2);
+
+        let mut empty = History::with_limit(0);
+        empty.push(0, measurement(400.0));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn gaps_are_found_in_history() {
+        let mut history = History::new();
+        for timestamp_ms in [0, 2000, 10_000, 12_000] {
+            history.push(timestamp_ms, measurement(400.0));
+        }
+        let gaps: Vec<_> = history
+            .gaps(MeasurementInterval::try_from(2).unwrap())
+            .collect();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].missed, 3);
+
+        history.clear();
+        assert_eq!(history.latest().map(|(timestamp_ms, _)| timestamp_ms), None);
+    }
+}
@@ -1,13 +1,31 @@
 use duplicate::duplicate_item;
 
-const ADDRESS: u8 = 0x61;
-const WRITE_FLAG: u8 = 0x00;
-const READ_FLAG: u8 = 0x01;
+use crate::{command::Command, data::AutomaticSelfCalibration};
+
+pub(crate) const ADDRESS: u8 = 0x61;
+pub(crate) const GENERAL_CALL_ADDRESS: u8 = 0x00;
+pub(crate) const GENERAL_CALL_RESET: u8 = 0x06;
+
+// Frames for commands with fixed arguments are encoded at compile time, so they do not have to be
+// rebuilt on every call.
+const TRIGGER_WITH_DEFAULT_PRESSURE: [u8; 5] =
+    Command::TriggerContinuousMeasurement.encode_with_argument([0x00, 0x00]);
+pub(crate) const DEFAULT_INTERVAL: u16 = 2;
+// Time the sensor needs to boot after a soft reset.
+pub(crate) const RESET_DELAY_MS: u32 = 2000;
+// Interval in which the data ready status is polled while waiting for a measurement.
+const DATA_READY_POLL_INTERVAL_MS: u32 = 100;
+// Length of the longest response, the measurement.
+const MAX_RESPONSE_LEN: usize = 18;
+const ACTIVATE_ASC: [u8; 5] = Command::ActivateAutomaticSelfCalibration
+    .encode_with_argument(AutomaticSelfCalibration::Active.to_be_bytes());
+const DEACTIVATE_ASC: [u8; 5] = Command::ActivateAutomaticSelfCalibration
+    .encode_with_argument(AutomaticSelfCalibration::Inactive.to_be_bytes());
 
 // `await` replacement needs to be a callable due to the dot notation. This tricks enables that
 // use case.
 #[cfg(not(tarpaulin_include))]
-trait Identity: Sized {
+pub(crate) trait Identity: Sized {
     fn identity(self) -> Self {
         core::convert::identity(self)
     }
@@ -15,37 +33,354 @@ trait Identity: Sized {
 
 impl<T: Sized> Identity for T {}
 
+// Blocking transfers can not be interrupted, so their duration is only checked once they
+// completed.
+#[cfg(feature = "blocking")]
+fn unguarded<T>(_timeout_ms: Option<u64>, result: T) -> Option<T> {
+    Some(result)
+}
+
+// With embassy, async transfers are cancelled once they exceed the timeout, returning `None`.
+#[cfg(feature = "async")]
+async fn guarded<F: core::future::Future>(
+    _timeout_ms: Option<u64>,
+    transfer: F,
+) -> Option<F::Output> {
+    #[cfg(feature = "embassy")]
+    if let Some(timeout_ms) = _timeout_ms {
+        let timeout = embassy_time::Duration::from_millis(timeout_ms);
+        return embassy_time::with_timeout(timeout, transfer).await.ok();
+    }
+    Some(transfer.await)
+}
+
 #[duplicate_item(
-    feature_        module      async   await               i2c_trait                                       test_macro;
-    ["blocking"]    [blocking]  []      [identity()]        [embedded_hal::i2c::I2c<Error = I2cErr>]        [test];
-    ["async"]       [asynch]    [async] [await.identity()]  [embedded_hal_async::i2c::I2c<Error = I2cErr>]  [tokio::test];
+    feature_        module      async   await               i2c_trait                                       delay_trait                         test_macro      bus_guard;
+    ["blocking"]    [blocking]  []      [identity()]        [embedded_hal::i2c::I2c<Error = I2cErr>]        [embedded_hal::delay::DelayNs]       [test]          [unguarded];
+    ["async"]       [asynch]    [async] [await.identity()]  [embedded_hal_async::i2c::I2c<Error = I2cErr>]  [embedded_hal_async::delay::DelayNs] [tokio::test]   [guarded];
 )]
 pub mod module {
     //! Implementation of the SCD30's interface
 
     #[cfg(feature=feature_)]
     mod inner {
+        use embedded_hal::i2c::ErrorKind;
+
         use crate::{
-            command::Command,
+            codec::validate_frame,
+            command::{Command, Response},
             data::{
                 AltitudeCompensation, AmbientPressureCompensation, AutomaticSelfCalibration,
-                DataStatus, FirmwareVersion, ForcedRecalibrationValue, Measurement,
-                MeasurementInterval, TemperatureOffset,
+                CompensationMode, DataStatus, FirmwareVersion, ForcedRecalibrationValue,
+                Measurement, MeasurementInterval, MeasuringState, Preset, QualifiedMeasurement,
+                SampleQuality, Scd30Config, SensorSnapshot, SettingsSnapshot, TemperatureOffset,
+            },
+            error::{DataError, Phase, Scd30Error, SequenceViolation},
+            event::{EventHook, Scd30Event},
+            health::{
+                BusMetrics, ErrorCounters, HealthReport, ResetCause, SampleAccounting, Timings,
+            },
+            interface::{
+                bus_guard, Identity, ACTIVATE_ASC, ADDRESS, DATA_READY_POLL_INTERVAL_MS,
+                DEACTIVATE_ASC, GENERAL_CALL_ADDRESS, GENERAL_CALL_RESET, MAX_RESPONSE_LEN,
+                RESET_DELAY_MS, TRIGGER_WITH_DEFAULT_PRESSURE,
             },
-            error::Scd30Error,
-            interface::{Identity, ADDRESS, READ_FLAG, WRITE_FLAG},
-            util::compute_crc8,
+            log::{log_debug, log_info, log_trace, log_warn},
+            options::{
+                Clock, CrcValidation, NoDelay, ReadStrategy, ReadVerification, ValueDecoding,
+                WarmUpAction, WarmUpPolicy, Watchdog,
+            },
+            sequencing::{SequenceGuard, CALIBRATION_SOAK_MS},
+            state::{Health, Options, Scd30State, Sequence, Settings},
+            transaction::{ConfigStep, ConfigTransaction, COMMAND_GAP_US},
+            util::replace_crcs,
         };
 
         /// Interface for the [SCD30 CO2 sensor by Sensirion](https://sensirion.com/products/catalog/SCD30).
-        pub struct Scd30<I2C> {
+        ///
+        /// The delay `D` is only used by the configured [ReadStrategy], see
+        /// [with_read_strategy](Self::with_read_strategy).
+        pub struct Scd30<I2C, D = NoDelay> {
             i2c: I2C,
+            address: u8,
+            delay: D,
+            options: Options,
+            settings: Settings,
+            sequence: Sequence,
+            health: Health,
         }
 
         impl<I2C: i2c_trait, I2cErr: embedded_hal::i2c::Error> Scd30<I2C> {
             /// Create a new SCD30 interface.
             pub fn new(i2c: I2C) -> Self {
-                Self { i2c }
+                Self::new_with_address(i2c, ADDRESS)
+            }
+
+            /// Create a new SCD30 interface for a sensor at the 7-bit `address`, e.g. behind an
+            /// address translator like the LTC4316.
+            pub fn new_with_address(i2c: I2C, address: u8) -> Self {
+                Self {
+                    i2c,
+                    address,
+                    delay: NoDelay,
+                    options: Options::default(),
+                    settings: Settings::default(),
+                    sequence: Sequence::default(),
+                    health: Health::default(),
+                }
+            }
+        }
+
+        impl<I2C: i2c_trait, D: delay_trait, I2cErr: embedded_hal::i2c::Error> Scd30<I2C, D> {
+            /// Selects how responses are read after writing their command, using `delay` to wait
+            /// between both. Required for I2C implementations mishandling the clock stretching of
+            /// the sensor, see [ReadStrategy].
+            pub fn with_read_strategy<D2: delay_trait>(
+                self,
+                delay: D2,
+                read_strategy: ReadStrategy,
+            ) -> Scd30<I2C, D2> {
+                Scd30 {
+                    i2c: self.i2c,
+                    address: self.address,
+                    delay,
+                    options: Options {
+                        read_strategy,
+                        ..self.options
+                    },
+                    settings: self.settings,
+                    sequence: self.sequence,
+                    health: self.health,
+                }
+            }
+
+            /// Limits the duration of each I2C operation to `timeout_ms` milliseconds. Operations
+            /// taking longer fail with [Timeout](crate::error::Scd30Error::Timeout), even if they
+            /// completed eventually, as a long clock-stretch hints at a misbehaving sensor.
+            /// Requires a [Clock] set through [with_clock](Self::with_clock) or the [Watchdog].
+            ///
+            /// With the `embassy` feature, async operations are additionally cancelled once they
+            /// exceed the timeout, so a wedged bus can not hang the task. This does not require a
+            /// [Clock].
+            pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
+                self.options.timeout_ms = Some(timeout_ms);
+                self
+            }
+
+            /// Calls `hook` for every maintenance action performed through this interface: resets,
+            /// forced recalibrations and changes of the automatic self-calibration, see
+            /// [Scd30Event].
+            pub fn on_event(mut self, hook: EventHook) -> Self {
+                self.options.on_event = Some(hook);
+                self
+            }
+
+            /// Selects whether the CRCs of responses are validated, see [CrcValidation]. Validation
+            /// is enabled by default and should only be disabled where wrong readings are
+            /// acceptable.
+            pub fn with_crc_validation(mut self, validation: CrcValidation) -> Self {
+                self.options.crc_validation = validation;
+                self
+            }
+
+            /// Checks every command against the sequencing rules of the interface description and
+            /// rejects violations with [Sequence](crate::error::Scd30Error::Sequence), see
+            /// [sequencing](crate::sequencing). The timing rules require a [Clock], see
+            /// [with_clock](Self::with_clock).
+            pub fn with_strict_sequencing(mut self) -> Self {
+                self.sequence.guard = Some(SequenceGuard::new());
+                self
+            }
+
+            /// Returns the bookkeeping of the strict sequencing, if enabled, e.g. to wait for the
+            /// calibration soak before a forced recalibration.
+            pub fn sequence_guard(&self) -> Option<SequenceGuard> {
+                self.sequence.guard
+            }
+
+            /// Selects how the data ready status and measurements are verified before they are
+            /// returned, see [ReadVerification]. Reads are not verified by default.
+            pub fn with_read_verification(mut self, verification: ReadVerification) -> Self {
+                self.options.verification = verification;
+                self
+            }
+
+            /// Selects how values outside the documented range are handled, e.g. an automatic
+            /// self-calibration state other than `0` or `1` returned by a newer firmware, see
+            /// [ValueDecoding]. Such values are rejected by default.
+            pub fn with_value_decoding(mut self, decoding: ValueDecoding) -> Self {
+                self.options.value_decoding = decoding;
+                self
+            }
+
+            /// Sets the time source used for timing diagnostics like the data-ready latency. If
+            /// none is set, the clock of the [Watchdog] is used.
+            pub fn with_clock(mut self, clock: Clock) -> Self {
+                self.options.clock = Some(clock);
+                self
+            }
+
+            /// Returns the number of errors encountered so far.
+            pub fn error_counters(&self) -> ErrorCounters {
+                self.health.errors
+            }
+
+            /// Returns the I2C traffic caused by this interface so far.
+            pub fn metrics(&self) -> BusMetrics {
+                self.health.metrics
+            }
+
+            /// Returns the durations of the operations of this interface so far. Requires a
+            /// [Clock] to be configured, see [with_clock](Self::with_clock).
+            pub fn timings(&self) -> Timings {
+                self.health.timings
+            }
+
+            /// Returns the accounting of the samples delivered by the sensor so far. Missed samples
+            /// are only detected if a [Clock] is configured, see [with_clock](Self::with_clock),
+            /// and are reported as [SampleGap](Scd30Event::SampleGap) events as well.
+            pub fn sample_accounting(&self) -> SampleAccounting {
+                self.health.samples
+            }
+
+            /// Collects a [HealthReport] of the sensor. The configuration is read back from the
+            /// sensor, the firmware version only if it was not read before.
+            pub async fn health_report(&mut self) -> Result<HealthReport, Scd30Error<I2cErr>> {
+                let firmware_version = self.cached_firmware_version().await?;
+                let config = self.read_config().await?;
+                Ok(HealthReport {
+                    firmware_version,
+                    config,
+                    errors: self.health.errors,
+                    metrics: self.health.metrics,
+                    timings: self.health.timings,
+                    samples: self.health.samples,
+                    last_reset: self.sequence.last_reset,
+                    data_ready_latency_ms: self.health.data_ready_latency_ms,
+                })
+            }
+
+            /// Reads every readable setting from the sensor into a [SensorSnapshot], which can be
+            /// re-applied with [restore](Self::restore). The firmware version is only read if it
+            /// was not read before.
+            pub async fn snapshot(&mut self) -> Result<SensorSnapshot, Scd30Error<I2cErr>> {
+                Ok(SensorSnapshot {
+                    firmware_version: self.cached_firmware_version().await?,
+                    config: self.read_config().await?,
+                    forced_recalibration: self.get_forced_recalibration().await?,
+                })
+            }
+
+            /// Reads every setting, the firmware version and the data ready status into a
+            /// [SettingsSnapshot], e.g. for diagnostics dumps or to verify a provisioned sensor.
+            /// With the [configuration cache](Self::with_config_cache), cached settings are not
+            /// read again.
+            pub async fn read_all_settings(
+                &mut self,
+            ) -> Result<SettingsSnapshot, Scd30Error<I2cErr>> {
+                Ok(SettingsSnapshot {
+                    measurement_interval: self.get_measurement_interval().await?,
+                    automatic_self_calibration: self.get_automatic_self_calibration().await?,
+                    forced_recalibration: self.get_forced_recalibration().await?,
+                    temperature_offset: self.get_temperature_offset().await?,
+                    altitude_compensation: self.get_altitude_compensation().await?,
+                    firmware_version: self.read_firmware_version().await?,
+                    data_status: self.is_data_ready().await?,
+                })
+            }
+
+            /// Re-applies the settings of `snapshot`, e.g. after a firmware update or on a
+            /// replacement sensor. The settings are written like a [ConfigTransaction], see
+            /// [commit](Self::commit). The forced recalibration value is not restored.
+            pub async fn restore<W: delay_trait>(
+                &mut self,
+                snapshot: &SensorSnapshot,
+                delay: &mut W,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                log_info!(?snapshot, "restoring snapshot");
+                self.commit(&ConfigTransaction::from_config(snapshot.config), delay)
+                    .await
+            }
+
+            /// Reads back the configuration from the sensor. The ambient pressure compensation is
+            /// reported as `None` if continuous measurements were stopped through this interface,
+            /// as the sensor keeps reporting the last one.
+            pub async fn read_config(&mut self) -> Result<Scd30Config, Scd30Error<I2cErr>> {
+                self.read_back_config().await
+            }
+
+            // Reads back every setting, the ambient pressure compensation only unless continuous
+            // measurements were stopped through this interface.
+            async fn read_back_config(&mut self) -> Result<Scd30Config, Scd30Error<I2cErr>> {
+                let continuous_measurement = match self.sequence.measuring {
+                    MeasuringState::Stopped => None,
+                    MeasuringState::Measuring | MeasuringState::Unknown => {
+                        Some(self.get_ambient_pressure().await?)
+                    }
+                };
+                Ok(Scd30Config {
+                    continuous_measurement,
+                    measurement_interval: Some(self.get_measurement_interval().await?),
+                    automatic_self_calibration: Some(self.get_automatic_self_calibration().await?),
+                    temperature_offset: Some(self.get_temperature_offset().await?),
+                    altitude_compensation: Some(self.get_altitude_compensation().await?),
+                })
+            }
+
+            /// Returns the settings applied through this interface so far. These are restored by
+            /// [soft_reset_and_reinit](Self::soft_reset_and_reinit) if no other configuration is
+            /// supplied.
+            pub fn config(&self) -> Scd30Config {
+                self.settings.config
+            }
+
+            /// Caches the settings written to or read from the sensor, so the `get_*` methods of
+            /// the measurement interval, automatic self-calibration, temperature offset, altitude
+            /// and ambient pressure compensation only read settings from the sensor which are not
+            /// known yet. Changes made by other means, e.g. another bus master, are only picked up
+            /// by [refresh](Self::refresh).
+            pub fn with_config_cache(mut self) -> Self {
+                self.settings.cache = Some(Scd30Config::default());
+                self
+            }
+
+            /// Returns the cached settings, `None` if caching is disabled.
+            pub fn cached_config(&self) -> Option<Scd30Config> {
+                self.settings.cache
+            }
+
+            /// Reads back all cached settings from the sensor, replacing the cached values. Without
+            /// caching, the settings are only read. The ambient pressure compensation is reported
+            /// like by [read_config](Self::read_config).
+            pub async fn refresh(&mut self) -> Result<Scd30Config, Scd30Error<I2cErr>> {
+                if let Some(cache) = &mut self.settings.cache {
+                    *cache = Scd30Config::default();
+                }
+                self.read_back_config().await
+            }
+
+            /// Applies a warm-up policy for the samples read after starting continuous
+            /// measurements or resetting the sensor.
+            pub fn with_warm_up(mut self, policy: WarmUpPolicy) -> Self {
+                self.options.warm_up = Some(policy);
+                self
+            }
+
+            /// Returns whether the next measurement read falls into the configured warm-up period.
+            /// Always `false` if no warm-up policy is configured.
+            pub fn is_warming_up(&self) -> bool {
+                self.options.warm_up.is_some_and(|policy| {
+                    self.sequence.samples_read < policy.period.samples(self.settings.interval)
+                })
+            }
+
+            /// Enables the data-ready watchdog. Once continuous measurements are triggered,
+            /// [is_data_ready](Self::is_data_ready) returns
+            /// [MeasurementOverdue](crate::error::Scd30Error::MeasurementOverdue) if no sample became
+            /// ready within the configured multiple of the measurement interval.
+            pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+                self.options.watchdog = Some(watchdog);
+                self
             }
 
             /// Start continuous measurements.
@@ -58,17 +393,60 @@ pub mod module {
                 &mut self,
                 pressure_compensation: Option<AmbientPressureCompensation>,
             ) -> Result<(), Scd30Error<I2cErr>> {
-                let data = match pressure_compensation {
-                    None => [0x0, 0x0],
-                    Some(pres) => pres.to_be_bytes(),
-                };
-                self.write(Command::TriggerContinuousMeasurement, Some(&data))
-                    .await
+                match pressure_compensation {
+                    None | Some(AmbientPressureCompensation::DefaultPressure) => {
+                        self.send(
+                            Command::TriggerContinuousMeasurement,
+                            &TRIGGER_WITH_DEFAULT_PRESSURE,
+                        )
+                        .await?
+                    }
+                    Some(pres) => {
+                        self.write(
+                            Command::TriggerContinuousMeasurement,
+                            Some(&pres.to_be_bytes()),
+                        )
+                        .await?
+                    }
+                }
+                self.sequence.samples_read = 0;
+                self.sequence.last_read = None;
+                self.mark_sample();
+                self.sequence.ready_since = self.now();
+                let pressure_compensation =
+                    pressure_compensation.unwrap_or(AmbientPressureCompensation::DefaultPressure);
+                self.settings.config.continuous_measurement = Some(pressure_compensation);
+                self.update_cache(|cache| {
+                    cache.continuous_measurement = Some(pressure_compensation)
+                });
+                self.sequence.measuring = MeasuringState::Measuring;
+                Ok(())
+            }
+
+            /// Reads out the ambient pressure compensation in effect, as set when starting
+            /// continuous measurements.
+            pub async fn get_ambient_pressure(
+                &mut self,
+            ) -> Result<AmbientPressureCompensation, Scd30Error<I2cErr>> {
+                if let Some(pressure) = self
+                    .settings
+                    .cache
+                    .and_then(|cache| cache.continuous_measurement)
+                {
+                    return Ok(pressure);
+                }
+                let pressure = self.read_response().await?;
+                self.update_cache(|cache| cache.continuous_measurement = Some(pressure));
+                Ok(pressure)
             }
 
             /// Stop continuous measurements.
             pub async fn stop_continuous_measurements(&mut self) -> Result<(), Scd30Error<I2cErr>> {
-                self.write(Command::StopContinuousMeasurement, None).await
+                self.write(Command::StopContinuousMeasurement, None).await?;
+                self.sequence.last_sample = None;
+                self.settings.config.continuous_measurement = None;
+                self.sequence.measuring = MeasuringState::Stopped;
+                Ok(())
             }
 
             /// Configures the measurement interval in seconds, ranging from to 2s to 1800s.
@@ -80,67 +458,320 @@ pub mod module {
                     Command::SetMeasurementInterval,
                     Some(&interval.to_be_bytes()),
                 )
-                .await
+                .await?;
+                self.settings.interval = interval.seconds();
+                self.settings.config.measurement_interval = Some(interval);
+                self.update_cache(|cache| cache.measurement_interval = Some(interval));
+                Ok(())
             }
 
             /// Reads out the configured continuous measurement interval
             pub async fn get_measurement_interval(
                 &mut self,
             ) -> Result<MeasurementInterval, Scd30Error<I2cErr>> {
-                let receive = self.read::<3>(Command::SetMeasurementInterval).await?;
-                Ok(MeasurementInterval::try_from(&receive[..])?)
+                if let Some(interval) = self
+                    .settings
+                    .cache
+                    .and_then(|cache| cache.measurement_interval)
+                {
+                    return Ok(interval);
+                }
+                let interval: MeasurementInterval = self.read_response().await?;
+                self.settings.interval = interval.seconds();
+                self.update_cache(|cache| cache.measurement_interval = Some(interval));
+                Ok(interval)
             }
 
             /// Checks whether a measurement is ready for readout.
+            ///
+            /// With the watchdog enabled, this returns
+            /// [MeasurementOverdue](crate::error::Scd30Error::MeasurementOverdue) if the sensor
+            /// missed its interval by more than the configured factor.
             pub async fn is_data_ready(&mut self) -> Result<DataStatus, Scd30Error<I2cErr>> {
-                let receive = self.read::<3>(Command::GetDataReady).await?;
-                Ok(DataStatus::try_from(&receive[..])?)
+                let status = self.read_verified_response().await?;
+                match status {
+                    DataStatus::Ready => {
+                        self.health.samples.data_ready =
+                            self.health.samples.data_ready.saturating_add(1);
+                        self.mark_sample();
+                        if let (Some(since), Some(now)) =
+                            (self.sequence.ready_since.take(), self.now())
+                        {
+                            self.health.data_ready_latency_ms = Some(now.saturating_sub(since));
+                        }
+                    }
+                    DataStatus::NotReady | DataStatus::Unknown(_) => self.check_overdue()?,
+                }
+                Ok(status)
             }
 
             /// Reads out a [Measurement](crate::data::Measurement) from the sensor.
+            ///
+            /// If a warm-up policy discarding samples is configured, samples read during the
+            /// warm-up result in [WarmingUp](crate::error::Scd30Error::WarmingUp). Fails with
+            /// [NotMeasuring](crate::error::Scd30Error::NotMeasuring) after continuous measurements
+            /// were stopped.
             pub async fn read_measurement(&mut self) -> Result<Measurement, Scd30Error<I2cErr>> {
-                let receive = self.read::<18>(Command::ReadMeasurement).await?;
-                Ok(Measurement::try_from(&receive[..])?)
+                self.ensure_measuring()?;
+                let measurement = self.read_verified_response().await?;
+                self.count_sample()?;
+                Ok(measurement)
+            }
+
+            /// Reads out a [Measurement](crate::data::Measurement) together with [SampleQuality]
+            /// flags and plausibility warnings, so downstream pipelines can weigh the sample. The
+            /// read is verified like by [read_measurement](Self::read_measurement), see
+            /// [with_read_verification](Self::with_read_verification). Samples read again after
+            /// failing the CRC check are flagged with [RETRIED_CRC](SampleQuality::RETRIED_CRC).
+            ///
+            /// Like [read_measurement](Self::read_measurement), samples discarded by the warm-up
+            /// policy result in [WarmingUp](crate::error::Scd30Error::WarmingUp) and reads after
+            /// stopping continuous measurements in
+            /// [NotMeasuring](crate::error::Scd30Error::NotMeasuring).
+            pub async fn read_qualified_measurement(
+                &mut self,
+            ) -> Result<QualifiedMeasurement, Scd30Error<I2cErr>> {
+                self.ensure_measuring()?;
+                let (measurement, retried): (Measurement, _) =
+                    self.read_verified_response_with_retry().await?;
+                let mut quality = if retried {
+                    SampleQuality::RETRIED_CRC
+                } else {
+                    SampleQuality::NONE
+                };
+                quality |= self.count_sample()?;
+                let warnings = measurement.validate();
+                if !warnings.is_empty() {
+                    quality |= SampleQuality::OUT_OF_SPEC;
+                }
+                Ok(QualifiedMeasurement {
+                    measurement,
+                    quality,
+                    warnings,
+                })
+            }
+
+            /// Reads out a [Measurement](crate::data::Measurement) from the sensor using `buf` as
+            /// receive buffer. This allows statically placed or DMA capable buffers to be used
+            /// instead of a buffer on the stack. The read is verified like by
+            /// [read_measurement](Self::read_measurement), only
+            /// [DoubleRead](ReadVerification::DoubleRead) needs a second frame on the stack.
+            ///
+            /// After the call `buf` contains the received frame. With CRC validation disabled its
+            /// CRCs are replaced by matching ones.
+            pub async fn read_measurement_into(
+                &mut self,
+                buf: &mut [u8; 18],
+            ) -> Result<Measurement, Scd30Error<I2cErr>> {
+                self.ensure_measuring()?;
+                self.read_verified_into(Measurement::COMMAND, buf).await?;
+                self.skip_crc_validation(buf);
+                let measurement = Measurement::try_from(&buf[..])
+                    .map_err(|error| self.record_error(error.into()))?;
+                self.count_sample()?;
+                Ok(measurement)
+            }
+
+            /// Reads out the raw measurement frame, including its CRCs, into `buf`. The frame is
+            /// neither validated nor parsed and is not accounted for by the warm-up policy.
+            pub async fn read_measurement_raw(
+                &mut self,
+                buf: &mut [u8; 18],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.ensure_measuring()?;
+                self.read_into(Measurement::COMMAND, buf).await
+            }
+
+            /// Starts a measurement session: starts continuous measurements and returns a
+            /// [MeasurementSession] yielding the next `samples` samples as they become ready. The
+            /// measurements are stopped again after the last sample or the first error. The data
+            /// ready status is polled every 100 ms using `delay`. Samples discarded by the warm-up
+            /// policy are skipped.
+            pub async fn measure_n<'a, W: delay_trait>(
+                &'a mut self,
+                samples: usize,
+                delay: &'a mut W,
+                pressure_compensation: Option<AmbientPressureCompensation>,
+            ) -> Result<MeasurementSession<'a, I2C, D, W>, Scd30Error<I2cErr>> {
+                self.trigger_continuous_measurements(pressure_compensation)
+                    .await?;
+                let mut session = MeasurementSession {
+                    sensor: self,
+                    delay,
+                    remaining: samples,
+                    stop_error: None,
+                };
+                if samples == 0 {
+                    session.stop().await;
+                }
+                Ok(session)
+            }
+
+            // Waits for the next sample not discarded by the warm-up policy and reads it out.
+            async fn read_next_sample<W: delay_trait>(
+                &mut self,
+                delay: &mut W,
+            ) -> Result<Measurement, Scd30Error<I2cErr>> {
+                loop {
+                    self.wait_for_sample(delay, self.sample_timeout_ms())
+                        .await?;
+                    match self.read_measurement().await {
+                        Err(Scd30Error::WarmingUp) => {}
+                        result => return result,
+                    }
+                }
+            }
+
+            // Twice the measurement interval, after which a sample is considered missing.
+            pub(crate) fn sample_timeout_ms(&self) -> u32 {
+                u32::from(self.settings.interval) * 2000
+            }
+
+            // Polls the data ready status until a sample is ready or `timeout_ms` passed.
+            pub(crate) async fn wait_for_sample<W: delay_trait>(
+                &mut self,
+                delay: &mut W,
+                timeout_ms: u32,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                let mut waited_ms = 0;
+                while self.is_data_ready().await? != DataStatus::Ready {
+                    if waited_ms >= timeout_ms {
+                        log_warn!(waited_ms, "no sample became ready");
+                        return Err(self.record_error(Scd30Error::Timeout));
+                    }
+                    delay.delay_ms(DATA_READY_POLL_INTERVAL_MS).await;
+                    waited_ms = waited_ms.saturating_add(DATA_READY_POLL_INTERVAL_MS);
+                }
+                Ok(())
+            }
+
+            /// Suspends the sensor to save power between measurements: stops continuous
+            /// measurements and remembers the ambient pressure compensation they were started with,
+            /// so [resume](Self::resume) can restore it. Suspending an already suspended sensor
+            /// keeps the remembered compensation.
+            pub async fn suspend(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                let pressure_compensation = self
+                    .sequence
+                    .suspended
+                    .or(self.settings.config.continuous_measurement)
+                    .unwrap_or(AmbientPressureCompensation::DefaultPressure);
+                self.stop_continuous_measurements().await?;
+                log_info!("suspended");
+                self.sequence.suspended = Some(pressure_compensation);
+                Ok(())
+            }
+
+            /// Returns whether the sensor was suspended through [suspend](Self::suspend) and not
+            /// resumed yet.
+            pub fn is_suspended(&self) -> bool {
+                self.sequence.suspended.is_some()
+            }
+
+            /// Returns whether continuous measurements were started or stopped through the
+            /// interface.
+            pub fn measuring_state(&self) -> MeasuringState {
+                self.sequence.measuring
+            }
+
+            /// Resumes a sensor suspended through [suspend](Self::suspend): restarts continuous
+            /// measurements with the remembered ambient pressure compensation and waits until the
+            /// first sample is ready for readout, polling the data ready status every 100 ms using
+            /// `delay`. If a warm-up policy discarding samples is configured, the warm-up samples
+            /// are read and dropped while waiting, so the next sample read is a valid one.
+            ///
+            /// A sensor which was not suspended is started with the default ambient pressure.
+            /// Fails with [Timeout](crate::error::Scd30Error::Timeout) if no sample became ready
+            /// within twice the measurement interval.
+            pub async fn resume<W: delay_trait>(
+                &mut self,
+                delay: &mut W,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                let pressure_compensation = self.sequence.suspended;
+                self.trigger_continuous_measurements(pressure_compensation)
+                    .await?;
+                self.sequence.suspended = None;
+                log_info!("resumed");
+                loop {
+                    self.wait_for_sample(delay, self.sample_timeout_ms())
+                        .await?;
+                    let discarding = matches!(
+                        self.options.warm_up,
+                        Some(WarmUpPolicy {
+                            action: WarmUpAction::Discard,
+                            ..
+                        })
+                    );
+                    if !(discarding && self.is_warming_up()) {
+                        return Ok(());
+                    }
+                    match self.read_measurement().await {
+                        Ok(_) | Err(Scd30Error::WarmingUp) => {}
+                        Err(error) => return Err(error),
+                    }
+                }
             }
 
             /// Activates or deactivates automatic self-calibration.
+            ///
+            /// # Errors
+            ///
+            /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if `setting` is
+            ///   [Unknown](AutomaticSelfCalibration::Unknown), nothing is written to the sensor.
             pub async fn set_automatic_self_calibration(
                 &mut self,
                 setting: AutomaticSelfCalibration,
             ) -> Result<(), Scd30Error<I2cErr>> {
-                self.write(
-                    Command::ActivateAutomaticSelfCalibration,
-                    Some(&setting.to_be_bytes()),
-                )
-                .await
+                let frame = if setting.is_active()? {
+                    ACTIVATE_ASC
+                } else {
+                    DEACTIVATE_ASC
+                };
+                self.send(Command::ActivateAutomaticSelfCalibration, &frame)
+                    .await?;
+                self.settings.config.automatic_self_calibration = Some(setting);
+                self.update_cache(|cache| cache.automatic_self_calibration = Some(setting));
+                self.emit(Scd30Event::AutomaticSelfCalibration(setting));
+                Ok(())
             }
 
             /// Reads out the current state of the automatic self-calibration.
             pub async fn get_automatic_self_calibration(
                 &mut self,
             ) -> Result<AutomaticSelfCalibration, Scd30Error<I2cErr>> {
-                let receive = self
-                    .read::<3>(Command::ActivateAutomaticSelfCalibration)
-                    .await?;
-                Ok(AutomaticSelfCalibration::try_from(&receive[..])?)
+                if let Some(setting) = self
+                    .settings
+                    .cache
+                    .and_then(|cache| cache.automatic_self_calibration)
+                {
+                    return Ok(setting);
+                }
+                let setting = self.read_response().await?;
+                self.update_cache(|cache| cache.automatic_self_calibration = Some(setting));
+                Ok(setting)
             }
 
             /// Configures the forced re-calibration (FRC) value to compensate for sensor drift. The value
-            /// can range from 400 ppm to 2000 ppm.
+            /// can range from 400 ppm to 2000 ppm. Fails with
+            /// [NotMeasuring](crate::error::Scd30Error::NotMeasuring) after continuous measurements
+            /// were stopped, as the sensor needs to measure the reference concentration.
             pub async fn set_forced_recalibration(
                 &mut self,
                 frc: ForcedRecalibrationValue,
             ) -> Result<(), Scd30Error<I2cErr>> {
+                self.ensure_measuring()?;
                 self.write(Command::ForcedRecalibrationValue, Some(&frc.to_be_bytes()))
-                    .await
+                    .await?;
+                self.emit(Scd30Event::ForcedRecalibration {
+                    reference_ppm: u16::from_be_bytes(frc.to_be_bytes()),
+                });
+                Ok(())
             }
 
             /// Reads out the configured value of the forced re-calibration (FRC) value.
             pub async fn get_forced_recalibration(
                 &mut self,
             ) -> Result<ForcedRecalibrationValue, Scd30Error<I2cErr>> {
-                let receive = self.read::<3>(Command::ForcedRecalibrationValue).await?;
-                Ok(ForcedRecalibrationValue::try_from(&receive[..])?)
+                self.read_response().await
             }
 
             /// Configures the temperature offset to compensate for self-heating electric components. The
@@ -150,19 +781,34 @@ pub mod module {
                 offset: TemperatureOffset,
             ) -> Result<(), Scd30Error<I2cErr>> {
                 self.write(Command::SetTemperatureOffset, Some(&offset.to_be_bytes()))
-                    .await
+                    .await?;
+                self.settings.config.temperature_offset = Some(offset);
+                self.update_cache(|cache| cache.temperature_offset = Some(offset));
+                Ok(())
             }
 
             /// Reads out the configured temperature offset.
             pub async fn get_temperature_offset(
                 &mut self,
             ) -> Result<TemperatureOffset, Scd30Error<I2cErr>> {
-                let receive = self.read::<3>(Command::SetTemperatureOffset).await?;
-                Ok(TemperatureOffset::try_from(&receive[..])?)
+                if let Some(offset) = self
+                    .settings
+                    .cache
+                    .and_then(|cache| cache.temperature_offset)
+                {
+                    return Ok(offset);
+                }
+                let offset = self.read_response().await?;
+                self.update_cache(|cache| cache.temperature_offset = Some(offset));
+                Ok(offset)
             }
 
             /// Configures the altitude compensation. The value can range from 0 m to 65535 m above sea
             /// level.
+            ///
+            /// An ambient pressure passed to
+            /// [trigger_continuous_measurements](Self::trigger_continuous_measurements) overrides
+            /// the altitude compensation, see [set_compensation](Self::set_compensation).
             pub async fn set_altitude_compensation(
                 &mut self,
                 altitude: AltitudeCompensation,
@@ -171,380 +817,2706 @@ pub mod module {
                     Command::SetAltitudeCompensation,
                     Some(&altitude.to_be_bytes()),
                 )
-                .await
+                .await?;
+                self.settings.config.altitude_compensation = Some(altitude);
+                self.update_cache(|cache| cache.altitude_compensation = Some(altitude));
+                Ok(())
+            }
+
+            /// Configures the compensation for the air density, replacing the previous one, as the
+            /// sensor only applies either an altitude or an ambient pressure compensation.
+            ///
+            /// - [Altitude](CompensationMode::Altitude) sets the altitude and, if continuous
+            ///   measurements run with an ambient pressure compensation, restarts them with the
+            ///   default pressure to deactivate it.
+            /// - [AmbientPressure](CompensationMode::AmbientPressure) (re-)starts continuous
+            ///   measurements with the given pressure, as the pressure can only be set along with
+            ///   starting measurements.
+            pub async fn set_compensation(
+                &mut self,
+                mode: CompensationMode,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                match mode {
+                    CompensationMode::Altitude(altitude) => {
+                        self.set_altitude_compensation(altitude).await?;
+                        if matches!(
+                            self.settings.config.continuous_measurement,
+                            Some(AmbientPressureCompensation::CompensationPressure(_))
+                        ) {
+                            self.trigger_continuous_measurements(Some(
+                                AmbientPressureCompensation::DefaultPressure,
+                            ))
+                            .await?;
+                        }
+                        Ok(())
+                    }
+                    CompensationMode::AmbientPressure(pressure) => {
+                        self.trigger_continuous_measurements(Some(
+                            AmbientPressureCompensation::CompensationPressure(pressure),
+                        ))
+                        .await
+                    }
+                }
+            }
+
+            /// Selects the best compensation from a barometer reading `pressure_mbar` and an
+            /// altitude `altitude_m` above sea level, whichever the application has, and applies
+            /// it with [set_compensation](Self::set_compensation). Without usable inputs, the
+            /// altitude is reset to sea level and a pressure compensation deactivated. See
+            /// [CompensationMode::select] for the precedence rules. Returns the selected
+            /// compensation, `None` if the defaults were applied.
+            pub async fn select_compensation(
+                &mut self,
+                pressure_mbar: Option<f32>,
+                altitude_m: Option<f32>,
+            ) -> Result<Option<CompensationMode>, Scd30Error<I2cErr>> {
+                let mode = CompensationMode::select(pressure_mbar, altitude_m);
+                log_debug!(?mode, "selected compensation");
+                self.set_compensation(
+                    mode.unwrap_or(CompensationMode::Altitude(AltitudeCompensation::from(0))),
+                )
+                .await?;
+                Ok(mode)
+            }
+
+            /// Returns the compensation in effect according to the settings applied through this
+            /// interface, `None` if neither altitude nor ambient pressure were set.
+            pub fn compensation(&self) -> Option<CompensationMode> {
+                CompensationMode::from_settings(
+                    self.settings.config.altitude_compensation,
+                    self.settings.config.continuous_measurement,
+                )
             }
 
             /// Reads out the configured altitude compensation.
             pub async fn get_altitude_compensation(
                 &mut self,
             ) -> Result<AltitudeCompensation, Scd30Error<I2cErr>> {
-                let receive = self.read::<3>(Command::SetAltitudeCompensation).await?;
-                Ok(AltitudeCompensation::try_from(&receive[..])?)
+                if let Some(altitude) = self
+                    .settings
+                    .cache
+                    .and_then(|cache| cache.altitude_compensation)
+                {
+                    return Ok(altitude);
+                }
+                let altitude = self.read_response().await?;
+                self.update_cache(|cache| cache.altitude_compensation = Some(altitude));
+                Ok(altitude)
             }
 
-            /// Reads out the version of the firmware deployed on the sensor.
+            /// Reads out the version of the firmware deployed on the sensor. The version is kept
+            /// and returned by [firmware](Self::firmware) afterwards.
             pub async fn read_firmware_version(
                 &mut self,
             ) -> Result<FirmwareVersion, Scd30Error<I2cErr>> {
-                let receive = self.read::<3>(Command::ReadFirmwareVersion).await?;
-                Ok(FirmwareVersion::try_from(&receive[..])?)
+                let version = self.read_response().await?;
+                self.settings.firmware = Some(version);
+                Ok(version)
+            }
+
+            /// Initializes the interface by reading the firmware version once, so it is available
+            /// through [firmware](Self::firmware) without further bus traffic.
+            pub async fn init(&mut self) -> Result<FirmwareVersion, Scd30Error<I2cErr>> {
+                let version = self.read_firmware_version().await?;
+                log_info!(?version, "initialized");
+                Ok(version)
+            }
+
+            /// Returns the firmware version read by [init](Self::init) or
+            /// [read_firmware_version](Self::read_firmware_version), `None` if it was not read yet.
+            pub fn firmware(&self) -> Option<FirmwareVersion> {
+                self.settings.firmware
+            }
+
+            async fn cached_firmware_version(
+                &mut self,
+            ) -> Result<FirmwareVersion, Scd30Error<I2cErr>> {
+                match self.settings.firmware {
+                    Some(version) => Ok(version),
+                    None => self.read_firmware_version().await,
+                }
             }
 
             /// Executes a soft reset of the sensor.
             pub async fn soft_reset(&mut self) -> Result<(), Scd30Error<I2cErr>> {
-                self.write(Command::SoftReset, None).await
+                log_info!("soft reset");
+                self.write(Command::SoftReset, None).await?;
+                self.sequence.last_reset = Some(ResetCause::SoftReset);
+                self.emit(Scd30Event::Reset(ResetCause::SoftReset));
+                self.restart_tracking();
+                Ok(())
+            }
+
+            /// Sends an I2C general call reset, the recovery suggested by
+            /// [RecoveryAction::GeneralCallReset](crate::error::RecoveryAction::GeneralCallReset).
+            /// Note that all devices on the bus supporting the general call are reset.
+            pub async fn general_call_reset(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                log_info!("general call reset");
+                self.health.metrics.record(None, 1, 0);
+                self.bus_write(GENERAL_CALL_ADDRESS, &[GENERAL_CALL_RESET])
+                    .await
+                    .inspect_err(|_error| {
+                        log_warn!(error = ?_error, "general call reset failed");
+                    })?;
+                self.sequence.last_reset = Some(ResetCause::GeneralCallReset);
+                let now = self.now();
+                if let Some(guard) = &mut self.sequence.guard {
+                    guard.record_reset(now);
+                }
+                self.emit(Scd30Event::Reset(ResetCause::GeneralCallReset));
+                self.restart_tracking();
+                Ok(())
             }
 
-            async fn read<const DATA_SIZE: usize>(
+            /// Executes a soft reset, waits for the sensor to boot and re-applies `config`. If no
+            /// configuration is supplied, the settings applied through this interface before the
+            /// reset are restored, see [config](Self::config).
+            pub async fn soft_reset_and_reinit<W: delay_trait>(
                 &mut self,
-                command: Command,
-            ) -> Result<[u8; DATA_SIZE], Scd30Error<I2cErr>> {
-                self.write(command, None).await?;
-                let mut data = [0; DATA_SIZE];
-                self.i2c.read(ADDRESS | READ_FLAG, &mut data).await?;
-                Ok(data)
+                delay: &mut W,
+                config: Option<&Scd30Config>,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                let config = config.copied().unwrap_or(self.settings.config);
+                self.soft_reset().await?;
+                delay.delay_ms(RESET_DELAY_MS).await;
+                log_info!(?config, "restoring configuration");
+                self.apply_config(&config).await
             }
 
-            async fn write(
+            /// Applies all settings of `config` which are not `None`. Continuous measurements are
+            /// started last, after all other settings are in place.
+            pub async fn apply_config(
                 &mut self,
-                command: Command,
-                data: Option<&[u8]>,
+                config: &Scd30Config,
             ) -> Result<(), Scd30Error<I2cErr>> {
-                let mut sent = [command.to_be_bytes()[0], command.to_be_bytes()[1], 0, 0, 0];
+                if let Some(interval) = config.measurement_interval {
+                    self.set_measurement_interval(interval).await?;
+                }
+                if let Some(setting) = config.automatic_self_calibration {
+                    self.set_automatic_self_calibration(setting).await?;
+                }
+                if let Some(offset) = config.temperature_offset {
+                    self.set_temperature_offset(offset).await?;
+                }
+                if let Some(altitude) = config.altitude_compensation {
+                    self.set_altitude_compensation(altitude).await?;
+                }
+                if let Some(pressure_compensation) = config.continuous_measurement {
+                    self.trigger_continuous_measurements(Some(pressure_compensation))
+                        .await?;
+                }
+                Ok(())
+            }
+
+            /// Applies the settings of `preset` and starts continuous measurements, see [Preset].
+            pub async fn apply_preset(&mut self, preset: Preset) -> Result<(), Scd30Error<I2cErr>> {
+                log_info!(?preset, "applying preset");
+                self.apply_config(&preset.config()).await
+            }
 
-                let len = if let Some(data) = data {
-                    if data.len() != 2 {
-                        return Err(Scd30Error::SentDataToBig);
+            /// Writes all changes recorded in `transaction` in the order required by the sensor,
+            /// waiting [COMMAND_GAP_US](crate::transaction::COMMAND_GAP_US) between the commands.
+            /// Stops at the first failing command, leaving the remaining changes unwritten.
+            pub async fn commit<W: delay_trait>(
+                &mut self,
+                transaction: &ConfigTransaction,
+                delay: &mut W,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                for (index, step) in transaction.steps().enumerate() {
+                    if index > 0 {
+                        delay.delay_us(COMMAND_GAP_US).await;
                     }
-                    sent[2] = data[0];
-                    sent[3] = data[1];
-                    sent[4] = compute_crc8(data);
-                    5
-                } else {
-                    2
-                };
-                Ok(self.i2c.write(ADDRESS | WRITE_FLAG, &sent[..len]).await?)
+                    log_debug!(?step, "committing configuration change");
+                    match step {
+                        ConfigStep::MeasurementInterval(interval) => {
+                            self.set_measurement_interval(interval).await
+                        }
+                        ConfigStep::AutomaticSelfCalibration(setting) => {
+                            self.set_automatic_self_calibration(setting).await
+                        }
+                        ConfigStep::TemperatureOffset(offset) => {
+                            self.set_temperature_offset(offset).await
+                        }
+                        ConfigStep::AltitudeCompensation(altitude) => {
+                            self.set_altitude_compensation(altitude).await
+                        }
+                        ConfigStep::ForcedRecalibration(frc) => {
+                            self.set_forced_recalibration(frc).await
+                        }
+                        ConfigStep::ContinuousMeasurement(compensation) => {
+                            self.trigger_continuous_measurements(Some(compensation))
+                                .await
+                        }
+                    }?;
+                }
+                Ok(())
             }
 
-            /// Consumes the sensor and returns the contained I2C peripheral.
-            #[cfg(not(tarpaulin_include))]
-            pub fn shutdown(self) -> I2C {
-                self.i2c
+            /// Stops continuous measurements, writes the changes recorded by `changes` like
+            /// [commit](Self::commit) and restarts the measurements, so the settings do not change
+            /// while a sample is taken. Measurements are restarted with the pressure compensation
+            /// recorded by `changes`, or with the previous one if they were started through this
+            /// interface. Nothing is written if `changes` records no change.
+            ///
+            /// A forced recalibration is rejected with
+            /// [RecalibrationBeforeSoak](crate::error::SequenceViolation::RecalibrationBeforeSoak)
+            /// without writing anything, as the restarted measurements have not measured the
+            /// reference yet. Use [set_forced_recalibration](Self::set_forced_recalibration) once
+            /// the sensor has measured for
+            /// [CALIBRATION_SOAK_MS](crate::sequencing::CALIBRATION_SOAK_MS) instead.
+            ///
+            /// ```ignore
+            /// sensor.reconfigure(&mut delay, |changes| {
+            ///     changes
+            ///         .measurement_interval(MeasurementInterval::try_from(30)?)
+            ///         .altitude_compensation(AltitudeCompensation::from(450))
+            /// })?;
+            /// ```
+            pub async fn reconfigure<W: delay_trait>(
+                &mut self,
+                delay: &mut W,
+                changes: impl FnOnce(ConfigTransaction) -> ConfigTransaction,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                let mut transaction = changes(ConfigTransaction::new());
+                if transaction.is_empty() {
+                    return Ok(());
+                }
+                if transaction.recorded_forced_recalibration().is_some() {
+                    log_warn!("forced recalibration rejected while reconfiguring");
+                    return Err(Scd30Error::Sequence(
+                        SequenceViolation::RecalibrationBeforeSoak {
+                            remaining_ms: CALIBRATION_SOAK_MS,
+                        },
+                    ));
+                }
+                if let (None, Some(previous)) = (
+                    transaction.config().continuous_measurement,
+                    self.settings.config.continuous_measurement,
+                ) {
+                    transaction = transaction.start_continuous_measurements(previous);
+                }
+                log_info!(?transaction, "reconfiguring sensor");
+                self.stop_continuous_measurements().await?;
+                delay.delay_us(COMMAND_GAP_US).await;
+                self.commit(&transaction, delay).await
             }
-        }
 
-        #[cfg(test)]
-        mod tests {
-            use super::*;
-            use crate::data::AmbientPressure;
-            use embedded_hal::i2c;
-            use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+            fn emit(&self, event: Scd30Event) {
+                if let Some(hook) = self.options.on_event {
+                    hook(event);
+                }
+            }
 
-            #[test_macro]
-            async fn trigger_continuous_measurements_with_ambient_pressure_compensation() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x00, 0x10, 0x03, 0x20, 0x2A],
-                )];
+            fn restart_tracking(&mut self) {
+                self.sequence.samples_read = 0;
+                self.sequence.last_read = None;
+                self.sequence.ready_since = self.now();
+                if self.sequence.last_sample.is_some() {
+                    self.mark_sample();
+                }
+            }
 
-                let i2c = I2cMock::new(&expected_transactions);
+            fn mark_sample(&mut self) {
+                if let Some(watchdog) = self.options.watchdog {
+                    self.sequence.last_sample = Some((watchdog.clock)());
+                }
+            }
 
-                let mut sensor = Scd30::new(i2c);
+            fn check_overdue(&self) -> Result<(), Scd30Error<I2cErr>> {
+                if let (Some(watchdog), Some(last_sample)) =
+                    (self.options.watchdog, self.sequence.last_sample)
+                {
+                    let elapsed_ms = (watchdog.clock)().saturating_sub(last_sample);
+                    if elapsed_ms > watchdog.deadline_ms(self.settings.interval) {
+                        log_warn!(elapsed_ms, "measurement overdue");
+                        return Err(Scd30Error::MeasurementOverdue { elapsed_ms });
+                    }
+                }
+                Ok(())
+            }
 
-                sensor
-                    .trigger_continuous_measurements(Some(
-                        AmbientPressureCompensation::CompensationPressure(
-                            AmbientPressure::try_from(800).unwrap(),
-                        ),
-                    ))
-                    .await
-                    .unwrap();
-                sensor.shutdown().done();
+            // Returns the current time of the configured clock, if any.
+            pub(crate) fn now(&self) -> Option<u64> {
+                self.options
+                    .clock
+                    .or(self.options.watchdog.map(|watchdog| watchdog.clock))
+                    .map(|clock| clock())
             }
 
-            #[test_macro]
-            async fn trigger_continuous_measurements_spec_example_with_none() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x00, 0x10, 0x00, 0x00, 0x81],
-                )];
+            fn check_timeout(&mut self, started: Option<u64>) -> Result<(), Scd30Error<I2cErr>> {
+                if let (Some(timeout_ms), Some(started), Some(now)) =
+                    (self.options.timeout_ms, started, self.now())
+                {
+                    if now.saturating_sub(started) > timeout_ms {
+                        log_warn!(
+                            elapsed_ms = now.saturating_sub(started),
+                            "operation timed out"
+                        );
+                        return Err(self.record_error(Scd30Error::Timeout));
+                    }
+                }
+                Ok(())
+            }
 
-                let i2c = I2cMock::new(&expected_transactions);
+            fn update_cache(&mut self, update: impl FnOnce(&mut Scd30Config)) {
+                if let Some(cache) = &mut self.settings.cache {
+                    update(cache);
+                }
+            }
 
-                let mut sensor = Scd30::new(i2c);
+            fn ensure_measuring(&self) -> Result<(), Scd30Error<I2cErr>> {
+                if self.sequence.measuring == MeasuringState::Stopped {
+                    log_warn!("continuous measurements are stopped");
+                    return Err(Scd30Error::NotMeasuring);
+                }
+                Ok(())
+            }
 
-                sensor.trigger_continuous_measurements(None).await.unwrap();
-                sensor.shutdown().done();
+            fn record_error(&mut self, error: Scd30Error<I2cErr>) -> Scd30Error<I2cErr> {
+                match &error {
+                    Scd30Error::Timeout => {
+                        self.health.errors.timeout = self.health.errors.timeout.saturating_add(1)
+                    }
+                    Scd30Error::I2cError(_) | Scd30Error::CommandFailed { .. } => {
+                        self.health.errors.i2c = self.health.errors.i2c.saturating_add(1)
+                    }
+                    Scd30Error::DataError(DataError::CrcFailed) => {
+                        self.health.errors.crc = self.health.errors.crc.saturating_add(1)
+                    }
+                    Scd30Error::DataError(_) => {
+                        self.health.errors.data = self.health.errors.data.saturating_add(1)
+                    }
+                    _ => {}
+                }
+                error
             }
 
-            #[test_macro]
-            async fn trigger_continuous_measurements_spec_example() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x00, 0x10, 0x00, 0x00, 0x81],
-                )];
+            fn count_sample(&mut self) -> Result<SampleQuality, Scd30Error<I2cErr>> {
+                self.sequence.ready_since = self.now();
+                let elapsed_ms = self
+                    .sequence
+                    .last_read
+                    .zip(self.sequence.ready_since)
+                    .map(|(last_read, now)| now.saturating_sub(last_read));
+                self.sequence.last_read = self.sequence.ready_since;
+                let missed = self
+                    .health
+                    .samples
+                    .record_read(elapsed_ms, self.settings.interval);
+                if missed > 0 {
+                    log_warn!(missed, "samples missed");
+                    self.emit(Scd30Event::SampleGap { missed });
+                }
+                let warming_up = self.is_warming_up();
+                self.sequence.samples_read = self.sequence.samples_read.saturating_add(1);
+                match self.options.warm_up {
+                    Some(WarmUpPolicy {
+                        action: WarmUpAction::Discard,
+                        ..
+                    }) if warming_up => {
+                        log_debug!(
+                            samples_read = self.sequence.samples_read,
+                            "discarding warm-up sample"
+                        );
+                        Err(Scd30Error::WarmingUp)
+                    }
+                    _ => {
+                        let mut quality = SampleQuality::NONE;
+                        if warming_up {
+                            quality |= SampleQuality::WARM_UP;
+                        }
+                        if missed > 0 {
+                            quality |= SampleQuality::OVERDUE;
+                        }
+                        Ok(quality)
+                    }
+                }
+            }
 
-                let i2c = I2cMock::new(&expected_transactions);
+            // The parsers always check the CRCs, so with validation disabled the received CRCs are
+            // replaced by matching ones instead.
+            fn skip_crc_validation(&self, data: &mut [u8]) {
+                if self.options.crc_validation == CrcValidation::Disabled {
+                    replace_crcs(data);
+                }
+            }
 
-                let mut sensor = Scd30::new(i2c);
+            async fn read_response<R: Response>(&mut self) -> Result<R, Scd30Error<I2cErr>> {
+                let mut data = R::Buffer::default();
+                self.read_into(R::COMMAND, data.as_mut()).await?;
+                self.decode_response(data).await
+            }
 
-                sensor
-                    .trigger_continuous_measurements(Some(
-                        AmbientPressureCompensation::DefaultPressure,
-                    ))
-                    .await
-                    .unwrap();
-                sensor.shutdown().done();
+            // Reads a response according to the configured read verification.
+            async fn read_verified_response<R: Response>(
+                &mut self,
+            ) -> Result<R, Scd30Error<I2cErr>> {
+                let (response, _) = self.read_verified_response_with_retry().await?;
+                Ok(response)
             }
 
-            #[test_macro]
-            async fn stop_continuous_measurements_spec_example() {
-                let expected_transactions = [I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04])];
+            // Like `read_verified_response`, additionally returning whether the response was read
+            // again after failing the CRC check.
+            async fn read_verified_response_with_retry<R: Response>(
+                &mut self,
+            ) -> Result<(R, bool), Scd30Error<I2cErr>> {
+                let mut data = R::Buffer::default();
+                let retried = self.read_verified_into(R::COMMAND, data.as_mut()).await?;
+                let response = self.decode_response(data).await?;
+                Ok((response, retried))
+            }
 
-                let i2c = I2cMock::new(&expected_transactions);
+            // Reads the response of `command` into `buf` according to the configured read
+            // verification. Returns whether the response was read again after failing the CRC
+            // check.
+            async fn read_verified_into(
+                &mut self,
+                command: Command,
+                buf: &mut [u8],
+            ) -> Result<bool, Scd30Error<I2cErr>> {
+                self.read_into(command, buf).await?;
+                match self.options.verification {
+                    ReadVerification::Disabled => Ok(false),
+                    ReadVerification::RetryOnCrc => {
+                        if self.options.crc_validation == CrcValidation::Enabled
+                            && validate_frame(buf).is_err()
+                        {
+                            log_debug!(?command, "retrying read after CRC failure");
+                            self.health.errors.crc = self.health.errors.crc.saturating_add(1);
+                            self.read_into(command, buf).await?;
+                            return Ok(true);
+                        }
+                        Ok(false)
+                    }
+                    ReadVerification::DoubleRead => {
+                        let mut again = [0; MAX_RESPONSE_LEN];
+                        let again = again.get_mut(..buf.len()).ok_or_else(|| {
+                            self.record_error(DataError::ReceivedBufferWrongSize.into())
+                        })?;
+                        self.read_into(command, again).await?;
+                        if again != buf {
+                            log_warn!(?command, "repeated reads differ");
+                            return Err(self.record_error(DataError::InconsistentReads.into()));
+                        }
+                        Ok(false)
+                    }
+                }
+            }
 
-                let mut sensor = Scd30::new(i2c);
+            // Parses a received response, handling values outside the documented range according
+            // to the configured value decoding.
+            async fn decode_response<R: Response>(
+                &mut self,
+                mut data: R::Buffer,
+            ) -> Result<R, Scd30Error<I2cErr>> {
+                self.skip_crc_validation(data.as_mut());
+                let mut response = data.try_into();
+                if let Err(DataError::UnexpectedValueReceived { actual, .. }) = response {
+                    match self.options.value_decoding {
+                        ValueDecoding::Strict => {}
+                        ValueDecoding::RetryOnce => {
+                            log_debug!(command = ?R::COMMAND, actual, "retrying read after unexpected value");
+                            let mut again = R::Buffer::default();
+                            self.read_into(R::COMMAND, again.as_mut()).await?;
+                            self.skip_crc_validation(again.as_mut());
+                            response = again.try_into();
+                        }
+                        ValueDecoding::Lenient => {
+                            if let Some(unknown) = R::unknown(actual) {
+                                log_warn!(command = ?R::COMMAND, actual, "accepting unexpected value");
+                                response = Ok(unknown);
+                            }
+                        }
+                    }
+                }
+                response.map_err(|error| {
+                    log_warn!(command = ?R::COMMAND, ?error, "received invalid data");
+                    self.record_error(error.into())
+                })
+            }
+
+            async fn read_into(
+                &mut self,
+                command: Command,
+                buf: &mut [u8],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                let command_sent = self.now();
+                self.check_sequence(command, false)?;
+                self.write_frame(command, &command.to_be_bytes()).await?;
+                self.record_sequence(command, false);
+                log_trace!(?command, len = buf.len(), "reading response");
+                let mut retries = self.options.read_strategy.retries();
+                loop {
+                    if let Some(gap_us) = self.options.read_strategy.gap_us() {
+                        self.delay.delay_us(gap_us).await;
+                    }
+                    let started = self.now();
+                    self.health.metrics.record(Some(command), 0, buf.len());
+                    match bus_guard(self.options.timeout_ms, self.i2c.read(self.address, buf)).await
+                    {
+                        None => {
+                            log_warn!(?command, "reading response timed out");
+                            return Err(self.record_error(Scd30Error::Timeout));
+                        }
+                        Some(Ok(())) => {
+                            self.check_timeout(started)?;
+                            if let (Some(sent), Some(now)) = (command_sent, self.now()) {
+                                self.health
+                                    .timings
+                                    .record(command, now.saturating_sub(sent));
+                            }
+                            log_trace!(?command, data = ?buf, "received response");
+                            return Ok(());
+                        }
+                        Some(Err(error))
+                            if retries > 0
+                                && matches!(error.kind(), ErrorKind::NoAcknowledge(_)) =>
+                        {
+                            log_debug!(?command, retries, "sensor busy, retrying read");
+                            retries -= 1;
+                        }
+                        Some(Err(error)) => {
+                            log_warn!(?command, ?error, "reading response failed");
+                            return Err(self.record_error(Scd30Error::CommandFailed {
+                                command,
+                                phase: Phase::Read,
+                                error,
+                            }));
+                        }
+                    }
+                }
+            }
+
+            async fn write(
+                &mut self,
+                command: Command,
+                data: Option<&[u8]>,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                match data {
+                    None => self.send(command, &command.to_be_bytes()).await,
+                    Some(&[msb, lsb]) => {
+                        self.send(command, &command.encode_with_argument([msb, lsb]))
+                            .await
+                    }
+                    Some(_) => Err(Scd30Error::SentDataToBig),
+                }
+            }
+
+            // Writes a command changing the state of the sensor, checked against the sequencing
+            // rules in strict mode.
+            async fn send(
+                &mut self,
+                command: Command,
+                frame: &[u8],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.check_sequence(command, true)?;
+                self.write_frame(command, frame).await?;
+                self.record_sequence(command, true);
+                Ok(())
+            }
+
+            fn check_sequence(
+                &self,
+                command: Command,
+                configures: bool,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                if let Some(guard) = &self.sequence.guard {
+                    guard
+                        .check(command, configures, self.sequence.measuring, self.now())
+                        .inspect_err(|_violation| {
+                            log_warn!(?command, violation = ?_violation, "sequencing violated");
+                        })?;
+                }
+                Ok(())
+            }
+
+            fn record_sequence(&mut self, command: Command, configures: bool) {
+                let now = self.now();
+                if let Some(guard) = &mut self.sequence.guard {
+                    guard.record(command, configures, now);
+                }
+            }
+
+            async fn write_frame(
+                &mut self,
+                command: Command,
+                frame: &[u8],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                log_debug!(
+                    opcode = command.opcode(),
+                    argument = ?frame.get(2..).unwrap_or_default(),
+                    "sending command"
+                );
+                self.health.metrics.record(Some(command), frame.len(), 0);
+                let started = self.now();
+                self.bus_write(self.address, frame)
+                    .await
+                    .map_err(|error| match error {
+                        Scd30Error::I2cError(error) => Scd30Error::CommandFailed {
+                            command,
+                            phase: Phase::Write,
+                            error,
+                        },
+                        error => error,
+                    })
+                    .inspect_err(|_error| {
+                        log_warn!(error = ?_error, "sending command failed");
+                    })?;
+                self.check_timeout(started)
+            }
+
+            async fn bus_write(
+                &mut self,
+                address: u8,
+                bytes: &[u8],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                match bus_guard(self.options.timeout_ms, self.i2c.write(address, bytes)).await {
+                    Some(result) => result.map_err(|error| self.record_error(error.into())),
+                    None => Err(self.record_error(Scd30Error::Timeout)),
+                }
+            }
+
+            /// Consumes the sensor and returns the contained I2C peripheral.
+            #[cfg(not(tarpaulin_include))]
+            pub fn shutdown(self) -> I2C {
+                self.i2c
+            }
+
+            /// Consumes the sensor and returns the contained I2C peripheral and delay.
+            pub fn release(self) -> (I2C, D) {
+                (self.i2c, self.delay)
+            }
+
+            /// Consumes the sensor and returns the contained I2C peripheral and delay together with
+            /// a snapshot of its state. The interface can be reconstructed with
+            /// [from_parts](Self::from_parts), e.g. after a deep sleep.
+            pub fn into_parts(self) -> (I2C, D, Scd30State) {
+                let state = Scd30State {
+                    address: self.address,
+                    options: self.options,
+                    settings: self.settings,
+                    sequence: self.sequence,
+                    health: self.health,
+                };
+                (self.i2c, self.delay, state)
+            }
+
+            /// Reconstructs an interface from its parts returned by
+            /// [into_parts](Self::into_parts).
+            pub fn from_parts(i2c: I2C, delay: D, state: Scd30State) -> Self {
+                Self {
+                    i2c,
+                    address: state.address,
+                    delay,
+                    options: state.options,
+                    settings: state.settings,
+                    sequence: state.sequence,
+                    health: state.health,
+                }
+            }
+        }
+
+        /// Measurement session started by [measure_n](Scd30::measure_n), yielding the samples as
+        /// they become ready. With the blocking interface the session is an [Iterator].
+        ///
+        /// The measurements are stopped after the last sample. A session ended early must be
+        /// closed with [finish](Self::finish), otherwise the sensor keeps measuring.
+        pub struct MeasurementSession<'a, I2C: embedded_hal::i2c::ErrorType, D, W> {
+            sensor: &'a mut Scd30<I2C, D>,
+            delay: &'a mut W,
+            remaining: usize,
+            stop_error: Option<Scd30Error<I2C::Error>>,
+        }
+
+        impl<I2C: i2c_trait, D: delay_trait, W: delay_trait, I2cErr: embedded_hal::i2c::Error>
+            MeasurementSession<'_, I2C, D, W>
+        {
+            /// Waits for the next sample and reads it out. Returns `None` once all samples were
+            /// taken. The measurements are stopped after the last sample, an error stopping them is
+            /// returned by the following call. After an error reading a sample the measurements
+            /// are stopped as well and the session ends.
+            pub async fn next_sample(&mut self) -> Option<Result<Measurement, Scd30Error<I2cErr>>> {
+                if self.remaining == 0 {
+                    return self.stop_error.take().map(Err);
+                }
+                let sample = self.sensor.read_next_sample(self.delay).await;
+                self.remaining = match sample {
+                    Ok(_) => self.remaining - 1,
+                    Err(_) => 0,
+                };
+                if self.remaining == 0 {
+                    self.stop().await;
+                    if sample.is_err() {
+                        self.stop_error = None;
+                    }
+                }
+                Some(sample)
+            }
+
+            /// Returns the number of samples still to be taken.
+            pub fn remaining(&self) -> usize {
+                self.remaining
+            }
+
+            /// Ends the session, stopping the measurements if samples are still outstanding.
+            /// Returns the error of stopping the measurements, also if they were stopped after the
+            /// last sample.
+            pub async fn finish(mut self) -> Result<(), Scd30Error<I2cErr>> {
+                if self.remaining > 0 {
+                    self.remaining = 0;
+                    self.stop().await;
+                }
+                self.stop_error.take().map_or(Ok(()), Err)
+            }
+
+            async fn stop(&mut self) {
+                self.stop_error = self.sensor.stop_continuous_measurements().await.err();
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::{
+                data::AmbientPressure,
+                error::SequenceViolation,
+                health::Traffic,
+                options::{WarmUp, Watchdog, RECOMMENDED_READ_GAP_US},
+            };
+            use embedded_hal::i2c;
+            use embedded_hal_mock::eh1::{
+                delay::{CheckedDelay, NoopDelay, Transaction as DelayTransaction},
+                i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+            };
+
+            #[test_macro]
+            async fn trigger_continuous_measurements_with_ambient_pressure_compensation() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x00, 0x10, 0x03, 0x20, 0x2A],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor
+                    .trigger_continuous_measurements(Some(
+                        AmbientPressureCompensation::CompensationPressure(
+                            AmbientPressure::try_from(800).unwrap(),
+                        ),
+                    ))
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn trigger_continuous_measurements_spec_example_with_none() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x00, 0x10, 0x00, 0x00, 0x81],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn trigger_continuous_measurements_spec_example() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x00, 0x10, 0x00, 0x00, 0x81],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor
+                    .trigger_continuous_measurements(Some(
+                        AmbientPressureCompensation::DefaultPressure,
+                    ))
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn stop_continuous_measurements_spec_example() {
+                let expected_transactions = [I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04])];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor.stop_continuous_measurements().await.unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn stopped_sensor_rejects_reads_and_recalibration() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                assert_eq!(sensor.measuring_state(), MeasuringState::Unknown);
+
+                sensor.stop_continuous_measurements().await.unwrap();
+                assert_eq!(sensor.measuring_state(), MeasuringState::Stopped);
+                assert_eq!(
+                    sensor.read_measurement().await.unwrap_err(),
+                    Scd30Error::NotMeasuring
+                );
+                assert_eq!(
+                    sensor
+                        .set_forced_recalibration(ForcedRecalibrationValue::try_from(450).unwrap())
+                        .await
+                        .unwrap_err(),
+                    Scd30Error::NotMeasuring
+                );
+
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                assert_eq!(sensor.measuring_state(), MeasuringState::Measuring);
+                sensor.read_measurement().await.unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_all_settings_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x52, 0x04]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xC2, 0x50]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xE8, 0xD4]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD1, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x42, 0xF3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                let settings = sensor.read_all_settings().await.unwrap();
+                assert_eq!(
+                    settings,
+                    SettingsSnapshot {
+                        measurement_interval: MeasurementInterval::try_from(2).unwrap(),
+                        automatic_self_calibration: AutomaticSelfCalibration::Inactive,
+                        forced_recalibration: ForcedRecalibrationValue::try_from(450).unwrap(),
+                        temperature_offset: TemperatureOffset::try_from(5.0_f32).unwrap(),
+                        altitude_compensation: AltitudeCompensation::from(1000),
+                        firmware_version: FirmwareVersion::new(3, 66),
+                        data_status: DataStatus::Ready,
+                    }
+                );
+                assert_eq!(sensor.firmware(), Some(FirmwareVersion::new(3, 66)));
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn apply_preset_writes_settings_and_starts_measuring() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00, 0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                sensor.apply_preset(Preset::HighAccuracy).await.unwrap();
+                assert_eq!(sensor.config(), Preset::HighAccuracy.config());
+                assert_eq!(sensor.measuring_state(), MeasuringState::Measuring);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn select_compensation_falls_back_to_defaults() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0xB6, 0xB5]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                assert_eq!(
+                    sensor
+                        .select_compensation(Some(950.2), Some(520.0))
+                        .await
+                        .unwrap(),
+                    Some(CompensationMode::AmbientPressure(
+                        AmbientPressure::try_from(950).unwrap()
+                    ))
+                );
+                assert_eq!(sensor.select_compensation(None, None).await.unwrap(), None);
+                assert_eq!(
+                    sensor.compensation(),
+                    Some(CompensationMode::Altitude(AltitudeCompensation::from(0)))
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn strict_sequencing_rejects_violations() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x52, 0x04, 0x01, 0xC2, 0x50]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                set_now(0);
+                let mut sensor = Scd30::new(i2c).with_clock(now).with_strict_sequencing();
+                let frc = ForcedRecalibrationValue::try_from(450).unwrap();
+                assert_eq!(
+                    sensor.set_forced_recalibration(frc).await.unwrap_err(),
+                    Scd30Error::Sequence(SequenceViolation::RecalibrationNotMeasuring)
+                );
+
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                set_now(60_000);
+                assert_eq!(
+                    sensor.set_forced_recalibration(frc).await.unwrap_err(),
+                    Scd30Error::Sequence(SequenceViolation::RecalibrationBeforeSoak {
+                        remaining_ms: 60_000
+                    })
+                );
+                assert_eq!(
+                    sensor
+                        .set_measurement_interval(MeasurementInterval::try_from(5).unwrap())
+                        .await
+                        .unwrap_err(),
+                    Scd30Error::Sequence(SequenceViolation::IntervalChangeDuringSoak {
+                        remaining_ms: 60_000
+                    })
+                );
+
+                set_now(120_000);
+                sensor.set_forced_recalibration(frc).await.unwrap();
+                set_now(120_001);
+                assert_eq!(
+                    sensor.soft_reset().await.unwrap_err(),
+                    Scd30Error::Sequence(SequenceViolation::CommandTooEarly { remaining_ms: 2 })
+                );
+                set_now(120_003);
+                sensor.soft_reset().await.unwrap();
+                set_now(121_000);
+                assert_eq!(
+                    sensor.is_data_ready().await.unwrap_err(),
+                    Scd30Error::Sequence(SequenceViolation::SensorBooting { remaining_ms: 1003 })
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn get_ambient_pressure_reads_compensation_in_effect() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x84, 0x12]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                assert_eq!(
+                    sensor.get_ambient_pressure().await.unwrap(),
+                    AmbientPressureCompensation::CompensationPressure(
+                        AmbientPressure::try_from(900).unwrap()
+                    )
+                );
+                assert_eq!(
+                    sensor.get_ambient_pressure().await.unwrap(),
+                    AmbientPressureCompensation::DefaultPressure
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn set_measurement_interval_spec_example() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x46, 0x00, 0x00, 0x02, 0xE3],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor
+                    .set_measurement_interval(MeasurementInterval::try_from(2).unwrap())
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_config_and_refresh_agree_on_pressure_compensation() {
+                let pressure = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xF5, 0xDB]),
+                ];
+                let settings = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xE8, 0xD4]),
+                ];
+                let mut expected_transactions = Vec::new();
+                for _ in 0..2 {
+                    expected_transactions.extend_from_slice(&pressure);
+                    expected_transactions.extend_from_slice(&settings);
+                }
+                expected_transactions.push(I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x00, 0x10, 0x03, 0xF5, 0xDB],
+                ));
+                for _ in 0..2 {
+                    expected_transactions.extend_from_slice(&pressure);
+                    expected_transactions.extend_from_slice(&settings);
+                }
+                expected_transactions.push(I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]));
+                for _ in 0..2 {
+                    expected_transactions.extend_from_slice(&settings);
+                }
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                let read_back = Some(AmbientPressureCompensation::CompensationPressure(
+                    AmbientPressure::try_from(1013).unwrap(),
+                ));
+
+                assert_eq!(sensor.measuring_state(), MeasuringState::Unknown);
+                assert_eq!(
+                    sensor.read_config().await.unwrap().continuous_measurement,
+                    read_back
+                );
+                assert_eq!(
+                    sensor.refresh().await.unwrap().continuous_measurement,
+                    read_back
+                );
+
+                sensor
+                    .trigger_continuous_measurements(read_back)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    sensor.read_config().await.unwrap().continuous_measurement,
+                    read_back
+                );
+                assert_eq!(
+                    sensor.refresh().await.unwrap().continuous_measurement,
+                    read_back
+                );
+
+                sensor.stop_continuous_measurements().await.unwrap();
+                assert_eq!(
+                    sensor.read_config().await.unwrap().continuous_measurement,
+                    None
+                );
+                assert_eq!(sensor.refresh().await.unwrap().continuous_measurement, None);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn config_cache_serves_known_settings() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00, 0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xE8, 0xD4]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_config_cache();
+
+                let interval = MeasurementInterval::try_from(2).unwrap();
+                sensor.set_measurement_interval(interval).await.unwrap();
+                assert_eq!(sensor.get_measurement_interval().await.unwrap(), interval);
+                let offset = TemperatureOffset::try_from(5.0).unwrap();
+                assert_eq!(sensor.get_temperature_offset().await.unwrap(), offset);
+                assert_eq!(sensor.get_temperature_offset().await.unwrap(), offset);
+
+                let config = Scd30Config {
+                    continuous_measurement: Some(AmbientPressureCompensation::DefaultPressure),
+                    measurement_interval: Some(interval),
+                    automatic_self_calibration: Some(AutomaticSelfCalibration::Inactive),
+                    temperature_offset: Some(offset),
+                    altitude_compensation: Some(AltitudeCompensation::from(1000)),
+                };
+                assert_eq!(sensor.refresh().await.unwrap(), config);
+                assert_eq!(sensor.cached_config(), Some(config));
+                assert_eq!(
+                    sensor.get_altitude_compensation().await.unwrap(),
+                    AltitudeCompensation::from(1000)
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn get_measurement_interval_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let interval = sensor.get_measurement_interval().await.unwrap();
+                assert_eq!(interval, MeasurementInterval::try_from(2).unwrap());
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn get_ready_status_sample_works() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let ready_status = sensor.is_data_ready().await.unwrap();
+                assert_eq!(ready_status, DataStatus::Ready);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_measurement_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let measurement = sensor.read_measurement().await.unwrap();
+                assert_eq!(measurement.co2_concentration, 439.09515);
+                assert_eq!(measurement.temperature, 27.23828);
+                assert_eq!(measurement.humidity, 48.806744);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_measurement_into_spec_example() {
+                let frame = [
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ];
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, frame.to_vec()),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let mut buf = [0; 18];
+                let measurement = sensor.read_measurement_into(&mut buf).await.unwrap();
+                assert_eq!(measurement.co2_concentration, 439.09515);
+                assert_eq!(measurement.temperature, 27.23828);
+                assert_eq!(measurement.humidity, 48.806744);
+                assert_eq!(buf, frame);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_measurement_into_replaces_crcs_without_validation() {
+                let mut frame = [
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ];
+                frame[2] = 0x00;
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, frame.to_vec()),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_crc_validation(CrcValidation::Disabled);
+
+                let mut buf = [0; 18];
+                let measurement = sensor.read_measurement_into(&mut buf).await.unwrap();
+                assert_eq!(measurement.co2_concentration, 439.09515);
+                frame[2] = 0xCB;
+                assert_eq!(buf, frame);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_measurement_into_follows_read_verification() {
+                let frame = [
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ];
+                let mut corrupted = frame;
+                corrupted[2] = 0x00;
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, corrupted.to_vec()),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, frame.to_vec()),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, frame.to_vec()),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, corrupted.to_vec()),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor =
+                    Scd30::new(i2c).with_read_verification(ReadVerification::RetryOnCrc);
+
+                let mut buf = [0; 18];
+                let measurement = sensor.read_measurement_into(&mut buf).await.unwrap();
+                assert_eq!(measurement.co2_concentration, 439.09515);
+                assert_eq!(buf, frame);
+
+                sensor = sensor.with_read_verification(ReadVerification::DoubleRead);
+                assert_eq!(
+                    sensor.read_measurement_into(&mut buf).await.unwrap_err(),
+                    Scd30Error::DataError(DataError::InconsistentReads)
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_measurement_raw_does_not_validate() {
+                let frame = [0xFF; 18];
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, frame.to_vec()),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let mut buf = [0; 18];
+                sensor.read_measurement_raw(&mut buf).await.unwrap();
+                assert_eq!(buf, frame);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn set_automatic_self_calibration_spec_example() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x53, 0x06, 0x00, 0x00, 0x81],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor
+                    .set_automatic_self_calibration(AutomaticSelfCalibration::Inactive)
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn get_automatic_self_calibration_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let asc = sensor.get_automatic_self_calibration().await.unwrap();
+                assert_eq!(asc, AutomaticSelfCalibration::Inactive);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn set_forced_recalibration_spec_example() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x52, 0x04, 0x01, 0xC2, 0x50],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor
+                    .set_forced_recalibration(ForcedRecalibrationValue::try_from(450).unwrap())
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn get_forced_recalibration_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x52, 0x04]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xC2, 0x50]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let frc = sensor.get_forced_recalibration().await.unwrap();
+                assert_eq!(frc, ForcedRecalibrationValue::try_from(450).unwrap());
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn set_temperature_offset_spec_example() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x54, 0x03, 0x01, 0xF4, 0x33],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor
+                    .set_temperature_offset(TemperatureOffset::try_from(5.0).unwrap())
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn get_temperature_offset_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let offset = sensor.get_temperature_offset().await.unwrap();
+                assert_eq!(offset, TemperatureOffset::try_from(5.0).unwrap());
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn set_altitude_compensation_spec_example() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x51, 0x02, 0x03, 0xE8, 0xD4],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor
+                    .set_altitude_compensation(AltitudeCompensation::try_from(1000).unwrap())
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn get_altitude_compensation_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xE8, 0xD4]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let altitude = sensor.get_altitude_compensation().await.unwrap();
+                assert_eq!(altitude, AltitudeCompensation::try_from(1000).unwrap());
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn init_caches_firmware_version() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD1, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x42, 0xF3]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                assert_eq!(sensor.firmware(), None);
+
+                let version = sensor.init().await.unwrap();
+                assert_eq!(version, FirmwareVersion::new(3, 66));
+                assert_eq!(sensor.firmware(), Some(version));
+                assert_eq!(sensor.firmware(), Some(version));
+
+                let (mut i2c, _, state) = sensor.into_parts();
+                assert_eq!(state.firmware(), Some(version));
+                i2c.done();
+            }
+
+            #[test_macro]
+            async fn read_firmware_spec_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD1, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x42, 0xF3]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                let version = sensor.read_firmware_version().await.unwrap();
+                assert_eq!(version.major, 3);
+                assert_eq!(version.minor, 66);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn execute_soft_reset_spec_example() {
+                let expected_transactions = [I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04])];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor.soft_reset().await.unwrap();
+                sensor.shutdown().done();
+            }
+
+            fn measurement_transactions(count: usize) -> Vec<I2cTransaction> {
+                (0..count)
+                    .flat_map(|_| {
+                        [
+                            I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                            I2cTransaction::read(
+                                0x61 | 0x01,
+                                vec![
+                                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7,
+                                    0xFF, 0xF5, 0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                                ],
+                            ),
+                        ]
+                    })
+                    .collect()
+            }
+
+            #[test_macro]
+            async fn warm_up_discards_early_samples() {
+                let mut expected_transactions = measurement_transactions(2);
+                expected_transactions.push(I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x00, 0x10, 0x00, 0x00, 0x81],
+                ));
+                expected_transactions.extend(measurement_transactions(3));
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_warm_up(WarmUpPolicy {
+                    period: WarmUp::Samples(1),
+                    action: WarmUpAction::Discard,
+                });
+
+                assert!(sensor.is_warming_up());
+                assert_eq!(
+                    sensor.read_measurement().await.unwrap_err(),
+                    Scd30Error::WarmingUp
+                );
+                assert!(!sensor.is_warming_up());
+                assert!(sensor.read_measurement().await.is_ok());
+
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                assert!(sensor.is_warming_up());
+                let mut buf = [0; 18];
+                assert_eq!(
+                    sensor.read_measurement_into(&mut buf).await.unwrap_err(),
+                    Scd30Error::WarmingUp
+                );
+                assert!(sensor.read_measurement_into(&mut buf).await.is_ok());
+                assert!(sensor.read_measurement().await.is_ok());
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn time_based_warm_up_uses_configured_interval() {
+                let mut expected_transactions = vec![I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x46, 0x00, 0x00, 0x05, 0x74],
+                )];
+                expected_transactions.extend(measurement_transactions(3));
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_warm_up(WarmUpPolicy {
+                    period: WarmUp::Seconds(10),
+                    action: WarmUpAction::Mark,
+                });
+                sensor
+                    .set_measurement_interval(MeasurementInterval::try_from(5).unwrap())
+                    .await
+                    .unwrap();
+
+                assert!(sensor.is_warming_up());
+                assert!(sensor.read_measurement().await.is_ok());
+                assert!(sensor.is_warming_up());
+                assert!(sensor.read_measurement().await.is_ok());
+                assert!(!sensor.is_warming_up());
+                assert!(sensor.read_measurement().await.is_ok());
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn qualified_measurement_carries_quality_flags() {
+                let mut expected_transactions = vec![
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x00,
+                        ],
+                    ),
+                ];
+                expected_transactions.extend(measurement_transactions(2));
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                set_now(0);
+                let mut sensor = Scd30::new(i2c)
+                    .with_clock(now)
+                    .with_read_verification(ReadVerification::RetryOnCrc)
+                    .with_warm_up(WarmUpPolicy {
+                        period: WarmUp::Samples(1),
+                        action: WarmUpAction::Mark,
+                    });
+
+                let sample = sensor.read_qualified_measurement().await.unwrap();
+                assert_eq!(
+                    sample.quality,
+                    SampleQuality::RETRIED_CRC | SampleQuality::WARM_UP
+                );
+                assert!(sample.warnings.is_empty());
+                assert!(!sample.is_clean());
+                assert_eq!(sample.measurement.co2_concentration, 439.09515);
+
+                set_now(6000);
+                let sample = sensor.read_qualified_measurement().await.unwrap();
+                assert_eq!(sample.quality, SampleQuality::OVERDUE);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn qualified_measurement_follows_read_verification() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x00,
+                        ],
+                    ),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                assert_eq!(
+                    sensor.read_qualified_measurement().await.unwrap_err(),
+                    Scd30Error::DataError(DataError::CrcFailed)
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn read_verification_retries_crc_failures() {
+                let mut expected_transactions = vec![
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0x00]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                ];
+                expected_transactions.extend(measurement_transactions(1));
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor =
+                    Scd30::new(i2c).with_read_verification(ReadVerification::RetryOnCrc);
+
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                assert!(sensor.read_measurement().await.is_ok());
+                assert_eq!(sensor.error_counters().crc, 1);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn double_read_rejects_inconsistent_reads() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDC, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor =
+                    Scd30::new(i2c).with_read_verification(ReadVerification::DoubleRead);
+
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                assert!(matches!(
+                    sensor.read_measurement().await,
+                    Err(Scd30Error::DataError(DataError::InconsistentReads))
+                ));
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn lenient_decoding_returns_unknown_values() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_value_decoding(ValueDecoding::Lenient);
+
+                assert_eq!(
+                    sensor.is_data_ready().await.unwrap(),
+                    DataStatus::Unknown(2)
+                );
+                assert_eq!(
+                    sensor.get_automatic_self_calibration().await.unwrap(),
+                    AutomaticSelfCalibration::Unknown(2)
+                );
+                assert_eq!(
+                    sensor
+                        .set_automatic_self_calibration(AutomaticSelfCalibration::Unknown(2))
+                        .await
+                        .unwrap_err(),
+                    Scd30Error::DataError(DataError::ValueOutOfRange {
+                        parameter: "Automatic self-calibration",
+                        min: 0,
+                        max: 1,
+                        unit: "",
+                    })
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn retry_once_rereads_unexpected_values() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_value_decoding(ValueDecoding::RetryOnce);
+
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                assert!(matches!(
+                    sensor.get_automatic_self_calibration().await,
+                    Err(Scd30Error::DataError(DataError::UnexpectedValueReceived {
+                        actual: 2,
+                        ..
+                    }))
+                ));
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn soft_reset_restarts_warm_up() {
+                let mut expected_transactions = measurement_transactions(1);
+                expected_transactions.push(I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04]));
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_warm_up(WarmUpPolicy {
+                    period: WarmUp::Samples(1),
+                    action: WarmUpAction::Mark,
+                });
+
+                sensor.read_measurement().await.unwrap();
+                assert!(!sensor.is_warming_up());
+                sensor.soft_reset().await.unwrap();
+                assert!(sensor.is_warming_up());
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn soft_reset_and_reinit_restores_cached_config() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00, 0x00, 0x05, 0x74]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0x20, 0x2A]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00, 0x00, 0x05, 0x74]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0x20, 0x2A]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                let mut delay = NoopDelay::new();
+
+                sensor
+                    .set_measurement_interval(MeasurementInterval::try_from(5).unwrap())
+                    .await
+                    .unwrap();
+                sensor
+                    .trigger_continuous_measurements(Some(
+                        AmbientPressureCompensation::CompensationPressure(
+                            AmbientPressure::try_from(800).unwrap(),
+                        ),
+                    ))
+                    .await
+                    .unwrap();
+                sensor
+                    .soft_reset_and_reinit(&mut delay, None)
+                    .await
+                    .unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn soft_reset_and_reinit_applies_supplied_config() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03, 0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02, 0x03, 0xE8, 0xD4]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                let mut delay = NoopDelay::new();
+
+                let config = Scd30Config {
+                    continuous_measurement: Some(AmbientPressureCompensation::DefaultPressure),
+                    automatic_self_calibration: Some(AutomaticSelfCalibration::Inactive),
+                    temperature_offset: Some(TemperatureOffset::try_from(5.0).unwrap()),
+                    altitude_compensation: Some(AltitudeCompensation::from(1000)),
+                    ..Default::default()
+                };
+                sensor
+                    .soft_reset_and_reinit(&mut delay, Some(&config))
+                    .await
+                    .unwrap();
+                assert_eq!(sensor.config(), config);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn commit_writes_changes_in_order_with_gaps() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00, 0x00, 0x0A, 0x5A]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02, 0x03, 0xE8, 0xD4]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x52, 0x04, 0x01, 0xC2, 0x50]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+                let mut delay = CheckedDelay::new(&[
+                    DelayTransaction::delay_us(3000),
+                    DelayTransaction::delay_us(3000),
+                    DelayTransaction::delay_us(3000),
+                ]);
+
+                let mut sensor = Scd30::new(i2c).with_strict_sequencing();
+
+                let transaction = ConfigTransaction::new()
+                    .start_continuous_measurements(AmbientPressureCompensation::DefaultPressure)
+                    .forced_recalibration(ForcedRecalibrationValue::try_from(450).unwrap())
+                    .altitude_compensation(AltitudeCompensation::from(1000))
+                    .measurement_interval(MeasurementInterval::try_from(10).unwrap());
+                sensor.commit(&transaction, &mut delay).await.unwrap();
+                assert_eq!(
+                    sensor.config().measurement_interval,
+                    transaction.config().measurement_interval
+                );
+                sensor.shutdown().done();
+                delay.done();
+            }
+
+            #[test_macro]
+            async fn reconfigure_restarts_with_previous_compensation() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0xF5, 0xDB]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00, 0x00, 0x0A, 0x5A]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0xF5, 0xDB]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+                let mut delay = CheckedDelay::new(&[
+                    DelayTransaction::delay_us(3000),
+                    DelayTransaction::delay_us(3000),
+                ]);
+
+                let mut sensor = Scd30::new(i2c);
+                let pressure = AmbientPressureCompensation::CompensationPressure(
+                    AmbientPressure::try_from(1013).unwrap(),
+                );
+                sensor
+                    .trigger_continuous_measurements(Some(pressure))
+                    .await
+                    .unwrap();
+
+                let interval = MeasurementInterval::try_from(10).unwrap();
+                sensor
+                    .reconfigure(&mut delay, |changes| changes.measurement_interval(interval))
+                    .await
+                    .unwrap();
+                sensor
+                    .reconfigure(&mut delay, |changes| changes)
+                    .await
+                    .unwrap();
+                assert_eq!(sensor.config().continuous_measurement, Some(pressure));
+                assert_eq!(sensor.config().measurement_interval, Some(interval));
+                sensor.shutdown().done();
+                delay.done();
+            }
+
+            #[test_macro]
+            async fn reconfigure_rejects_forced_recalibration() {
+                let expected_transactions = [I2cTransaction::write(
+                    0x61 | 0x00,
+                    vec![0x00, 0x10, 0x00, 0x00, 0x81],
+                )];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+
+                assert_eq!(
+                    sensor
+                        .reconfigure(&mut NoopDelay::new(), |changes| {
+                            changes
+                                .forced_recalibration(
+                                    ForcedRecalibrationValue::try_from(450).unwrap(),
+                                )
+                                .measurement_interval(MeasurementInterval::try_from(10).unwrap())
+                        })
+                        .await
+                        .unwrap_err(),
+                    Scd30Error::Sequence(SequenceViolation::RecalibrationBeforeSoak {
+                        remaining_ms: CALIBRATION_SOAK_MS
+                    })
+                );
+                assert_eq!(sensor.measuring_state(), MeasuringState::Measuring);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn reconfigure_keeps_stopped_sensor_stopped() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02, 0x03, 0xE8, 0xD4]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+                let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(3000)]);
+
+                let mut sensor = Scd30::new(i2c);
+                sensor
+                    .reconfigure(&mut delay, |changes| {
+                        changes.altitude_compensation(AltitudeCompensation::from(1000))
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(sensor.measuring_state(), MeasuringState::Stopped);
+                sensor.shutdown().done();
+                delay.done();
+            }
+
+            #[test_macro]
+            async fn snapshot_is_restored_on_replacement_sensor() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD1, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x42, 0xF3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xF5, 0xDB]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xE8, 0xD4]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x52, 0x04]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xC2, 0x50]),
+                ];
+                let replacement_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00, 0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03, 0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02, 0x03, 0xE8, 0xD4]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0xF5, 0xDB]),
+                ];
+
+                let mut sensor = Scd30::new(I2cMock::new(&expected_transactions));
+                let mut replacement = Scd30::new(I2cMock::new(&replacement_transactions));
+
+                let snapshot = sensor.snapshot().await.unwrap();
+                assert_eq!(
+                    snapshot.forced_recalibration,
+                    ForcedRecalibrationValue::try_from(450).unwrap()
+                );
+                replacement
+                    .restore(&snapshot, &mut NoopDelay::new())
+                    .await
+                    .unwrap();
+                assert_eq!(replacement.config(), snapshot.config);
+                sensor.shutdown().done();
+                replacement.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn general_call_reset_addresses_all_devices() {
+                let expected_transactions = [I2cTransaction::write(0x00, vec![0x06])];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor.general_call_reset().await.unwrap();
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn metrics_count_traffic_per_category() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD1, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x42, 0xF3]),
+                    I2cTransaction::write(0x00, vec![0x06]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c);
+
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                sensor.is_data_ready().await.unwrap();
+                sensor.read_firmware_version().await.unwrap();
+                sensor.general_call_reset().await.unwrap();
+
+                let metrics = sensor.metrics();
+                assert_eq!(
+                    metrics.measurement,
+                    Traffic {
+                        transactions: 2,
+                        bytes_written: 2,
+                        bytes_read: 3
+                    }
+                );
+                assert_eq!(
+                    metrics.configuration,
+                    Traffic {
+                        transactions: 2,
+                        bytes_written: 2,
+                        bytes_read: 3
+                    }
+                );
+                assert_eq!(
+                    metrics.control,
+                    Traffic {
+                        transactions: 2,
+                        bytes_written: 6,
+                        bytes_read: 0
+                    }
+                );
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn health_report_collects_diagnostics() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB1]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04])
+                        .with_error(i2c::ErrorKind::Other),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD1, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x42, 0xF3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xE8, 0xD4]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd30::new(i2c).with_clock(now);
+
+                set_now(1000);
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                set_now(3100);
+                assert_eq!(
+                    sensor.is_data_ready().await.unwrap_err(),
+                    Scd30Error::DataError(DataError::CrcFailed)
+                );
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                assert!(sensor.soft_reset().await.is_err());
+                sensor.soft_reset().await.unwrap();
+
+                let report = sensor.health_report().await.unwrap();
+                assert_eq!(
+                    report.firmware_version,
+                    FirmwareVersion {
+                        major: 3,
+                        minor: 66
+                    }
+                );
+                assert_eq!(
+                    report.config,
+                    Scd30Config {
+                        continuous_measurement: Some(AmbientPressureCompensation::DefaultPressure),
+                        measurement_interval: Some(MeasurementInterval::try_from(2).unwrap()),
+                        automatic_self_calibration: Some(AutomaticSelfCalibration::Inactive),
+                        temperature_offset: Some(TemperatureOffset::try_from(5.0).unwrap()),
+                        altitude_compensation: Some(AltitudeCompensation::from(1000)),
+                    }
+                );
+                assert_eq!(
+                    report.errors,
+                    ErrorCounters {
+                        i2c: 1,
+                        crc: 1,
+                        data: 0,
+                        timeout: 0
+                    }
+                );
+                assert_eq!(report.last_reset, Some(ResetCause::SoftReset));
+                assert_eq!(report.data_ready_latency_ms, Some(2100));
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn slow_operation_times_out() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                // Every call to the clock advances the time by 30 ms.
+                let mut sensor = Scd30::new(i2c)
+                    .with_clock(|| NOW.with(|now| now.replace(now.get() + 30)))
+                    .with_timeout(50);
+
+                set_now(0);
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                let mut sensor = sensor.with_timeout(20);
+                assert_eq!(sensor.soft_reset().await.unwrap_err(), Scd30Error::Timeout);
+                assert_eq!(sensor.error_counters().timeout, 1);
+                sensor.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn timings_measure_command_to_response() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                // Every call to the clock advances the time by 10 ms.
+                let mut sensor =
+                    Scd30::new(i2c).with_clock(|| NOW.with(|now| now.replace(now.get() + 10)));
+
+                set_now(0);
+                assert_eq!(sensor.timings(), Timings::default());
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                sensor.read_measurement().await.unwrap();
+
+                let timings = sensor.timings();
+                assert_eq!(timings.response.count, 2);
+                assert_eq!(timings.measurement_read.count, 1);
+                assert!(timings.response.last_ms > 0);
+                assert_eq!(timings.measurement_read.last_ms, timings.response.last_ms);
+                let (mut i2c, _, state) = sensor.into_parts();
+                assert_eq!(state.timings(), timings);
+                i2c.done();
+            }
+
+            #[test_macro]
+            async fn delayed_read_waits_between_command_and_read() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+                let delay = CheckedDelay::new(&[DelayTransaction::delay_us(3000)]);
+
+                let mut sensor = Scd30::new(i2c).with_read_strategy(
+                    delay,
+                    ReadStrategy::Delayed {
+                        gap_us: RECOMMENDED_READ_GAP_US,
+                    },
+                );
+
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                let (mut i2c, mut delay) = sensor.release();
+                i2c.done();
+                delay.done();
+            }
+
+            #[test_macro]
+            async fn no_stretch_read_retries_on_nack() {
+                let nack = i2c::ErrorKind::NoAcknowledge(i2c::NoAcknowledgeSource::Address);
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x00]).with_error(nack),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x00]).with_error(nack),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x00]).with_error(nack),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+                let delay = CheckedDelay::new(&[
+                    DelayTransaction::delay_us(1000),
+                    DelayTransaction::delay_us(1000),
+                    DelayTransaction::delay_us(1000),
+                    DelayTransaction::delay_us(1000),
+                ]);
+
+                let mut sensor = Scd30::new(i2c).with_read_strategy(
+                    delay,
+                    ReadStrategy::NoStretch {
+                        gap_us: 1000,
+                        retries: 1,
+                    },
+                );
 
-                sensor.stop_continuous_measurements().await.unwrap();
-                sensor.shutdown().done();
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                assert_eq!(
+                    sensor.is_data_ready().await.unwrap_err(),
+                    Scd30Error::CommandFailed {
+                        command: Command::GetDataReady,
+                        phase: Phase::Read,
+                        error: nack,
+                    }
+                );
+                let (mut i2c, mut delay) = sensor.release();
+                i2c.done();
+                delay.done();
             }
 
             #[test_macro]
-            async fn set_measurement_interval_spec_example() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x46, 0x00, 0x00, 0x02, 0xE3],
-                )];
+            async fn into_parts_keeps_state_across_reconstruction() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x62, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x62, vec![0x02, 0x02]).with_error(i2c::ErrorKind::Other),
+                    I2cTransaction::write(0x62, vec![0xD3, 0x04]),
+                    I2cTransaction::write(0x62, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
-                let mut sensor = Scd30::new(i2c);
+                let mut sensor = Scd30::new_with_address(i2c, 0x62);
+
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                assert!(sensor.is_data_ready().await.is_err());
+                let (i2c, delay, state) = sensor.into_parts();
+                assert_eq!(state.address(), 0x62);
+                assert_eq!(state.error_counters().i2c, 1);
 
+                let mut sensor = Scd30::from_parts(i2c, delay, state);
+                assert_eq!(sensor.error_counters().i2c, 1);
                 sensor
-                    .set_measurement_interval(MeasurementInterval::try_from(2).unwrap())
+                    .soft_reset_and_reinit(&mut NoopDelay::new(), None)
                     .await
                     .unwrap();
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn get_measurement_interval_spec_example() {
-                let expected_transactions = [
-                    I2cTransaction::write(0x61 | 0x00, vec![0x46, 0x00]),
-                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x02, 0xE3]),
+            async fn measure_n_collects_samples_and_stops() {
+                let mut expected_transactions = vec![
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
                 ];
+                for _ in 0..2 {
+                    expected_transactions
+                        .push(I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]));
+                    expected_transactions
+                        .push(I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]));
+                    expected_transactions.extend(measurement_transactions(1));
+                }
+                expected_transactions.push(I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]));
 
                 let i2c = I2cMock::new(&expected_transactions);
 
                 let mut sensor = Scd30::new(i2c);
-
-                let interval = sensor.get_measurement_interval().await.unwrap();
-                assert_eq!(interval, MeasurementInterval::try_from(2).unwrap());
+                let mut delay = NoopDelay::new();
+
+                let mut session = sensor.measure_n(2, &mut delay, None).await.unwrap();
+                assert!(session.next_sample().await.unwrap().is_ok());
+                assert_eq!(session.remaining(), 1);
+                let sample = session.next_sample().await.unwrap().unwrap();
+                assert_eq!(sample.co2_concentration, 439.09515);
+                assert!(session.next_sample().await.is_none());
+                session.finish().await.unwrap();
+                assert_eq!(sensor.measuring_state(), MeasuringState::Stopped);
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn get_ready_status_sample_works() {
-                let expected_transactions = [
+            async fn measure_n_session_finished_early_stops() {
+                let mut expected_transactions = vec![
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
                     I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
                     I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
                 ];
+                expected_transactions.extend(measurement_transactions(1));
+                expected_transactions.push(I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]));
 
                 let i2c = I2cMock::new(&expected_transactions);
 
                 let mut sensor = Scd30::new(i2c);
+                let mut delay = NoopDelay::new();
 
-                let ready_status = sensor.is_data_ready().await.unwrap();
-                assert_eq!(ready_status, DataStatus::Ready);
+                let mut session = sensor.measure_n(3, &mut delay, None).await.unwrap();
+                assert!(session.next_sample().await.unwrap().is_ok());
+                session.finish().await.unwrap();
+                assert_eq!(sensor.measuring_state(), MeasuringState::Stopped);
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn read_measurement_spec_example() {
+            async fn measure_n_stops_on_error() {
                 let expected_transactions = [
-                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
-                    I2cTransaction::read(
-                        0x61 | 0x01,
-                        vec![
-                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
-                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
-                        ],
-                    ),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02])
+                        .with_error(i2c::ErrorKind::Other),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]),
                 ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
                 let mut sensor = Scd30::new(i2c);
+                let mut delay = NoopDelay::new();
 
-                let measurement = sensor.read_measurement().await.unwrap();
-                assert_eq!(measurement.co2_concentration, 439.09515);
-                assert_eq!(measurement.temperature, 27.23828);
-                assert_eq!(measurement.humidity, 48.806744);
+                let mut session = sensor.measure_n(2, &mut delay, None).await.unwrap();
+                assert_eq!(
+                    session.next_sample().await.unwrap().unwrap_err(),
+                    Scd30Error::CommandFailed {
+                        command: Command::GetDataReady,
+                        phase: Phase::Write,
+                        error: i2c::ErrorKind::Other,
+                    }
+                );
+                assert!(session.next_sample().await.is_none());
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn set_automatic_self_calibration_spec_example() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x53, 0x06, 0x00, 0x00, 0x81],
-                )];
+            async fn resume_restores_pressure_compensation_and_skips_warm_up() {
+                let mut expected_transactions = vec![
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0x20, 0x2A]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0x20, 0x2A]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                ];
+                expected_transactions.extend(measurement_transactions(1));
+                expected_transactions.push(I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]));
+                expected_transactions
+                    .push(I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]));
 
                 let i2c = I2cMock::new(&expected_transactions);
 
-                let mut sensor = Scd30::new(i2c);
+                let mut sensor = Scd30::new(i2c).with_warm_up(WarmUpPolicy {
+                    period: WarmUp::Samples(1),
+                    action: WarmUpAction::Discard,
+                });
 
                 sensor
-                    .set_automatic_self_calibration(AutomaticSelfCalibration::Inactive)
+                    .trigger_continuous_measurements(Some(
+                        AmbientPressureCompensation::CompensationPressure(
+                            AmbientPressure::try_from(800).unwrap(),
+                        ),
+                    ))
                     .await
                     .unwrap();
+                sensor.suspend().await.unwrap();
+                assert!(sensor.is_suspended());
+                assert_eq!(sensor.config().continuous_measurement, None);
+
+                sensor.resume(&mut NoopDelay::new()).await.unwrap();
+                assert!(!sensor.is_suspended());
+                assert!(!sensor.is_warming_up());
+                assert_eq!(
+                    sensor.config().continuous_measurement,
+                    Some(AmbientPressureCompensation::CompensationPressure(
+                        AmbientPressure::try_from(800).unwrap()
+                    ))
+                );
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn get_automatic_self_calibration_spec_example() {
+            async fn disabled_crc_validation_accepts_corrupted_responses() {
                 let expected_transactions = [
-                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06]),
-                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB1]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB1]),
                 ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
                 let mut sensor = Scd30::new(i2c);
 
-                let asc = sensor.get_automatic_self_calibration().await.unwrap();
-                assert_eq!(asc, AutomaticSelfCalibration::Inactive);
+                assert_eq!(
+                    sensor.is_data_ready().await.unwrap_err(),
+                    Scd30Error::DataError(DataError::CrcFailed)
+                );
+                let mut sensor = sensor.with_crc_validation(CrcValidation::Disabled);
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn set_forced_recalibration_spec_example() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x52, 0x04, 0x01, 0xC2, 0x50],
-                )];
+            async fn set_compensation_keeps_modes_exclusive() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x03, 0x20, 0x2A]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02, 0x03, 0xE8, 0xD4]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
                 let mut sensor = Scd30::new(i2c);
 
+                let pressure = AmbientPressure::try_from(800).unwrap();
                 sensor
-                    .set_forced_recalibration(ForcedRecalibrationValue::try_from(450).unwrap())
+                    .set_compensation(CompensationMode::AmbientPressure(pressure))
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    sensor.compensation(),
+                    Some(CompensationMode::AmbientPressure(pressure))
+                );
+
+                let altitude = AltitudeCompensation::from(1000);
+                sensor
+                    .set_compensation(CompensationMode::Altitude(altitude))
                     .await
                     .unwrap();
+                assert_eq!(
+                    sensor.compensation(),
+                    Some(CompensationMode::Altitude(altitude))
+                );
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn get_forced_recalibration_spec_example() {
+            async fn new_with_address_uses_address() {
                 let expected_transactions = [
-                    I2cTransaction::write(0x61 | 0x00, vec![0x52, 0x04]),
-                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xC2, 0x50]),
+                    I2cTransaction::write(0x62, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x62, vec![0x00, 0x01, 0xB0]),
                 ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
-                let mut sensor = Scd30::new(i2c);
+                let mut sensor = Scd30::new_with_address(i2c, 0x62);
 
-                let frc = sensor.get_forced_recalibration().await.unwrap();
-                assert_eq!(frc, ForcedRecalibrationValue::try_from(450).unwrap());
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
                 sensor.shutdown().done();
             }
 
-            #[test_macro]
-            async fn set_temperature_offset_spec_example() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x54, 0x03, 0x01, 0xF4, 0x33],
-                )];
-
-                let i2c = I2cMock::new(&expected_transactions);
-
-                let mut sensor = Scd30::new(i2c);
-
-                sensor
-                    .set_temperature_offset(TemperatureOffset::try_from(5.0).unwrap())
-                    .await
-                    .unwrap();
-                sensor.shutdown().done();
+            std::thread_local! {
+                static NOW: core::cell::Cell<u64> = const { core::cell::Cell::new(0) };
             }
 
-            #[test_macro]
-            async fn get_temperature_offset_spec_example() {
-                let expected_transactions = [
-                    I2cTransaction::write(0x61 | 0x00, vec![0x54, 0x03]),
-                    I2cTransaction::read(0x61 | 0x01, vec![0x01, 0xF4, 0x33]),
-                ];
+            fn now() -> u64 {
+                NOW.with(|now| now.get())
+            }
 
-                let i2c = I2cMock::new(&expected_transactions);
+            fn set_now(ms: u64) {
+                NOW.with(|now| now.set(ms));
+            }
 
-                let mut sensor = Scd30::new(i2c);
+            std::thread_local! {
+                static EVENTS: core::cell::RefCell<Vec<Scd30Event>> =
+                    const { core::cell::RefCell::new(Vec::new()) };
+            }
 
-                let offset = sensor.get_temperature_offset().await.unwrap();
-                assert_eq!(offset, TemperatureOffset::try_from(5.0).unwrap());
-                sensor.shutdown().done();
+            fn record_event(event: Scd30Event) {
+                EVENTS.with(|events| events.borrow_mut().push(event));
             }
 
             #[test_macro]
-            async fn set_altitude_compensation_spec_example() {
-                let expected_transactions = [I2cTransaction::write(
-                    0x61 | 0x00,
-                    vec![0x51, 0x02, 0x03, 0xE8, 0xD4],
-                )];
+            async fn maintenance_actions_are_reported() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x53, 0x06, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x52, 0x04, 0x01, 0xC2, 0x50]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04]),
+                    I2cTransaction::write(0x00, vec![0x06]),
+                ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
-                let mut sensor = Scd30::new(i2c);
+                let mut sensor = Scd30::new(i2c).on_event(record_event);
 
                 sensor
-                    .set_altitude_compensation(AltitudeCompensation::try_from(1000).unwrap())
+                    .set_automatic_self_calibration(AutomaticSelfCalibration::Inactive)
+                    .await
+                    .unwrap();
+                sensor
+                    .set_forced_recalibration(ForcedRecalibrationValue::try_from(450).unwrap())
                     .await
                     .unwrap();
+                sensor.soft_reset().await.unwrap();
+                sensor.general_call_reset().await.unwrap();
+
+                assert_eq!(
+                    EVENTS.with(|events| events.take()),
+                    [
+                        Scd30Event::AutomaticSelfCalibration(AutomaticSelfCalibration::Inactive),
+                        Scd30Event::ForcedRecalibration { reference_ppm: 450 },
+                        Scd30Event::Reset(ResetCause::SoftReset),
+                        Scd30Event::Reset(ResetCause::GeneralCallReset),
+                    ]
+                );
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn get_altitude_compensation_spec_example() {
+            async fn missed_samples_are_counted_and_reported() {
                 let expected_transactions = [
-                    I2cTransaction::write(0x61 | 0x00, vec![0x51, 0x02]),
-                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0xE8, 0xD4]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61 | 0x01,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
                 ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
-                let mut sensor = Scd30::new(i2c);
+                let mut sensor = Scd30::new(i2c).with_clock(now).on_event(record_event);
 
-                let altitude = sensor.get_altitude_compensation().await.unwrap();
-                assert_eq!(altitude, AltitudeCompensation::try_from(1000).unwrap());
+                set_now(0);
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                set_now(2000);
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                sensor.read_measurement().await.unwrap();
+                set_now(4100);
+                sensor.read_measurement().await.unwrap();
+                set_now(10_000);
+                sensor.read_measurement().await.unwrap();
+
+                assert_eq!(
+                    sensor.sample_accounting(),
+                    SampleAccounting {
+                        data_ready: 1,
+                        read: 3,
+                        missed: 2
+                    }
+                );
+                assert_eq!(
+                    EVENTS.with(|events| events.take()),
+                    [Scd30Event::SampleGap { missed: 2 }]
+                );
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn read_firmware_spec_example() {
+            async fn watchdog_reports_overdue_measurement() {
                 let expected_transactions = [
-                    I2cTransaction::write(0x61 | 0x00, vec![0xD1, 0x00]),
-                    I2cTransaction::read(0x61 | 0x01, vec![0x03, 0x42, 0xF3]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
                 ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
-                let mut sensor = Scd30::new(i2c);
+                let mut sensor = Scd30::new(i2c).with_watchdog(Watchdog {
+                    clock: now,
+                    factor: 2,
+                });
 
-                let version = sensor.read_firmware_version().await.unwrap();
-                assert_eq!(version.major, 3);
-                assert_eq!(version.minor, 66);
+                set_now(1000);
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                set_now(5000);
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::NotReady);
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                set_now(9000);
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::NotReady);
+                set_now(9001);
+                assert_eq!(
+                    sensor.is_data_ready().await.unwrap_err(),
+                    Scd30Error::MeasurementOverdue { elapsed_ms: 4001 }
+                );
                 sensor.shutdown().done();
             }
 
             #[test_macro]
-            async fn execute_soft_reset_spec_example() {
-                let expected_transactions = [I2cTransaction::write(0x61 | 0x00, vec![0xD3, 0x04])];
+            async fn watchdog_is_disarmed_when_stopped() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61 | 0x00, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x01, 0x04]),
+                    I2cTransaction::write(0x61 | 0x00, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61 | 0x01, vec![0x00, 0x00, 0x81]),
+                ];
 
                 let i2c = I2cMock::new(&expected_transactions);
 
-                let mut sensor = Scd30::new(i2c);
+                let mut sensor = Scd30::new(i2c).with_watchdog(Watchdog {
+                    clock: now,
+                    factor: 1,
+                });
 
-                sensor.soft_reset().await.unwrap();
+                set_now(0);
+                sensor.trigger_continuous_measurements(None).await.unwrap();
+                sensor.stop_continuous_measurements().await.unwrap();
+                set_now(60_000);
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::NotReady);
                 sensor.shutdown().done();
             }
 
@@ -559,11 +3531,22 @@ pub mod module {
 
                 let mut sensor = Scd30::new(i2c);
 
-                let result = sensor.read::<3>(Command::ReadFirmwareVersion);
+                let result = sensor.read_response::<FirmwareVersion>();
+                let error = result.await.unwrap_err();
+                assert_eq!(
+                    error,
+                    Scd30Error::CommandFailed {
+                        command: Command::ReadFirmwareVersion,
+                        phase: Phase::Read,
+                        error: i2c::ErrorKind::Other,
+                    }
+                );
                 assert_eq!(
-                    result.await.unwrap_err(),
-                    Scd30Error::I2cError(i2c::ErrorKind::Other)
+                    error.failed_command(),
+                    Some((Command::ReadFirmwareVersion, Phase::Read))
                 );
+                assert!(format!("{}", error)
+                    .starts_with("Reading the response of ReadFirmwareVersion failed: "));
                 sensor.shutdown().done();
             }
 
@@ -576,10 +3559,16 @@ pub mod module {
                 let mut sensor = Scd30::new(i2c);
 
                 let result = sensor.write(Command::SoftReset, None);
+                let error = result.await.unwrap_err();
                 assert_eq!(
-                    result.await.unwrap_err(),
-                    Scd30Error::I2cError(i2c::ErrorKind::Other)
+                    error,
+                    Scd30Error::CommandFailed {
+                        command: Command::SoftReset,
+                        phase: Phase::Write,
+                        error: i2c::ErrorKind::Other,
+                    }
                 );
+                assert_eq!(error.i2c_error(), Some(&i2c::ErrorKind::Other));
                 sensor.shutdown().done();
             }
 
@@ -602,3 +3591,252 @@ pub mod module {
     #[cfg(feature=feature_)]
     pub use inner::*;
 }
+
+/// Blocking SCD30 interface driving the sensor through a type-erased I2C bus and delay.
+///
+/// Every distinct I2C and delay type used with [blocking::Scd30] compiles its own copy of the
+/// command logic. Projects accessing sensors through several bus types (e.g. I2C multiplexer
+/// channels or shared-bus wrappers) can use this alias instead, so the command logic is only
+/// compiled once per I2C error type, whatever [ReadStrategy](crate::options::ReadStrategy) is
+/// used. Create it with [new_dyn](blocking::Scd30::new_dyn).
+#[cfg(all(feature = "blocking", feature = "dyn-i2c"))]
+pub type DynI2cScd30<'a, I2cErr> = blocking::Scd30<
+    &'a mut dyn embedded_hal::i2c::I2c<Error = I2cErr>,
+    &'a mut dyn embedded_hal::delay::DelayNs,
+>;
+
+#[cfg(all(feature = "blocking", feature = "dyn-i2c"))]
+impl<'a, I2cErr: embedded_hal::i2c::Error> DynI2cScd30<'a, I2cErr> {
+    /// Creates a [DynI2cScd30] reading responses according to `read_strategy`, using `delay` for
+    /// its gaps. Pass [NoDelay](crate::options::NoDelay) for
+    /// [ClockStretch](crate::options::ReadStrategy::ClockStretch).
+    pub fn new_dyn(
+        i2c: &'a mut dyn embedded_hal::i2c::I2c<Error = I2cErr>,
+        delay: &'a mut dyn embedded_hal::delay::DelayNs,
+        read_strategy: crate::options::ReadStrategy,
+    ) -> Self {
+        blocking::Scd30::new(i2c).with_read_strategy(delay, read_strategy)
+    }
+}
+
+#[cfg(all(test, feature = "blocking", feature = "dyn-i2c"))]
+mod dyn_i2c_tests {
+    use super::*;
+    use crate::{
+        data::DataStatus,
+        options::{NoDelay, ReadStrategy},
+    };
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::{
+        delay::{CheckedDelay, Transaction as DelayTransaction},
+        i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+    };
+
+    fn data_ready(sensor: &mut DynI2cScd30<'_, ErrorKind>) -> DataStatus {
+        sensor.is_data_ready().unwrap()
+    }
+
+    #[test]
+    fn erased_bus_spec_example() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+        ];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+        let mut delay = NoDelay;
+
+        let mut sensor = DynI2cScd30::new_dyn(&mut i2c, &mut delay, ReadStrategy::ClockStretch);
+
+        assert_eq!(data_ready(&mut sensor), DataStatus::Ready);
+        sensor.shutdown();
+        i2c.done();
+    }
+
+    #[test]
+    fn erased_delay_waits_for_response() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+        ];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(3000)]);
+
+        let mut sensor =
+            DynI2cScd30::new_dyn(&mut i2c, &mut delay, ReadStrategy::Delayed { gap_us: 3000 });
+
+        assert_eq!(data_ready(&mut sensor), DataStatus::Ready);
+        sensor.shutdown();
+        i2c.done();
+        delay.done();
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<I2C, D, I2cErr> blocking::Scd30<I2C, D>
+where
+    I2C: embedded_hal::i2c::I2c<Error = I2cErr>,
+    D: embedded_hal::delay::DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    /// Waits until a measurement is available for readout, polling the data ready status every
+    /// 100 ms using `delay`. Fails with [Timeout](crate::error::Scd30Error::Timeout) if no
+    /// measurement became ready within `timeout_ms` milliseconds.
+    pub fn wait_for_data_ready<W: embedded_hal::delay::DelayNs>(
+        &mut self,
+        delay: &mut W,
+        timeout_ms: u32,
+    ) -> Result<(), crate::error::Scd30Error<I2cErr>> {
+        self.wait_for_sample(delay, timeout_ms)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<I2C, D, W, I2cErr> Iterator for blocking::MeasurementSession<'_, I2C, D, W>
+where
+    I2C: embedded_hal::i2c::I2c<Error = I2cErr>,
+    D: embedded_hal::delay::DelayNs,
+    W: embedded_hal::delay::DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    type Item = Result<crate::data::Measurement, crate::error::Scd30Error<I2cErr>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sample()
+    }
+}
+
+#[cfg(feature = "ftdi")]
+impl<I2C, D, I2cErr> blocking::Scd30<I2C, D>
+where
+    I2C: embedded_hal::i2c::I2c<Error = I2cErr>,
+    D: embedded_hal::delay::DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    /// Create a new SCD30 interface for a sensor attached to a desktop through an FT232H USB
+    /// adapter, e.g. using the `I2c` of [ftdi-embedded-hal](https://docs.rs/ftdi-embedded-hal).
+    /// Responses are read using [FT232H_READ_STRATEGY](crate::options::FT232H_READ_STRATEGY),
+    /// waiting with `delay` between command and read.
+    pub fn new_ft232h(i2c: I2C, delay: D) -> Self {
+        blocking::Scd30::new(i2c).with_read_strategy(delay, crate::options::FT232H_READ_STRATEGY)
+    }
+}
+
+#[cfg(all(test, feature = "ftdi"))]
+mod ft232h_tests {
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::{
+        delay::{CheckedDelay, Transaction as DelayTransaction},
+        i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+    };
+
+    #[test]
+    fn ft232h_waits_and_retries_reads() {
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0xD1, 0x00]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x00]).with_error(nack),
+            I2cTransaction::read(0x61, vec![0x03, 0x42, 0xF3]),
+        ];
+
+        let i2c = I2cMock::new(&expected_transactions);
+        let delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(5000),
+            DelayTransaction::delay_us(5000),
+        ]);
+
+        let mut sensor = blocking::Scd30::new_ft232h(i2c, delay);
+
+        let version = sensor.read_firmware_version().unwrap();
+        assert_eq!(version.major, 3);
+        let (mut i2c, mut delay) = sensor.release();
+        i2c.done();
+        delay.done();
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod blocking_wait_tests {
+    use super::*;
+    use crate::error::Scd30Error;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+    };
+
+    #[test]
+    fn wait_for_data_ready_polls_until_ready() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x81]),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+        ];
+
+        let i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = blocking::Scd30::new(i2c);
+
+        sensor
+            .wait_for_data_ready(&mut NoopDelay::new(), 1000)
+            .unwrap();
+        sensor.shutdown().done();
+    }
+
+    #[test]
+    fn wait_for_data_ready_times_out() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x81]),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x81]),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x00, 0x81]),
+        ];
+
+        let i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = blocking::Scd30::new(i2c);
+
+        assert_eq!(
+            sensor
+                .wait_for_data_ready(&mut NoopDelay::new(), 200)
+                .unwrap_err(),
+            Scd30Error::Timeout
+        );
+        assert_eq!(sensor.error_counters().timeout, 1);
+        sensor.shutdown().done();
+    }
+
+    #[test]
+    fn measurement_session_is_an_iterator() {
+        let mut expected_transactions = vec![I2cTransaction::write(
+            0x61,
+            vec![0x00, 0x10, 0x00, 0x00, 0x81],
+        )];
+        for _ in 0..2 {
+            expected_transactions.push(I2cTransaction::write(0x61, vec![0x02, 0x02]));
+            expected_transactions.push(I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]));
+            expected_transactions.push(I2cTransaction::write(0x61, vec![0x03, 0x00]));
+            expected_transactions.push(I2cTransaction::read(
+                0x61,
+                vec![
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ],
+            ));
+        }
+        expected_transactions.push(I2cTransaction::write(0x61, vec![0x01, 0x04]));
+
+        let i2c = I2cMock::new(&expected_transactions);
+
+        let mut sensor = blocking::Scd30::new(i2c);
+        let mut delay = NoopDelay::new();
+
+        let samples: Result<Vec<_>, _> = sensor.measure_n(2, &mut delay, None).unwrap().collect();
+        assert_eq!(samples.unwrap().len(), 2);
+        sensor.shutdown().done();
+    }
+}
@@ -0,0 +1,225 @@
+//! Read-only access to the latest readings from interrupt context.
+//!
+//! Bus operations take milliseconds and must not be executed in interrupt handlers. A
+//! `PublishingScd30` wraps the interface in thread context and publishes the data ready state and
+//! every measurement it reads into an [InterruptView]. The view is placed in a `static` and queried
+//! through a [critical section](critical_section) from anywhere, e.g. a timer interrupt updating a
+//! display:
+//!
+//! ```ignore
+//! use scd30_interface::interrupt::{blocking::PublishingScd30, InterruptView};
+//!
+//! static LATEST: InterruptView = InterruptView::new();
+//!
+//! // Thread context
+//! let mut sensor = PublishingScd30::new(Scd30::new(i2c), &LATEST);
+//! loop {
+//!     if sensor.is_data_ready()? == DataStatus::Ready {
+//!         sensor.read_measurement()?;
+//!     }
+//! }
+//!
+//! // Interrupt context
+//! #[interrupt]
+//! fn TIMER0() {
+//!     if let Some(measurement) = LATEST.latest_measurement() {
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use core::cell::Cell;
+
+use critical_section::Mutex;
+use duplicate::duplicate_item;
+
+use crate::data::{DataStatus, Measurement};
+
+#[derive(Clone, Copy)]
+struct Latest {
+    measurement: Option<Measurement>,
+    data_ready: bool,
+    samples: u32,
+}
+
+/// Latest readings of a sensor, readable from interrupt context. See the
+/// [module documentation](self).
+pub struct InterruptView {
+    latest: Mutex<Cell<Latest>>,
+}
+
+impl InterruptView {
+    /// Creates an empty view, usually placed in a `static`.
+    pub const fn new() -> Self {
+        Self {
+            latest: Mutex::new(Cell::new(Latest {
+                measurement: None,
+                data_ready: false,
+                samples: 0,
+            })),
+        }
+    }
+
+    /// Returns the latest measurement read, `None` if none was read yet.
+    pub fn latest_measurement(&self) -> Option<Measurement> {
+        self.get().measurement
+    }
+
+    /// Returns whether a new measurement was ready at the last data ready query and has not been
+    /// read since.
+    pub fn is_data_ready(&self) -> bool {
+        self.get().data_ready
+    }
+
+    /// Returns the number of measurements read so far, wrapping around. A changed count indicates
+    /// a new measurement.
+    pub fn samples(&self) -> u32 {
+        self.get().samples
+    }
+
+    fn get(&self) -> Latest {
+        critical_section::with(|cs| self.latest.borrow(cs).get())
+    }
+
+    fn update(&self, update: impl FnOnce(&mut Latest)) {
+        critical_section::with(|cs| {
+            let cell = self.latest.borrow(cs);
+            let mut latest = cell.get();
+            update(&mut latest);
+            cell.set(latest);
+        });
+    }
+
+    fn publish_status(&self, status: DataStatus) {
+        self.update(|latest| latest.data_ready = status == DataStatus::Ready);
+    }
+
+    fn publish_measurement(&self, measurement: Measurement) {
+        self.update(|latest| {
+            latest.measurement = Some(measurement);
+            latest.data_ready = false;
+            latest.samples = latest.samples.wrapping_add(1);
+        });
+    }
+}
+
+impl Default for InterruptView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[duplicate_item(
+    feature_        module      async   await               i2c_trait                                       delay_trait                         test_macro;
+    ["blocking"]    [blocking]  []      [identity()]        [embedded_hal::i2c::I2c<Error = I2cErr>]        [embedded_hal::delay::DelayNs]       [test];
+    ["async"]       [asynch]    [async] [await.identity()]  [embedded_hal_async::i2c::I2c<Error = I2cErr>]  [embedded_hal_async::delay::DelayNs] [tokio::test];
+)]
+pub mod module {
+    //! SCD30 interface publishing into an interrupt view
+
+    #[cfg(feature=feature_)]
+    mod inner {
+        use crate::{
+            data::{DataStatus, Measurement},
+            error::Scd30Error,
+            interface::{module::Scd30, Identity},
+            interrupt::InterruptView,
+            options::NoDelay,
+        };
+
+        /// SCD30 interface publishing its readings into an [InterruptView], see the
+        /// [module documentation](crate::interrupt).
+        pub struct PublishingScd30<'v, I2C, D = NoDelay> {
+            sensor: Scd30<I2C, D>,
+            view: &'v InterruptView,
+        }
+
+        impl<'v, I2C, D, I2cErr> PublishingScd30<'v, I2C, D>
+        where
+            I2C: i2c_trait,
+            D: delay_trait,
+            I2cErr: embedded_hal::i2c::Error,
+        {
+            /// Publishes the readings of `sensor` into `view`.
+            pub fn new(sensor: Scd30<I2C, D>, view: &'v InterruptView) -> Self {
+                Self { sensor, view }
+            }
+
+            /// Returns the view the readings are published into.
+            pub fn view(&self) -> &'v InterruptView {
+                self.view
+            }
+
+            /// Returns the sensor for operations which are not published, e.g. changing its
+            /// configuration.
+            pub fn inner_mut(&mut self) -> &mut Scd30<I2C, D> {
+                &mut self.sensor
+            }
+
+            /// Returns the sensor.
+            pub fn into_inner(self) -> Scd30<I2C, D> {
+                self.sensor
+            }
+
+            /// Publishing version of [Scd30::is_data_ready].
+            pub async fn is_data_ready(&mut self) -> Result<DataStatus, Scd30Error<I2cErr>> {
+                let status = self.sensor.is_data_ready().await?;
+                self.view.publish_status(status);
+                Ok(status)
+            }
+
+            /// Publishing version of [Scd30::read_measurement].
+            pub async fn read_measurement(&mut self) -> Result<Measurement, Scd30Error<I2cErr>> {
+                let measurement = self.sensor.read_measurement().await?;
+                self.view.publish_measurement(measurement);
+                Ok(measurement)
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use embedded_hal::i2c::ErrorKind;
+            use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+            #[test_macro]
+            async fn readings_are_published() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x61, vec![0x02, 0x02]),
+                    I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+                    I2cTransaction::write(0x61, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                    I2cTransaction::write(0x61, vec![0x03, 0x00]).with_error(ErrorKind::Other),
+                ];
+                let i2c = I2cMock::new(&expected_transactions);
+                static VIEW: InterruptView = InterruptView::new();
+                let mut sensor = PublishingScd30::new(Scd30::new(i2c), &VIEW);
+                assert_eq!(VIEW.latest_measurement().map(|m| m.co2_concentration), None);
+
+                sensor.is_data_ready().await.unwrap();
+                assert!(VIEW.is_data_ready());
+
+                sensor.read_measurement().await.unwrap();
+                assert!(!VIEW.is_data_ready());
+                assert_eq!(VIEW.samples(), 1);
+                assert_eq!(
+                    VIEW.latest_measurement().map(|m| m.co2_concentration),
+                    Some(439.09515)
+                );
+
+                assert!(sensor.read_measurement().await.is_err());
+                assert_eq!(VIEW.samples(), 1);
+                sensor.into_inner().shutdown().done();
+            }
+        }
+    }
+
+    #[cfg(feature=feature_)]
+    pub use inner::*;
+}
@@ -34,13 +34,80 @@
 //! ```
 
 #![cfg_attr(not(test), no_std)]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "ffi", deny(unsafe_code))]
 #![deny(missing_docs)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod aggregate;
+pub mod anomaly;
+pub mod ble;
+pub mod calibration;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod codec;
 pub mod command;
+pub mod conditioning;
+#[cfg(feature = "config-file")]
+pub mod config_file;
 pub mod data;
+#[cfg(feature = "decoder")]
+pub mod decoder;
+#[cfg(feature = "dyn-i2c")]
+pub mod dynamic;
+#[cfg(feature = "embassy")]
+pub mod embassy;
+pub mod encoder;
 pub mod error;
+pub mod event;
+pub mod exposure;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
+pub mod filter;
+pub mod format;
+pub mod gaps;
+pub mod health;
+#[cfg(feature = "alloc")]
+pub mod history;
 mod interface;
+#[cfg(feature = "critical-section")]
+pub mod interrupt;
+#[cfg(feature = "linux")]
+pub mod linux;
+mod log;
+pub mod mold;
+pub mod options;
+pub mod prelude;
+pub mod recovery;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod resample;
+#[cfg(feature = "scd4x")]
+pub mod scd4x;
+pub mod scheduler;
+pub mod sensirion;
+pub mod sequencing;
+#[cfg(feature = "embassy")]
+pub mod service;
+#[cfg(feature = "embassy")]
+pub mod shared;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "blocking")]
+pub mod split;
+pub mod state;
+#[cfg(feature = "storage")]
+pub mod storage;
+pub mod supervisor;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+pub mod transaction;
+pub mod transport;
 mod util;
 
 #[cfg(feature = "blocking")]
@@ -50,3 +117,6 @@ pub use interface::blocking;
 #[cfg(feature = "async")]
 /// Async interface for the SCD30
 pub use interface::asynch;
+
+#[cfg(all(feature = "blocking", feature = "dyn-i2c"))]
+pub use interface::DynI2cScd30;
@@ -0,0 +1,40 @@
+//! I2C bus of Linux based systems like the Raspberry Pi, accessed through the `i2c-dev` interface.
+//!
+//! The bus is provided by [linux-embedded-hal](https://docs.rs/linux-embedded-hal), whose
+//! [I2cdev] is re-exported here. The quickest way to get readings is
+//! [Scd30::open](crate::blocking::Scd30::open):
+//!
+//! ```ignore
+//! use scd30_interface::blocking::Scd30;
+//!
+//! let mut sensor = Scd30::open("/dev/i2c-1")?;
+//! sensor.trigger_continuous_measurements(None)?;
+//! ```
+
+extern crate std;
+
+use std::path::Path;
+
+pub use linux_embedded_hal::{I2CError, I2cdev};
+
+#[cfg(feature = "blocking")]
+impl crate::blocking::Scd30<I2cdev> {
+    /// Opens the I2C bus at `path`, e.g. `/dev/i2c-1`, and creates an interface for the sensor
+    /// attached to it.
+    ///
+    /// # Errors
+    ///
+    /// - [I2CError] if the device file can not be opened.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, I2CError> {
+        Ok(Self::new(I2cdev::new(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn opening_sensor_on_missing_bus_fails() {
+        assert!(crate::blocking::Scd30::open("/dev/i2c-does-not-exist").is_err());
+    }
+}
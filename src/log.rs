@@ -0,0 +1,32 @@
+//! Internal logging macros forwarding to [tracing](https://docs.rs/tracing) if the `tracing`
+//! feature is enabled. Without the feature the macros expand to nothing.
+
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(target: "scd30_interface", $($arg)*);
+    };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "scd30_interface", $($arg)*);
+    };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::info!(target: "scd30_interface", $($arg)*);
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(target: "scd30_interface", $($arg)*);
+    };
+}
+
+pub(crate) use {log_debug, log_info, log_trace, log_warn};
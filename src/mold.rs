@@ -0,0 +1,186 @@
+//! Mold risk indicator for indoor climate monitors.
+//!
+//! Mold grows on building materials if the relative humidity at the surface stays above a
+//! critical humidity, the lowest isopleth of mold growth, for a sustained time. The critical
+//! humidity depends on the temperature and is approximated with the isopleth of the VTT model
+//! (Hukka and Viitanen, 1999), see [critical_humidity].
+//!
+//! The [MoldRiskTracker] accumulates the time the measured conditions are above the isopleth.
+//! Favourable conditions only briefly, e.g. while showering, do not raise the risk, as the
+//! accumulated time decreases at half the rate while the conditions are unfavourable:
+//!
+//! ```ignore
+//! let mut tracker = MoldRiskTracker::new();
+//! loop {
+//!     let risk = tracker.push(clock(), &sensor.read_measurement()?);
+//!     display.set_mold_risk(risk);
+//! }
+//! ```
+//!
+//! The sensor measures the room air, which is warmer and thus drier than cold wall surfaces. The
+//! indicator therefore underestimates the risk at thermal bridges.
+
+use crate::data::Measurement;
+
+const HOUR_MS: u64 = 3_600_000;
+
+/// Accumulated time above the isopleth in milliseconds from which the risk is
+/// [Elevated](MoldRisk::Elevated).
+pub const ELEVATED_RISK_MS: u64 = 24 * HOUR_MS;
+
+/// Accumulated time above the isopleth in milliseconds from which the risk is
+/// [High](MoldRisk::High).
+pub const HIGH_RISK_MS: u64 = 72 * HOUR_MS;
+
+/// Returns the relative humidity in % above which mold can grow at `temperature` in °C. Mold
+/// does not grow outside of 0 to 50 °C, where 100 % is returned.
+pub fn critical_humidity(temperature: f32) -> f32 {
+    if !(0.0..50.0).contains(&temperature) {
+        return 100.0;
+    }
+    if temperature > 20.0 {
+        return 80.0;
+    }
+    let t = temperature;
+    -0.00267 * t * t * t + 0.16 * t * t - 3.13 * t + 100.0
+}
+
+/// Risk of mold growth under the measured conditions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoldRisk {
+    /// Conditions do not favour mold growth.
+    None,
+    /// Conditions favour mold growth, but not for long enough to let it grow.
+    Low,
+    /// Conditions favoured mold growth for at least [ELEVATED_RISK_MS], ventilation is advised.
+    Elevated,
+    /// Conditions favoured mold growth for at least [HIGH_RISK_MS], mold growth is likely.
+    High,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MoldRisk {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            MoldRisk::None => defmt::write!(f, "None"),
+            MoldRisk::Low => defmt::write!(f, "Low"),
+            MoldRisk::Elevated => defmt::write!(f, "Elevated"),
+            MoldRisk::High => defmt::write!(f, "High"),
+        }
+    }
+}
+
+/// Evaluates the history of measurements for sustained conditions favouring mold growth. See the
+/// [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MoldRiskTracker {
+    favourable_ms: u64,
+    last: Option<(u64, bool)>,
+}
+
+impl MoldRiskTracker {
+    /// Creates a tracker without any history.
+    pub const fn new() -> Self {
+        Self {
+            favourable_ms: 0,
+            last: None,
+        }
+    }
+
+    /// Adds a measurement taken at `timestamp_ms` and returns the resulting risk. The conditions
+    /// of a measurement are assumed to hold until the next one. Measurements older than the
+    /// previous one are ignored.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: &Measurement) -> MoldRisk {
+        let favourable = measurement.humidity > critical_humidity(measurement.temperature);
+        match self.last {
+            Some((last_ms, _)) if timestamp_ms < last_ms => return self.risk(),
+            Some((last_ms, true)) => {
+                self.favourable_ms = self.favourable_ms.saturating_add(timestamp_ms - last_ms)
+            }
+            Some((last_ms, false)) => {
+                self.favourable_ms = self
+                    .favourable_ms
+                    .saturating_sub((timestamp_ms - last_ms) / 2)
+            }
+            None => {}
+        }
+        self.last = Some((timestamp_ms, favourable));
+        self.risk()
+    }
+
+    /// Returns the risk resulting from the measurements so far.
+    pub fn risk(&self) -> MoldRisk {
+        let favourable = matches!(self.last, Some((_, true)));
+        match self.favourable_ms {
+            ms if ms >= HIGH_RISK_MS => MoldRisk::High,
+            ms if ms >= ELEVATED_RISK_MS => MoldRisk::Elevated,
+            ms if ms > 0 || favourable => MoldRisk::Low,
+            _ => MoldRisk::None,
+        }
+    }
+
+    /// Returns the accumulated time in milliseconds the conditions favoured mold growth.
+    pub const fn favourable_ms(&self) -> u64 {
+        self.favourable_ms
+    }
+
+    /// Clears the history, e.g. after the affected surfaces were dried and cleaned.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn climate(temperature: f32, humidity: f32) -> Measurement {
+        Measurement::new(600.0, temperature, humidity)
+    }
+
+    #[test]
+    fn isopleth_rises_towards_low_temperatures() {
+        assert_eq!(critical_humidity(25.0), 80.0);
+        assert!((critical_humidity(20.0) - 80.04).abs() < 0.01);
+        assert!((critical_humidity(10.0) - 82.03).abs() < 0.01);
+        assert_eq!(critical_humidity(-5.0), 100.0);
+    }
+
+    #[test]
+    fn sustained_humidity_raises_risk() {
+        let mut tracker = MoldRiskTracker::new();
+        assert_eq!(tracker.push(0, &climate(21.0, 60.0)), MoldRisk::None);
+        assert_eq!(tracker.push(HOUR_MS, &climate(21.0, 85.0)), MoldRisk::Low);
+        assert_eq!(
+            tracker.push(25 * HOUR_MS, &climate(21.0, 85.0)),
+            MoldRisk::Elevated
+        );
+        assert_eq!(
+            tracker.push(73 * HOUR_MS, &climate(21.0, 60.0)),
+            MoldRisk::High
+        );
+        assert_eq!(
+            tracker.push(97 * HOUR_MS, &climate(21.0, 60.0)),
+            MoldRisk::Elevated
+        );
+        assert_eq!(tracker.favourable_ms(), 60 * HOUR_MS);
+
+        tracker.reset();
+        assert_eq!(tracker.risk(), MoldRisk::None);
+    }
+
+    #[test]
+    fn short_peaks_decay() {
+        let mut tracker = MoldRiskTracker::new();
+        tracker.push(0, &climate(22.0, 90.0));
+        tracker.push(HOUR_MS, &climate(22.0, 50.0));
+        assert_eq!(
+            tracker.push(3 * HOUR_MS, &climate(22.0, 50.0)),
+            MoldRisk::None
+        );
+        assert_eq!(
+            tracker.push(2 * HOUR_MS, &climate(22.0, 90.0)),
+            MoldRisk::None
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Options configuring the behaviour of the driver.
+
+/// Duration after starting continuous measurements during which the sensor's samples are not yet
+/// reliable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WarmUp {
+    /// The first `n` samples are affected.
+    Samples(u16),
+    /// Samples taken within the first `n` seconds are affected. The duration is converted to a
+    /// number of samples using the measurement interval configured through the driver, or the
+    /// sensor's default of 2 s.
+    Seconds(u16),
+}
+
+impl WarmUp {
+    /// Returns the number of samples affected by the warm-up for a measurement interval of
+    /// `interval` seconds.
+    pub const fn samples(&self, interval: u16) -> u16 {
+        match self {
+            WarmUp::Samples(samples) => *samples,
+            WarmUp::Seconds(seconds) => {
+                let interval = if interval == 0 { 1 } else { interval };
+                seconds.div_ceil(interval)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for WarmUp {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            WarmUp::Samples(samples) => defmt::write!(f, "{} samples", samples),
+            WarmUp::Seconds(seconds) => defmt::write!(f, "{}s", seconds),
+        }
+    }
+}
+
+/// Handling of samples read during the warm-up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WarmUpAction {
+    /// Samples are returned as usual, whether the sensor is still warming up can be checked with
+    /// `is_warming_up()`.
+    Mark,
+    /// Samples are discarded and reading a measurement returns
+    /// [WarmingUp](crate::error::Scd30Error::WarmingUp).
+    Discard,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for WarmUpAction {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            WarmUpAction::Mark => defmt::write!(f, "Mark"),
+            WarmUpAction::Discard => defmt::write!(f, "Discard"),
+        }
+    }
+}
+
+/// Policy for handling unreliable samples after starting continuous measurements.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WarmUpPolicy {
+    /// Duration of the warm-up.
+    pub period: WarmUp,
+    /// Handling of samples read during the warm-up.
+    pub action: WarmUpAction,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for WarmUpPolicy {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{} for {}", self.action, self.period)
+    }
+}
+
+/// Monotonic time source returning milliseconds, e.g. the uptime of the system.
+pub type Clock = fn() -> u64;
+
+/// Configuration of the data-ready watchdog, detecting a sensor that stopped delivering samples,
+/// e.g. after a brown-out.
+#[derive(Clone, Copy, Debug)]
+pub struct Watchdog {
+    /// Time source used to track the time since the last sample became ready.
+    pub clock: Clock,
+    /// Multiple of the measurement interval after which the next sample is considered overdue.
+    /// A factor of 0 is treated as 1.
+    pub factor: u8,
+}
+
+impl Watchdog {
+    /// Returns the time in milliseconds after which a sample is overdue for a measurement interval
+    /// of `interval` seconds.
+    pub const fn deadline_ms(&self, interval: u16) -> u64 {
+        let factor = if self.factor == 0 { 1 } else { self.factor };
+        interval as u64 * 1000 * factor as u64
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Watchdog {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Watchdog {{ factor: {} }}", self.factor)
+    }
+}
+
+/// Recommended gap between writing a command and reading its response in microseconds.
+pub const RECOMMENDED_READ_GAP_US: u32 = 3000;
+
+/// Timing of reading a response after its command was written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ReadStrategy {
+    /// The response is read right after the command. The sensor stretches the clock until the
+    /// response is available, which requires an I2C implementation handling clock stretching.
+    #[default]
+    ClockStretch,
+    /// The response is read after waiting `gap_us` microseconds, giving the sensor time to prepare
+    /// the response. Reduces clock stretching to a minimum.
+    Delayed {
+        /// Gap between command and read in microseconds, see [RECOMMENDED_READ_GAP_US].
+        gap_us: u32,
+    },
+    /// For I2C implementations not supporting clock stretching at all, e.g. on the RP2040 or bit
+    /// banged buses. The response is read after waiting `gap_us` microseconds. If the sensor is
+    /// still busy, it does not acknowledge its address and the read is retried after another gap,
+    /// up to `retries` times.
+    NoStretch {
+        /// Gap between command and read, as well as between retries, in microseconds.
+        gap_us: u32,
+        /// Number of retries of a read not acknowledged by the sensor.
+        retries: u8,
+    },
+}
+
+/// Read strategy for FT232H based USB adapters, e.g. driven through
+/// [ftdi-embedded-hal](https://docs.rs/ftdi-embedded-hal). The adapter's I2C engine can not handle
+/// clock stretching and every transfer is delayed by the USB latency, so responses are read after
+/// an extended gap and retried while the sensor is still busy.
+pub const FT232H_READ_STRATEGY: ReadStrategy = ReadStrategy::NoStretch {
+    gap_us: 5000,
+    retries: 10,
+};
+
+impl ReadStrategy {
+    pub(crate) const fn gap_us(&self) -> Option<u32> {
+        match self {
+            ReadStrategy::ClockStretch => None,
+            ReadStrategy::Delayed { gap_us } | ReadStrategy::NoStretch { gap_us, .. } => {
+                Some(*gap_us)
+            }
+        }
+    }
+
+    pub(crate) const fn retries(&self) -> u8 {
+        match self {
+            ReadStrategy::NoStretch { retries, .. } => *retries,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ReadStrategy {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            ReadStrategy::ClockStretch => defmt::write!(f, "ClockStretch"),
+            ReadStrategy::Delayed { gap_us } => defmt::write!(f, "Delayed({}us)", gap_us),
+            ReadStrategy::NoStretch { gap_us, retries } => {
+                defmt::write!(f, "NoStretch({}us, {} retries)", gap_us, retries)
+            }
+        }
+    }
+}
+
+/// Validation of the CRC the sensor appends to every 16-bit word of its responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CrcValidation {
+    /// Responses failing the CRC check are rejected with
+    /// [CrcFailed](crate::error::DataError::CrcFailed).
+    #[default]
+    Enabled,
+    /// CRCs are not checked. Responses corrupted on the bus, e.g. by noise on long cables, are
+    /// accepted as valid data and may contain arbitrary values. Only use this on paths which can
+    /// tolerate wrong readings, e.g. when the values are filtered or the bus is known to be
+    /// reliable.
+    Disabled,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CrcValidation {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            CrcValidation::Enabled => defmt::write!(f, "Enabled"),
+            CrcValidation::Disabled => defmt::write!(f, "Disabled"),
+        }
+    }
+}
+
+/// Verification of the data ready status and measurements before they are returned, for
+/// applications feeding the readings into safety interlocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ReadVerification {
+    /// Every response is read once.
+    #[default]
+    Disabled,
+    /// A response failing the CRC check is read again once before failing.
+    RetryOnCrc,
+    /// Every response is read twice and both reads must be identical, otherwise
+    /// [InconsistentReads](crate::error::DataError::InconsistentReads) is returned. Doubles the
+    /// I2C traffic of these reads.
+    DoubleRead,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ReadVerification {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            ReadVerification::Disabled => defmt::write!(f, "Disabled"),
+            ReadVerification::RetryOnCrc => defmt::write!(f, "RetryOnCrc"),
+            ReadVerification::DoubleRead => defmt::write!(f, "DoubleRead"),
+        }
+    }
+}
+
+/// Handling of values outside the documented range, e.g. an automatic self-calibration state
+/// other than `0` or `1` returned by a newer firmware.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ValueDecoding {
+    /// Unexpected values are rejected with
+    /// [UnexpectedValueReceived](crate::error::DataError::UnexpectedValueReceived).
+    #[default]
+    Strict,
+    /// A response with an unexpected value is read again once before failing.
+    RetryOnce,
+    /// Unexpected values of the [DataStatus](crate::data::DataStatus) and
+    /// [AutomaticSelfCalibration](crate::data::AutomaticSelfCalibration) are returned as their
+    /// `Unknown` variant instead of failing the read.
+    Lenient,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ValueDecoding {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            ValueDecoding::Strict => defmt::write!(f, "Strict"),
+            ValueDecoding::RetryOnce => defmt::write!(f, "RetryOnce"),
+            ValueDecoding::Lenient => defmt::write!(f, "Lenient"),
+        }
+    }
+}
+
+/// Delay doing nothing, used by the interface as long as no delay is required by the configured
+/// [ReadStrategy].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_based_warm_up_ignores_interval() {
+        assert_eq!(WarmUp::Samples(5).samples(2), 5);
+        assert_eq!(WarmUp::Samples(5).samples(60), 5);
+    }
+
+    #[test]
+    fn time_based_warm_up_rounds_up_to_whole_samples() {
+        assert_eq!(WarmUp::Seconds(10).samples(2), 5);
+        assert_eq!(WarmUp::Seconds(11).samples(2), 6);
+        assert_eq!(WarmUp::Seconds(10).samples(0), 10);
+    }
+
+    #[test]
+    fn watchdog_deadline_scales_with_interval() {
+        let watchdog = Watchdog {
+            clock: || 0,
+            factor: 3,
+        };
+        assert_eq!(watchdog.deadline_ms(2), 6000);
+        assert_eq!(watchdog.deadline_ms(1800), 5_400_000);
+        let watchdog = Watchdog {
+            factor: 0,
+            ..watchdog
+        };
+        assert_eq!(watchdog.deadline_ms(2), 2000);
+    }
+}
@@ -0,0 +1,38 @@
+//! Commonly used types and traits, to get going with a single import:
+//!
+//! ```
+//! use scd30_interface::{encoder::Csv, prelude::*};
+//!
+//! fn encoded_len(encoder: &dyn MeasurementEncoder, measurement: &Measurement) -> usize {
+//!     let mut buf = [0; 64];
+//!     encoder.encode(measurement, &mut buf).unwrap_or(0)
+//! }
+//!
+//! let measurement = Measurement::new(811.6, 23.44, 45.06);
+//! assert_eq!(encoded_len(&Csv, &measurement), 14);
+//! assert!(MeasurementInterval::try_from(1).is_err());
+//! ```
+//!
+//! With the `blocking` feature the sensor is exported as `Scd30`, with the `async` feature as
+//! `AsyncScd30`, so both can be used at once.
+
+#[cfg(feature = "blocking")]
+pub use crate::blocking::Scd30;
+
+#[cfg(feature = "async")]
+pub use crate::asynch::Scd30 as AsyncScd30;
+
+#[cfg(feature = "dyn-i2c")]
+pub use crate::dynamic::AnyScd30;
+
+#[cfg(feature = "async")]
+pub use crate::transport::AsyncScd30Transport;
+
+pub use crate::{
+    data::*,
+    encoder::MeasurementEncoder,
+    error::{DataError, Scd30Error},
+    filter::Filter,
+    transaction::ConfigTransaction,
+    transport::Scd30Transport,
+};
@@ -0,0 +1,103 @@
+//! Recovery of an I2C bus blocked by the sensor.
+//!
+//! If the sensor browns out or is reset in the middle of a transfer, it may keep pulling SDA low
+//! while waiting for the remaining clock pulses. The bus stays blocked until these pulses are
+//! supplied, which the I2C peripheral can not do on its own, see
+//! [RecoveryAction::BusClear](crate::error::RecoveryAction::BusClear).
+//!
+//! To recover, release the I2C peripheral, e.g. with `Scd30::shutdown()`, reconfigure its pins as
+//! open drain outputs, call [clear_bus] and afterwards recreate the I2C peripheral and the
+//! interface.
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{Error, ErrorKind, OutputPin},
+};
+
+/// Maximum number of clock pulses needed to complete a pending byte and its acknowledge bit.
+const CLOCK_PULSES: u8 = 9;
+/// Half of the clock period for 100 kHz.
+const HALF_PERIOD_US: u32 = 5;
+
+/// Clocks out 9 pulses on `scl` with `sda` released, so a device stuck in the middle of a transfer
+/// can finish it, and then generates a stop condition to reset the bus state of all devices.
+///
+/// # Errors
+///
+/// - The [ErrorKind] of the pin failing to be set.
+pub fn clear_bus<SCL, SDA, D>(scl: &mut SCL, sda: &mut SDA, delay: &mut D) -> Result<(), ErrorKind>
+where
+    SCL: OutputPin,
+    SDA: OutputPin,
+    D: DelayNs,
+{
+    sda.set_high().map_err(|error| error.kind())?;
+    scl.set_high().map_err(|error| error.kind())?;
+    delay.delay_us(HALF_PERIOD_US);
+
+    for _ in 0..CLOCK_PULSES {
+        scl.set_low().map_err(|error| error.kind())?;
+        delay.delay_us(HALF_PERIOD_US);
+        scl.set_high().map_err(|error| error.kind())?;
+        delay.delay_us(HALF_PERIOD_US);
+    }
+
+    // Stop condition: SDA rising while SCL is high.
+    scl.set_low().map_err(|error| error.kind())?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_low().map_err(|error| error.kind())?;
+    delay.delay_us(HALF_PERIOD_US);
+    scl.set_high().map_err(|error| error.kind())?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_high().map_err(|error| error.kind())?;
+    delay.delay_us(HALF_PERIOD_US);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        digital::{Mock as PinMock, State, Transaction as PinTransaction},
+        MockError,
+    };
+
+    #[test]
+    fn clear_bus_clocks_nine_pulses_and_stops() {
+        let mut scl_transactions = vec![PinTransaction::set(State::High)];
+        for _ in 0..9 {
+            scl_transactions.push(PinTransaction::set(State::Low));
+            scl_transactions.push(PinTransaction::set(State::High));
+        }
+        scl_transactions.push(PinTransaction::set(State::Low));
+        scl_transactions.push(PinTransaction::set(State::High));
+        let sda_transactions = [
+            PinTransaction::set(State::High),
+            PinTransaction::set(State::Low),
+            PinTransaction::set(State::High),
+        ];
+
+        let mut scl = PinMock::new(&scl_transactions);
+        let mut sda = PinMock::new(&sda_transactions);
+
+        clear_bus(&mut scl, &mut sda, &mut NoopDelay::new()).unwrap();
+        scl.done();
+        sda.done();
+    }
+
+    #[test]
+    fn clear_bus_errors_on_pin_error() {
+        let mut scl = PinMock::new(&[]);
+        let mut sda =
+            PinMock::new(&[PinTransaction::set(State::High)
+                .with_error(MockError::Io(std::io::ErrorKind::Other))]);
+
+        assert_eq!(
+            clear_bus(&mut scl, &mut sda, &mut NoopDelay::new()),
+            Err(ErrorKind::Other)
+        );
+        scl.done();
+        sda.done();
+    }
+}
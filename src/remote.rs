@@ -0,0 +1,445 @@
+//! Remote access to a sensor attached to another controller through a serial link.
+//!
+//! A [RemoteServer] runs on the controller next to the sensor, e.g. a development board, and
+//! forwards the frames received over a serial link onto its I2C bus. On the other end of the link,
+//! e.g. a lab PC, [RemoteScd30] drives the sensor with the same API as a locally attached one:
+//!
+//! ```ignore
+//! // Development board
+//! let mut server = RemoteServer::new(uart, i2c);
+//! let error = server.run();
+//!
+//! // Lab PC
+//! let mut sensor = RemoteScd30::remote(serial_port);
+//! sensor.trigger_continuous_measurements(None)?;
+//! let measurement = sensor.read_measurement()?;
+//! ```
+//!
+//! The link carries [postcard](https://docs.rs/postcard) encoded requests and responses, framed
+//! with COBS and delimited by a zero byte. Every request is answered by exactly one response, so
+//! the client can only have a single request in flight. The serial links are accessed through the
+//! [embedded_io] traits.
+
+use embedded_hal::i2c::{
+    self, Error as _, ErrorKind, ErrorType, NoAcknowledgeSource, SevenBitAddress,
+};
+use embedded_io::{Read, ReadExactError, Write};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::transport::{Scd30Transport, TransportBus};
+
+/// Longest frame which can be written to or read from the sensor remotely, in bytes.
+pub const MAX_FRAME_LEN: usize = 32;
+
+/// Size of the buffers holding an encoded message, large enough for a frame of
+/// [MAX_FRAME_LEN] and the COBS overhead.
+const MESSAGE_LEN: usize = MAX_FRAME_LEN + 8;
+
+/// Errors of the remote link, emitted by the [RemoteServer] and as bus error of [RemoteScd30].
+#[derive(Debug, Error, PartialEq)]
+pub enum RemoteError<E: embedded_io::Error> {
+    /// Emitted when the serial link failed.
+    #[error("Serial link failed: {0:?}")]
+    Link(E),
+    /// Emitted when the serial link reached its end.
+    #[error("Serial link closed")]
+    Disconnected,
+    /// Emitted when a message could not be encoded or decoded, e.g. due to a corrupted
+    /// transmission or a frame longer than [MAX_FRAME_LEN].
+    #[error("Malformed message")]
+    Malformed,
+    /// Emitted when the I2C bus of the server reported an error.
+    #[error("Remote bus failed: {0:?}")]
+    Bus(ErrorKind),
+}
+
+#[cfg(feature = "defmt")]
+impl<E: embedded_io::Error> defmt::Format for RemoteError<E> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            RemoteError::Link(error) => {
+                defmt::write!(f, "Link({})", defmt::Debug2Format(&error.kind()))
+            }
+            RemoteError::Disconnected => defmt::write!(f, "Disconnected"),
+            RemoteError::Malformed => defmt::write!(f, "Malformed"),
+            RemoteError::Bus(kind) => defmt::write!(f, "Bus({})", kind),
+        }
+    }
+}
+
+impl<E: embedded_io::Error> i2c::Error for RemoteError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            RemoteError::Bus(kind) => *kind,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl<E: embedded_io::Error> From<ReadExactError<E>> for RemoteError<E> {
+    fn from(error: ReadExactError<E>) -> Self {
+        match error {
+            ReadExactError::UnexpectedEof => RemoteError::Disconnected,
+            ReadExactError::Other(error) => RemoteError::Link(error),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Request<'a> {
+    Write { address: u8, frame: &'a [u8] },
+    Read { address: u8, len: u8 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Response<'a> {
+    Written,
+    Read(&'a [u8]),
+    Failed(BusError),
+    Malformed,
+}
+
+/// [ErrorKind] as transmitted over the link.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum BusError {
+    NoAcknowledgeAddress,
+    NoAcknowledgeData,
+    NoAcknowledge,
+    ArbitrationLoss,
+    Bus,
+    Overrun,
+    Other,
+}
+
+impl From<ErrorKind> for BusError {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address) => {
+                BusError::NoAcknowledgeAddress
+            }
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data) => BusError::NoAcknowledgeData,
+            ErrorKind::NoAcknowledge(_) => BusError::NoAcknowledge,
+            ErrorKind::ArbitrationLoss => BusError::ArbitrationLoss,
+            ErrorKind::Bus => BusError::Bus,
+            ErrorKind::Overrun => BusError::Overrun,
+            _ => BusError::Other,
+        }
+    }
+}
+
+impl From<BusError> for ErrorKind {
+    fn from(error: BusError) -> Self {
+        match error {
+            BusError::NoAcknowledgeAddress => {
+                ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+            }
+            BusError::NoAcknowledgeData => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data),
+            BusError::NoAcknowledge => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+            BusError::ArbitrationLoss => ErrorKind::ArbitrationLoss,
+            BusError::Bus => ErrorKind::Bus,
+            BusError::Overrun => ErrorKind::Overrun,
+            BusError::Other => ErrorKind::Other,
+        }
+    }
+}
+
+fn send<S: Write, T: Serialize>(link: &mut S, message: &T) -> Result<(), RemoteError<S::Error>> {
+    let mut buffer = [0; MESSAGE_LEN];
+    let encoded =
+        postcard::to_slice_cobs(message, &mut buffer).map_err(|_| RemoteError::Malformed)?;
+    link.write_all(encoded).map_err(RemoteError::Link)?;
+    link.flush().map_err(RemoteError::Link)
+}
+
+/// Reads the next message, including the delimiter, into `buffer`. Messages exceeding the buffer
+/// are skipped and reported as [RemoteError::Malformed].
+fn receive<'b, S: Read>(
+    link: &mut S,
+    buffer: &'b mut [u8; MESSAGE_LEN],
+) -> Result<&'b mut [u8], RemoteError<S::Error>> {
+    let mut len = 0;
+    loop {
+        let mut byte = [0];
+        link.read_exact(&mut byte)?;
+        if len < MESSAGE_LEN {
+            buffer[len] = byte[0];
+        }
+        len += 1;
+        if byte[0] == 0 {
+            return match buffer.get_mut(..len) {
+                Some(message) => Ok(message),
+                None => Err(RemoteError::Malformed),
+            };
+        }
+    }
+}
+
+/// Server forwarding the frames received over a serial link to the sensor on its I2C bus, see
+/// the [module documentation](self).
+pub struct RemoteServer<S, I2C> {
+    link: S,
+    i2c: I2C,
+}
+
+impl<S: Read + Write, I2C: i2c::I2c> RemoteServer<S, I2C> {
+    /// Creates a server answering the requests received on `link` with the sensor on `i2c`.
+    pub const fn new(link: S, i2c: I2C) -> Self {
+        Self { link, i2c }
+    }
+
+    /// Waits for the next request and answers it. Malformed requests are answered as such, so
+    /// the client does not wait forever.
+    ///
+    /// # Errors
+    ///
+    /// - [RemoteError::Link] and [RemoteError::Disconnected] if the serial link failed.
+    pub fn serve(&mut self) -> Result<(), RemoteError<S::Error>> {
+        let mut buffer = [0; MESSAGE_LEN];
+        let request = match receive(&mut self.link, &mut buffer) {
+            Ok(message) => postcard::from_bytes_cobs::<Request>(message).ok(),
+            Err(RemoteError::Malformed) => None,
+            Err(error) => return Err(error),
+        };
+
+        let mut frame = [0; MAX_FRAME_LEN];
+        let response = match request {
+            Some(Request::Write { address, frame }) => match self.i2c.write(address, frame) {
+                Ok(()) => Response::Written,
+                Err(error) => Response::Failed(error.kind().into()),
+            },
+            Some(Request::Read { address, len }) if usize::from(len) <= MAX_FRAME_LEN => {
+                let frame = &mut frame[..usize::from(len)];
+                match self.i2c.read(address, frame) {
+                    Ok(()) => Response::Read(frame),
+                    Err(error) => Response::Failed(error.kind().into()),
+                }
+            }
+            Some(Request::Read { .. }) | None => Response::Malformed,
+        };
+        send(&mut self.link, &response)
+    }
+
+    /// Answers requests until the serial link fails, returning the error.
+    pub fn run(&mut self) -> RemoteError<S::Error> {
+        loop {
+            if let Err(error) = self.serve() {
+                return error;
+            }
+        }
+    }
+
+    /// Returns the serial link and the I2C bus.
+    pub fn release(self) -> (S, I2C) {
+        (self.link, self.i2c)
+    }
+}
+
+/// Client end of the serial link to a [RemoteServer], transporting the frames of a
+/// [RemoteScd30].
+pub struct RemoteLink<S> {
+    link: S,
+}
+
+impl<S> RemoteLink<S> {
+    /// Creates a client communicating with the server over `link`.
+    pub const fn new(link: S) -> Self {
+        Self { link }
+    }
+
+    /// Returns the serial link.
+    pub fn into_inner(self) -> S {
+        self.link
+    }
+}
+
+impl<S: Read + Write> RemoteLink<S> {
+    fn request(
+        &mut self,
+        request: &Request,
+        on_response: impl FnOnce(Response) -> Result<(), RemoteError<S::Error>>,
+    ) -> Result<(), RemoteError<S::Error>> {
+        send(&mut self.link, request)?;
+        let mut buffer = [0; MESSAGE_LEN];
+        let message = receive(&mut self.link, &mut buffer)?;
+        match postcard::from_bytes_cobs(message) {
+            Ok(Response::Failed(error)) => Err(RemoteError::Bus(error.into())),
+            Ok(response) => on_response(response),
+            Err(_) => Err(RemoteError::Malformed),
+        }
+    }
+}
+
+impl<S: embedded_io::ErrorType> ErrorType for RemoteLink<S> {
+    type Error = RemoteError<S::Error>;
+}
+
+impl<S: Read + Write> Scd30Transport for RemoteLink<S> {
+    fn write_frame(&mut self, address: SevenBitAddress, frame: &[u8]) -> Result<(), Self::Error> {
+        if frame.len() > MAX_FRAME_LEN {
+            return Err(RemoteError::Malformed);
+        }
+        self.request(
+            &Request::Write { address, frame },
+            |response| match response {
+                Response::Written => Ok(()),
+                _ => Err(RemoteError::Malformed),
+            },
+        )
+    }
+
+    fn read_frame(
+        &mut self,
+        address: SevenBitAddress,
+        frame: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let len = u8::try_from(frame.len())
+            .ok()
+            .filter(|&len| usize::from(len) <= MAX_FRAME_LEN)
+            .ok_or(RemoteError::Malformed)?;
+        self.request(&Request::Read { address, len }, |response| match response {
+            Response::Read(data) if data.len() == frame.len() => {
+                frame.copy_from_slice(data);
+                Ok(())
+            }
+            _ => Err(RemoteError::Malformed),
+        })
+    }
+}
+
+/// Blocking SCD30 interface driving a sensor attached to a [RemoteServer].
+pub type RemoteScd30<S> = crate::blocking::Scd30<TransportBus<RemoteLink<S>>>;
+
+impl<S: Read + Write> RemoteScd30<S> {
+    /// Creates an interface for the sensor attached to the [RemoteServer] on the other end of
+    /// `link`.
+    pub fn remote(link: S) -> Self {
+        Self::new(TransportBus::new(RemoteLink::new(link)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use std::collections::VecDeque;
+
+    #[derive(Default)]
+    struct Pipe {
+        rx: VecDeque<u8>,
+        tx: Vec<u8>,
+    }
+
+    impl embedded_io::ErrorType for Pipe {
+        type Error = Infallible;
+    }
+
+    impl Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let len = buf.len().min(self.rx.len());
+            for (byte, received) in buf.iter_mut().zip(self.rx.drain(..len)) {
+                *byte = received;
+            }
+            Ok(len)
+        }
+    }
+
+    impl Write for Pipe {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.tx.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    /// Serial link to a server, which answers every request once it is flushed.
+    struct Loopback {
+        server: RemoteServer<Pipe, I2cMock>,
+        client: Pipe,
+    }
+
+    impl embedded_io::ErrorType for Loopback {
+        type Error = Infallible;
+    }
+
+    impl Read for Loopback {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            self.client.read(buf)
+        }
+    }
+
+    impl Write for Loopback {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.server.link.rx.extend(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            self.server.serve().unwrap();
+            self.client.rx.extend(self.server.link.tx.drain(..));
+            Ok(())
+        }
+    }
+
+    fn remote(expected_transactions: &[I2cTransaction]) -> RemoteScd30<Loopback> {
+        RemoteScd30::remote(Loopback {
+            server: RemoteServer::new(Pipe::default(), I2cMock::new(expected_transactions)),
+            client: Pipe::default(),
+        })
+    }
+
+    fn finish(sensor: RemoteScd30<Loopback>) {
+        let (_, mut i2c) = sensor.shutdown().into_inner().into_inner().server.release();
+        i2c.done();
+    }
+
+    #[test]
+    fn sensor_is_driven_over_the_link() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x03, 0x00]),
+            I2cTransaction::read(
+                0x61,
+                vec![
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ],
+            ),
+        ];
+        let mut sensor = remote(&expected_transactions);
+
+        let measurement = sensor.read_measurement().unwrap();
+        assert_eq!(measurement.co2_concentration, 439.09515);
+        assert_eq!(measurement.humidity, 48.806744);
+        finish(sensor);
+    }
+
+    #[test]
+    fn remote_bus_errors_are_passed_on() {
+        let expected_transactions = [I2cTransaction::write(0x61, vec![0xD1, 0x00])
+            .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))];
+        let mut sensor = remote(&expected_transactions);
+
+        let error = sensor.read_firmware_version().unwrap_err();
+        assert_eq!(error.code(), "E_NACK");
+        finish(sensor);
+    }
+
+    #[test]
+    fn malformed_requests_are_answered() {
+        let mut server = RemoteServer::new(Pipe::default(), I2cMock::new(&[]));
+        server.link.rx.extend([0x05, 0xFF, 0xFF, 0xFF, 0xFF, 0x00]);
+        server.serve().unwrap();
+        assert_eq!(server.serve(), Err(RemoteError::Disconnected));
+
+        let (mut link, mut i2c) = server.release();
+        assert_eq!(
+            postcard::from_bytes_cobs::<Response>(&mut link.tx),
+            Ok(Response::Malformed)
+        );
+        i2c.done();
+    }
+}
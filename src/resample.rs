@@ -0,0 +1,214 @@
+//! Resampling of measurements onto a fixed time grid.
+//!
+//! Polled measurements arrive with jitter, e.g. as the poll loop also serves other tasks. A
+//! [Resampler] turns the timestamped measurements into points on a grid of a fixed period, so
+//! analytics and graphs can rely on uniform spacing:
+//!
+//! ```
+//! use scd30_interface::{
+//!     data::Measurement,
+//!     resample::{Interpolation, Resampler},
+//! };
+//!
+//! let mut resampler = Resampler::new(1000, Interpolation::Linear);
+//! resampler.push(500, &Measurement::new(400.0, 20.0, 40.0)).count();
+//! let points: Vec<_> = resampler
+//!     .push(4500, &Measurement::new(800.0, 20.0, 40.0))
+//!     .map(|(timestamp_ms, measurement)| (timestamp_ms, measurement.co2_concentration))
+//!     .collect();
+//! assert_eq!(
+//!     points,
+//!     [(1000, 450.0), (2000, 550.0), (3000, 650.0), (4000, 750.0)]
+//! );
+//! ```
+//!
+//! Grid points are multiples of the period in the time base of the timestamps.
+
+use crate::data::Measurement;
+
+/// How values between two measurements are derived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// The previous measurement holds until the next one.
+    #[default]
+    Hold,
+    /// Values are interpolated linearly between the measurements.
+    Linear,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Interpolation {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Interpolation::Hold => defmt::write!(f, "Hold"),
+            Interpolation::Linear => defmt::write!(f, "Linear"),
+        }
+    }
+}
+
+/// Resamples timestamped measurements onto a grid of a fixed period. See the
+/// [module documentation](self).
+#[derive(Clone, Copy, Debug)]
+pub struct Resampler {
+    period_ms: u64,
+    interpolation: Interpolation,
+    max_gap_ms: u64,
+    last: Option<(u64, Measurement)>,
+    next_ms: u64,
+}
+
+impl Resampler {
+    /// Creates a resampler with a grid of `period_ms` milliseconds. A period of 0 is treated as
+    /// 1 ms.
+    pub const fn new(period_ms: u64, interpolation: Interpolation) -> Self {
+        Self {
+            period_ms: if period_ms == 0 { 1 } else { period_ms },
+            interpolation,
+            max_gap_ms: u64::MAX,
+            last: None,
+            next_ms: 0,
+        }
+    }
+
+    /// Sets the longest time in milliseconds between two measurements to resample. No grid points
+    /// are created within longer gaps, e.g. sensor outages, instead of bridging them with made-up
+    /// values. By default, all gaps are bridged.
+    pub const fn with_max_gap(mut self, max_gap_ms: u64) -> Self {
+        self.max_gap_ms = max_gap_ms;
+        self
+    }
+
+    /// Adds a measurement taken at `timestamp_ms` and returns the grid points up to it, which
+    /// were not returned before. Measurements older than the previous one are ignored.
+    pub fn push(&mut self, timestamp_ms: u64, measurement: &Measurement) -> GridPoints {
+        let current = (timestamp_ms, *measurement);
+        let previous = match self.last {
+            Some((last_ms, _)) if timestamp_ms < last_ms => {
+                return GridPoints::empty(self.period_ms)
+            }
+            Some((last_ms, last)) if timestamp_ms - last_ms <= self.max_gap_ms => (last_ms, last),
+            _ => {
+                self.next_ms = timestamp_ms.next_multiple_of(self.period_ms);
+                current
+            }
+        };
+        self.last = Some(current);
+        let points = GridPoints {
+            period_ms: self.period_ms,
+            interpolation: self.interpolation,
+            previous,
+            current,
+            next_ms: self.next_ms,
+        };
+        if self.next_ms <= timestamp_ms {
+            self.next_ms = (timestamp_ms + 1).next_multiple_of(self.period_ms);
+        }
+        points
+    }
+
+    /// Forgets the previous measurement, e.g. after the sensor was restarted.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Iterator over the grid points between two measurements, returned by [Resampler::push]. Yields
+/// the timestamp of each point with the derived measurement.
+#[derive(Clone, Copy, Debug)]
+pub struct GridPoints {
+    period_ms: u64,
+    interpolation: Interpolation,
+    previous: (u64, Measurement),
+    current: (u64, Measurement),
+    next_ms: u64,
+}
+
+impl GridPoints {
+    fn empty(period_ms: u64) -> Self {
+        let none = (0, Measurement::new(0.0, 0.0, 0.0));
+        Self {
+            period_ms,
+            interpolation: Interpolation::Hold,
+            previous: none,
+            current: none,
+            next_ms: 1,
+        }
+    }
+}
+
+impl Iterator for GridPoints {
+    type Item = (u64, Measurement);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (previous_ms, previous) = self.previous;
+        let (current_ms, current) = self.current;
+        let timestamp_ms = self.next_ms;
+        if timestamp_ms > current_ms {
+            return None;
+        }
+        self.next_ms += self.period_ms;
+        let measurement = match self.interpolation {
+            _ if timestamp_ms == current_ms => current,
+            Interpolation::Hold => previous,
+            Interpolation::Linear => {
+                let weight =
+                    (timestamp_ms - previous_ms) as f32 / (current_ms - previous_ms) as f32;
+                let lerp = |from: f32, to: f32| from + (to - from) * weight;
+                Measurement::new(
+                    lerp(previous.co2_concentration, current.co2_concentration),
+                    lerp(previous.temperature, current.temperature),
+                    lerp(previous.humidity, current.humidity),
+                )
+            }
+        };
+        Some((timestamp_ms, measurement))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn co2(ppm: f32) -> Measurement {
+        Measurement::new(ppm, 21.0, 45.0)
+    }
+
+    fn resample(resampler: &mut Resampler, timestamp_ms: u64, ppm: f32) -> Vec<(u64, f32)> {
+        resampler
+            .push(timestamp_ms, &co2(ppm))
+            .map(|(timestamp_ms, measurement)| (timestamp_ms, measurement.co2_concentration))
+            .collect()
+    }
+
+    #[test]
+    fn hold_repeats_previous_measurement() {
+        let mut resampler = Resampler::new(1000, Interpolation::Hold);
+        assert_eq!(resample(&mut resampler, 0, 400.0), [(0, 400.0)]);
+        assert_eq!(resample(&mut resampler, 950, 500.0), []);
+        assert_eq!(
+            resample(&mut resampler, 3000, 600.0),
+            [(1000, 500.0), (2000, 500.0), (3000, 600.0)]
+        );
+        assert_eq!(resample(&mut resampler, 3900, 700.0), []);
+        assert_eq!(resample(&mut resampler, 3500, 800.0), []);
+        assert_eq!(resample(&mut resampler, 4100, 800.0), [(4000, 700.0)]);
+    }
+
+    #[test]
+    fn linear_interpolates_between_measurements() {
+        let mut resampler = Resampler::new(1000, Interpolation::Linear);
+        assert_eq!(resample(&mut resampler, 500, 400.0), []);
+        assert_eq!(
+            resample(&mut resampler, 2500, 600.0),
+            [(1000, 450.0), (2000, 550.0)]
+        );
+    }
+
+    #[test]
+    fn long_gaps_are_not_bridged() {
+        let mut resampler = Resampler::new(1000, Interpolation::Linear).with_max_gap(2000);
+        resample(&mut resampler, 0, 400.0);
+        assert_eq!(resample(&mut resampler, 5500, 600.0), []);
+        assert_eq!(resample(&mut resampler, 6500, 800.0), [(6000, 700.0)]);
+    }
+}
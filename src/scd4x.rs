@@ -0,0 +1,509 @@
+//! Driver for Sensirion's [SCD4x](https://sensirion.com/products/catalog/SCD41) CO2 sensors, the
+//! SCD40 and SCD41.
+//!
+//! The SCD4x uses the same framing as the SCD30, so the data types of this crate are reused where
+//! the sensors agree, e.g. [Measurement] and [AltitudeCompensation]. Unlike the SCD30, the SCD4x
+//! does not stretch the clock while executing a command, so the driver waits for the execution
+//! time of each command using the supplied delay.
+//!
+//! ```ignore
+//! use scd30_interface::scd4x::blocking::Scd4x;
+//!
+//! let mut sensor = Scd4x::new(i2c, delay);
+//! sensor.start_periodic_measurement()?;
+//! loop {
+//!     if sensor.is_data_ready()? == DataStatus::Ready {
+//!         let measurement = sensor.read_measurement()?;
+//!     }
+//! }
+//! ```
+//!
+//! [Measurement]: crate::data::Measurement
+//! [AltitudeCompensation]: crate::data::AltitudeCompensation
+
+use duplicate::duplicate_item;
+
+/// Commands of the SCD4x according to its datasheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scd4xCommand {
+    /// Starts periodic measurements every 5 s.
+    StartPeriodicMeasurement = 0x21B1,
+    /// Reads out a measurement.
+    ReadMeasurement = 0xEC05,
+    /// Stops periodic measurements.
+    StopPeriodicMeasurement = 0x3F86,
+    /// Sets the temperature offset.
+    SetTemperatureOffset = 0x241D,
+    /// Reads out the temperature offset.
+    GetTemperatureOffset = 0x2318,
+    /// Sets the altitude above sea level.
+    SetSensorAltitude = 0x2427,
+    /// Reads out the altitude above sea level.
+    GetSensorAltitude = 0x2322,
+    /// Sets the ambient pressure, overriding the altitude compensation.
+    SetAmbientPressure = 0xE000,
+    /// Recalibrates the sensor to a reference CO2 concentration.
+    PerformForcedRecalibration = 0x362F,
+    /// (De-)Activates the automatic self-calibration.
+    SetAutomaticSelfCalibrationEnabled = 0x2416,
+    /// Reads out whether the automatic self-calibration is active.
+    GetAutomaticSelfCalibrationEnabled = 0x2313,
+    /// Starts periodic measurements every 30 s.
+    StartLowPowerPeriodicMeasurement = 0x21AC,
+    /// Queries whether a measurement can be read out.
+    GetDataReadyStatus = 0xE4B8,
+    /// Stores the settings in the sensor's EEPROM.
+    PersistSettings = 0x3615,
+    /// Reads out the 48-bit serial number.
+    GetSerialNumber = 0x3682,
+    /// Runs the built-in self test.
+    PerformSelfTest = 0x3639,
+    /// Resets all settings and erases the calibration history.
+    PerformFactoryReset = 0x3632,
+    /// Reloads the settings stored in the EEPROM.
+    Reinit = 0x3646,
+    /// Triggers a single measurement, SCD41 only.
+    MeasureSingleShot = 0x219D,
+    /// Puts the sensor into sleep mode, SCD41 only.
+    PowerDown = 0x36E0,
+    /// Wakes the sensor from sleep mode, SCD41 only.
+    WakeUp = 0x36F6,
+}
+
+impl Scd4xCommand {
+    /// Returns the raw 16-bit opcode of the command.
+    pub const fn opcode(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Returns the maximum time in milliseconds the sensor needs to execute the command, before
+    /// its response can be read or the next command can be sent.
+    pub const fn execution_time_ms(&self) -> u32 {
+        match self {
+            Scd4xCommand::StartPeriodicMeasurement
+            | Scd4xCommand::StartLowPowerPeriodicMeasurement => 0,
+            Scd4xCommand::StopPeriodicMeasurement => 500,
+            Scd4xCommand::PerformForcedRecalibration => 400,
+            Scd4xCommand::PersistSettings => 800,
+            Scd4xCommand::PerformSelfTest => 10_000,
+            Scd4xCommand::PerformFactoryReset => 1200,
+            Scd4xCommand::Reinit | Scd4xCommand::WakeUp => 30,
+            Scd4xCommand::MeasureSingleShot => 5000,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Scd4xCommand {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u16:#06X}", self.opcode())
+    }
+}
+
+const ADDRESS: u8 = 0x62;
+// Temperature and relative humidity are transmitted as fractions of these spans.
+const TEMPERATURE_SPAN: f32 = 175.0;
+const TEMPERATURE_MIN: f32 = -45.0;
+const HUMIDITY_SPAN: f32 = 100.0;
+const WORD_MAX: f32 = u16::MAX as f32;
+// Response of the forced recalibration signalling a failure.
+const FRC_FAILED: u16 = 0xFFFF;
+const FRC_CORRECTION_OFFSET: i32 = 0x8000;
+// Bits of the data ready status which are set if a measurement is ready.
+const DATA_READY_MASK: u16 = 0x07FF;
+
+#[duplicate_item(
+    feature_        module      async   await               i2c_trait                                       delay_trait                         test_macro;
+    ["blocking"]    [blocking]  []      [identity()]        [embedded_hal::i2c::I2c<Error = I2cErr>]        [embedded_hal::delay::DelayNs]       [test];
+    ["async"]       [asynch]    [async] [await.identity()]  [embedded_hal_async::i2c::I2c<Error = I2cErr>]  [embedded_hal_async::delay::DelayNs] [tokio::test];
+)]
+pub mod module {
+    //! Implementation of the SCD4x's interface
+
+    #[cfg(feature=feature_)]
+    mod inner {
+        use crate::{
+            data::{
+                AltitudeCompensation, AmbientPressure, AutomaticSelfCalibration, DataStatus,
+                ForcedRecalibrationValue, Measurement, TemperatureOffset,
+            },
+            error::{DataError, Scd30Error},
+            interface::Identity,
+            log::{log_info, log_trace},
+            scd4x::{
+                Scd4xCommand, ADDRESS, DATA_READY_MASK, FRC_CORRECTION_OFFSET, FRC_FAILED,
+                HUMIDITY_SPAN, TEMPERATURE_MIN, TEMPERATURE_SPAN, WORD_MAX,
+            },
+            sensirion::{decode_words, encode_command, encode_with_argument, WORD_LEN},
+        };
+
+        /// Interface for the SCD40 and SCD41 CO2 sensors. The delay `D` is used to wait for the
+        /// execution of each command.
+        pub struct Scd4x<I2C, D> {
+            i2c: I2C,
+            delay: D,
+        }
+
+        impl<I2C: i2c_trait, D: delay_trait, I2cErr: embedded_hal::i2c::Error> Scd4x<I2C, D> {
+            /// Creates a new SCD4x interface.
+            pub fn new(i2c: I2C, delay: D) -> Self {
+                Self { i2c, delay }
+            }
+
+            /// Starts periodic measurements, delivering a sample every 5 s.
+            pub async fn start_periodic_measurement(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(Scd4xCommand::StartPeriodicMeasurement).await
+            }
+
+            /// Starts low power periodic measurements, delivering a sample every 30 s.
+            pub async fn start_low_power_periodic_measurement(
+                &mut self,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(Scd4xCommand::StartLowPowerPeriodicMeasurement)
+                    .await
+            }
+
+            /// Stops periodic measurements. Most settings can only be changed while the sensor is
+            /// idle.
+            pub async fn stop_periodic_measurement(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(Scd4xCommand::StopPeriodicMeasurement).await
+            }
+
+            /// Checks whether a measurement is ready for readout.
+            pub async fn is_data_ready(&mut self) -> Result<DataStatus, Scd30Error<I2cErr>> {
+                let [status] = self.read(Scd4xCommand::GetDataReadyStatus).await?;
+                Ok(if status & DATA_READY_MASK != 0 {
+                    DataStatus::Ready
+                } else {
+                    DataStatus::NotReady
+                })
+            }
+
+            /// Reads out a [Measurement](crate::data::Measurement) from the sensor.
+            pub async fn read_measurement(&mut self) -> Result<Measurement, Scd30Error<I2cErr>> {
+                let [co2, temperature, humidity] = self.read(Scd4xCommand::ReadMeasurement).await?;
+                Ok(Measurement::new(
+                    co2 as f32,
+                    TEMPERATURE_MIN + TEMPERATURE_SPAN * temperature as f32 / WORD_MAX,
+                    HUMIDITY_SPAN * humidity as f32 / WORD_MAX,
+                ))
+            }
+
+            /// Triggers a single measurement and waits until it is ready for readout, SCD41 only.
+            pub async fn measure_single_shot(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(Scd4xCommand::MeasureSingleShot).await
+            }
+
+            /// Configures the temperature offset compensating for self-heating. The SCD4x supports
+            /// offsets of up to 175 °C.
+            ///
+            /// # Errors
+            ///
+            /// - [ValueOutOfRange](crate::error::DataError::ValueOutOfRange) if `offset` exceeds
+            ///   175 °C.
+            pub async fn set_temperature_offset(
+                &mut self,
+                offset: TemperatureOffset,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                let degrees = u16::from_be_bytes(offset.to_be_bytes()) as f32 / 100.0;
+                if degrees > TEMPERATURE_SPAN {
+                    return Err(DataError::ValueOutOfRange {
+                        parameter: "Temperature offset",
+                        min: 0,
+                        max: TEMPERATURE_SPAN as u16 * 100,
+                        unit: "°C",
+                    }
+                    .into());
+                }
+                let word = (degrees * WORD_MAX / TEMPERATURE_SPAN + 0.5) as u16;
+                self.send_with_argument(Scd4xCommand::SetTemperatureOffset, word.to_be_bytes())
+                    .await
+            }
+
+            /// Reads out the configured temperature offset.
+            pub async fn get_temperature_offset(
+                &mut self,
+            ) -> Result<TemperatureOffset, Scd30Error<I2cErr>> {
+                let [word] = self.read(Scd4xCommand::GetTemperatureOffset).await?;
+                Ok(TemperatureOffset::try_from(
+                    word as f32 * TEMPERATURE_SPAN / WORD_MAX,
+                )?)
+            }
+
+            /// Configures the altitude compensation in m above sea level.
+            pub async fn set_altitude_compensation(
+                &mut self,
+                altitude: AltitudeCompensation,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.send_with_argument(Scd4xCommand::SetSensorAltitude, altitude.to_be_bytes())
+                    .await
+            }
+
+            /// Reads out the configured altitude compensation.
+            pub async fn get_altitude_compensation(
+                &mut self,
+            ) -> Result<AltitudeCompensation, Scd30Error<I2cErr>> {
+                let [altitude] = self.read(Scd4xCommand::GetSensorAltitude).await?;
+                Ok(AltitudeCompensation::from(altitude))
+            }
+
+            /// Configures the ambient pressure compensation, overriding the altitude compensation.
+            /// Can be set during periodic measurements.
+            pub async fn set_ambient_pressure(
+                &mut self,
+                pressure: AmbientPressure,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.send_with_argument(Scd4xCommand::SetAmbientPressure, pressure.to_be_bytes())
+                    .await
+            }
+
+            /// Activates or deactivates the automatic self-calibration.
+            pub async fn set_automatic_self_calibration(
+                &mut self,
+                setting: AutomaticSelfCalibration,
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.send_with_argument(
+                    Scd4xCommand::SetAutomaticSelfCalibrationEnabled,
+                    setting.to_be_bytes(),
+                )
+                .await
+            }
+
+            /// Reads out whether the automatic self-calibration is active.
+            pub async fn get_automatic_self_calibration(
+                &mut self,
+            ) -> Result<AutomaticSelfCalibration, Scd30Error<I2cErr>> {
+                let mut data = [0; WORD_LEN];
+                self.read_into(Scd4xCommand::GetAutomaticSelfCalibrationEnabled, &mut data)
+                    .await?;
+                Ok(AutomaticSelfCalibration::try_from(data)?)
+            }
+
+            /// Recalibrates the sensor to the `reference` CO2 concentration. The sensor has to be
+            /// measuring in the reference environment for at least 3 minutes before and has to be
+            /// idle when the recalibration is issued. Returns the applied correction in ppm.
+            ///
+            /// # Errors
+            ///
+            /// - [RecalibrationFailed](crate::error::DataError::RecalibrationFailed) if the
+            ///   sensor reports a failure.
+            pub async fn perform_forced_recalibration(
+                &mut self,
+                reference: ForcedRecalibrationValue,
+            ) -> Result<i16, Scd30Error<I2cErr>> {
+                log_info!("forced recalibration");
+                self.send_with_argument(
+                    Scd4xCommand::PerformForcedRecalibration,
+                    reference.to_be_bytes(),
+                )
+                .await?;
+                let mut data = [0; WORD_LEN];
+                self.i2c.read(ADDRESS, &mut data).await?;
+                match decode_words(&data)? {
+                    [FRC_FAILED] => Err(DataError::RecalibrationFailed.into()),
+                    [correction] => Ok((correction as i32 - FRC_CORRECTION_OFFSET) as i16),
+                }
+            }
+
+            /// Stores the current settings in the sensor's EEPROM, so they persist a power cycle.
+            /// The EEPROM endures a limited number of write cycles, so this should not be called
+            /// regularly.
+            pub async fn persist_settings(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(Scd4xCommand::PersistSettings).await
+            }
+
+            /// Reads out the 48-bit serial number of the sensor.
+            pub async fn serial_number(&mut self) -> Result<u64, Scd30Error<I2cErr>> {
+                let words: [u16; 3] = self.read(Scd4xCommand::GetSerialNumber).await?;
+                Ok(words
+                    .into_iter()
+                    .fold(0, |serial, word| (serial << 16) | word as u64))
+            }
+
+            /// Runs the built-in self test, taking 10 s. Returns whether the sensor passed.
+            pub async fn perform_self_test(&mut self) -> Result<bool, Scd30Error<I2cErr>> {
+                let [status] = self.read(Scd4xCommand::PerformSelfTest).await?;
+                Ok(status == 0)
+            }
+
+            /// Resets all settings to their factory defaults and erases the calibration history.
+            pub async fn perform_factory_reset(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                log_info!("factory reset");
+                self.send(Scd4xCommand::PerformFactoryReset).await
+            }
+
+            /// Reloads the settings stored in the sensor's EEPROM.
+            pub async fn reinit(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(Scd4xCommand::Reinit).await
+            }
+
+            /// Puts the sensor into sleep mode, SCD41 only.
+            pub async fn power_down(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(Scd4xCommand::PowerDown).await
+            }
+
+            /// Wakes the sensor from sleep mode, SCD41 only. The sensor does not acknowledge the
+            /// command, so bus errors are ignored.
+            pub async fn wake_up(&mut self) {
+                let command = Scd4xCommand::WakeUp;
+                let _ = self
+                    .i2c
+                    .write(ADDRESS, &encode_command(command.opcode()))
+                    .await;
+                self.wait(command).await;
+            }
+
+            /// Releases the I2C peripheral and the delay.
+            pub fn release(self) -> (I2C, D) {
+                (self.i2c, self.delay)
+            }
+
+            async fn send(&mut self, command: Scd4xCommand) -> Result<(), Scd30Error<I2cErr>> {
+                self.transmit(command, &encode_command(command.opcode()))
+                    .await
+            }
+
+            async fn send_with_argument(
+                &mut self,
+                command: Scd4xCommand,
+                argument: [u8; 2],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.transmit(command, &encode_with_argument(command.opcode(), argument))
+                    .await
+            }
+
+            async fn read<const N: usize>(
+                &mut self,
+                command: Scd4xCommand,
+            ) -> Result<[u16; N], Scd30Error<I2cErr>> {
+                // Large enough for the longest response, the measurement of three words.
+                let mut data = [0; 3 * WORD_LEN];
+                let data = &mut data[..N * WORD_LEN];
+                self.read_into(command, data).await?;
+                Ok(decode_words(data)?)
+            }
+
+            async fn read_into(
+                &mut self,
+                command: Scd4xCommand,
+                data: &mut [u8],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                self.send(command).await?;
+                self.i2c.read(ADDRESS, data).await?;
+                Ok(())
+            }
+
+            async fn transmit(
+                &mut self,
+                command: Scd4xCommand,
+                frame: &[u8],
+            ) -> Result<(), Scd30Error<I2cErr>> {
+                log_trace!(?command, "sending command");
+                self.i2c.write(ADDRESS, frame).await?;
+                self.wait(command).await;
+                Ok(())
+            }
+
+            async fn wait(&mut self, command: Scd4xCommand) {
+                let execution_time_ms = command.execution_time_ms();
+                if execution_time_ms > 0 {
+                    self.delay.delay_ms(execution_time_ms).await;
+                }
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use embedded_hal_mock::eh1::{
+                delay::NoopDelay,
+                i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+            };
+
+            #[test_macro]
+            async fn read_measurement_datasheet_example() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x62, vec![0xE4, 0xB8]),
+                    I2cTransaction::read(0x62, vec![0x80, 0x06, 0x04]),
+                    I2cTransaction::write(0x62, vec![0xEC, 0x05]),
+                    I2cTransaction::read(
+                        0x62,
+                        vec![0x01, 0xF4, 0x33, 0x66, 0x67, 0xA2, 0x5E, 0xB9, 0x3C],
+                    ),
+                ];
+
+                let mut i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd4x::new(i2c.clone(), NoopDelay::new());
+
+                assert_eq!(sensor.is_data_ready().await.unwrap(), DataStatus::Ready);
+                let measurement = sensor.read_measurement().await.unwrap();
+                assert!(measurement.approx_eq(&Measurement::new(500.0, 25.0, 37.0), 0.01));
+                i2c.done();
+            }
+
+            #[test_macro]
+            async fn settings_datasheet_examples() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x62, vec![0x24, 0x1D, 0x07, 0xE6, 0x48]),
+                    I2cTransaction::write(0x62, vec![0x24, 0x27, 0x07, 0x9E, 0x09]),
+                    I2cTransaction::write(0x62, vec![0x36, 0x82]),
+                    I2cTransaction::read(
+                        0x62,
+                        vec![0xF8, 0x96, 0x31, 0x9F, 0x07, 0xC2, 0x3B, 0xBE, 0x89],
+                    ),
+                ];
+
+                let mut i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd4x::new(i2c.clone(), NoopDelay::new());
+
+                sensor
+                    .set_temperature_offset(TemperatureOffset::try_from(5.4).unwrap())
+                    .await
+                    .unwrap();
+                sensor
+                    .set_altitude_compensation(AltitudeCompensation::from(1950))
+                    .await
+                    .unwrap();
+                assert_eq!(sensor.serial_number().await.unwrap(), 273_325_796_834_238);
+                i2c.done();
+            }
+
+            #[test_macro]
+            async fn forced_recalibration_reports_correction_and_failure() {
+                let expected_transactions = [
+                    I2cTransaction::write(0x62, vec![0x36, 0x2F, 0x01, 0xE0, 0xB4]),
+                    I2cTransaction::read(0x62, vec![0x7F, 0xCE, 0x7B]),
+                    I2cTransaction::write(0x62, vec![0x36, 0x2F, 0x01, 0xE0, 0xB4]),
+                    I2cTransaction::read(0x62, vec![0xFF, 0xFF, 0xAC]),
+                ];
+
+                let mut i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = Scd4x::new(i2c.clone(), NoopDelay::new());
+                let reference = || ForcedRecalibrationValue::try_from(480).unwrap();
+
+                assert_eq!(
+                    sensor
+                        .perform_forced_recalibration(reference())
+                        .await
+                        .unwrap(),
+                    -50
+                );
+                assert_eq!(
+                    sensor
+                        .perform_forced_recalibration(reference())
+                        .await
+                        .unwrap_err(),
+                    Scd30Error::DataError(DataError::RecalibrationFailed)
+                );
+                i2c.done();
+            }
+        }
+    }
+
+    #[cfg(feature=feature_)]
+    pub use inner::*;
+}
@@ -0,0 +1,233 @@
+//! Cooperative scheduling of several sensors and tasks sharing one bus.
+//!
+//! A [PollScheduler] keeps track of when each of its slots is due, e.g. a sensor whose next
+//! sample should be ready or a periodic callback of the application. It does not own the sensors,
+//! the application dispatches the due slot and reports the outcome:
+//!
+//! ```ignore
+//! let mut scheduler = PollScheduler::<3>::new();
+//! let kitchen = scheduler.add_sensor(kitchen_interval, clock()).unwrap();
+//! let bedroom = scheduler.add_sensor(bedroom_interval, clock()).unwrap();
+//! let display = scheduler.add(1000, clock()).unwrap();
+//! loop {
+//!     let now = clock();
+//!     match scheduler.next_due(now) {
+//!         Some(slot) if slot == display => {
+//!             refresh_display();
+//!             scheduler.complete(slot, now, Poll::Done);
+//!         }
+//!         Some(slot) => {
+//!             let sensor = if slot == kitchen { &mut kitchen_sensor } else { &mut bedroom_sensor };
+//!             let poll = match sensor.is_data_ready()? {
+//!                 DataStatus::Ready => {
+//!                     publish(slot, sensor.read_measurement()?);
+//!                     Poll::Done
+//!                 }
+//!                 _ => Poll::Pending,
+//!             };
+//!             scheduler.complete(slot, now, poll);
+//!         }
+//!         None => delay.delay_ms(scheduler.time_until_due(now).unwrap_or(100) as u32),
+//!     }
+//! }
+//! ```
+//!
+//! Due slots are served round-robin, so a slot which is polled repeatedly can not starve the
+//! others.
+
+use heapless::Vec;
+
+use crate::data::MeasurementInterval;
+
+/// Default delay in milliseconds before a [Pending](Poll::Pending) slot is due again.
+pub const DEFAULT_RETRY_MS: u64 = 100;
+
+/// Handle of a slot of a [PollScheduler].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotId(usize);
+
+impl SlotId {
+    /// Returns the position of the slot in the order the slots were added.
+    pub const fn index(&self) -> usize {
+        self.0
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SlotId {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "SlotId({})", self.0)
+    }
+}
+
+/// Outcome of serving a due slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Poll {
+    /// The slot was served, e.g. a measurement was read. It is due again after its period.
+    Done,
+    /// The slot could not be served yet, e.g. no measurement was ready. It is due again after
+    /// the retry delay.
+    Pending,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Poll {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Poll::Done => defmt::write!(f, "Done"),
+            Poll::Pending => defmt::write!(f, "Pending"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Slot {
+    period_ms: u64,
+    due_ms: u64,
+}
+
+/// Schedules up to `N` periodic slots, see the [module documentation](self).
+#[derive(Debug)]
+pub struct PollScheduler<const N: usize> {
+    slots: Vec<Slot, N>,
+    retry_ms: u64,
+    cursor: usize,
+}
+
+impl<const N: usize> Default for PollScheduler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PollScheduler<N> {
+    /// Creates a scheduler without slots, retrying pending slots after [DEFAULT_RETRY_MS].
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            retry_ms: DEFAULT_RETRY_MS,
+            cursor: 0,
+        }
+    }
+
+    /// Retries pending slots after `retry_ms` milliseconds.
+    pub const fn with_retry_delay(mut self, retry_ms: u64) -> Self {
+        self.retry_ms = retry_ms;
+        self
+    }
+
+    /// Adds a slot which is due every `period_ms` milliseconds, for the first time one period
+    /// after `now_ms`. Returns `None` if all `N` slots are in use.
+    pub fn add(&mut self, period_ms: u64, now_ms: u64) -> Option<SlotId> {
+        let id = SlotId(self.slots.len());
+        self.slots
+            .push(Slot {
+                period_ms,
+                due_ms: now_ms.saturating_add(period_ms),
+            })
+            .ok()?;
+        Some(id)
+    }
+
+    /// Adds a slot for a sensor measuring every `interval`, which was started at `now_ms`.
+    /// Returns `None` if all `N` slots are in use.
+    pub fn add_sensor(&mut self, interval: MeasurementInterval, now_ms: u64) -> Option<SlotId> {
+        self.add(u64::from(interval.seconds()) * 1000, now_ms)
+    }
+
+    /// Changes the period of `slot`, e.g. after the measurement interval of its sensor was
+    /// changed. The slot is due one period after `now_ms`.
+    pub fn set_period(&mut self, slot: SlotId, period_ms: u64, now_ms: u64) {
+        if let Some(slot) = self.slots.get_mut(slot.0) {
+            slot.period_ms = period_ms;
+            slot.due_ms = now_ms.saturating_add(period_ms);
+        }
+    }
+
+    /// Returns the next slot due at `now_ms`, if any. Due slots are returned round-robin,
+    /// starting after the slot returned last.
+    pub fn next_due(&mut self, now_ms: u64) -> Option<SlotId> {
+        let len = self.slots.len();
+        let index = (0..len)
+            .map(|offset| (self.cursor + offset) % len)
+            .find(|&index| self.slots[index].due_ms <= now_ms)?;
+        self.cursor = index + 1;
+        Some(SlotId(index))
+    }
+
+    /// Records the outcome of serving `slot` at `now_ms` and schedules it accordingly.
+    pub fn complete(&mut self, slot: SlotId, now_ms: u64, poll: Poll) {
+        let retry_ms = self.retry_ms;
+        if let Some(slot) = self.slots.get_mut(slot.0) {
+            let delay_ms = match poll {
+                Poll::Done => slot.period_ms,
+                Poll::Pending => retry_ms,
+            };
+            slot.due_ms = now_ms.saturating_add(delay_ms);
+        }
+    }
+
+    /// Returns the time in milliseconds until the next slot is due, 0 if a slot is already due.
+    /// Returns `None` without slots.
+    pub fn time_until_due(&self, now_ms: u64) -> Option<u64> {
+        self.slots
+            .iter()
+            .map(|slot| slot.due_ms.saturating_sub(now_ms))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_due_after_their_period() {
+        let mut scheduler = PollScheduler::<2>::new();
+        let sensor = scheduler
+            .add_sensor(MeasurementInterval::try_from(2).unwrap(), 0)
+            .unwrap();
+        let callback = scheduler.add(500, 0).unwrap();
+        assert_eq!(scheduler.add(1000, 0), None);
+
+        assert_eq!(scheduler.next_due(0), None);
+        assert_eq!(scheduler.time_until_due(0), Some(500));
+        assert_eq!(scheduler.next_due(500), Some(callback));
+        scheduler.complete(callback, 500, Poll::Done);
+        assert_eq!(scheduler.time_until_due(500), Some(500));
+
+        assert_eq!(scheduler.next_due(2000), Some(sensor));
+        scheduler.complete(sensor, 2000, Poll::Pending);
+        assert_eq!(scheduler.next_due(2000), Some(callback));
+        scheduler.complete(callback, 2000, Poll::Done);
+        assert_eq!(scheduler.next_due(2050), None);
+        assert_eq!(scheduler.next_due(2100), Some(sensor));
+        scheduler.complete(sensor, 2100, Poll::Done);
+        assert_eq!(scheduler.time_until_due(2100), Some(400));
+    }
+
+    #[test]
+    fn due_slots_are_served_round_robin() {
+        let mut scheduler = PollScheduler::<3>::new().with_retry_delay(0);
+        let slots = [
+            scheduler.add(100, 0).unwrap(),
+            scheduler.add(100, 0).unwrap(),
+            scheduler.add(100, 0).unwrap(),
+        ];
+        for expected in [0, 1, 2, 0, 1] {
+            let slot = scheduler.next_due(100).unwrap();
+            assert_eq!(slot, slots[expected]);
+            scheduler.complete(slot, 100, Poll::Pending);
+        }
+    }
+
+    #[test]
+    fn period_can_be_changed() {
+        let mut scheduler = PollScheduler::<1>::default();
+        let slot = scheduler.add(2000, 0).unwrap();
+        scheduler.set_period(slot, 10_000, 1000);
+        assert_eq!(scheduler.next_due(2000), None);
+        assert_eq!(scheduler.next_due(11_000), Some(slot));
+        assert_eq!(slot.index(), 0);
+    }
+}
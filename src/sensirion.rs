@@ -0,0 +1,108 @@
+//! Framing shared by Sensirion's I2C sensors like the SCD30, SCD4x, SPS30 or SHT4x.
+//!
+//! A transfer starts with a 16-bit command word, optionally followed by 16-bit argument words.
+//! Every argument and response word is transmitted big endian and followed by its CRC-8. The
+//! building blocks of this module encode and decode these frames, so further Sensirion sensors can
+//! be driven on top of any I2C implementation:
+//!
+//! ```
+//! use scd30_interface::sensirion::{decode_words, encode_with_argument, WORD_LEN};
+//!
+//! // Starts the measurements of an SPS30 with big endian floats as output format.
+//! let frame = encode_with_argument(0x0010, [0x03, 0x00]);
+//! assert_eq!(frame, [0x00, 0x10, 0x03, 0x00, 0xAC]);
+//!
+//! // Response to a command reading two words.
+//! let response = [0x01, 0xF4, 0x33, 0x66, 0x67, 0xA2];
+//! assert_eq!(response.len(), 2 * WORD_LEN);
+//! assert_eq!(decode_words(&response), Ok([0x01F4, 0x6667]));
+//! ```
+
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::indexing_slicing,
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used
+    )
+)]
+use crate::{
+    codec::{compute_crc8, Frame},
+    error::DataError,
+};
+
+pub use crate::codec::WORD_LEN;
+
+/// Computes the CRC-8 of a `word`, with polynomial 0x31 and initialization 0xFF.
+pub const fn crc8(word: [u8; 2]) -> u8 {
+    compute_crc8(&word)
+}
+
+/// Returns a 16-bit `word` followed by its CRC, e.g. to assemble frames with several argument
+/// words.
+pub const fn encode_word(word: u16) -> [u8; WORD_LEN] {
+    let bytes = word.to_be_bytes();
+    [bytes[0], bytes[1], crc8(bytes)]
+}
+
+/// Returns the frame for sending `opcode` without arguments.
+pub const fn encode_command(opcode: u16) -> [u8; 2] {
+    opcode.to_be_bytes()
+}
+
+/// Returns the frame for sending `opcode` followed by a 16-bit `argument` and its CRC.
+pub const fn encode_with_argument(opcode: u16, argument: [u8; 2]) -> [u8; 5] {
+    let command = encode_command(opcode);
+    [
+        command[0],
+        command[1],
+        argument[0],
+        argument[1],
+        crc8(argument),
+    ]
+}
+
+/// Decodes `N` words from `data`, checking the length of `data` and the CRC of every word.
+///
+/// # Errors
+///
+/// - [ReceivedBufferWrongSize](crate::error::DataError::ReceivedBufferWrongSize) if `data` does not
+///   hold exactly `N` words with their CRCs.
+/// - [CrcFailed](crate::error::DataError::CrcFailed) if the CRC of a word does not match.
+pub fn decode_words<const N: usize>(data: &[u8]) -> Result<[u16; N], DataError> {
+    Ok(Frame::<N>::new(data)?.words())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argument_is_followed_by_crc() {
+        assert_eq!(
+            encode_with_argument(0x4600, [0x00, 0x02]),
+            [0x46, 0x00, 0x00, 0x02, 0xE3]
+        );
+    }
+
+    #[test]
+    fn word_is_followed_by_crc() {
+        assert_eq!(crc8([0xBE, 0xEF]), 0x92);
+        assert_eq!(encode_word(0xBEEF), [0xBE, 0xEF, 0x92]);
+    }
+
+    #[test]
+    fn words_are_decoded_and_checked() {
+        let data = [0x01, 0xF4, 0x33, 0x66, 0x67, 0xA2];
+        assert_eq!(decode_words::<2>(&data), Ok([0x01F4, 0x6667]));
+        assert_eq!(
+            decode_words::<2>(&data[..3]),
+            Err(DataError::ReceivedBufferWrongSize)
+        );
+        assert_eq!(
+            decode_words::<1>(&[0x01, 0xF4, 0x34]),
+            Err(DataError::CrcFailed)
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Strict validation of the command sequencing.
+//!
+//! The sensor silently ignores some commands sent at the wrong time, e.g. a forced recalibration
+//! before it measured the reference concentration long enough. With
+//! [with_strict_sequencing](crate::blocking::Scd30::with_strict_sequencing), the interface checks
+//! every command against the rules of the interface description and rejects violations with a
+//! [SequenceViolation] instead:
+//!
+//! * No command is sent within [RESET_BOOT_MS] after a reset, while the sensor is booting.
+//! * Configuration commands are at least [COMMAND_GAP_US](crate::transaction::COMMAND_GAP_US)
+//!   apart, so use [commit](crate::blocking::Scd30::commit) instead of
+//!   [apply_config](crate::blocking::Scd30::apply_config) to change several settings.
+//! * A forced recalibration is only performed during continuous measurements, after the sensor
+//!   measured for [CALIBRATION_SOAK_MS] with the current measurement interval.
+//! * The measurement interval is not changed during this calibration soak. Set it before
+//!   starting continuous measurements instead. Changing it afterwards restarts the soak.
+//!
+//! The timing rules require a [Clock](crate::options::Clock), see
+//! [with_clock](crate::blocking::Scd30::with_clock), and are skipped without.
+
+use crate::{
+    command::Command, data::MeasuringState, error::SequenceViolation, interface::RESET_DELAY_MS,
+    transaction::COMMAND_GAP_US,
+};
+
+/// Time the sensor needs to boot after a reset in milliseconds.
+pub const RESET_BOOT_MS: u64 = RESET_DELAY_MS as u64;
+
+/// Time in milliseconds the sensor needs to measure with the desired measurement interval before
+/// a forced recalibration.
+pub const CALIBRATION_SOAK_MS: u64 = 120_000;
+
+const COMMAND_GAP_MS: u64 = (COMMAND_GAP_US as u64).div_ceil(1000);
+
+/// Bookkeeping of the commands sent in strict mode, see the [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SequenceGuard {
+    reset_at: Option<u64>,
+    last_config_at: Option<u64>,
+    soak_since: Option<u64>,
+}
+
+impl SequenceGuard {
+    /// Creates a guard which has not seen any command yet.
+    pub const fn new() -> Self {
+        Self {
+            reset_at: None,
+            last_config_at: None,
+            soak_since: None,
+        }
+    }
+
+    /// Returns the time in milliseconds until the calibration soak completes at `now_ms`, 0 if it
+    /// completed. Returns `None` if continuous measurements were not started with a clock.
+    pub fn soak_remaining_ms(&self, now_ms: u64) -> Option<u64> {
+        self.soak_since
+            .map(|since| remaining(since, CALIBRATION_SOAK_MS, now_ms))
+    }
+
+    /// Checks whether `command` may be sent at `now_ms`. `configures` marks commands changing the
+    /// state of the sensor, in contrast to reading a response.
+    pub(crate) fn check(
+        &self,
+        command: Command,
+        configures: bool,
+        measuring: MeasuringState,
+        now_ms: Option<u64>,
+    ) -> Result<(), SequenceViolation> {
+        if command == Command::ForcedRecalibrationValue
+            && configures
+            && measuring != MeasuringState::Measuring
+        {
+            return Err(SequenceViolation::RecalibrationNotMeasuring);
+        }
+        let Some(now_ms) = now_ms else {
+            return Ok(());
+        };
+        if let Some(reset_at) = self.reset_at {
+            let remaining_ms = remaining(reset_at, RESET_BOOT_MS, now_ms);
+            if remaining_ms > 0 {
+                return Err(SequenceViolation::SensorBooting { remaining_ms });
+            }
+        }
+        if !configures {
+            return Ok(());
+        }
+        if let Some(last_config_at) = self.last_config_at {
+            let remaining_ms = remaining(last_config_at, COMMAND_GAP_MS, now_ms);
+            if remaining_ms > 0 {
+                return Err(SequenceViolation::CommandTooEarly { remaining_ms });
+            }
+        }
+        match (command, self.soak_remaining_ms(now_ms)) {
+            (Command::ForcedRecalibrationValue, Some(remaining_ms)) if remaining_ms > 0 => {
+                Err(SequenceViolation::RecalibrationBeforeSoak { remaining_ms })
+            }
+            (Command::SetMeasurementInterval, Some(remaining_ms)) if remaining_ms > 0 => {
+                Err(SequenceViolation::IntervalChangeDuringSoak { remaining_ms })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records that `command` was sent at `now_ms`.
+    pub(crate) fn record(&mut self, command: Command, configures: bool, now_ms: Option<u64>) {
+        if configures {
+            self.last_config_at = now_ms;
+        }
+        match command {
+            Command::TriggerContinuousMeasurement => self.soak_since = now_ms,
+            Command::SetMeasurementInterval if self.soak_since.is_some() => {
+                self.soak_since = now_ms
+            }
+            Command::StopContinuousMeasurement => self.soak_since = None,
+            Command::SoftReset => self.record_reset(now_ms),
+            _ => {}
+        }
+    }
+
+    /// Records a reset of the sensor at `now_ms`.
+    pub(crate) fn record_reset(&mut self, now_ms: Option<u64>) {
+        self.reset_at = now_ms;
+        self.soak_since = None;
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SequenceGuard {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "SequenceGuard {{ reset_at: {}, soak_since: {} }}",
+            self.reset_at,
+            self.soak_since
+        )
+    }
+}
+
+fn remaining(since_ms: u64, duration_ms: u64, now_ms: u64) -> u64 {
+    since_ms.saturating_add(duration_ms).saturating_sub(now_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recalibration_requires_completed_soak() {
+        let mut guard = SequenceGuard::new();
+        assert_eq!(
+            guard.check(
+                Command::ForcedRecalibrationValue,
+                true,
+                MeasuringState::Unknown,
+                None
+            ),
+            Err(SequenceViolation::RecalibrationNotMeasuring)
+        );
+
+        guard.record(Command::TriggerContinuousMeasurement, true, Some(1000));
+        assert_eq!(guard.soak_remaining_ms(61_000), Some(60_000));
+        assert_eq!(
+            guard.check(
+                Command::ForcedRecalibrationValue,
+                true,
+                MeasuringState::Measuring,
+                Some(61_000)
+            ),
+            Err(SequenceViolation::RecalibrationBeforeSoak {
+                remaining_ms: 60_000
+            })
+        );
+        assert_eq!(
+            guard.check(
+                Command::ForcedRecalibrationValue,
+                false,
+                MeasuringState::Measuring,
+                Some(61_000)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            guard.check(
+                Command::ForcedRecalibrationValue,
+                true,
+                MeasuringState::Measuring,
+                Some(121_000)
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn interval_change_restarts_soak() {
+        let mut guard = SequenceGuard::new();
+        guard.record(Command::SetMeasurementInterval, true, Some(0));
+        assert_eq!(guard.soak_remaining_ms(0), None);
+        guard.record(Command::TriggerContinuousMeasurement, true, Some(10));
+        assert_eq!(
+            guard.check(
+                Command::SetMeasurementInterval,
+                true,
+                MeasuringState::Measuring,
+                Some(100_010)
+            ),
+            Err(SequenceViolation::IntervalChangeDuringSoak {
+                remaining_ms: 20_000
+            })
+        );
+        guard.record(Command::SetMeasurementInterval, true, Some(120_010));
+        assert_eq!(guard.soak_remaining_ms(120_010), Some(CALIBRATION_SOAK_MS));
+        guard.record(Command::StopContinuousMeasurement, true, Some(130_000));
+        assert_eq!(guard.soak_remaining_ms(130_000), None);
+    }
+
+    #[test]
+    fn commands_respect_boot_time_and_gap() {
+        let mut guard = SequenceGuard::new();
+        guard.record(Command::SoftReset, true, Some(0));
+        assert_eq!(
+            guard.check(
+                Command::ReadFirmwareVersion,
+                false,
+                MeasuringState::Unknown,
+                Some(1500)
+            ),
+            Err(SequenceViolation::SensorBooting { remaining_ms: 500 })
+        );
+        assert_eq!(
+            guard.check(
+                Command::ReadFirmwareVersion,
+                false,
+                MeasuringState::Unknown,
+                None
+            ),
+            Ok(())
+        );
+
+        guard.record(Command::SetTemperatureOffset, true, Some(2000));
+        assert_eq!(
+            guard.check(
+                Command::SetAltitudeCompensation,
+                true,
+                MeasuringState::Unknown,
+                Some(2001)
+            ),
+            Err(SequenceViolation::CommandTooEarly { remaining_ms: 2 })
+        );
+        assert_eq!(
+            guard.check(
+                Command::GetDataReady,
+                false,
+                MeasuringState::Unknown,
+                Some(2001)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            guard.check(
+                Command::SetAltitudeCompensation,
+                true,
+                MeasuringState::Unknown,
+                Some(2003)
+            ),
+            Ok(())
+        );
+    }
+}
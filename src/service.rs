@@ -0,0 +1,201 @@
+//! Sensor service owning the SCD30 in a dedicated task.
+//!
+//! If several tasks need access to the sensor, [Scd30Service::run] is spawned as the only owner of
+//! the sensor. Other tasks send [Request]s through the service and receive the matching [Reply]:
+//!
+//! ```ignore
+//! static SERVICE: Scd30Service<CriticalSectionRawMutex, i2c::Error> = Scd30Service::new();
+//!
+//! #[embassy_executor::task]
+//! async fn sensor_task(sensor: Scd30<I2c<'static, Async>>) {
+//!     SERVICE.run(sensor).await
+//! }
+//!
+//! #[embassy_executor::task]
+//! async fn display_task() {
+//!     if let Ok(Reply::Measurement(measurement)) = SERVICE.request(Request::ReadMeasurement).await {
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use embassy_sync::{blocking_mutex::raw::RawMutex, channel::Channel, mutex::Mutex};
+
+use crate::{
+    asynch::Scd30,
+    data::{ForcedRecalibrationValue, Measurement, Scd30Config},
+    error::Scd30Error,
+};
+
+/// Requests handled by the [Scd30Service].
+#[derive(Debug, PartialEq)]
+pub enum Request {
+    /// Waits for the next measurement and reads it out, answered with [Reply::Measurement].
+    ReadMeasurement,
+    /// Reads back the configuration from the sensor, answered with [Reply::Config].
+    ReadConfig,
+    /// Applies all settings of the configuration which are not `None`, answered with
+    /// [Reply::Done].
+    ApplyConfig(Scd30Config),
+    /// Runs a forced recalibration to the given reference value, answered with [Reply::Done].
+    ForcedRecalibration(ForcedRecalibrationValue),
+    /// Executes a soft reset of the sensor, answered with [Reply::Done].
+    SoftReset,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Request {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Request::ReadMeasurement => defmt::write!(f, "ReadMeasurement"),
+            Request::ReadConfig => defmt::write!(f, "ReadConfig"),
+            Request::ApplyConfig(config) => defmt::write!(f, "ApplyConfig({})", config),
+            Request::ForcedRecalibration(value) => {
+                defmt::write!(f, "ForcedRecalibration({})", value)
+            }
+            Request::SoftReset => defmt::write!(f, "SoftReset"),
+        }
+    }
+}
+
+/// Successful replies of the [Scd30Service] to a [Request].
+#[derive(Debug)]
+pub enum Reply {
+    /// Measurement read out.
+    Measurement(Measurement),
+    /// Configuration read back from the sensor.
+    Config(Scd30Config),
+    /// The request was executed.
+    Done,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Reply {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Reply::Measurement(measurement) => defmt::write!(f, "Measurement({})", measurement),
+            Reply::Config(config) => defmt::write!(f, "Config({})", config),
+            Reply::Done => defmt::write!(f, "Done"),
+        }
+    }
+}
+
+/// Request and reply channel between the task owning the sensor and the tasks using it, see the
+/// [module documentation](self).
+pub struct Scd30Service<M: RawMutex, I2cErr: embedded_hal::i2c::Error> {
+    requests: Channel<M, Request, 1>,
+    replies: Channel<M, Result<Reply, Scd30Error<I2cErr>>, 1>,
+    // Serializes the clients, so every reply reaches the client that sent the request.
+    client: Mutex<M, ()>,
+}
+
+impl<M: RawMutex, I2cErr: embedded_hal::i2c::Error> Scd30Service<M, I2cErr> {
+    /// Creates a service, usually placed in a `static`.
+    pub const fn new() -> Self {
+        Self {
+            requests: Channel::new(),
+            replies: Channel::new(),
+            client: Mutex::new(()),
+        }
+    }
+
+    /// Sends `request` to the task owning the sensor and waits for its reply. Requests of
+    /// concurrent callers are handled one after another.
+    pub async fn request(&self, request: Request) -> Result<Reply, Scd30Error<I2cErr>> {
+        let _client = self.client.lock().await;
+        // Drop the reply of a previous caller which was cancelled while waiting.
+        self.replies.clear();
+        self.requests.send(request).await;
+        self.replies.receive().await
+    }
+
+    /// Owns `sensor` and executes the requests sent through the service. Errors are passed on to
+    /// the requesting task, so the service keeps running.
+    pub async fn run<I2C, D>(&self, mut sensor: Scd30<I2C, D>) -> !
+    where
+        I2C: embedded_hal_async::i2c::I2c<Error = I2cErr>,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        loop {
+            let reply = match self.requests.receive().await {
+                Request::ReadMeasurement => sensor.next_measurement().await.map(Reply::Measurement),
+                Request::ReadConfig => sensor.read_config().await.map(Reply::Config),
+                Request::ApplyConfig(config) => {
+                    sensor.apply_config(&config).await.map(|()| Reply::Done)
+                }
+                Request::ForcedRecalibration(value) => sensor
+                    .set_forced_recalibration(value)
+                    .await
+                    .map(|()| Reply::Done),
+                Request::SoftReset => sensor.soft_reset().await.map(|()| Reply::Done),
+            };
+            self.replies.send(reply).await;
+        }
+    }
+}
+
+impl<M: RawMutex, I2cErr: embedded_hal::i2c::Error> Default for Scd30Service<M, I2cErr> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command::Command, error::Phase};
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    #[tokio::test]
+    async fn requests_are_executed_by_owning_task() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+            I2cTransaction::write(0x61, vec![0x03, 0x00]),
+            I2cTransaction::read(
+                0x61,
+                vec![
+                    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42,
+                    0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                ],
+            ),
+            I2cTransaction::write(0x61, vec![0x52, 0x04, 0x01, 0xC2, 0x50]),
+            I2cTransaction::write(0x61, vec![0xD3, 0x04]).with_error(ErrorKind::Other),
+        ];
+
+        let mut i2c = I2cMock::new(&expected_transactions);
+        let service = Scd30Service::<NoopRawMutex, ErrorKind>::new();
+
+        let clients = async {
+            let reply = service.request(Request::ReadMeasurement).await.unwrap();
+            let Reply::Measurement(measurement) = reply else {
+                panic!("unexpected reply {:?}", reply);
+            };
+            assert_eq!(measurement.co2_concentration, 439.09515);
+            assert!(matches!(
+                service
+                    .request(Request::ForcedRecalibration(
+                        ForcedRecalibrationValue::try_from(450).unwrap()
+                    ))
+                    .await,
+                Ok(Reply::Done)
+            ));
+            assert!(matches!(
+                service.request(Request::SoftReset).await,
+                Err(Scd30Error::CommandFailed {
+                    command: Command::SoftReset,
+                    phase: Phase::Write,
+                    error: ErrorKind::Other,
+                })
+            ));
+        };
+
+        tokio::select! {
+            _ = service.run(Scd30::new(i2c.clone())) => unreachable!(),
+            _ = clients => {}
+        }
+        i2c.done();
+    }
+}
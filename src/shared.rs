@@ -0,0 +1,268 @@
+//! SCD30 shared between several embassy tasks.
+//!
+//! [SharedScd30] guards the sensor with an [embassy_sync] mutex and exposes the methods of the
+//! [async interface](crate::asynch::Scd30) taking `&self`, so e.g. a display task and an uplink
+//! task can use the same sensor without a dedicated owning task:
+//!
+//! ```ignore
+//! type Sensor = SharedScd30<CriticalSectionRawMutex, I2c<'static, Async>>;
+//! static SENSOR: StaticCell<Sensor> = StaticCell::new();
+//!
+//! #[embassy_executor::task]
+//! async fn display_task(sensor: &'static Sensor) {
+//!     let measurement = sensor.next_measurement().await?;
+//!     // ...
+//! }
+//! ```
+//!
+//! Every call locks the sensor for its duration, so calls of different tasks are executed one
+//! after another. Sequences which must not be interleaved with other tasks, e.g. stopping,
+//! reconfiguring and restarting the measurements or a
+//! [measurement session](crate::asynch::Scd30::measure_n), are executed on the guard returned by
+//! [lock](SharedScd30::lock).
+
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    mutex::{Mutex, MutexGuard},
+};
+use embassy_time::Duration;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    asynch::Scd30,
+    data::{
+        AltitudeCompensation, AmbientPressureCompensation, AutomaticSelfCalibration,
+        CompensationMode, DataStatus, FirmwareVersion, ForcedRecalibrationValue, Measurement,
+        MeasurementInterval, MeasuringState, Preset, QualifiedMeasurement, Scd30Config,
+        SensorSnapshot, SettingsSnapshot, TemperatureOffset,
+    },
+    error::Scd30Error,
+    health::{BusMetrics, ErrorCounters, HealthReport, SampleAccounting, Timings},
+    options::NoDelay,
+    transaction::ConfigTransaction,
+};
+
+/// Generates methods locking the sensor and delegating to the method of the same name.
+macro_rules! delegate {
+    ($(
+        $(#[$attr:meta])*
+        $kind:ident fn $name:ident $(<$generic:ident: $bound:path>)?
+            ($($arg:ident: $ty:ty),*) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub async fn $name $(<$generic: $bound>)? (&self, $($arg: $ty),*) -> $ret {
+                delegate!(@call $kind self.sensor.lock().await.$name($($arg),*))
+            }
+        )*
+    };
+    (@call async $call:expr) => { $call.await };
+    (@call sync $call:expr) => { $call };
+}
+
+/// SCD30 guarded by a mutex, see the [module documentation](self).
+pub struct SharedScd30<M: RawMutex, I2C, D = NoDelay> {
+    sensor: Mutex<M, Scd30<I2C, D>>,
+}
+
+impl<M: RawMutex, I2C, D> SharedScd30<M, I2C, D> {
+    /// Wraps `sensor` to share it between tasks.
+    pub const fn new(sensor: Scd30<I2C, D>) -> Self {
+        Self {
+            sensor: Mutex::new(sensor),
+        }
+    }
+
+    /// Waits until no other task uses the sensor and locks it for exclusive use until the
+    /// returned guard is dropped.
+    pub async fn lock(&self) -> MutexGuard<'_, M, Scd30<I2C, D>> {
+        self.sensor.lock().await
+    }
+
+    /// Returns the sensor.
+    pub fn into_inner(self) -> Scd30<I2C, D> {
+        self.sensor.into_inner()
+    }
+}
+
+impl<M, I2C, D, I2cErr> SharedScd30<M, I2C, D>
+where
+    M: RawMutex,
+    I2C: embedded_hal_async::i2c::I2c<Error = I2cErr>,
+    D: DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    delegate! {
+        /// See [Scd30::error_counters].
+        sync fn error_counters() -> ErrorCounters;
+        /// See [Scd30::metrics].
+        sync fn metrics() -> BusMetrics;
+        /// See [Scd30::timings].
+        sync fn timings() -> Timings;
+        /// See [Scd30::sample_accounting].
+        sync fn sample_accounting() -> SampleAccounting;
+        /// See [Scd30::config].
+        sync fn config() -> Scd30Config;
+        /// See [Scd30::cached_config].
+        sync fn cached_config() -> Option<Scd30Config>;
+        /// See [Scd30::is_warming_up].
+        sync fn is_warming_up() -> bool;
+        /// See [Scd30::is_suspended].
+        sync fn is_suspended() -> bool;
+        /// See [Scd30::measuring_state].
+        sync fn measuring_state() -> MeasuringState;
+        /// See [Scd30::compensation].
+        sync fn compensation() -> Option<CompensationMode>;
+        /// See [Scd30::firmware].
+        sync fn firmware() -> Option<FirmwareVersion>;
+        /// See [Scd30::health_report].
+        async fn health_report() -> Result<HealthReport, Scd30Error<I2cErr>>;
+        /// See [Scd30::snapshot].
+        async fn snapshot() -> Result<SensorSnapshot, Scd30Error<I2cErr>>;
+        /// See [Scd30::read_all_settings].
+        async fn read_all_settings() -> Result<SettingsSnapshot, Scd30Error<I2cErr>>;
+        /// See [Scd30::restore].
+        async fn restore<W: DelayNs>(
+            snapshot: &SensorSnapshot,
+            delay: &mut W
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::read_config].
+        async fn read_config() -> Result<Scd30Config, Scd30Error<I2cErr>>;
+        /// See [Scd30::refresh].
+        async fn refresh() -> Result<Scd30Config, Scd30Error<I2cErr>>;
+        /// See [Scd30::trigger_continuous_measurements].
+        async fn trigger_continuous_measurements(
+            pressure_compensation: Option<AmbientPressureCompensation>
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::get_ambient_pressure].
+        async fn get_ambient_pressure() -> Result<AmbientPressureCompensation, Scd30Error<I2cErr>>;
+        /// See [Scd30::stop_continuous_measurements].
+        async fn stop_continuous_measurements() -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::set_measurement_interval].
+        async fn set_measurement_interval(
+            interval: MeasurementInterval
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::get_measurement_interval].
+        async fn get_measurement_interval() -> Result<MeasurementInterval, Scd30Error<I2cErr>>;
+        /// See [Scd30::is_data_ready].
+        async fn is_data_ready() -> Result<DataStatus, Scd30Error<I2cErr>>;
+        /// See [Scd30::wait_for_data_ready]. Other tasks can not use the sensor while waiting.
+        async fn wait_for_data_ready(timeout: Duration) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::next_measurement]. Other tasks can not use the sensor while waiting.
+        async fn next_measurement() -> Result<Measurement, Scd30Error<I2cErr>>;
+        /// See [Scd30::read_measurement].
+        async fn read_measurement() -> Result<Measurement, Scd30Error<I2cErr>>;
+        /// See [Scd30::read_qualified_measurement].
+        async fn read_qualified_measurement() -> Result<QualifiedMeasurement, Scd30Error<I2cErr>>;
+        /// See [Scd30::read_measurement_into].
+        async fn read_measurement_into(
+            buf: &mut [u8; 18]
+        ) -> Result<Measurement, Scd30Error<I2cErr>>;
+        /// See [Scd30::read_measurement_raw].
+        async fn read_measurement_raw(buf: &mut [u8; 18]) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::suspend].
+        async fn suspend() -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::resume].
+        async fn resume<W: DelayNs>(delay: &mut W) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::set_automatic_self_calibration].
+        async fn set_automatic_self_calibration(
+            setting: AutomaticSelfCalibration
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::get_automatic_self_calibration].
+        async fn get_automatic_self_calibration()
+            -> Result<AutomaticSelfCalibration, Scd30Error<I2cErr>>;
+        /// See [Scd30::set_forced_recalibration].
+        async fn set_forced_recalibration(
+            frc: ForcedRecalibrationValue
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::get_forced_recalibration].
+        async fn get_forced_recalibration()
+            -> Result<ForcedRecalibrationValue, Scd30Error<I2cErr>>;
+        /// See [Scd30::set_temperature_offset].
+        async fn set_temperature_offset(
+            offset: TemperatureOffset
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::get_temperature_offset].
+        async fn get_temperature_offset() -> Result<TemperatureOffset, Scd30Error<I2cErr>>;
+        /// See [Scd30::set_altitude_compensation].
+        async fn set_altitude_compensation(
+            altitude: AltitudeCompensation
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::set_compensation].
+        async fn set_compensation(mode: CompensationMode) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::select_compensation].
+        async fn select_compensation(
+            pressure_mbar: Option<f32>,
+            altitude_m: Option<f32>
+        ) -> Result<Option<CompensationMode>, Scd30Error<I2cErr>>;
+        /// See [Scd30::get_altitude_compensation].
+        async fn get_altitude_compensation() -> Result<AltitudeCompensation, Scd30Error<I2cErr>>;
+        /// See [Scd30::read_firmware_version].
+        async fn read_firmware_version() -> Result<FirmwareVersion, Scd30Error<I2cErr>>;
+        /// See [Scd30::init].
+        async fn init() -> Result<FirmwareVersion, Scd30Error<I2cErr>>;
+        /// See [Scd30::soft_reset].
+        async fn soft_reset() -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::general_call_reset].
+        async fn general_call_reset() -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::soft_reset_and_reinit].
+        async fn soft_reset_and_reinit<W: DelayNs>(
+            delay: &mut W,
+            config: Option<&Scd30Config>
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::apply_config].
+        async fn apply_config(config: &Scd30Config) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::apply_preset].
+        async fn apply_preset(preset: Preset) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::commit].
+        async fn commit<W: DelayNs>(
+            transaction: &ConfigTransaction,
+            delay: &mut W
+        ) -> Result<(), Scd30Error<I2cErr>>;
+        /// See [Scd30::reconfigure].
+        async fn reconfigure<W: DelayNs>(
+            delay: &mut W,
+            changes: impl FnOnce(ConfigTransaction) -> ConfigTransaction
+        ) -> Result<(), Scd30Error<I2cErr>>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    #[tokio::test]
+    async fn tasks_share_the_sensor() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0xD1, 0x00]),
+            I2cTransaction::read(0x61, vec![0x03, 0x42, 0xF3]),
+            I2cTransaction::write(0x61, vec![0x46, 0x00]),
+            I2cTransaction::read(0x61, vec![0x00, 0x02, 0xE3]),
+            I2cTransaction::write(0x61, vec![0x03, 0x00]).with_error(ErrorKind::Other),
+        ];
+        let mut i2c = I2cMock::new(&expected_transactions);
+        let sensor = SharedScd30::<NoopRawMutex, _>::new(Scd30::new(i2c.clone()));
+
+        let display = async {
+            assert_eq!(
+                sensor.read_firmware_version().await,
+                Ok(FirmwareVersion::new(3, 66))
+            );
+            assert_eq!(
+                sensor.get_measurement_interval().await,
+                Ok(MeasurementInterval::try_from(2).unwrap())
+            );
+        };
+        let uplink = async {
+            assert!(sensor.read_measurement().await.is_err());
+            assert_eq!(sensor.error_counters().await.i2c, 1);
+        };
+        tokio::join!(display, uplink);
+
+        sensor.into_inner().shutdown();
+        i2c.done();
+    }
+}
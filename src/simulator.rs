@@ -0,0 +1,506 @@
+//! Simulated SCD30 for developing and validating algorithms on the host.
+//!
+//! [SimulatedScd30] implements the I2C traits and answers the sensor's commands, so it can be
+//! handed to the interface in place of a real bus. The simulated air follows a configurable model:
+//!
+//! - [Noise] with a normal distribution added to every sample,
+//! - a slow drift of the CO2 baseline, as caused by aging of the sensor,
+//! - [Occupancy] raising the CO2 concentration while people are present and the ventilation
+//!   lowering it again afterwards,
+//! - [SimulatedFaults] corrupting responses or keeping the sensor from becoming ready.
+//!
+//! The simulated time is read from a [Clock] on every I2C operation. Using a clock controlled by the
+//! simulation, rather than the system time, keeps simulations deterministic and lets them run as
+//! fast as the host allows:
+//!
+//! ```ignore
+//! use scd30_interface::simulator::{Noise, SimulatedScd30};
+//!
+//! static NOW: AtomicU64 = AtomicU64::new(0);
+//!
+//! let i2c = SimulatedScd30::new(Measurement::new(420.0, 21.0, 40.0), || NOW.load(Relaxed))
+//!     .with_noise(Noise { co2_ppm: 10.0, temperature: 0.1, humidity: 0.5 }, 42)
+//!     .with_drift(5.0);
+//! let mut sensor = Scd30::new(i2c);
+//! sensor.trigger_continuous_measurements(None)?;
+//! for _ in 0..1000 {
+//!     NOW.fetch_add(2000, Relaxed);
+//!     filter.push(sensor.read_measurement()?);
+//! }
+//! ```
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation, SevenBitAddress};
+
+use crate::{
+    codec::{compute_crc8, Frame},
+    command::Command,
+    data::Measurement,
+    options::Clock,
+};
+
+const ADDRESS: u8 = 0x61;
+const GENERAL_CALL_ADDRESS: u8 = 0x00;
+const GENERAL_CALL_RESET: u8 = 0x06;
+const FIRMWARE_VERSION: [u8; 2] = [3, 66];
+const MS_PER_DAY: f32 = 86_400_000.0;
+
+/// Decides whether a fault is active at the given simulated time in milliseconds.
+pub type FaultSchedule = fn(time_ms: u64) -> bool;
+
+/// Returns the number of people present at the given simulated time in milliseconds.
+pub type OccupancySchedule = fn(time_ms: u64) -> u8;
+
+/// Standard deviations of the normally distributed noise added to every sample.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Noise {
+    /// Standard deviation of the CO2 concentration in ppm.
+    pub co2_ppm: f32,
+    /// Standard deviation of the temperature in °C.
+    pub temperature: f32,
+    /// Standard deviation of the relative humidity in %.
+    pub humidity: f32,
+}
+
+/// Model of people present in the room, each exhaling CO2, and of the ventilation removing it.
+#[derive(Clone, Copy, Debug)]
+pub struct Occupancy {
+    /// Number of people present over time.
+    pub schedule: OccupancySchedule,
+    /// Increase of the CO2 concentration per person and minute in ppm.
+    pub ppm_per_person_minute: f32,
+    /// Share of the room's air exchanged per hour, e.g. `0.5` for a poorly ventilated room.
+    pub air_changes_per_hour: f32,
+}
+
+/// Faults of the simulated sensor. Each fault is active while its schedule returns `true`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SimulatedFaults {
+    /// Responses read are corrupted, so their CRC check fails.
+    pub crc_corruption: Option<FaultSchedule>,
+    /// The sensor reports no data to be ready, even though samples are taken.
+    pub stuck_not_ready: Option<FaultSchedule>,
+}
+
+/// Simulated SCD30 attached to an I2C bus, see the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct SimulatedScd30 {
+    clock: Clock,
+    environment: Measurement,
+    noise: Option<Noise>,
+    drift_ppm_per_day: f32,
+    occupancy: Option<Occupancy>,
+    faults: SimulatedFaults,
+    rng: u64,
+    now_ms: u64,
+    measuring: bool,
+    next_sample_ms: u64,
+    occupancy_ppm: f32,
+    correction_ppm: f32,
+    sample: Option<Measurement>,
+    last_sample: Measurement,
+    response: [u8; 18],
+    response_len: usize,
+    interval: u16,
+    automatic_self_calibration: u16,
+    forced_recalibration: u16,
+    temperature_offset: u16,
+    altitude: u16,
+    pressure: u16,
+}
+
+impl SimulatedScd30 {
+    /// Creates a simulated sensor in air with the conditions of `environment`, without noise,
+    /// drift, occupancy or faults. The simulated time in milliseconds is read from `clock`.
+    pub fn new(environment: Measurement, clock: Clock) -> Self {
+        Self {
+            clock,
+            environment,
+            noise: None,
+            drift_ppm_per_day: 0.0,
+            occupancy: None,
+            faults: SimulatedFaults::default(),
+            rng: 1,
+            now_ms: 0,
+            measuring: false,
+            next_sample_ms: 0,
+            occupancy_ppm: 0.0,
+            correction_ppm: 0.0,
+            sample: None,
+            last_sample: environment,
+            response: [0; 18],
+            response_len: 0,
+            interval: 2,
+            automatic_self_calibration: 0,
+            forced_recalibration: 400,
+            temperature_offset: 0,
+            altitude: 0,
+            pressure: 0,
+        }
+    }
+
+    /// Adds `noise` to every sample. The noise is generated from `seed`, so simulations using the
+    /// same seed produce the same samples.
+    pub fn with_noise(mut self, noise: Noise, seed: u64) -> Self {
+        self.noise = Some(noise);
+        // Xorshift does not leave the all-zero state.
+        self.rng = if seed == 0 { 1 } else { seed };
+        self
+    }
+
+    /// Lets the CO2 baseline drift by `ppm_per_day`.
+    pub fn with_drift(mut self, ppm_per_day: f32) -> Self {
+        self.drift_ppm_per_day = ppm_per_day;
+        self
+    }
+
+    /// Adds CO2 exhaled by people present according to `occupancy`.
+    pub fn with_occupancy(mut self, occupancy: Occupancy) -> Self {
+        self.occupancy = Some(occupancy);
+        self
+    }
+
+    /// Injects `faults` into the communication with the sensor.
+    pub fn with_faults(mut self, faults: SimulatedFaults) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    // Advances the simulated time to the clock's time, taking all samples due meanwhile if
+    // continuous measurements are running.
+    fn advance(&mut self) {
+        let target = (self.clock)().max(self.now_ms);
+        while self.measuring && self.next_sample_ms <= target {
+            self.now_ms = self.next_sample_ms;
+            self.take_sample();
+            self.next_sample_ms += self.interval_ms();
+        }
+        self.now_ms = target;
+    }
+
+    fn interval_ms(&self) -> u64 {
+        self.interval as u64 * 1000
+    }
+
+    fn take_sample(&mut self) {
+        if let Some(occupancy) = self.occupancy {
+            let minutes = self.interval as f32 / 60.0;
+            let people = (occupancy.schedule)(self.now_ms) as f32;
+            let exchanged = (occupancy.air_changes_per_hour * minutes / 60.0).min(1.0);
+            self.occupancy_ppm += people * occupancy.ppm_per_person_minute * minutes;
+            self.occupancy_ppm -= self.occupancy_ppm * exchanged;
+        }
+        let mut sample = Measurement::new(
+            self.true_co2_ppm() + self.correction_ppm,
+            self.environment.temperature - self.temperature_offset as f32 / 100.0,
+            self.environment.humidity,
+        );
+        if let Some(noise) = self.noise {
+            sample.co2_concentration += noise.co2_ppm * self.gaussian();
+            sample.temperature += noise.temperature * self.gaussian();
+            sample.humidity += noise.humidity * self.gaussian();
+        }
+        sample.co2_concentration = sample.co2_concentration.max(0.0);
+        sample.humidity = sample.humidity.clamp(0.0, 100.0);
+        self.sample = Some(sample);
+        self.last_sample = sample;
+    }
+
+    fn true_co2_ppm(&self) -> f32 {
+        self.environment.co2_concentration
+            + self.drift_ppm_per_day * self.now_ms as f32 / MS_PER_DAY
+            + self.occupancy_ppm
+    }
+
+    // Xorshift64 generating uniformly distributed values in [0, 1).
+    fn uniform(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u32 << 24) as f32
+    }
+
+    // Approximates a standard normal distribution by the sum of 12 uniform values, which only
+    // requires arithmetic available in `core`.
+    fn gaussian(&mut self) -> f32 {
+        (0..12).map(|_| self.uniform()).sum::<f32>() - 6.0
+    }
+
+    fn respond_word(&mut self, word: u16) {
+        let bytes = word.to_be_bytes();
+        self.response[..2].copy_from_slice(&bytes);
+        self.response[2] = compute_crc8(&bytes);
+        self.response_len = 3;
+    }
+
+    fn handle_write(&mut self, write: &[u8]) -> Result<(), ErrorKind> {
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+        let (opcode, argument) = match write {
+            [msb, lsb] => (u16::from_be_bytes([*msb, *lsb]), None),
+            [msb, lsb, argument @ ..] => {
+                let [argument] = Frame::<1>::new(argument).map_err(|_| nack)?.words();
+                (u16::from_be_bytes([*msb, *lsb]), Some(argument))
+            }
+            _ => return Err(nack),
+        };
+        self.response_len = 0;
+        match (Command::try_from(opcode).map_err(|_| nack)?, argument) {
+            (Command::TriggerContinuousMeasurement, Some(pressure)) => {
+                self.measuring = true;
+                self.pressure = pressure;
+                self.next_sample_ms = self.now_ms + self.interval_ms();
+            }
+            (Command::TriggerContinuousMeasurement, None) => self.respond_word(self.pressure),
+            (Command::StopContinuousMeasurement, None) => {
+                self.measuring = false;
+                self.sample = None;
+            }
+            (Command::SetMeasurementInterval, Some(interval @ 2..=1800)) => {
+                self.interval = interval;
+                self.next_sample_ms = self.now_ms + self.interval_ms();
+            }
+            (Command::SetMeasurementInterval, None) => self.respond_word(self.interval),
+            (Command::GetDataReady, None) => {
+                let stuck = self
+                    .faults
+                    .stuck_not_ready
+                    .is_some_and(|schedule| schedule(self.now_ms));
+                self.respond_word((self.sample.is_some() && !stuck) as u16);
+            }
+            (Command::ReadMeasurement, None) => {
+                let sample = self.sample.take().unwrap_or(self.last_sample);
+                self.response = sample.to_frame();
+                self.response_len = 18;
+            }
+            (Command::ActivateAutomaticSelfCalibration, Some(setting @ 0..=1)) => {
+                self.automatic_self_calibration = setting;
+            }
+            (Command::ActivateAutomaticSelfCalibration, None) => {
+                self.respond_word(self.automatic_self_calibration)
+            }
+            (Command::ForcedRecalibrationValue, Some(reference @ 400..=2000)) => {
+                self.forced_recalibration = reference;
+                self.correction_ppm = reference as f32 - self.true_co2_ppm();
+            }
+            (Command::ForcedRecalibrationValue, None) => {
+                self.respond_word(self.forced_recalibration)
+            }
+            (Command::SetTemperatureOffset, Some(offset)) => self.temperature_offset = offset,
+            (Command::SetTemperatureOffset, None) => self.respond_word(self.temperature_offset),
+            (Command::SetAltitudeCompensation, Some(altitude)) => self.altitude = altitude,
+            (Command::SetAltitudeCompensation, None) => self.respond_word(self.altitude),
+            (Command::ReadFirmwareVersion, None) => {
+                self.respond_word(u16::from_be_bytes(FIRMWARE_VERSION))
+            }
+            (Command::SoftReset, None) => self.reset(),
+            _ => return Err(nack),
+        }
+        Ok(())
+    }
+
+    fn handle_read(&mut self, read: &mut [u8]) -> Result<(), ErrorKind> {
+        if self.response_len == 0 {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        let len = read.len().min(self.response_len);
+        read[..len].copy_from_slice(&self.response[..len]);
+        if self
+            .faults
+            .crc_corruption
+            .is_some_and(|schedule| schedule(self.now_ms))
+        {
+            read[0] ^= 0xFF;
+        }
+        self.response_len = 0;
+        Ok(())
+    }
+
+    // Like the real sensor, the measurement settings are kept across resets.
+    fn reset(&mut self) {
+        self.sample = None;
+        self.response_len = 0;
+        if self.measuring {
+            self.next_sample_ms = self.now_ms + self.interval_ms();
+        }
+    }
+
+    fn handle(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        self.advance();
+        match address {
+            ADDRESS => operations
+                .iter_mut()
+                .try_for_each(|operation| match operation {
+                    Operation::Write(write) => self.handle_write(write),
+                    Operation::Read(read) => self.handle_read(read),
+                }),
+            GENERAL_CALL_ADDRESS => {
+                if let [Operation::Write([GENERAL_CALL_RESET])] = operations {
+                    self.reset();
+                }
+                Ok(())
+            }
+            _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        }
+    }
+}
+
+impl ErrorType for SimulatedScd30 {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal::i2c::I2c for SimulatedScd30 {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.handle(address, operations)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for SimulatedScd30 {
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.handle(address, operations)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "blocking")]
+mod tests {
+    use super::*;
+    use crate::{
+        blocking::Scd30,
+        data::{DataStatus, FirmwareVersion, ForcedRecalibrationValue},
+        error::{DataError, Scd30Error},
+    };
+
+    const ENVIRONMENT: Measurement = Measurement::new(420.0, 21.0, 40.0);
+
+    std::thread_local! {
+        static NOW: core::cell::Cell<u64> = const { core::cell::Cell::new(0) };
+    }
+
+    fn now() -> u64 {
+        NOW.with(|now| now.get())
+    }
+
+    fn advance(ms: u64) {
+        NOW.with(|now| now.set(now.get() + ms));
+    }
+
+    fn simulator() -> SimulatedScd30 {
+        NOW.with(|now| now.set(0));
+        SimulatedScd30::new(ENVIRONMENT, now)
+    }
+
+    fn next_measurement(sensor: &mut Scd30<SimulatedScd30>) -> Measurement {
+        advance(2000);
+        assert_eq!(sensor.is_data_ready().unwrap(), DataStatus::Ready);
+        sensor.read_measurement().unwrap()
+    }
+
+    #[test]
+    fn driver_reads_simulated_environment() {
+        let mut sensor = Scd30::new(simulator());
+
+        assert_eq!(
+            sensor.read_firmware_version().unwrap(),
+            FirmwareVersion::new(3, 66)
+        );
+        sensor.trigger_continuous_measurements(None).unwrap();
+        assert_eq!(sensor.is_data_ready().unwrap(), DataStatus::NotReady);
+        assert!(next_measurement(&mut sensor).approx_eq(&ENVIRONMENT, 0.001));
+        assert_eq!(sensor.is_data_ready().unwrap(), DataStatus::NotReady);
+    }
+
+    #[test]
+    fn noise_has_configured_spread() {
+        let noise = Noise {
+            co2_ppm: 10.0,
+            temperature: 0.1,
+            humidity: 0.5,
+        };
+        let mut sensor = Scd30::new(simulator().with_noise(noise, 42));
+        sensor.trigger_continuous_measurements(None).unwrap();
+
+        let samples: Vec<f32> = (0..1000)
+            .map(|_| next_measurement(&mut sensor).co2_concentration)
+            .collect();
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        let variance =
+            samples.iter().map(|co2| (co2 - mean).powi(2)).sum::<f32>() / samples.len() as f32;
+        assert!((mean - 420.0).abs() < 1.0, "mean {}", mean);
+        assert!(
+            (variance.sqrt() - 10.0).abs() < 1.0,
+            "deviation {}",
+            variance
+        );
+    }
+
+    #[test]
+    fn baseline_drifts_over_time() {
+        let mut sensor = Scd30::new(simulator().with_drift(10.0));
+        sensor.trigger_continuous_measurements(None).unwrap();
+
+        advance(3 * 86_400_000 - 2000);
+        let measurement = next_measurement(&mut sensor);
+        assert!((measurement.co2_concentration - 450.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn occupancy_raises_and_ventilation_lowers_co2() {
+        let occupancy = Occupancy {
+            schedule: |time_ms| if time_ms <= 3_600_000 { 2 } else { 0 },
+            ppm_per_person_minute: 5.0,
+            air_changes_per_hour: 0.5,
+        };
+        let mut sensor = Scd30::new(simulator().with_occupancy(occupancy));
+        sensor.trigger_continuous_measurements(None).unwrap();
+
+        advance(3_600_000 - 2000);
+        let occupied = next_measurement(&mut sensor).co2_concentration;
+        assert!(occupied > 800.0, "occupied {}", occupied);
+        advance(4 * 3_600_000);
+        let vacated = next_measurement(&mut sensor).co2_concentration;
+        assert!(vacated < 500.0, "vacated {}", vacated);
+    }
+
+    #[test]
+    fn forced_recalibration_corrects_drift() {
+        let mut sensor = Scd30::new(simulator().with_drift(100.0));
+        sensor.trigger_continuous_measurements(None).unwrap();
+
+        advance(86_400_000);
+        sensor
+            .set_forced_recalibration(ForcedRecalibrationValue::try_from(420).unwrap())
+            .unwrap();
+        let measurement = next_measurement(&mut sensor);
+        assert!((measurement.co2_concentration - 420.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn injects_faults_on_schedule() {
+        let faults = SimulatedFaults {
+            crc_corruption: Some(|time_ms| time_ms == 2000),
+            stuck_not_ready: Some(|time_ms| time_ms >= 4000),
+        };
+        let mut sensor = Scd30::new(simulator().with_faults(faults));
+        sensor.trigger_continuous_measurements(None).unwrap();
+
+        advance(2000);
+        assert_eq!(
+            sensor.is_data_ready().unwrap_err(),
+            Scd30Error::DataError(DataError::CrcFailed)
+        );
+        advance(2000);
+        assert_eq!(sensor.is_data_ready().unwrap(), DataStatus::NotReady);
+    }
+}
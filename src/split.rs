@@ -0,0 +1,304 @@
+//! Separate handles for measuring and configuring the sensor.
+//!
+//! Often one part of an application polls the measurements, while another part, e.g. a command
+//! shell or a remote management channel, occasionally changes the configuration. [split] hands
+//! each part its own handle to a sensor placed in a [RefCell]:
+//!
+//! ```ignore
+//! let sensor = RefCell::new(Scd30::new(i2c));
+//! let (mut config, mut measure) = split(&sensor);
+//!
+//! let logger = Logger::new(measure);
+//! let shell = Shell::new(config);
+//! ```
+//!
+//! Both handles share the bus and the state of the interface, e.g. the cached configuration, so
+//! a [MeasureHandle] sees the measurement interval set through the [ConfigHandle]. The sensor is
+//! only borrowed for the duration of a call and the handles are not [Sync], so the calls can not
+//! overlap. Tasks of async applications share the sensor with
+//! [SharedScd30](crate::shared::SharedScd30) instead.
+
+use core::cell::{RefCell, RefMut};
+
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+use crate::{
+    blocking::Scd30,
+    data::{
+        AltitudeCompensation, AmbientPressureCompensation, AutomaticSelfCalibration,
+        CompensationMode, DataStatus, FirmwareVersion, ForcedRecalibrationValue, Measurement,
+        MeasurementInterval, MeasuringState, Preset, QualifiedMeasurement, Scd30Config,
+        TemperatureOffset,
+    },
+    error::Scd30Error,
+    transaction::ConfigTransaction,
+};
+
+/// Splits the sensor in `cell` into a [ConfigHandle] and a [MeasureHandle], see the
+/// [module documentation](self).
+pub fn split<I2C, D>(
+    cell: &RefCell<Scd30<I2C, D>>,
+) -> (ConfigHandle<'_, I2C, D>, MeasureHandle<'_, I2C, D>) {
+    (ConfigHandle { cell }, MeasureHandle { cell })
+}
+
+/// Handle configuring a split sensor, see [split].
+pub struct ConfigHandle<'a, I2C, D> {
+    cell: &'a RefCell<Scd30<I2C, D>>,
+}
+
+/// Handle measuring with a split sensor, see [split].
+pub struct MeasureHandle<'a, I2C, D> {
+    cell: &'a RefCell<Scd30<I2C, D>>,
+}
+
+impl<I2C, D, I2cErr> ConfigHandle<'_, I2C, D>
+where
+    I2C: I2c<Error = I2cErr>,
+    D: DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    fn sensor(&self) -> RefMut<'_, Scd30<I2C, D>> {
+        self.cell.borrow_mut()
+    }
+
+    /// See [Scd30::config].
+    pub fn config(&self) -> Scd30Config {
+        self.sensor().config()
+    }
+
+    /// See [Scd30::read_config].
+    pub fn read_config(&mut self) -> Result<Scd30Config, Scd30Error<I2cErr>> {
+        self.sensor().read_config()
+    }
+
+    /// See [Scd30::apply_config].
+    pub fn apply_config(&mut self, config: &Scd30Config) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().apply_config(config)
+    }
+
+    /// See [Scd30::apply_preset].
+    pub fn apply_preset(&mut self, preset: Preset) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().apply_preset(preset)
+    }
+
+    /// See [Scd30::commit].
+    pub fn commit<W: DelayNs>(
+        &mut self,
+        transaction: &ConfigTransaction,
+        delay: &mut W,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().commit(transaction, delay)
+    }
+
+    /// See [Scd30::reconfigure]. The changes are recorded before the sensor is borrowed, so
+    /// `changes` may use the [MeasureHandle].
+    pub fn reconfigure<W: DelayNs>(
+        &mut self,
+        delay: &mut W,
+        changes: impl FnOnce(ConfigTransaction) -> ConfigTransaction,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        let transaction = changes(ConfigTransaction::new());
+        self.sensor().reconfigure(delay, |_| transaction)
+    }
+
+    /// See [Scd30::set_measurement_interval].
+    pub fn set_measurement_interval(
+        &mut self,
+        interval: MeasurementInterval,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().set_measurement_interval(interval)
+    }
+
+    /// See [Scd30::get_measurement_interval].
+    pub fn get_measurement_interval(&mut self) -> Result<MeasurementInterval, Scd30Error<I2cErr>> {
+        self.sensor().get_measurement_interval()
+    }
+
+    /// See [Scd30::set_automatic_self_calibration].
+    pub fn set_automatic_self_calibration(
+        &mut self,
+        setting: AutomaticSelfCalibration,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().set_automatic_self_calibration(setting)
+    }
+
+    /// See [Scd30::get_automatic_self_calibration].
+    pub fn get_automatic_self_calibration(
+        &mut self,
+    ) -> Result<AutomaticSelfCalibration, Scd30Error<I2cErr>> {
+        self.sensor().get_automatic_self_calibration()
+    }
+
+    /// See [Scd30::set_forced_recalibration].
+    pub fn set_forced_recalibration(
+        &mut self,
+        frc: ForcedRecalibrationValue,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().set_forced_recalibration(frc)
+    }
+
+    /// See [Scd30::get_forced_recalibration].
+    pub fn get_forced_recalibration(
+        &mut self,
+    ) -> Result<ForcedRecalibrationValue, Scd30Error<I2cErr>> {
+        self.sensor().get_forced_recalibration()
+    }
+
+    /// See [Scd30::set_temperature_offset].
+    pub fn set_temperature_offset(
+        &mut self,
+        offset: TemperatureOffset,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().set_temperature_offset(offset)
+    }
+
+    /// See [Scd30::get_temperature_offset].
+    pub fn get_temperature_offset(&mut self) -> Result<TemperatureOffset, Scd30Error<I2cErr>> {
+        self.sensor().get_temperature_offset()
+    }
+
+    /// See [Scd30::set_altitude_compensation].
+    pub fn set_altitude_compensation(
+        &mut self,
+        altitude: AltitudeCompensation,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().set_altitude_compensation(altitude)
+    }
+
+    /// See [Scd30::get_altitude_compensation].
+    pub fn get_altitude_compensation(
+        &mut self,
+    ) -> Result<AltitudeCompensation, Scd30Error<I2cErr>> {
+        self.sensor().get_altitude_compensation()
+    }
+
+    /// See [Scd30::set_compensation].
+    pub fn set_compensation(&mut self, mode: CompensationMode) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().set_compensation(mode)
+    }
+
+    /// See [Scd30::read_firmware_version].
+    pub fn read_firmware_version(&mut self) -> Result<FirmwareVersion, Scd30Error<I2cErr>> {
+        self.sensor().read_firmware_version()
+    }
+
+    /// See [Scd30::soft_reset].
+    pub fn soft_reset(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().soft_reset()
+    }
+}
+
+impl<I2C, D, I2cErr> MeasureHandle<'_, I2C, D>
+where
+    I2C: I2c<Error = I2cErr>,
+    D: DelayNs,
+    I2cErr: embedded_hal::i2c::Error,
+{
+    fn sensor(&self) -> RefMut<'_, Scd30<I2C, D>> {
+        self.cell.borrow_mut()
+    }
+
+    /// See [Scd30::measuring_state].
+    pub fn measuring_state(&self) -> MeasuringState {
+        self.sensor().measuring_state()
+    }
+
+    /// See [Scd30::is_warming_up].
+    pub fn is_warming_up(&self) -> bool {
+        self.sensor().is_warming_up()
+    }
+
+    /// See [Scd30::trigger_continuous_measurements].
+    pub fn trigger_continuous_measurements(
+        &mut self,
+        pressure_compensation: Option<AmbientPressureCompensation>,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor()
+            .trigger_continuous_measurements(pressure_compensation)
+    }
+
+    /// See [Scd30::stop_continuous_measurements].
+    pub fn stop_continuous_measurements(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().stop_continuous_measurements()
+    }
+
+    /// See [Scd30::is_data_ready].
+    pub fn is_data_ready(&mut self) -> Result<DataStatus, Scd30Error<I2cErr>> {
+        self.sensor().is_data_ready()
+    }
+
+    /// See [Scd30::wait_for_data_ready].
+    pub fn wait_for_data_ready<W: DelayNs>(
+        &mut self,
+        delay: &mut W,
+        timeout_ms: u32,
+    ) -> Result<(), Scd30Error<I2cErr>> {
+        self.sensor().wait_for_data_ready(delay, timeout_ms)
+    }
+
+    /// See [Scd30::read_measurement].
+    pub fn read_measurement(&mut self) -> Result<Measurement, Scd30Error<I2cErr>> {
+        self.sensor().read_measurement()
+    }
+
+    /// See [Scd30::read_qualified_measurement].
+    pub fn read_qualified_measurement(
+        &mut self,
+    ) -> Result<QualifiedMeasurement, Scd30Error<I2cErr>> {
+        self.sensor().read_qualified_measurement()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+    };
+
+    #[test]
+    fn handles_share_the_sensor() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+            I2cTransaction::write(0x61, vec![0x46, 0x00, 0x00, 0x05, 0x74]),
+            I2cTransaction::write(0x61, vec![0x02, 0x02]),
+            I2cTransaction::read(0x61, vec![0x00, 0x01, 0xB0]),
+        ];
+        let sensor = RefCell::new(Scd30::new(I2cMock::new(&expected_transactions)));
+        let (mut config, mut measure) = split(&sensor);
+
+        measure.trigger_continuous_measurements(None).unwrap();
+        config
+            .set_measurement_interval(MeasurementInterval::try_from(5).unwrap())
+            .unwrap();
+        assert_eq!(measure.is_data_ready(), Ok(DataStatus::Ready));
+        assert_eq!(measure.measuring_state(), MeasuringState::Measuring);
+        assert_eq!(
+            config.config().measurement_interval,
+            Some(MeasurementInterval::try_from(5).unwrap())
+        );
+
+        sensor.into_inner().shutdown().done();
+    }
+
+    #[test]
+    fn reconfigure_changes_may_use_the_measure_handle() {
+        let expected_transactions = [
+            I2cTransaction::write(0x61, vec![0x01, 0x04]),
+            I2cTransaction::write(0x61, vec![0x46, 0x00, 0x00, 0x05, 0x74]),
+        ];
+        let sensor = RefCell::new(Scd30::new(I2cMock::new(&expected_transactions)));
+        let (mut config, measure) = split(&sensor);
+
+        config
+            .reconfigure(&mut NoopDelay::new(), |changes| {
+                assert_eq!(measure.measuring_state(), MeasuringState::Unknown);
+                changes.measurement_interval(MeasurementInterval::try_from(5).unwrap())
+            })
+            .unwrap();
+
+        sensor.into_inner().shutdown().done();
+    }
+}
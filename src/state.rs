@@ -0,0 +1,158 @@
+//! Snapshot of the interface's state, see `Scd30::into_parts()`.
+
+use crate::{
+    data::{AmbientPressureCompensation, FirmwareVersion, MeasuringState, Scd30Config},
+    event::EventHook,
+    health::{BusMetrics, ErrorCounters, ResetCause, SampleAccounting, Timings},
+    interface::DEFAULT_INTERVAL,
+    options::{
+        Clock, CrcValidation, ReadStrategy, ReadVerification, ValueDecoding, WarmUpPolicy, Watchdog,
+    },
+    sequencing::SequenceGuard,
+};
+
+/// State of an interface besides its I2C peripheral and delay: its options, the settings applied
+/// to the sensor and the collected statistics.
+///
+/// Tearing down an interface with `into_parts()` and reconstructing it with `from_parts()` keeps
+/// this state, e.g. across a deep sleep of the MCU. Timestamps taken from the configured
+/// [Clock] are kept as well, so the clock should keep running during the sleep.
+#[derive(Clone, Copy, Debug)]
+pub struct Scd30State {
+    pub(crate) address: u8,
+    pub(crate) options: Options,
+    pub(crate) settings: Settings,
+    pub(crate) sequence: Sequence,
+    pub(crate) health: Health,
+}
+
+/// Options selected through the builder methods of the interface.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Options {
+    pub(crate) read_strategy: ReadStrategy,
+    pub(crate) crc_validation: CrcValidation,
+    pub(crate) verification: ReadVerification,
+    pub(crate) value_decoding: ValueDecoding,
+    pub(crate) warm_up: Option<WarmUpPolicy>,
+    pub(crate) watchdog: Option<Watchdog>,
+    pub(crate) clock: Option<Clock>,
+    pub(crate) timeout_ms: Option<u64>,
+    pub(crate) on_event: Option<EventHook>,
+}
+
+/// Settings applied to or read back from the sensor.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Settings {
+    pub(crate) interval: u16,
+    pub(crate) config: Scd30Config,
+    pub(crate) cache: Option<Scd30Config>,
+    pub(crate) firmware: Option<FirmwareVersion>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            config: Scd30Config::default(),
+            cache: None,
+            firmware: None,
+        }
+    }
+}
+
+/// Tracked progress of the measurements: whether the sensor is measuring, the commands sent and
+/// the samples read.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Sequence {
+    pub(crate) measuring: MeasuringState,
+    pub(crate) suspended: Option<AmbientPressureCompensation>,
+    pub(crate) guard: Option<SequenceGuard>,
+    pub(crate) samples_read: u16,
+    pub(crate) last_sample: Option<u64>,
+    pub(crate) last_read: Option<u64>,
+    pub(crate) last_reset: Option<ResetCause>,
+    pub(crate) ready_since: Option<u64>,
+}
+
+/// Statistics collected by the interface.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Health {
+    pub(crate) errors: ErrorCounters,
+    pub(crate) metrics: BusMetrics,
+    pub(crate) timings: Timings,
+    pub(crate) samples: SampleAccounting,
+    pub(crate) data_ready_latency_ms: Option<u64>,
+}
+
+impl Scd30State {
+    /// Returns the 7-bit I2C address of the sensor.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns the settings applied through the interface.
+    pub fn config(&self) -> Scd30Config {
+        self.settings.config
+    }
+
+    /// Returns the firmware version read from the sensor, if it was read.
+    pub fn firmware(&self) -> Option<FirmwareVersion> {
+        self.settings.firmware
+    }
+
+    /// Returns whether the sensor was suspended and not resumed yet.
+    pub fn is_suspended(&self) -> bool {
+        self.sequence.suspended.is_some()
+    }
+
+    /// Returns whether continuous measurements were started or stopped.
+    pub fn measuring_state(&self) -> MeasuringState {
+        self.sequence.measuring
+    }
+
+    /// Returns the number of errors encountered by the interface.
+    pub fn error_counters(&self) -> ErrorCounters {
+        self.health.errors
+    }
+
+    /// Returns the I2C traffic caused by the interface.
+    pub fn metrics(&self) -> BusMetrics {
+        self.health.metrics
+    }
+
+    /// Returns the durations of the operations of the interface.
+    pub fn timings(&self) -> Timings {
+        self.health.timings
+    }
+
+    /// Returns the accounting of the samples delivered by the sensor.
+    pub fn sample_accounting(&self) -> SampleAccounting {
+        self.health.samples
+    }
+
+    /// Returns the cause of the last reset issued through the interface.
+    pub fn last_reset(&self) -> Option<ResetCause> {
+        self.sequence.last_reset
+    }
+
+    /// Returns the number of samples read since continuous measurements were started or the
+    /// sensor was reset.
+    pub fn samples_read(&self) -> u16 {
+        self.sequence.samples_read
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Scd30State {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Scd30State {{ address: {=u8:#x}, config: {}, errors: {}, last_reset: {}, samples_read: {} }}",
+            self.address,
+            self.settings.config,
+            self.health.errors,
+            self.sequence.last_reset,
+            self.sequence.samples_read
+        )
+    }
+}
@@ -0,0 +1,381 @@
+//! Persistence of the sensor configuration in NOR flash.
+//!
+//! The settings applied through the interface, like the ambient pressure compensation, are only
+//! tracked in RAM and are lost when the device loses power. [save_config] stores them together
+//! with the bookkeeping of the last forced recalibration as a small, versioned record, from which
+//! [load_config] restores them after a power loss:
+//!
+//! ```ignore
+//! let record = ConfigRecord {
+//!     config: sensor.config(),
+//!     ..ConfigRecord::default()
+//! };
+//! save_config(&mut flash, CONFIG_OFFSET, &record)?;
+//!
+//! // After a power loss
+//! if let Some(record) = load_config(&mut flash, CONFIG_OFFSET)? {
+//!     sensor.apply_config(&record.config)?;
+//! }
+//! ```
+//!
+//! The record occupies [RECORD_LEN] bytes at the start of an erase sector of the flash. The
+//! remainder of the sector is erased when saving, so it should not be used for other data.
+
+use embedded_storage::nor_flash::{NorFlash, NorFlashError, ReadNorFlash};
+
+use crate::{
+    codec::compute_crc8,
+    data::{AmbientPressure, AmbientPressureCompensation, ForcedRecalibrationValue, Scd30Config},
+    error::StorageError,
+};
+
+/// Size of a stored record in bytes.
+pub const RECORD_LEN: usize = 32;
+
+const MAGIC: [u8; 2] = *b"S3";
+const VERSION: u8 = 1;
+
+// Bits of the flags byte, marking the values present in the record.
+const CONTINUOUS_MEASUREMENT: u8 = 1 << 0;
+const MEASUREMENT_INTERVAL: u8 = 1 << 1;
+const AUTOMATIC_SELF_CALIBRATION: u8 = 1 << 2;
+const TEMPERATURE_OFFSET: u8 = 1 << 3;
+const ALTITUDE_COMPENSATION: u8 = 1 << 4;
+const FORCED_RECALIBRATION: u8 = 1 << 5;
+const RECALIBRATED_AT: u8 = 1 << 6;
+
+/// Configuration and calibration bookkeeping of a sensor, as stored in flash.
+///
+/// Version 1 of the record is laid out as follows, multi-byte values are big endian:
+///
+/// | Bytes  | Content                                                          |
+/// |--------|------------------------------------------------------------------|
+/// | 0..2   | Magic `"S3"`                                                     |
+/// | 2      | Version                                                          |
+/// | 3      | Flags marking the values present                                 |
+/// | 4..6   | Ambient pressure in mBar, 0 for the default pressure             |
+/// | 6..21  | Interval, ASC, temperature offset, altitude, FRC value with CRC  |
+/// | 21..29 | Timestamp of the last forced recalibration                       |
+/// | 29..31 | Reserved                                                         |
+/// | 31     | CRC of bytes 0..31                                               |
+///
+/// Sensor settings are stored as on the wire: a 16-bit word followed by its CRC.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigRecord {
+    /// Settings applied to the sensor, see `Scd30::config()`.
+    pub config: Scd30Config,
+    /// Reference value of the last forced recalibration.
+    pub forced_recalibration: Option<ForcedRecalibrationValue>,
+    /// Time of the last forced recalibration. The unit is up to the application, it should use a
+    /// time base surviving power loss, e.g. seconds since the epoch from a real-time clock.
+    pub recalibrated_at: Option<u64>,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ConfigRecord {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "ConfigRecord {{ config: {}, forced_recalibration: {}, recalibrated_at: {} }}",
+            self.config,
+            self.forced_recalibration,
+            self.recalibrated_at
+        )
+    }
+}
+
+impl ConfigRecord {
+    fn encode(&self) -> [u8; RECORD_LEN] {
+        let mut record = [0; RECORD_LEN];
+        record[..2].copy_from_slice(&MAGIC);
+        record[2] = VERSION;
+        let mut flags = 0;
+        let config = &self.config;
+        if let Some(pressure_compensation) = config.continuous_measurement {
+            flags |= CONTINUOUS_MEASUREMENT;
+            record[4..6].copy_from_slice(&pressure_compensation.to_be_bytes());
+        }
+        let words = [
+            (
+                MEASUREMENT_INTERVAL,
+                config.measurement_interval.map(|value| value.to_be_bytes()),
+            ),
+            (
+                AUTOMATIC_SELF_CALIBRATION,
+                config
+                    .automatic_self_calibration
+                    .map(|value| value.to_be_bytes()),
+            ),
+            (
+                TEMPERATURE_OFFSET,
+                config.temperature_offset.map(|value| value.to_be_bytes()),
+            ),
+            (
+                ALTITUDE_COMPENSATION,
+                config
+                    .altitude_compensation
+                    .map(|value| value.to_be_bytes()),
+            ),
+            (
+                FORCED_RECALIBRATION,
+                self.forced_recalibration
+                    .as_ref()
+                    .map(|value| value.to_be_bytes()),
+            ),
+        ];
+        for (word, (flag, value)) in record[6..21].chunks_mut(3).zip(words) {
+            if let Some(value) = value {
+                flags |= flag;
+                word[..2].copy_from_slice(&value);
+            }
+            word[2] = compute_crc8(&word[..2]);
+        }
+        if let Some(recalibrated_at) = self.recalibrated_at {
+            flags |= RECALIBRATED_AT;
+            record[21..29].copy_from_slice(&recalibrated_at.to_be_bytes());
+        }
+        record[3] = flags;
+        record[RECORD_LEN - 1] = compute_crc8(&record[..RECORD_LEN - 1]);
+        record
+    }
+
+    fn decode<FlashErr: NorFlashError>(
+        record: &[u8; RECORD_LEN],
+    ) -> Result<Option<Self>, StorageError<FlashErr>> {
+        if record.iter().all(|&byte| byte == 0xFF) {
+            return Ok(None);
+        }
+        if record[..2] != MAGIC || compute_crc8(&record[..RECORD_LEN - 1]) != record[RECORD_LEN - 1]
+        {
+            return Err(StorageError::Corrupted);
+        }
+        if record[2] != VERSION {
+            return Err(StorageError::UnsupportedVersion(record[2]));
+        }
+        let flags = record[3];
+        let continuous_measurement = match u16::from_be_bytes([record[4], record[5]]) {
+            _ if flags & CONTINUOUS_MEASUREMENT == 0 => None,
+            0 => Some(AmbientPressureCompensation::DefaultPressure),
+            pressure => Some(AmbientPressureCompensation::CompensationPressure(
+                AmbientPressure::try_from(pressure).map_err(|_| StorageError::Corrupted)?,
+            )),
+        };
+        let recalibrated_at = (flags & RECALIBRATED_AT != 0).then(|| {
+            let mut timestamp = [0; 8];
+            timestamp.copy_from_slice(&record[21..29]);
+            u64::from_be_bytes(timestamp)
+        });
+        Ok(Some(Self {
+            config: Scd30Config {
+                continuous_measurement,
+                measurement_interval: word(record, 6, flags & MEASUREMENT_INTERVAL)?,
+                automatic_self_calibration: word(record, 9, flags & AUTOMATIC_SELF_CALIBRATION)?,
+                temperature_offset: word(record, 12, flags & TEMPERATURE_OFFSET)?,
+                altitude_compensation: word(record, 15, flags & ALTITUDE_COMPENSATION)?,
+            },
+            forced_recalibration: word(record, 18, flags & FORCED_RECALIBRATION)?,
+            recalibrated_at,
+        }))
+    }
+}
+
+// Parses the word starting at `start`, if its flag is set.
+fn word<'a, T: TryFrom<&'a [u8]>, FlashErr: NorFlashError>(
+    record: &'a [u8; RECORD_LEN],
+    start: usize,
+    flag: u8,
+) -> Result<Option<T>, StorageError<FlashErr>> {
+    if flag == 0 {
+        return Ok(None);
+    }
+    T::try_from(&record[start..start + 3])
+        .map(Some)
+        .map_err(|_| StorageError::Corrupted)
+}
+
+/// Saves `record` to `flash` at `offset`, which must be aligned to the erase size of the flash.
+///
+/// # Errors
+///
+/// - [Flash](StorageError::Flash) if erasing or writing the flash fails.
+/// - [UnsupportedFlash](StorageError::UnsupportedFlash) if [RECORD_LEN] is not a multiple of the
+///   flash's write size.
+/// - [Invalid](StorageError::Invalid) if the automatic self-calibration is
+///   [Unknown](crate::data::AutomaticSelfCalibration::Unknown), the flash is left untouched.
+pub fn save_config<F: NorFlash>(
+    flash: &mut F,
+    offset: u32,
+    record: &ConfigRecord,
+) -> Result<(), StorageError<F::Error>> {
+    if RECORD_LEN % F::WRITE_SIZE != 0 {
+        return Err(StorageError::UnsupportedFlash);
+    }
+    if let Some(setting) = record.config.automatic_self_calibration {
+        setting.is_active().map_err(StorageError::Invalid)?;
+    }
+    let erase_len = RECORD_LEN.div_ceil(F::ERASE_SIZE) * F::ERASE_SIZE;
+    flash
+        .erase(offset, offset + erase_len as u32)
+        .map_err(StorageError::Flash)?;
+    flash
+        .write(offset, &record.encode())
+        .map_err(StorageError::Flash)
+}
+
+/// Loads the record saved to `flash` at `offset` by [save_config]. Returns `None` if no record
+/// was saved yet.
+///
+/// # Errors
+///
+/// - [Flash](StorageError::Flash) if reading the flash fails.
+/// - [UnsupportedFlash](StorageError::UnsupportedFlash) if [RECORD_LEN] is not a multiple of the
+///   flash's read size.
+/// - [Corrupted](StorageError::Corrupted) if the stored record is damaged.
+/// - [UnsupportedVersion](StorageError::UnsupportedVersion) if the record was saved by a newer
+///   version of this library.
+pub fn load_config<F: ReadNorFlash>(
+    flash: &mut F,
+    offset: u32,
+) -> Result<Option<ConfigRecord>, StorageError<F::Error>> {
+    if RECORD_LEN % F::READ_SIZE != 0 {
+        return Err(StorageError::UnsupportedFlash);
+    }
+    let mut record = [0; RECORD_LEN];
+    flash
+        .read(offset, &mut record)
+        .map_err(StorageError::Flash)?;
+    ConfigRecord::decode(&record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        AltitudeCompensation, AutomaticSelfCalibration, MeasurementInterval, TemperatureOffset,
+    };
+    use crate::error::DataError;
+    use embedded_storage::nor_flash::{
+        check_erase, check_read, check_write, ErrorType, NorFlashErrorKind,
+    };
+
+    struct RamFlash([u8; 256]);
+
+    impl RamFlash {
+        fn new() -> Self {
+            Self([0xFF; 256])
+        }
+    }
+
+    impl ErrorType for RamFlash {
+        type Error = NorFlashErrorKind;
+    }
+
+    impl ReadNorFlash for RamFlash {
+        const READ_SIZE: usize = 1;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            check_read(self, offset, bytes.len())?;
+            let offset = offset as usize;
+            bytes.copy_from_slice(&self.0[offset..offset + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    impl NorFlash for RamFlash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = 64;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            check_erase(self, from, to)?;
+            self.0[from as usize..to as usize].fill(0xFF);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            check_write(self, offset, bytes.len())?;
+            for (cell, byte) in self.0[offset as usize..].iter_mut().zip(bytes) {
+                *cell &= byte;
+            }
+            Ok(())
+        }
+    }
+
+    fn record() -> ConfigRecord {
+        ConfigRecord {
+            config: Scd30Config {
+                continuous_measurement: Some(AmbientPressureCompensation::CompensationPressure(
+                    AmbientPressure::try_from(950).unwrap(),
+                )),
+                measurement_interval: Some(MeasurementInterval::try_from(30).unwrap()),
+                automatic_self_calibration: Some(AutomaticSelfCalibration::Inactive),
+                temperature_offset: Some(TemperatureOffset::try_from(2.5).unwrap()),
+                altitude_compensation: Some(AltitudeCompensation::from(480)),
+            },
+            forced_recalibration: Some(ForcedRecalibrationValue::try_from(420).unwrap()),
+            recalibrated_at: Some(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn saved_record_is_loaded() {
+        let mut flash = RamFlash::new();
+        save_config(&mut flash, 64, &record()).unwrap();
+        assert_eq!(load_config(&mut flash, 64).unwrap(), Some(record()));
+        assert_eq!(load_config(&mut flash, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn empty_record_is_loaded() {
+        let mut flash = RamFlash::new();
+        save_config(&mut flash, 0, &record()).unwrap();
+        save_config(&mut flash, 0, &ConfigRecord::default()).unwrap();
+        assert_eq!(
+            load_config(&mut flash, 0).unwrap(),
+            Some(ConfigRecord::default())
+        );
+    }
+
+    #[test]
+    fn unknown_self_calibration_is_not_saved() {
+        let mut flash = RamFlash::new();
+        let mut record = record();
+        record.config.automatic_self_calibration = Some(AutomaticSelfCalibration::Unknown(2));
+        assert!(matches!(
+            save_config(&mut flash, 0, &record),
+            Err(StorageError::Invalid(DataError::ValueOutOfRange { .. }))
+        ));
+        assert_eq!(load_config(&mut flash, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn damaged_record_is_rejected() {
+        let mut flash = RamFlash::new();
+        save_config(&mut flash, 0, &record()).unwrap();
+        flash.0[7] ^= 0x01;
+        assert_eq!(load_config(&mut flash, 0), Err(StorageError::Corrupted));
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut encoded = record().encode();
+        encoded[2] = 2;
+        encoded[RECORD_LEN - 1] = compute_crc8(&encoded[..RECORD_LEN - 1]);
+        assert_eq!(
+            ConfigRecord::decode::<NorFlashErrorKind>(&encoded),
+            Err(StorageError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn unaligned_offset_fails() {
+        let mut flash = RamFlash::new();
+        assert_eq!(
+            save_config(&mut flash, 8, &record()),
+            Err(StorageError::Flash(NorFlashErrorKind::NotAligned))
+        );
+    }
+}
@@ -0,0 +1,479 @@
+//! Supervision of the sensor with automatic recovery.
+//!
+//! A `SupervisedScd30` wraps the interface and handles transient errors itself, escalating the
+//! recovery with every consecutive failure:
+//!
+//! 1. The operation is retried up to the configured number of times.
+//! 2. The sensor is soft reset and its configuration restored.
+//! 3. All devices on the bus are reset with an I2C general call and the configuration restored.
+//! 4. The sensor is reported [Unresponsive](crate::error::SupervisorError::Unresponsive).
+//!
+//! Missed samples are detected by the [Watchdog](crate::options::Watchdog) of the wrapped
+//! interface and escalate the same way. Each result is returned as [OperationOutcome] reporting
+//! the attempts, the recovery taken and the time spent, so telemetry can tell clean reads from
+//! struggled ones:
+//!
+//! ```ignore
+//! use scd30_interface::supervisor::blocking::SupervisedScd30;
+//!
+//! let mut sensor = SupervisedScd30::new(Scd30::new(i2c), delay).with_retries(3);
+//! let measurement = sensor.read_measurement()?;
+//! if let Some(recovery) = measurement.recovery {
+//!     defmt::warn!(
+//!         "sensor recovered by {} after {} attempts",
+//!         recovery,
+//!         measurement.attempts
+//!     );
+//! }
+//! ```
+
+use duplicate::duplicate_item;
+
+/// Default number of retries of a failed operation before the sensor is reset.
+pub const DEFAULT_RETRIES: u8 = 2;
+
+/// Recovery step taken by the supervisor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Recovery {
+    /// The operation was retried the contained number of times.
+    Retried(u8),
+    /// The sensor was soft reset and its configuration restored.
+    SoftReset,
+    /// An I2C general call reset was issued and the sensor's configuration restored.
+    GeneralCallReset,
+}
+
+impl Recovery {
+    // Returns the step following `step`, `None` if all steps are exhausted.
+    const fn escalate(step: Option<Recovery>, retries: u8) -> Option<Recovery> {
+        match step {
+            None if retries > 0 => Some(Recovery::Retried(1)),
+            Some(Recovery::Retried(count)) if count < retries => Some(Recovery::Retried(count + 1)),
+            None | Some(Recovery::Retried(_)) => Some(Recovery::SoftReset),
+            Some(Recovery::SoftReset) => Some(Recovery::GeneralCallReset),
+            Some(Recovery::GeneralCallReset) => None,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Recovery {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Recovery::Retried(count) => defmt::write!(f, "Retried({})", count),
+            Recovery::SoftReset => defmt::write!(f, "SoftReset"),
+            Recovery::GeneralCallReset => defmt::write!(f, "GeneralCallReset"),
+        }
+    }
+}
+
+/// Result of a supervised operation together with the effort it took.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OperationOutcome<T> {
+    /// Value returned by the operation.
+    pub value: T,
+    /// Most severe recovery step taken until the operation succeeded, `None` if it succeeded
+    /// right away.
+    pub recovery: Option<Recovery>,
+    /// Number of times the operation was executed, 1 if it succeeded right away.
+    pub attempts: u32,
+    /// Number of recovery steps taken, including steps which failed themselves and were escalated.
+    pub recovery_actions: u32,
+    /// Time from the first attempt until the operation succeeded in milliseconds. Requires a
+    /// [Clock](crate::options::Clock) configured on the sensor.
+    pub elapsed_ms: Option<u64>,
+}
+
+impl<T> OperationOutcome<T> {
+    /// Returns whether the operation succeeded at the first attempt.
+    pub const fn is_clean(&self) -> bool {
+        self.attempts <= 1 && self.recovery_actions == 0
+    }
+
+    /// Converts the value of the outcome, keeping its metadata.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> OperationOutcome<U> {
+        OperationOutcome {
+            value: f(self.value),
+            recovery: self.recovery,
+            attempts: self.attempts,
+            recovery_actions: self.recovery_actions,
+            elapsed_ms: self.elapsed_ms,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T: defmt::Format> defmt::Format for OperationOutcome<T> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{} (attempts: {}, recovery: {}, elapsed: {} ms)",
+            self.value,
+            self.attempts,
+            self.recovery,
+            self.elapsed_ms
+        )
+    }
+}
+
+// Bookkeeping of a supervised operation until it succeeds.
+struct Attempts {
+    started_ms: Option<u64>,
+    failures_before: u32,
+    count: u32,
+    recovery: Option<Recovery>,
+}
+
+#[duplicate_item(
+    feature_        module      async   await               i2c_trait                                       delay_trait                         test_macro;
+    ["blocking"]    [blocking]  []      [identity()]        [embedded_hal::i2c::I2c<Error = I2cErr>]        [embedded_hal::delay::DelayNs]       [test];
+    ["async"]       [asynch]    [async] [await.identity()]  [embedded_hal_async::i2c::I2c<Error = I2cErr>]  [embedded_hal_async::delay::DelayNs] [tokio::test];
+)]
+pub mod module {
+    //! Supervised SCD30 interface
+
+    #[cfg(feature=feature_)]
+    mod inner {
+        use crate::{
+            data::{DataStatus, Measurement},
+            error::{Scd30Error, SupervisorError},
+            interface::{module::Scd30, Identity, RESET_DELAY_MS},
+            log::{log_info, log_warn},
+            options::NoDelay,
+            supervisor::{Attempts, OperationOutcome, Recovery, DEFAULT_RETRIES},
+        };
+
+        /// SCD30 interface recovering from transient errors by escalating retries and resets, see
+        /// the [module documentation](crate::supervisor).
+        ///
+        /// The delay `W` is used to wait for the sensor to boot after a reset.
+        pub struct SupervisedScd30<I2C, W, D = NoDelay> {
+            sensor: Scd30<I2C, D>,
+            delay: W,
+            retries: u8,
+            consecutive_failures: u32,
+        }
+
+        impl<I2C, W, D, I2cErr> SupervisedScd30<I2C, W, D>
+        where
+            I2C: i2c_trait,
+            W: delay_trait,
+            D: delay_trait,
+            I2cErr: embedded_hal::i2c::Error,
+        {
+            /// Supervises `sensor`, using `delay` to wait for it to boot after a reset. Failed
+            /// operations are retried [DEFAULT_RETRIES] times before resetting the sensor.
+            pub fn new(sensor: Scd30<I2C, D>, delay: W) -> Self {
+                Self {
+                    sensor,
+                    delay,
+                    retries: DEFAULT_RETRIES,
+                    consecutive_failures: 0,
+                }
+            }
+
+            /// Sets the number of retries of a failed operation before the sensor is reset.
+            pub fn with_retries(mut self, retries: u8) -> Self {
+                self.retries = retries;
+                self
+            }
+
+            /// Returns the number of failures since the last successful operation, including failed
+            /// recovery steps.
+            pub fn consecutive_failures(&self) -> u32 {
+                self.consecutive_failures
+            }
+
+            /// Returns the supervised sensor.
+            pub fn inner(&self) -> &Scd30<I2C, D> {
+                &self.sensor
+            }
+
+            /// Returns the supervised sensor for operations without supervision, e.g. changing
+            /// its configuration.
+            pub fn inner_mut(&mut self) -> &mut Scd30<I2C, D> {
+                &mut self.sensor
+            }
+
+            /// Consumes the supervisor and returns the sensor and delay.
+            pub fn into_inner(self) -> (Scd30<I2C, D>, W) {
+                (self.sensor, self.delay)
+            }
+
+            /// Supervised version of [Scd30::is_data_ready].
+            pub async fn is_data_ready(
+                &mut self,
+            ) -> Result<OperationOutcome<DataStatus>, SupervisorError<I2cErr>> {
+                let mut attempts = self.start();
+                loop {
+                    attempts.count += 1;
+                    match self.sensor.is_data_ready().await {
+                        Ok(value) => return Ok(self.succeed(value, attempts)),
+                        Err(error) => {
+                            attempts.recovery = Some(self.recover(error, attempts.recovery).await?)
+                        }
+                    }
+                }
+            }
+
+            /// Supervised version of [Scd30::read_measurement].
+            pub async fn read_measurement(
+                &mut self,
+            ) -> Result<OperationOutcome<Measurement>, SupervisorError<I2cErr>> {
+                let mut attempts = self.start();
+                loop {
+                    attempts.count += 1;
+                    match self.sensor.read_measurement().await {
+                        Ok(value) => return Ok(self.succeed(value, attempts)),
+                        Err(error) => {
+                            attempts.recovery = Some(self.recover(error, attempts.recovery).await?)
+                        }
+                    }
+                }
+            }
+
+            fn start(&self) -> Attempts {
+                Attempts {
+                    started_ms: self.sensor.now(),
+                    failures_before: self.consecutive_failures,
+                    count: 0,
+                    recovery: None,
+                }
+            }
+
+            fn succeed<T>(&mut self, value: T, attempts: Attempts) -> OperationOutcome<T> {
+                // Every failure during the operation triggered one recovery step.
+                let recovery_actions = self
+                    .consecutive_failures
+                    .saturating_sub(attempts.failures_before);
+                self.consecutive_failures = 0;
+                OperationOutcome {
+                    value,
+                    recovery: attempts.recovery,
+                    attempts: attempts.count,
+                    recovery_actions,
+                    elapsed_ms: self
+                        .sensor
+                        .now()
+                        .zip(attempts.started_ms)
+                        .map(|(now, started)| now.saturating_sub(started)),
+                }
+            }
+
+            // Takes the recovery step following `step` and returns it. If the step itself fails,
+            // the recovery is escalated further.
+            async fn recover(
+                &mut self,
+                mut error: Scd30Error<I2cErr>,
+                step: Option<Recovery>,
+            ) -> Result<Recovery, SupervisorError<I2cErr>> {
+                if error.recovery_action().is_none() {
+                    return Err(SupervisorError::Sensor(error));
+                }
+                let mut step = step;
+                loop {
+                    self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+                    let Some(next) = Recovery::escalate(step, self.retries) else {
+                        log_warn!(?error, "sensor unresponsive");
+                        return Err(SupervisorError::Unresponsive(error));
+                    };
+                    log_info!(?error, recovery = ?next, "recovering sensor");
+                    let result = match next {
+                        Recovery::Retried(_) => Ok(()),
+                        Recovery::SoftReset => {
+                            self.sensor
+                                .soft_reset_and_reinit(&mut self.delay, None)
+                                .await
+                        }
+                        Recovery::GeneralCallReset => self.general_call_reset_and_reinit().await,
+                    };
+                    match result {
+                        Ok(()) => return Ok(next),
+                        Err(step_error) => {
+                            error = step_error;
+                            step = Some(next);
+                        }
+                    }
+                }
+            }
+
+            async fn general_call_reset_and_reinit(&mut self) -> Result<(), Scd30Error<I2cErr>> {
+                let config = self.sensor.config();
+                self.sensor.general_call_reset().await?;
+                self.delay.delay_ms(RESET_DELAY_MS).await;
+                self.sensor.apply_config(&config).await
+            }
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::{
+                command::Command,
+                data::AmbientPressureCompensation,
+                error::Phase,
+                options::{WarmUp, WarmUpAction, WarmUpPolicy},
+            };
+            use embedded_hal::i2c;
+            use embedded_hal_mock::eh1::{
+                delay::NoopDelay,
+                i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+            };
+
+            fn measurement_read() -> [I2cTransaction; 2] {
+                [
+                    I2cTransaction::write(0x61, vec![0x03, 0x00]),
+                    I2cTransaction::read(
+                        0x61,
+                        vec![
+                            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5,
+                            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74,
+                        ],
+                    ),
+                ]
+            }
+
+            fn failed_measurement_read() -> I2cTransaction {
+                I2cTransaction::write(0x61, vec![0x03, 0x00]).with_error(i2c::ErrorKind::Other)
+            }
+
+            #[test_macro]
+            async fn read_measurement_without_failure_takes_no_recovery() {
+                let i2c = I2cMock::new(&measurement_read());
+
+                let mut sensor = SupervisedScd30::new(Scd30::new(i2c), NoopDelay::new());
+
+                let measurement = sensor.read_measurement().await.unwrap();
+                assert_eq!(measurement.value.co2_concentration, 439.09515);
+                assert_eq!(measurement.recovery, None);
+                assert_eq!(measurement.attempts, 1);
+                assert!(measurement.is_clean());
+                sensor.into_inner().0.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn transient_failure_is_retried() {
+                let mut expected_transactions = vec![failed_measurement_read()];
+                expected_transactions.extend(measurement_read());
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor = SupervisedScd30::new(Scd30::new(i2c), NoopDelay::new());
+
+                let measurement = sensor.read_measurement().await.unwrap();
+                assert_eq!(measurement.recovery, Some(Recovery::Retried(1)));
+                assert_eq!(measurement.attempts, 2);
+                assert_eq!(measurement.recovery_actions, 1);
+                assert!(!measurement.is_clean());
+                assert_eq!(sensor.consecutive_failures(), 0);
+                sensor.into_inner().0.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn elapsed_time_is_measured_with_clock() {
+                std::thread_local! {
+                    static NOW: core::cell::Cell<u64> = const { core::cell::Cell::new(0) };
+                }
+                // Advances by 5 ms on every reading of the clock.
+                fn clock() -> u64 {
+                    NOW.with(|now| now.replace(now.get() + 5))
+                }
+
+                let mut expected_transactions = vec![failed_measurement_read()];
+                expected_transactions.extend(measurement_read());
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor =
+                    SupervisedScd30::new(Scd30::new(i2c).with_clock(clock), NoopDelay::new());
+
+                let measurement = sensor.read_measurement().await.unwrap();
+                assert!(measurement
+                    .elapsed_ms
+                    .is_some_and(|elapsed_ms| elapsed_ms > 0));
+                sensor.into_inner().0.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn soft_reset_restores_continuous_measurements() {
+                let mut expected_transactions = vec![
+                    I2cTransaction::write(0x61, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                    failed_measurement_read(),
+                    failed_measurement_read(),
+                    I2cTransaction::write(0x61, vec![0xD3, 0x04]),
+                    I2cTransaction::write(0x61, vec![0x00, 0x10, 0x00, 0x00, 0x81]),
+                ];
+                expected_transactions.extend(measurement_read());
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor =
+                    SupervisedScd30::new(Scd30::new(i2c), NoopDelay::new()).with_retries(1);
+
+                sensor
+                    .inner_mut()
+                    .trigger_continuous_measurements(Some(
+                        AmbientPressureCompensation::DefaultPressure,
+                    ))
+                    .await
+                    .unwrap();
+                let measurement = sensor.read_measurement().await.unwrap();
+                assert_eq!(measurement.recovery, Some(Recovery::SoftReset));
+                assert_eq!(measurement.attempts, 3);
+                assert_eq!(measurement.recovery_actions, 2);
+                assert_eq!(measurement.elapsed_ms, None);
+                sensor.into_inner().0.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn exhausted_recovery_reports_unresponsive_sensor() {
+                let expected_transactions = [
+                    failed_measurement_read(),
+                    failed_measurement_read(),
+                    I2cTransaction::write(0x61, vec![0xD3, 0x04]).with_error(i2c::ErrorKind::Other),
+                    I2cTransaction::write(0x00, vec![0x06]),
+                    failed_measurement_read(),
+                ];
+
+                let i2c = I2cMock::new(&expected_transactions);
+
+                let mut sensor =
+                    SupervisedScd30::new(Scd30::new(i2c), NoopDelay::new()).with_retries(1);
+
+                assert_eq!(
+                    sensor.read_measurement().await.unwrap_err(),
+                    SupervisorError::Unresponsive(Scd30Error::CommandFailed {
+                        command: Command::ReadMeasurement,
+                        phase: Phase::Write,
+                        error: i2c::ErrorKind::Other,
+                    })
+                );
+                assert_eq!(sensor.consecutive_failures(), 4);
+                sensor.into_inner().0.shutdown().done();
+            }
+
+            #[test_macro]
+            async fn permanent_error_is_passed_on() {
+                let i2c = I2cMock::new(&measurement_read());
+
+                let mut sensor = SupervisedScd30::new(
+                    Scd30::new(i2c).with_warm_up(WarmUpPolicy {
+                        period: WarmUp::Samples(1),
+                        action: WarmUpAction::Discard,
+                    }),
+                    NoopDelay::new(),
+                );
+
+                assert_eq!(
+                    sensor.read_measurement().await.unwrap_err(),
+                    SupervisorError::Sensor(Scd30Error::WarmingUp)
+                );
+                assert_eq!(sensor.consecutive_failures(), 0);
+                sensor.into_inner().0.shutdown().done();
+            }
+        }
+    }
+
+    #[cfg(feature=feature_)]
+    pub use inner::*;
+}
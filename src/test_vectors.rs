@@ -0,0 +1,160 @@
+//! Example frames of the SCD30 interface description.
+//!
+//! The frames are sent to or received from a sensor at the default address 0x61, with the CRC
+//! bytes included. They can be reused as fixtures for tests of parsers, bus mocks or decoders:
+//!
+//! ```
+//! use scd30_interface::{data::Measurement, test_vectors};
+//!
+//! let measurement = Measurement::try_from(test_vectors::MEASUREMENT_FRAME).unwrap();
+//! assert_eq!(measurement.co2_concentration, test_vectors::MEASUREMENT_CO2_PPM);
+//! ```
+//!
+//! Commands sending a value are named after the value of the example, the matching response of
+//! the getter is the value and CRC, i.e. the last three bytes of the command.
+
+/// Starts continuous measurements without ambient pressure compensation.
+pub const TRIGGER_CONTINUOUS_MEASUREMENT: [u8; 5] = [0x00, 0x10, 0x00, 0x00, 0x81];
+
+/// Stops continuous measurements.
+pub const STOP_CONTINUOUS_MEASUREMENT: [u8; 2] = [0x01, 0x04];
+
+/// Sets the measurement interval to 2 s.
+pub const SET_MEASUREMENT_INTERVAL_2S: [u8; 5] = [0x46, 0x00, 0x00, 0x02, 0xE3];
+
+/// Requests the measurement interval.
+pub const GET_MEASUREMENT_INTERVAL: [u8; 2] = [0x46, 0x00];
+
+/// Measurement interval of 2 s.
+pub const MEASUREMENT_INTERVAL_2S: [u8; 3] = [0x00, 0x02, 0xE3];
+
+/// Requests the data ready status.
+pub const GET_DATA_READY: [u8; 2] = [0x02, 0x02];
+
+/// Data ready status of a sensor with a measurement ready for readout.
+pub const DATA_READY: [u8; 3] = [0x00, 0x01, 0xB0];
+
+/// Requests a measurement.
+pub const READ_MEASUREMENT: [u8; 2] = [0x03, 0x00];
+
+/// Measurement of [MEASUREMENT_CO2_PPM], [MEASUREMENT_TEMPERATURE_C] and
+/// [MEASUREMENT_HUMIDITY_PERCENT].
+pub const MEASUREMENT_FRAME: [u8; 18] = [
+    0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, 0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, 0x42, 0x43, 0xBF, 0x3A,
+    0x1B, 0x74,
+];
+
+/// CO2 concentration of [MEASUREMENT_FRAME] in ppm.
+pub const MEASUREMENT_CO2_PPM: f32 = 439.09515;
+
+/// Temperature of [MEASUREMENT_FRAME] in °C.
+pub const MEASUREMENT_TEMPERATURE_C: f32 = 27.23828;
+
+/// Relative humidity of [MEASUREMENT_FRAME] in %.
+pub const MEASUREMENT_HUMIDITY_PERCENT: f32 = 48.806744;
+
+/// Deactivates the automatic self-calibration.
+pub const SET_AUTOMATIC_SELF_CALIBRATION_INACTIVE: [u8; 5] = [0x53, 0x06, 0x00, 0x00, 0x81];
+
+/// Requests the automatic self-calibration setting.
+pub const GET_AUTOMATIC_SELF_CALIBRATION: [u8; 2] = [0x53, 0x06];
+
+/// Inactive automatic self-calibration.
+pub const AUTOMATIC_SELF_CALIBRATION_INACTIVE: [u8; 3] = [0x00, 0x00, 0x81];
+
+/// Performs a forced recalibration to 450 ppm.
+pub const SET_FORCED_RECALIBRATION_450PPM: [u8; 5] = [0x52, 0x04, 0x01, 0xC2, 0x50];
+
+/// Requests the forced recalibration value.
+pub const GET_FORCED_RECALIBRATION: [u8; 2] = [0x52, 0x04];
+
+/// Forced recalibration value of 450 ppm.
+pub const FORCED_RECALIBRATION_450PPM: [u8; 3] = [0x01, 0xC2, 0x50];
+
+/// Sets the temperature offset to 5 °C.
+pub const SET_TEMPERATURE_OFFSET_5C: [u8; 5] = [0x54, 0x03, 0x01, 0xF4, 0x33];
+
+/// Requests the temperature offset.
+pub const GET_TEMPERATURE_OFFSET: [u8; 2] = [0x54, 0x03];
+
+/// Temperature offset of 5 °C.
+pub const TEMPERATURE_OFFSET_5C: [u8; 3] = [0x01, 0xF4, 0x33];
+
+/// Sets the altitude compensation to 1000 m.
+pub const SET_ALTITUDE_COMPENSATION_1000M: [u8; 5] = [0x51, 0x02, 0x03, 0xE8, 0xD4];
+
+/// Requests the altitude compensation.
+pub const GET_ALTITUDE_COMPENSATION: [u8; 2] = [0x51, 0x02];
+
+/// Altitude compensation of 1000 m.
+pub const ALTITUDE_COMPENSATION_1000M: [u8; 3] = [0x03, 0xE8, 0xD4];
+
+/// Requests the firmware version.
+pub const READ_FIRMWARE_VERSION: [u8; 2] = [0xD1, 0x00];
+
+/// Firmware version 3.66.
+pub const FIRMWARE_VERSION_3_66: [u8; 3] = [0x03, 0x42, 0xF3];
+
+/// Executes a soft reset.
+pub const SOFT_RESET: [u8; 2] = [0xD3, 0x04];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        AltitudeCompensation, AutomaticSelfCalibration, DataStatus, FirmwareVersion,
+        ForcedRecalibrationValue, Measurement, MeasurementInterval, TemperatureOffset,
+    };
+
+    #[test]
+    fn responses_parse_to_documented_values() {
+        let measurement = Measurement::try_from(MEASUREMENT_FRAME).unwrap();
+        assert_eq!(measurement.co2_concentration, MEASUREMENT_CO2_PPM);
+        assert_eq!(measurement.temperature, MEASUREMENT_TEMPERATURE_C);
+        assert_eq!(measurement.humidity, MEASUREMENT_HUMIDITY_PERCENT);
+        assert_eq!(DataStatus::try_from(DATA_READY).unwrap(), DataStatus::Ready);
+        assert_eq!(
+            MeasurementInterval::try_from(MEASUREMENT_INTERVAL_2S).unwrap(),
+            MeasurementInterval::try_from(2).unwrap()
+        );
+        assert_eq!(
+            AutomaticSelfCalibration::try_from(AUTOMATIC_SELF_CALIBRATION_INACTIVE).unwrap(),
+            AutomaticSelfCalibration::Inactive
+        );
+        assert_eq!(
+            ForcedRecalibrationValue::try_from(FORCED_RECALIBRATION_450PPM).unwrap(),
+            ForcedRecalibrationValue::try_from(450).unwrap()
+        );
+        assert_eq!(
+            TemperatureOffset::try_from(TEMPERATURE_OFFSET_5C).unwrap(),
+            TemperatureOffset::try_from(5.0_f32).unwrap()
+        );
+        assert_eq!(
+            AltitudeCompensation::try_from(ALTITUDE_COMPENSATION_1000M).unwrap(),
+            AltitudeCompensation::from(1000)
+        );
+        assert_eq!(
+            FirmwareVersion::try_from(FIRMWARE_VERSION_3_66).unwrap(),
+            FirmwareVersion::new(3, 66)
+        );
+    }
+
+    #[test]
+    fn setters_end_with_the_getter_response() {
+        assert_eq!(SET_MEASUREMENT_INTERVAL_2S[..2], GET_MEASUREMENT_INTERVAL);
+        assert_eq!(SET_MEASUREMENT_INTERVAL_2S[2..], MEASUREMENT_INTERVAL_2S);
+        assert_eq!(
+            SET_AUTOMATIC_SELF_CALIBRATION_INACTIVE[2..],
+            AUTOMATIC_SELF_CALIBRATION_INACTIVE
+        );
+        assert_eq!(
+            SET_FORCED_RECALIBRATION_450PPM[2..],
+            FORCED_RECALIBRATION_450PPM
+        );
+        assert_eq!(SET_TEMPERATURE_OFFSET_5C[2..], TEMPERATURE_OFFSET_5C);
+        assert_eq!(
+            SET_ALTITUDE_COMPENSATION_1000M[2..],
+            ALTITUDE_COMPENSATION_1000M
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Batched configuration changes.
+//!
+//! A [ConfigTransaction] records several setting changes, which are then written in one go by
+//! [commit](crate::blocking::Scd30::commit):
+//!
+//! ```ignore
+//! let transaction = ConfigTransaction::new()
+//!     .measurement_interval(MeasurementInterval::try_from(10)?)
+//!     .temperature_offset(TemperatureOffset::try_from(1.5)?)
+//!     .start_continuous_measurements(AmbientPressureCompensation::DefaultPressure);
+//! sensor.commit(&transaction, &mut delay)?;
+//! ```
+
+use crate::data::{
+    AltitudeCompensation, AmbientPressureCompensation, AutomaticSelfCalibration,
+    ForcedRecalibrationValue, MeasurementInterval, Scd30Config, TemperatureOffset,
+};
+
+/// Gap between two commands of a transaction in microseconds, giving the sensor time to process
+/// the previous command.
+pub const COMMAND_GAP_US: u32 = 3000;
+
+/// Single setting change of a [ConfigTransaction], in the order they are written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ConfigStep {
+    MeasurementInterval(MeasurementInterval),
+    AutomaticSelfCalibration(AutomaticSelfCalibration),
+    TemperatureOffset(TemperatureOffset),
+    AltitudeCompensation(AltitudeCompensation),
+    ContinuousMeasurement(AmbientPressureCompensation),
+    ForcedRecalibration(ForcedRecalibrationValue),
+}
+
+/// Set of setting changes, written in the order required by the sensor with a gap of
+/// [COMMAND_GAP_US] between the commands:
+///
+/// 1. Measurement interval
+/// 2. Automatic self-calibration
+/// 3. Temperature offset
+/// 4. Altitude compensation
+/// 5. Start of continuous measurements, after all other settings are in place
+/// 6. Forced recalibration, as the sensor ignores it while the measurements are stopped
+///
+/// The forced recalibration only takes effect once the sensor has been running in the reference
+/// environment for at least two minutes. Record it in a transaction of its own after the
+/// measurements have settled, or use
+/// [set_forced_recalibration](crate::blocking::Scd30::set_forced_recalibration).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConfigTransaction {
+    config: Scd30Config,
+    forced_recalibration: Option<ForcedRecalibrationValue>,
+}
+
+impl ConfigTransaction {
+    /// Creates an empty transaction.
+    pub const fn new() -> Self {
+        Self {
+            config: Scd30Config {
+                continuous_measurement: None,
+                measurement_interval: None,
+                automatic_self_calibration: None,
+                temperature_offset: None,
+                altitude_compensation: None,
+            },
+            forced_recalibration: None,
+        }
+    }
+
+    /// Creates a transaction writing all settings of `config` which are not `None`.
+    pub const fn from_config(config: Scd30Config) -> Self {
+        Self {
+            config,
+            forced_recalibration: None,
+        }
+    }
+
+    /// Records a change of the measurement interval.
+    pub const fn measurement_interval(mut self, interval: MeasurementInterval) -> Self {
+        self.config.measurement_interval = Some(interval);
+        self
+    }
+
+    /// Records a change of the automatic self-calibration.
+    pub const fn automatic_self_calibration(mut self, setting: AutomaticSelfCalibration) -> Self {
+        self.config.automatic_self_calibration = Some(setting);
+        self
+    }
+
+    /// Records a change of the temperature offset.
+    pub const fn temperature_offset(mut self, offset: TemperatureOffset) -> Self {
+        self.config.temperature_offset = Some(offset);
+        self
+    }
+
+    /// Records a change of the altitude compensation.
+    pub const fn altitude_compensation(mut self, altitude: AltitudeCompensation) -> Self {
+        self.config.altitude_compensation = Some(altitude);
+        self
+    }
+
+    /// Records a forced recalibration to the reference value `frc`, written after continuous
+    /// measurements are started. It only takes effect once the sensor has been running in the
+    /// reference environment, see [ConfigTransaction].
+    pub const fn forced_recalibration(mut self, frc: ForcedRecalibrationValue) -> Self {
+        self.forced_recalibration = Some(frc);
+        self
+    }
+
+    /// Records a (re-)start of continuous measurements with the given pressure compensation.
+    pub const fn start_continuous_measurements(
+        mut self,
+        compensation: AmbientPressureCompensation,
+    ) -> Self {
+        self.config.continuous_measurement = Some(compensation);
+        self
+    }
+
+    /// Returns the settings changed by the transaction.
+    pub const fn config(&self) -> &Scd30Config {
+        &self.config
+    }
+
+    /// Returns whether the transaction does not change anything.
+    pub fn is_empty(&self) -> bool {
+        self.steps().next().is_none()
+    }
+
+    /// Returns the recorded forced recalibration.
+    pub(crate) const fn recorded_forced_recalibration(&self) -> Option<ForcedRecalibrationValue> {
+        self.forced_recalibration
+    }
+
+    /// Returns the recorded changes in the order they are written.
+    pub(crate) fn steps(&self) -> impl Iterator<Item = ConfigStep> {
+        let config = self.config;
+        [
+            config
+                .measurement_interval
+                .map(ConfigStep::MeasurementInterval),
+            config
+                .automatic_self_calibration
+                .map(ConfigStep::AutomaticSelfCalibration),
+            config.temperature_offset.map(ConfigStep::TemperatureOffset),
+            config
+                .altitude_compensation
+                .map(ConfigStep::AltitudeCompensation),
+            config
+                .continuous_measurement
+                .map(ConfigStep::ContinuousMeasurement),
+            self.forced_recalibration
+                .map(ConfigStep::ForcedRecalibration),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl From<Scd30Config> for ConfigTransaction {
+    fn from(config: Scd30Config) -> Self {
+        Self::from_config(config)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ConfigTransaction {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "ConfigTransaction {{ config: {}, forced_recalibration: {} }}",
+            self.config,
+            self.forced_recalibration
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_ordered_independent_of_recording() {
+        let frc = ForcedRecalibrationValue::try_from(450).unwrap();
+        let transaction = ConfigTransaction::new()
+            .start_continuous_measurements(AmbientPressureCompensation::DefaultPressure)
+            .forced_recalibration(frc)
+            .altitude_compensation(AltitudeCompensation::from(300))
+            .measurement_interval(MeasurementInterval::try_from(10).unwrap());
+
+        let mut steps = transaction.steps();
+        assert_eq!(
+            steps.next(),
+            Some(ConfigStep::MeasurementInterval(
+                MeasurementInterval::try_from(10).unwrap()
+            ))
+        );
+        assert_eq!(
+            steps.next(),
+            Some(ConfigStep::AltitudeCompensation(
+                AltitudeCompensation::from(300)
+            ))
+        );
+        assert_eq!(
+            steps.next(),
+            Some(ConfigStep::ContinuousMeasurement(
+                AmbientPressureCompensation::DefaultPressure
+            ))
+        );
+        assert_eq!(steps.next(), Some(ConfigStep::ForcedRecalibration(frc)));
+        assert_eq!(steps.next(), None);
+    }
+
+    #[test]
+    fn empty_transaction_has_no_steps() {
+        assert!(ConfigTransaction::new().is_empty());
+        assert!(ConfigTransaction::from(Scd30Config::default()).is_empty());
+        assert!(!ConfigTransaction::new()
+            .automatic_self_calibration(AutomaticSelfCalibration::Active)
+            .is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+//! Transports carrying the frames of the SCD30 commands.
+//!
+//! The interface only ever writes a command frame to the sensor or reads a response frame from
+//! it. [Scd30Transport] captures these two operations, so sensors behind exotic transports, e.g.
+//! I2C-over-USB bridges or RPC tunnels to another controller, can be driven with the command,
+//! parsing and validation logic of this crate. Every [I2c](embedded_hal::i2c::I2c) peripheral is
+//! a transport.
+//!
+//! A transport is handed to the interface wrapped into a [TransportBus]:
+//!
+//! ```ignore
+//! let tunnel = RpcTunnel::connect(&mut link)?;
+//! let mut sensor = Scd30::new(TransportBus::new(tunnel));
+//! let measurement = sensor.read_measurement()?;
+//! ```
+//!
+//! Errors of the transport implement [i2c::Error], so the interface can tell e.g. a missing
+//! acknowledge from other failures. Transports without such a distinction report
+//! [ErrorKind::Other](embedded_hal::i2c::ErrorKind::Other).
+
+use embedded_hal::i2c::{self, ErrorType, Operation, SevenBitAddress};
+
+/// Blocking transport of frames to and from the sensor.
+pub trait Scd30Transport: ErrorType {
+    /// Writes a command frame, including the argument and its CRC, to the sensor at `address`.
+    fn write_frame(&mut self, address: SevenBitAddress, frame: &[u8]) -> Result<(), Self::Error>;
+
+    /// Reads a response frame, including the CRCs, from the sensor at `address`, filling `frame`
+    /// completely.
+    fn read_frame(&mut self, address: SevenBitAddress, frame: &mut [u8])
+        -> Result<(), Self::Error>;
+}
+
+impl<I2C: i2c::I2c> Scd30Transport for I2C {
+    fn write_frame(&mut self, address: SevenBitAddress, frame: &[u8]) -> Result<(), Self::Error> {
+        self.write(address, frame)
+    }
+
+    fn read_frame(
+        &mut self,
+        address: SevenBitAddress,
+        frame: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.read(address, frame)
+    }
+}
+
+/// Asynchronous transport of frames to and from the sensor, see [Scd30Transport].
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncScd30Transport: ErrorType {
+    /// Writes a command frame, including the argument and its CRC, to the sensor at `address`.
+    async fn write_frame(
+        &mut self,
+        address: SevenBitAddress,
+        frame: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Reads a response frame, including the CRCs, from the sensor at `address`, filling `frame`
+    /// completely.
+    async fn read_frame(
+        &mut self,
+        address: SevenBitAddress,
+        frame: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "async")]
+impl<I2C: embedded_hal_async::i2c::I2c> AsyncScd30Transport for I2C {
+    async fn write_frame(
+        &mut self,
+        address: SevenBitAddress,
+        frame: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.write(address, frame).await
+    }
+
+    async fn read_frame(
+        &mut self,
+        address: SevenBitAddress,
+        frame: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.read(address, frame).await
+    }
+}
+
+/// Adapter handing a transport to the interface in place of an I2C peripheral.
+pub struct TransportBus<T> {
+    transport: T,
+}
+
+impl<T> TransportBus<T> {
+    /// Wraps `transport`.
+    pub const fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Returns the wrapped transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+impl<T: ErrorType> ErrorType for TransportBus<T> {
+    type Error = T::Error;
+}
+
+impl<T: Scd30Transport> i2c::I2c for TransportBus<T> {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Write(frame) => self.transport.write_frame(address, frame)?,
+                Operation::Read(frame) => self.transport.read_frame(address, frame)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncScd30Transport> embedded_hal_async::i2c::I2c for TransportBus<T> {
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Write(frame) => self.transport.write_frame(address, frame).await?,
+                Operation::Read(frame) => self.transport.read_frame(address, frame).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Blocking SCD30 interface driving a sensor through a [Scd30Transport].
+#[cfg(feature = "blocking")]
+pub type TransportScd30<T> = crate::blocking::Scd30<TransportBus<T>>;
+
+#[cfg(test)]
+#[cfg(feature = "blocking")]
+mod tests {
+    use super::*;
+    use crate::data::FirmwareVersion;
+    use embedded_hal::i2c::ErrorKind;
+
+    /// Transport tunneling the frames through a message queue, like an RPC link would.
+    #[derive(Default)]
+    struct Tunnel {
+        sent: Vec<(u8, Vec<u8>)>,
+        responses: Vec<Vec<u8>>,
+    }
+
+    impl ErrorType for Tunnel {
+        type Error = ErrorKind;
+    }
+
+    impl Scd30Transport for Tunnel {
+        fn write_frame(&mut self, address: u8, frame: &[u8]) -> Result<(), ErrorKind> {
+            self.sent.push((address, frame.to_vec()));
+            Ok(())
+        }
+
+        fn read_frame(&mut self, _address: u8, frame: &mut [u8]) -> Result<(), ErrorKind> {
+            let response = self.responses.pop().ok_or(ErrorKind::Other)?;
+            frame.copy_from_slice(&response);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn interface_runs_over_custom_transport() {
+        let tunnel = Tunnel {
+            responses: vec![vec![0x03, 0x42, 0xF3]],
+            ..Default::default()
+        };
+        let mut sensor = TransportScd30::new(TransportBus::new(tunnel));
+
+        assert_eq!(
+            sensor.read_firmware_version(),
+            Ok(FirmwareVersion::new(3, 66))
+        );
+        assert_eq!(
+            sensor.read_firmware_version(),
+            Err(crate::error::Scd30Error::CommandFailed {
+                command: crate::command::Command::ReadFirmwareVersion,
+                phase: crate::error::Phase::Read,
+                error: ErrorKind::Other,
+            })
+        );
+
+        let tunnel = sensor.shutdown().into_inner();
+        assert_eq!(
+            tunnel.sent,
+            [(0x61, vec![0xD1, 0x00]), (0x61, vec![0xD1, 0x00])]
+        );
+    }
+}
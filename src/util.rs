@@ -1,95 +1,101 @@
-use crate::error::DataError;
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::indexing_slicing,
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used
+    )
+)]
 
-pub(crate) fn crc8_matches(data: &[u8], crc: u8) -> bool {
-    compute_crc8(data) == crc
+use crate::{
+    codec::{compute_crc8, Frame},
+    error::DataError,
+};
+
+/// Checks the length of a response of `N` bytes and returns it as array, to be passed to the
+/// array based parsers.
+pub(crate) fn sized_frame<const N: usize>(data: &[u8]) -> Result<[u8; N], DataError> {
+    <[u8; N]>::try_from(data).map_err(|_| DataError::ReceivedBufferWrongSize)
 }
 
-const INITIAL: u8 = 0xFF;
-const XOR: u8 = 0x31;
+/// Checks a response consisting of a single word and returns its value.
+pub(crate) fn deserialize_word(frame: &[u8; 3]) -> Result<u16, DataError> {
+    let [word] = Frame::<1>::new(frame)?.words();
+    Ok(word)
+}
 
-/// Computes a CRC-8 according to NRSC-5
-/// width=8 poly=0x31 init=0xff refin=false refout=false xorout=0x00 check=0xf7 residue=0x00 name="CRC-8/NRSC-5"
-pub(crate) fn compute_crc8(data: &[u8]) -> u8 {
-    let mut crc = INITIAL;
-    for byte in data.iter() {
-        crc ^= byte;
-        for _ in 0..8 {
-            if (crc & 0x80) != 0 {
-                crc = (crc << 1) ^ XOR;
-            } else {
-                crc <<= 1;
-            }
+/// Replaces the CRC of every 16-bit word in `data` by the CRC of the word, so the data passes the
+/// CRC check of the parsers.
+pub(crate) fn replace_crcs(data: &mut [u8]) {
+    for chunk in data.chunks_exact_mut(3) {
+        if let [msb, lsb, crc] = chunk {
+            *crc = compute_crc8(&[*msb, *lsb]);
         }
     }
-    crc
 }
 
-pub(crate) fn check_deserialization(data: &[u8], expected_len: usize) -> Result<(), DataError> {
-    if data.len() != expected_len {
-        return Err(DataError::ReceivedBufferWrongSize);
-    }
-    if data
-        .chunks(3)
-        .any(|chunk| !crc8_matches(&chunk[..2], chunk[2]))
-    {
-        return Err(DataError::CrcFailed);
+/// `f32::abs` is not available in `core` for all supported Rust versions.
+pub(crate) fn abs(value: f32) -> f32 {
+    if value < 0.0 {
+        -value
+    } else {
+        value
     }
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn sample_data_crc_computes_properly() {
-        let result = compute_crc8(&[0xBE, 0xEF]);
-        assert_eq!(result, 0x92);
-    }
-
-    #[test]
-    fn sample_data_2_crc_computes_properly() {
-        let result = compute_crc8(&[0x43, 0xDB]);
-        assert_eq!(result, 0xCB);
-    }
-
-    #[test]
-    fn sample_firmware_version_crc_computes_properly() {
-        let result = compute_crc8(&[0x03, 0x42]);
-        assert_eq!(result, 0xF3);
-    }
-
-    #[test]
-    fn zero_data_crc_computes_properly() {
-        let result = compute_crc8(&[0x00]);
-        assert_eq!(result, 0xAC);
-    }
-
     #[test]
     fn deserialization_with_spec_sample_works() {
         let data = [0x03, 0x42, 0xF3];
-        let result = check_deserialization(&data[..], 3);
+        let result = deserialize_word(&data);
         assert!(result.is_ok());
     }
 
     #[test]
     fn deserialize_errors_if_buffer_to_big() {
         let data = [0x03, 0x42, 0xF3, 0x12];
-        let result = check_deserialization(&data[..], 3);
+        let result = sized_frame::<3>(&data[..]);
         assert_eq!(result.unwrap_err(), DataError::ReceivedBufferWrongSize)
     }
 
     #[test]
     fn deserialize_errors_if_buffer_to_small() {
         let data = [0x03, 0x42];
-        let result = check_deserialization(&data[..], 3);
+        let result = sized_frame::<3>(&data[..]);
         assert_eq!(result.unwrap_err(), DataError::ReceivedBufferWrongSize)
     }
 
     #[test]
     fn deserialize_errors_if_crc_is_wrong() {
         let data = [0x03, 0x42, 0xFF];
-        let result = check_deserialization(&data[..], 3);
+        let result = deserialize_word(&data);
         assert_eq!(result.unwrap_err(), DataError::CrcFailed)
     }
+
+    #[test]
+    fn deserialize_word_returns_value() {
+        assert_eq!(deserialize_word(&[0x03, 0x42, 0xF3]), Ok(0x0342));
+        assert_eq!(
+            deserialize_word(&[0x03, 0x42, 0xFF]),
+            Err(DataError::CrcFailed)
+        );
+    }
+
+    #[test]
+    fn parsers_reject_every_truncated_or_overlong_buffer() {
+        use crate::data::{FirmwareVersion, Measurement};
+
+        let mut data = [0u8; 21];
+        replace_crcs(&mut data);
+        for len in 0..=data.len() {
+            let buffer = &data[..len];
+            assert_eq!(len == 3, sized_frame::<3>(buffer).is_ok());
+            assert_eq!(len == 3, FirmwareVersion::try_from(buffer).is_ok());
+            assert_eq!(len == 18, Measurement::try_from(buffer).is_ok());
+        }
+    }
 }